@@ -0,0 +1,197 @@
+//!
+//! # Prometheus metrics of the ABCI node
+//!
+//! All metrics are registered in the default registry of `prometheus`,
+//! the web3 RPC metrics of `fp-rpc-server` live in the same registry,
+//! so a single `/metrics` listener can expose all of them.
+//!
+//! Nothing is observed until `enable` is called.
+//!
+
+#[cfg(test)]
+mod test;
+
+use {
+    abci::ResponseDeliverTx,
+    baseapp::{deliver_tx_error, BaseApp as AccountBaseApp},
+    fp_traits::base::BaseProvider,
+    lazy_static::lazy_static,
+    ledger::{data_model::TxErrorCode, store::LedgerState},
+    prometheus::{
+        exponential_buckets, register_histogram_vec, register_int_counter_vec,
+        register_int_gauge, register_int_gauge_vec, HistogramTimer, HistogramVec,
+        IntCounterVec, IntGauge, IntGaugeVec,
+    },
+    ruc::*,
+    std::sync::atomic::{AtomicBool, AtomicI64, Ordering},
+};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+// transactions delivered in the current block, both UTXO and EVM ones
+static BLOCK_TX_CNT: AtomicI64 = AtomicI64::new(0);
+
+lazy_static! {
+    static ref ABCI_CALLBACK_TIME: HistogramVec = pnk!(register_histogram_vec!(
+        "abci_callback_duration_seconds",
+        "Latency of ABCI callbacks in seconds.",
+        &["callback"],
+        pnk!(exponential_buckets(0.0005, 2.0, 16))
+    ));
+    static ref ABCI_REJECTED_TXS: IntCounterVec = pnk!(register_int_counter_vec!(
+        "abci_rejected_txs_total",
+        "Number of transactions rejected by check_tx/deliver_tx, by error code.",
+        &["callback", "reason"]
+    ));
+    static ref ABCI_DELIVERED_TXS: IntCounterVec = pnk!(register_int_counter_vec!(
+        "abci_delivered_txs_total",
+        "Number of transactions processed by deliver_tx.",
+        &["status"]
+    ));
+    static ref ABCI_BLOCK_TXS: IntGauge = pnk!(register_int_gauge!(
+        "abci_block_txs",
+        "Number of transactions delivered in the last block."
+    ));
+    static ref ABCI_HEIGHT: IntGauge = pnk!(register_int_gauge!(
+        "abci_block_height",
+        "Height of the last committed block."
+    ));
+    static ref LEDGER_STATUS: IntGaugeVec = pnk!(register_int_gauge_vec!(
+        "ledger_status",
+        "Sizes of the committed `LedgerStatus`.",
+        &["item"]
+    ));
+    static ref STAKING_STATUS: IntGaugeVec = pnk!(register_int_gauge_vec!(
+        "staking_status",
+        "Global numbers of the committed staking state.",
+        &["item"]
+    ));
+    static ref EVM_BLOCK_GAS_USED: IntGauge = pnk!(register_int_gauge!(
+        "evm_block_gas_used",
+        "Gas used by the last EVM block."
+    ));
+    static ref EVM_BLOCK_TXS: IntGauge = pnk!(register_int_gauge!(
+        "evm_block_txs",
+        "Number of transactions in the last EVM block."
+    ));
+}
+
+/// Start observing, called once if `--enable-metrics` is set.
+pub(crate) fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+#[inline(always)]
+fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Start a timer for the given ABCI callback,
+/// the latency will be observed when the timer is dropped.
+#[inline(always)]
+pub(crate) fn callback_timer(callback: &str) -> Option<HistogramTimer> {
+    if !enabled() {
+        return None;
+    }
+    Some(
+        ABCI_CALLBACK_TIME
+            .with_label_values(&[callback])
+            .start_timer(),
+    )
+}
+
+/// Record the result of a `check_tx`.
+pub(crate) fn observe_check_tx(code: u32) {
+    if enabled() && 0 != code {
+        ABCI_REJECTED_TXS
+            .with_label_values(&[
+                "check_tx",
+                TxErrorCode::from_code(code)
+                    .unwrap_or(TxErrorCode::Internal)
+                    .name(),
+            ])
+            .inc();
+    }
+}
+
+/// Record the result of a `deliver_tx`.
+pub(crate) fn observe_deliver_tx(resp: &ResponseDeliverTx) {
+    if !enabled() {
+        return;
+    }
+    BLOCK_TX_CNT.fetch_add(1, Ordering::Relaxed);
+    if 0 == resp.code {
+        ABCI_DELIVERED_TXS.with_label_values(&["ok"]).inc();
+    } else {
+        let code = deliver_tx_error(resp).unwrap_or(TxErrorCode::Internal);
+        ABCI_DELIVERED_TXS.with_label_values(&["failed"]).inc();
+        ABCI_REJECTED_TXS
+            .with_label_values(&["deliver_tx", code.name()])
+            .inc();
+    }
+}
+
+/// Called at the start of every block.
+pub(crate) fn observe_begin_block() {
+    if !enabled() {
+        return;
+    }
+    BLOCK_TX_CNT.swap(0, Ordering::Relaxed);
+}
+
+/// Called at the end of every block.
+pub(crate) fn observe_end_block() {
+    if !enabled() {
+        return;
+    }
+    ABCI_BLOCK_TXS.set(BLOCK_TX_CNT.load(Ordering::Relaxed));
+}
+
+/// Record the committed state of the ledger and the staking module.
+pub(crate) fn observe_ledger(la: &LedgerState) {
+    if !enabled() {
+        return;
+    }
+    ABCI_HEIGHT.set(la.get_tendermint_height() as i64);
+
+    LEDGER_STATUS
+        .with_label_values(&["utxos"])
+        .set(la.get_utxo_count() as i64);
+    LEDGER_STATUS
+        .with_label_values(&["next_txo"])
+        .set(la.get_next_txo().0 as i64);
+    LEDGER_STATUS
+        .with_label_values(&["next_txn"])
+        .set(la.get_next_txn().0 as i64);
+    LEDGER_STATUS
+        .with_label_values(&["blocks"])
+        .set(la.get_block_count() as i64);
+
+    let staking = la.get_staking();
+    STAKING_STATUS
+        .with_label_values(&["global_delegation_amount"])
+        .set(staking.get_global_delegation_amount() as i64);
+    STAKING_STATUS
+        .with_label_values(&["global_power"])
+        .set(staking.validator_global_power() as i64);
+    STAKING_STATUS
+        .with_label_values(&["coinbase_balance"])
+        .set(staking.coinbase_balance() as i64);
+    STAKING_STATUS.with_label_values(&["validators"]).set(
+        staking
+            .validator_get_current()
+            .map(|v| v.get_validators().len())
+            .unwrap_or_default() as i64,
+    );
+}
+
+/// Record the last EVM block.
+pub(crate) fn observe_evm_block(app: &AccountBaseApp) {
+    if !enabled() {
+        return;
+    }
+    if let Some(block) = app.current_block(None) {
+        EVM_BLOCK_GAS_USED.set(block.header.gas_used.low_u64() as i64);
+        EVM_BLOCK_TXS.set(block.transactions.len() as i64);
+    }
+}
@@ -0,0 +1,71 @@
+use {super::*, baseapp::set_deliver_tx_error, parking_lot::Mutex};
+
+// The metrics are process-global, every test observing them holds this lock,
+// counters are still checked by their deltas.
+static LOCK: Mutex<()> = Mutex::new(());
+
+#[test]
+fn observe_block_and_rejections() {
+    let _guard = LOCK.lock();
+    enable();
+
+    let rejected = |callback: &str, code: TxErrorCode| {
+        ABCI_REJECTED_TXS
+            .with_label_values(&[callback, code.name()])
+            .get()
+    };
+
+    let fee_too_low = rejected("check_tx", TxErrorCode::FeeTooLow);
+    observe_check_tx(TxErrorCode::FeeTooLow.code());
+    observe_check_tx(0);
+    assert_eq!(
+        rejected("check_tx", TxErrorCode::FeeTooLow),
+        fee_too_low + 1
+    );
+
+    let ok = ABCI_DELIVERED_TXS.with_label_values(&["ok"]).get();
+    let failed = ABCI_DELIVERED_TXS.with_label_values(&["failed"]).get();
+    let input_spent = rejected("deliver_tx", TxErrorCode::InputSpent);
+    let mut spent = ResponseDeliverTx::new();
+    spent.code = 1;
+    set_deliver_tx_error(&mut spent, TxErrorCode::InputSpent);
+    observe_begin_block();
+    observe_deliver_tx(&ResponseDeliverTx::new());
+    observe_deliver_tx(&ResponseDeliverTx::new());
+    observe_deliver_tx(&spent);
+    observe_end_block();
+    assert_eq!(ABCI_BLOCK_TXS.get(), 3);
+    assert_eq!(ABCI_DELIVERED_TXS.with_label_values(&["ok"]).get(), ok + 2);
+    assert_eq!(
+        ABCI_DELIVERED_TXS.with_label_values(&["failed"]).get(),
+        failed + 1
+    );
+    assert_eq!(
+        rejected("deliver_tx", TxErrorCode::InputSpent),
+        input_spent + 1
+    );
+
+    observe_begin_block();
+    observe_end_block();
+    assert_eq!(ABCI_BLOCK_TXS.get(), 0);
+
+    assert!(callback_timer("check_tx").is_some());
+}
+
+#[test]
+fn observe_ledger_status() {
+    let _guard = LOCK.lock();
+    enable();
+
+    let la = LedgerState::tmp_ledger();
+    observe_ledger(&la);
+    assert_eq!(ABCI_HEIGHT.get(), la.get_tendermint_height() as i64);
+    assert_eq!(
+        LEDGER_STATUS.with_label_values(&["utxos"]).get(),
+        la.get_utxo_count() as i64
+    );
+    assert_eq!(
+        LEDGER_STATUS.with_label_values(&["blocks"]).get(),
+        la.get_block_count() as i64
+    );
+}
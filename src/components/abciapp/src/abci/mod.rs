@@ -5,26 +5,35 @@
 #![deny(warnings)]
 #![allow(clippy::needless_borrow)]
 
+mod metrics;
 mod server;
 pub mod staking;
 
 use {
     crate::api::{
-        query_server::query_api, submission_server::submission_api::SubmissionApi,
+        metrics_server::MetricsApi, query_server::query_api,
+        submission_server::submission_api::SubmissionApi,
     },
+    baseapp::BaseApp as AccountBaseAPP,
     config::abci::{global_cfg::CFG, ABCIConfig},
     futures::executor::ThreadPool,
     lazy_static::lazy_static,
-    ledger::converter::{LOWLEVEL_DATA_MAX, LOWLEVEL_DATA_MIN},
+    ledger::{
+        converter::{LOWLEVEL_DATA_MAX, LOWLEVEL_DATA_MIN},
+        store::{fbnc::flush_data, LedgerState},
+    },
     ruc::*,
     std::{
         env, fs, mem,
         net::SocketAddr,
+        path::Path,
+        process::exit,
         sync::{
             atomic::{AtomicBool, Ordering},
             Arc,
         },
         thread,
+        time::Duration,
     },
 };
 
@@ -50,6 +59,45 @@ pub fn run() -> Result<()> {
 
     env::set_var("BNC_DATA_DIR", format!("{}/__bnc__", &config.ledger_dir));
 
+    if !CFG.state_sync_peers.is_empty() {
+        let trust = server::state_sync::TrustOptions {
+            height: CFG.state_sync_trust_height,
+            hash: CFG.state_sync_trust_hash.clone(),
+            period: Duration::from_secs(CFG.state_sync_trust_period),
+        };
+        let snapshot = server::state_sync::sync_from_peers(
+            &CFG.state_sync_peers,
+            &trust,
+            &CFG.ledger_dir,
+        )
+        .c(d!())?;
+        println!(
+            "Snapshot at height {} has been restored, app hash: {}",
+            snapshot.height, snapshot.app_hash
+        );
+        println!(
+            "The data of tendermint is not restored, bootstrap it at height {} before starting the node",
+            snapshot.height
+        );
+        exit(0);
+    }
+
+    if CFG.prune_and_exit {
+        prune_and_exit().c(d!())?;
+    }
+
+    if let Some(height) = CFG.export_evm_state {
+        export_evm_state_and_exit(height).c(d!())?;
+    }
+
+    if CFG.backfill_log_index {
+        backfill_log_index_and_exit().c(d!())?;
+    }
+
+    if CFG.migrate_archive {
+        migrate_archive_and_exit().c(d!())?;
+    }
+
     LOWLEVEL_DATA_MIN.swap(CFG.checkpoint.lowlevel_data_min as i64, Ordering::Relaxed);
     LOWLEVEL_DATA_MAX.swap(CFG.checkpoint.lowlevel_data_max as i64, Ordering::Relaxed);
     if CFG.enable_query_service {
@@ -87,6 +135,15 @@ pub fn run() -> Result<()> {
         });
     }
 
+    if CFG.enable_metrics {
+        metrics::enable();
+        let metrics_host = config.abci_host.clone();
+        let metrics_port = CFG.metrics_port;
+        thread::spawn(move || {
+            pnk!(MetricsApi::create(&metrics_host, metrics_port));
+        });
+    }
+
     if CFG.enable_eth_api_service {
         let base_app = if CFG.enable_eth_api_secondary {
             app.eth_api_base_app.clone().c(d!())?
@@ -117,3 +174,70 @@ pub fn run() -> Result<()> {
 
     Ok(())
 }
+
+// Offline pruning, all the eligible blocks will be checked in one pass,
+// recommended before enabling the runtime pruning on an existing chain.
+fn prune_and_exit() -> Result<()> {
+    let keep_blocks = CFG.prune_keep_blocks.c(d!())?;
+    let mut ledger = LedgerState::load_or_init(&CFG.ledger_dir).c(d!())?;
+    let n = ledger.prune(keep_blocks, None).c(d!())?;
+    flush_data();
+
+    println!(
+        "{} transactions have been pruned, the latest {} blocks are kept",
+        n, keep_blocks
+    );
+    exit(0);
+}
+
+// Export the state of the EVM side as the `app_state` of a tendermint genesis,
+// which can be used to start a test chain forked from this one.
+fn export_evm_state_and_exit(height: u64) -> Result<()> {
+    let app = AccountBaseAPP::new(
+        Path::new(&CFG.ledger_dir),
+        CFG.disable_eth_empty_blocks,
+        CFG.arc_history,
+        false,
+    )
+    .c(d!())?;
+    let state = app.export_genesis(height).c(d!())?;
+
+    let output = CFG
+        .export_evm_state_output
+        .as_deref()
+        .unwrap_or("./evm_state.json");
+    fs::write(output, state).c(d!())?;
+
+    println!("The EVM state at height {height} has been exported to {output}");
+    exit(0);
+}
+
+// Index the logs of the EVM blocks stored before the log index of `eth_getLogs`,
+// which can be interrupted and resumed.
+fn backfill_log_index_and_exit() -> Result<()> {
+    let app = AccountBaseAPP::new(
+        Path::new(&CFG.ledger_dir),
+        CFG.disable_eth_empty_blocks,
+        CFG.arc_history,
+        false,
+    )
+    .c(d!())?;
+    let n = app.backfill_log_index().c(d!())?;
+
+    println!("The logs of {n} blocks have been indexed");
+    exit(0);
+}
+
+// Keep all the versions of the EVM state from now on,
+// the node is started with `--arc-archive` afterwards.
+fn migrate_archive_and_exit() -> Result<()> {
+    let archive =
+        AccountBaseAPP::migrate_to_archive(Path::new(&CFG.ledger_dir), CFG.arc_history)
+            .c(d!())?;
+
+    println!(
+        "The EVM state is an archive since height {}, with snapshots every {} blocks",
+        archive.since, archive.interval
+    );
+    exit(0);
+}
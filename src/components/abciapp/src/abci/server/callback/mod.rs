@@ -7,7 +7,10 @@ mod utils;
 use {
     crate::{
         abci::{
-            server::{tx_sender::TX_SIZE, ABCISubmissionServer},
+            metrics,
+            server::{
+                native_bridge, state_sync, tx_sender::TX_SIZE, ABCISubmissionServer,
+            },
             staking, IN_SAFE_ITV, IS_EXITING, POOL,
         },
         api::{
@@ -21,6 +24,7 @@ use {
         ResponseBeginBlock, ResponseCheckTx, ResponseCommit, ResponseDeliverTx,
         ResponseEndBlock, ResponseInfo, ResponseInitChain, ResponseQuery,
     },
+    baseapp::{set_check_tx_code, set_deliver_tx_error},
     chrono::Local,
     config::abci::global_cfg::CFG,
     enterprise_web3::{
@@ -33,7 +37,7 @@ use {
     lazy_static::lazy_static,
     ledger::{
         converter::is_convert_account,
-        data_model::{Operation, ASSET_TYPE_FRA},
+        data_model::{Operation, TxErrorCode, ASSET_TYPE_FRA},
         staking::{
             evm::EVM_STAKING, FF_ADDR_EXTRA_120_0000, FF_ADDR_LIST, KEEP_HIST,
             VALIDATOR_UPDATE_BLOCK_ITV,
@@ -64,6 +68,9 @@ pub(crate) static TENDERMINT_BLOCK_HEIGHT: AtomicI64 = AtomicI64::new(0);
 pub(crate) static BEGIN_BLOCK_TIME: AtomicI64 = AtomicI64::new(0);
 pub(crate) static END_BLOCK_TIME: AtomicI64 = AtomicI64::new(0);
 
+// how many blocks are visited by the runtime pruning in each commit
+const PRUNE_BATCH_BLOCKS: usize = 16;
+
 lazy_static! {
     // save the request parameters from the begin_block for use in the end_block
     static ref REQ_BEGIN_BLOCK: Arc<Mutex<RequestBeginBlock>> =
@@ -122,6 +129,12 @@ pub fn info(s: &mut ABCISubmissionServer, req: &RequestInfo) -> ResponseInfo {
 }
 
 pub fn query(s: &mut ABCISubmissionServer, req: &RequestQuery) -> ResponseQuery {
+    if req.path.starts_with(state_sync::QUERY_PREFIX) {
+        return state_sync::query(req);
+    }
+    if req.path.starts_with(native_bridge::QUERY_PREFIX) {
+        return native_bridge::query(s, req);
+    }
     s.account_base_app.write().query(req)
 }
 
@@ -140,7 +153,7 @@ pub fn check_tx(s: &mut ABCISubmissionServer, req: &RequestCheckTx) -> ResponseC
     let tx = req.get_tx();
     if td_height > CFG.checkpoint.check_tx_size_height && tx.len() > TX_SIZE {
         resp.log = format!("Transaction too large:{}", tx.len());
-        resp.code = 1;
+        set_check_tx_code(&mut resp, TxErrorCode::TxTooLarge);
         return resp;
     }
 
@@ -157,7 +170,10 @@ pub fn check_tx(s: &mut ABCISubmissionServer, req: &RequestCheckTx) -> ResponseC
                                 body_signatures.dedup();
                                 if body_signatures.len() > 1 {
                                     "too many body_signatures".clone_into(&mut resp.log);
-                                    resp.code = 1;
+                                    set_check_tx_code(
+                                        &mut resp,
+                                        TxErrorCode::TooManySignatures,
+                                    );
                                     return resp;
                                 }
                             }
@@ -166,25 +182,25 @@ pub fn check_tx(s: &mut ABCISubmissionServer, req: &RequestCheckTx) -> ResponseC
                         signatures.dedup();
                         if signatures.len() > 1 {
                             "Too many signatures".clone_into(&mut resp.log);
-                            resp.code = 1;
+                            set_check_tx_code(&mut resp, TxErrorCode::TooManySignatures);
                             return resp;
                         }
 
                         if tx.pubkey_sign_map.len() > 1 {
                             "too many pubkey_sign_map".clone_into(&mut resp.log);
-                            resp.code = 1;
+                            set_check_tx_code(&mut resp, TxErrorCode::TooManySignatures);
                             return resp;
                         }
-                    } else if !tx.valid_in_abci() {
-                        "Should not appear in ABCI".clone_into(&mut resp.log);
-                        resp.code = 1;
+                    } else if let Err(e) = tx.check_in_abci() {
+                        resp.log = e.to_string();
+                        set_check_tx_code(&mut resp, e.code);
                     } else if TX_HISTORY.read().contains_key(&tx.hash_tm_rawbytes()) {
                         "Historical transaction".clone_into(&mut resp.log);
-                        resp.code = 1;
+                        set_check_tx_code(&mut resp, TxErrorCode::HistoricalTx);
                     }
                 } else {
                     "Invalid format".clone_into(&mut resp.log);
-                    resp.code = 1;
+                    set_check_tx_code(&mut resp, TxErrorCode::InvalidFormat);
                 }
             }
             resp
@@ -193,7 +209,7 @@ pub fn check_tx(s: &mut ABCISubmissionServer, req: &RequestCheckTx) -> ResponseC
             if CFG.checkpoint.disable_evm_block_height < td_height
                 && td_height < CFG.checkpoint.enable_frc20_height
             {
-                resp.code = 2;
+                set_check_tx_code(&mut resp, TxErrorCode::EvmDisabled);
                 "EVM is disabled".clone_into(&mut resp.log);
                 resp
             } else {
@@ -201,7 +217,7 @@ pub fn check_tx(s: &mut ABCISubmissionServer, req: &RequestCheckTx) -> ResponseC
             }
         }
         TxCatalog::Unknown => {
-            resp.code = 1;
+            set_check_tx_code(&mut resp, TxErrorCode::UnknownTx);
             "Unknown transaction".clone_into(&mut resp.log);
             resp
         }
@@ -270,7 +286,22 @@ pub fn begin_block(
     {
         ResponseBeginBlock::default()
     } else {
-        s.account_base_app.write().begin_block(req)
+        let mut app = s.account_base_app.write();
+        let resp = app.begin_block(req);
+
+        // export the staking state for the staking precompile,
+        // a failed export is retried in full on the next block
+        if header.height >= CFG.checkpoint.staking_precompile_height {
+            if let Err(e) = app.export_staking(
+                la.get_staking(),
+                la.staking_get_global_unlocked_amount(),
+                la.staking_get_block_rewards_rate(),
+            ) {
+                tracing::error!(target: "abciapp", "export staking snapshot at {}: {}", header.height, e);
+            }
+        }
+
+        resp
     }
 }
 
@@ -286,6 +317,7 @@ pub fn deliver_tx(
     if td_height > CFG.checkpoint.check_tx_size_height && tx.len() > TX_SIZE {
         resp.log = format!("Transaction too large:{}", tx.len());
         resp.code = 1;
+        set_deliver_tx_error(&mut resp, TxErrorCode::TxTooLarge);
         return resp;
     }
 
@@ -302,6 +334,10 @@ pub fn deliver_tx(
                             if body_signatures.len() > 1 {
                                 "too many body_signatures".clone_into(&mut resp.log);
                                 resp.code = 1;
+                                set_deliver_tx_error(
+                                    &mut resp,
+                                    TxErrorCode::TooManySignatures,
+                                );
                                 return resp;
                             }
                         }
@@ -311,12 +347,14 @@ pub fn deliver_tx(
                     if signatures.len() > 1 {
                         "Too many signatures".clone_into(&mut resp.log);
                         resp.code = 1;
+                        set_deliver_tx_error(&mut resp, TxErrorCode::TooManySignatures);
                         return resp;
                     }
 
                     if tx.pubkey_sign_map.len() > 1 {
                         "too many pubkey_sign_map".clone_into(&mut resp.log);
                         resp.code = 1;
+                        set_deliver_tx_error(&mut resp, TxErrorCode::TooManySignatures);
                         return resp;
                     }
                 }
@@ -325,7 +363,8 @@ pub fn deliver_tx(
                     TX_HISTORY.write().set_value(txhash, Default::default());
                 });
 
-                if tx.valid_in_abci() {
+                let checked = tx.check_in_abci();
+                if checked.is_ok() {
                     // Log print for monitor purpose
                     if td_height < CFG.checkpoint.evm_first_block_height {
                         info!(target: "abciapp",
@@ -348,10 +387,12 @@ pub fn deliver_tx(
                         if is_convert_account(&tx) {
                             resp.code = 2;
                             "EVM is disabled".clone_into(&mut resp.log);
+                            set_deliver_tx_error(&mut resp, TxErrorCode::EvmDisabled);
                             return resp;
                         } else if let Err(e) = s.la.write().cache_transaction(tx) {
                             resp.code = 1;
                             resp.log = e.to_string();
+                            set_deliver_tx_error(&mut resp, e.code);
                         }
                     } else if is_convert_account(&tx) {
                         match s.account_base_app.write().deliver_findora_tx(&tx) {
@@ -378,27 +419,36 @@ pub fn deliver_tx(
                                 resp.log = format!(
                                     "deliver convert account tx failed: {err:?}"
                                 );
+                                set_deliver_tx_error(
+                                    &mut resp,
+                                    TxErrorCode::EvmCheckFailed,
+                                );
                                 return resp;
                             }
                         }
 
-                        if s.la.write().cache_transaction(tx).is_ok() {
-                            s.account_base_app
-                                .read()
-                                .deliver_state
-                                .state
-                                .write()
-                                .commit_session();
-                            s.account_base_app
-                                .read()
-                                .deliver_state
-                                .db
-                                .write()
-                                .commit_session();
-                            return resp;
-                        } else if td_height > CFG.checkpoint.fix_exec_code {
-                            resp.code = 1;
-                            "cache_transaction failed".clone_into(&mut resp.log);
+                        match s.la.write().cache_transaction(tx) {
+                            Ok(_) => {
+                                s.account_base_app
+                                    .read()
+                                    .deliver_state
+                                    .state
+                                    .write()
+                                    .commit_session();
+                                s.account_base_app
+                                    .read()
+                                    .deliver_state
+                                    .db
+                                    .write()
+                                    .commit_session();
+                                return resp;
+                            }
+                            Err(e) if td_height > CFG.checkpoint.fix_exec_code => {
+                                resp.code = 1;
+                                "cache_transaction failed".clone_into(&mut resp.log);
+                                set_deliver_tx_error(&mut resp, e.code);
+                            }
+                            Err(_) => {}
                         }
 
                         s.account_base_app
@@ -419,24 +469,32 @@ pub fn deliver_tx(
                                 if let Err(e) = s.la.write().cache_transaction(tx) {
                                     resp.code = 1;
                                     resp.log = e.to_string();
+                                    set_deliver_tx_error(&mut resp, e.code);
                                 }
                             }
                             Err(e) => {
                                 resp.code = 1;
-                                resp.log = e.to_string();
+                                resp.log = format!("Invalid signature: {e}");
+                                set_deliver_tx_error(
+                                    &mut resp,
+                                    TxErrorCode::InvalidSignature,
+                                );
                             }
                         }
                     } else if let Err(e) = s.la.write().cache_transaction(tx) {
                         resp.code = 1;
                         resp.log = e.to_string();
+                        set_deliver_tx_error(&mut resp, e.code);
                     }
-                } else {
+                } else if let Err(e) = checked {
                     resp.code = 1;
-                    "Should not appear in ABCI".clone_into(&mut resp.log);
+                    resp.log = e.to_string();
+                    set_deliver_tx_error(&mut resp, e.code);
                 }
             } else {
                 resp.code = 1;
                 "Invalid format".clone_into(&mut resp.log);
+                set_deliver_tx_error(&mut resp, TxErrorCode::InvalidFormat);
             }
 
             resp
@@ -447,6 +505,7 @@ pub fn deliver_tx(
             {
                 resp.code = 2;
                 "EVM is disabled".clone_into(&mut resp.log);
+                set_deliver_tx_error(&mut resp, TxErrorCode::EvmDisabled);
                 resp
             } else {
                 // Log print for monitor purpose
@@ -471,8 +530,9 @@ pub fn deliver_tx(
                             non_confidential_output,
                         ) {
                             drop(laa);
-                            if la.cache_transaction(tx).is_ok() {
-                                return resp;
+                            match la.cache_transaction(tx) {
+                                Ok(_) => return resp,
+                                Err(e) => set_deliver_tx_error(&mut resp, e.code),
                             }
                             resp.code = 1;
                             s.account_base_app
@@ -496,6 +556,7 @@ pub fn deliver_tx(
         TxCatalog::Unknown => {
             resp.code = 1;
             "Unknown transaction".clone_into(&mut resp.log);
+            set_deliver_tx_error(&mut resp, TxErrorCode::UnknownTx);
             resp
         }
     }
@@ -623,6 +684,16 @@ pub fn commit(s: &mut ABCISubmissionServer, req: &RequestCommit) -> ResponseComm
     // cache last block for QueryServer
     pnk!(api_cache::update_api_cache(&mut state));
 
+    // drop stale transactions in a bounded batch,
+    // the state commitment will not be affected
+    if let Some(keep_blocks) = CFG.prune_keep_blocks {
+        if let Err(e) = state.prune(keep_blocks, Some(PRUNE_BATCH_BLOCKS)) {
+            e.print(None);
+        }
+    }
+
+    metrics::observe_ledger(&state);
+
     // snapshot them finally
     let path = format!("{}/{}", &CFG.ledger_dir, &state.get_status().snapshot_file);
     pnk!(serde_json::to_vec(&state.get_status())
@@ -637,6 +708,8 @@ pub fn commit(s: &mut ABCISubmissionServer, req: &RequestCommit) -> ResponseComm
     let end_cs_hash = Local::now().timestamp_millis();
     info!(target: "abcitime", "commit height:{}, la_hash:{} cs_hash:{}", td_height, begin_cs_hash - begin_la_hash, end_cs_hash - begin_cs_hash);
 
+    metrics::observe_evm_block(&s.account_base_app.read());
+
     if CFG.checkpoint.disable_evm_block_height < td_height
         && td_height < CFG.checkpoint.enable_frc20_height
     {
@@ -644,6 +717,14 @@ pub fn commit(s: &mut ABCISubmissionServer, req: &RequestCommit) -> ResponseComm
     } else {
         r.set_data(app_hash("commit", td_height, la_hash, cs_hash));
     }
+
+    if state_sync::is_snapshot_height(td_height as u64) {
+        info_omit!(state_sync::take_snapshot(
+            td_height as u64,
+            r.get_data(),
+            &s.account_base_app.read()
+        ));
+    }
     let end = END_BLOCK_TIME.load(Ordering::Relaxed);
     let commit = Local::now().timestamp_millis();
     info!(target: "abcitime", "commit height:{}, commit:{}-end:{}={}", td_height, commit, end, commit - end);
@@ -126,6 +126,9 @@ fn gen_tendermint_attr_addr(tx: &Transaction) -> (Vec<TagAttr>, Vec<TagAttr>) {
                 Operation::UpdateMemo(d) => {
                     append_attr!(d);
                 }
+                Operation::UpdateIssuer(d) => {
+                    append_attr!(d);
+                }
                 _ => {}
             }
 
@@ -4,7 +4,7 @@
 
 use {
     crate::{
-        abci::server::callback::TENDERMINT_BLOCK_HEIGHT,
+        abci::{metrics, server::callback::TENDERMINT_BLOCK_HEIGHT},
         api::submission_server::SubmissionServer,
     },
     abci::{
@@ -28,6 +28,8 @@ use {
 };
 
 pub mod callback;
+pub mod native_bridge;
+pub mod state_sync;
 pub mod tx_sender;
 
 /// findora impl of tendermint abci
@@ -127,7 +129,10 @@ impl abci::Application for ABCISubmissionServer {
 
     #[inline(always)]
     fn check_tx(&mut self, req: &RequestCheckTx) -> ResponseCheckTx {
-        callback::check_tx(self, req)
+        let _timer = metrics::callback_timer("check_tx");
+        let resp = callback::check_tx(self, req);
+        metrics::observe_check_tx(resp.code);
+        resp
     }
 
     #[inline(always)]
@@ -137,21 +142,30 @@ impl abci::Application for ABCISubmissionServer {
 
     #[inline(always)]
     fn begin_block(&mut self, req: &RequestBeginBlock) -> ResponseBeginBlock {
+        let _timer = metrics::callback_timer("begin_block");
+        metrics::observe_begin_block();
         callback::begin_block(self, req)
     }
 
     #[inline(always)]
     fn deliver_tx(&mut self, req: &RequestDeliverTx) -> ResponseDeliverTx {
-        callback::deliver_tx(self, req)
+        let _timer = metrics::callback_timer("deliver_tx");
+        let resp = callback::deliver_tx(self, req);
+        metrics::observe_deliver_tx(&resp);
+        resp
     }
 
     #[inline(always)]
     fn end_block(&mut self, req: &RequestEndBlock) -> ResponseEndBlock {
-        callback::end_block(self, req)
+        let _timer = metrics::callback_timer("end_block");
+        let resp = callback::end_block(self, req);
+        metrics::observe_end_block();
+        resp
     }
 
     #[inline(always)]
     fn commit(&mut self, req: &RequestCommit) -> ResponseCommit {
+        let _timer = metrics::callback_timer("commit");
         callback::commit(self, req)
     }
 }
@@ -0,0 +1,128 @@
+//!
+//! # Supply reconciliation of the native asset bridge
+//!
+//! Custom assets converted natively from UTXOs are held by the xhub module on the EVM side,
+//! while the api cache accounts what has crossed the bridge on the UTXO side,
+//! so it only reconciles on nodes keeping the api cache since `native_asset_bridge_height`.
+//! The query is served through `abci_query`:
+//! - `/native_bridge/supply`, the request data is the asset type code in json
+//!
+
+use {
+    super::ABCISubmissionServer,
+    abci::{RequestQuery, ResponseQuery},
+    fp_types::U256,
+    ledger::{data_model::AssetTypeCode, store::api_cache::NativeBridgeSupply},
+    ruc::*,
+    serde::{Deserialize, Serialize},
+};
+
+/// Query path prefix of the native bridge
+pub const QUERY_PREFIX: &str = "/native_bridge/";
+
+/// Both sides of the supply of a custom asset
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SupplyReconciliation {
+    pub asset: AssetTypeCode,
+    /// converted from UTXOs, as accounted by the api cache
+    pub to_account: u128,
+    /// withdrawn to UTXOs, as accounted by the api cache
+    pub to_utxo: u128,
+    /// held by EVM accounts, as accounted by the xhub module
+    pub account_supply: U256,
+    /// whether both sides agree
+    pub balanced: bool,
+}
+
+impl SupplyReconciliation {
+    fn new(
+        asset: AssetTypeCode,
+        bridged: NativeBridgeSupply,
+        account_supply: U256,
+    ) -> Self {
+        SupplyReconciliation {
+            asset,
+            to_account: bridged.to_account,
+            to_utxo: bridged.to_utxo,
+            account_supply,
+            balanced: account_supply == U256::from(bridged.in_account()),
+        }
+    }
+}
+
+fn supply(
+    s: &ABCISubmissionServer,
+    asset: AssetTypeCode,
+) -> Result<SupplyReconciliation> {
+    let bridged = {
+        let la = s.la.read();
+        let ledger = la.get_committed_state().read();
+        let api_cache = ledger
+            .api_cache
+            .as_ref()
+            .c(d!("the api cache is disabled"))?;
+        api_cache
+            .native_bridge_supply
+            .get(&asset)
+            .unwrap_or_default()
+    };
+    let account_supply = s
+        .account_base_app
+        .read()
+        .native_asset_supply(asset.val)
+        .c(d!())?;
+
+    Ok(SupplyReconciliation::new(asset, bridged, account_supply))
+}
+
+/// Serve the queries of the native bridge
+pub fn query(s: &ABCISubmissionServer, req: &RequestQuery) -> ResponseQuery {
+    let mut resp = ResponseQuery::new();
+
+    let res = match req.path.trim_start_matches(QUERY_PREFIX) {
+        "supply" => serde_json::from_slice::<AssetTypeCode>(&req.data)
+            .c(d!())
+            .and_then(|asset| supply(s, asset).c(d!()))
+            .and_then(|r| serde_json::to_vec(&r).c(d!())),
+        p => Err(eg!(format!("unknown path: {p}"))),
+    };
+
+    match res {
+        Ok(v) => resp.value = v,
+        Err(e) => {
+            resp.code = 1;
+            resp.log = e.to_string();
+        }
+    }
+
+    resp
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_supply_reconciliation() {
+        let asset = AssetTypeCode::default();
+        let bridged = NativeBridgeSupply {
+            to_account: 10,
+            to_utxo: 4,
+        };
+
+        let r = SupplyReconciliation::new(asset, bridged, U256::from(6));
+        assert!(r.balanced);
+        assert_eq!((r.to_account, r.to_utxo), (10, 4));
+
+        // a withdrawal missing on either side is reported
+        assert!(!SupplyReconciliation::new(asset, bridged, U256::from(10)).balanced);
+        assert!(
+            !SupplyReconciliation::new(
+                asset,
+                NativeBridgeSupply::default(),
+                U256::from(6)
+            )
+            .balanced
+        );
+    }
+}
@@ -0,0 +1,707 @@
+//!
+//! # Application snapshots for state sync
+//!
+//! A snapshot is a consistent copy of everything under the `ledger-dir`
+//! (`LedgerStatus`, staking, api cache, the merkle trees and the chain state of baseapp),
+//! frozen at the end of a commit and split into hash-verified chunks.
+//!
+//! Only hard links and the rocksdb checkpoints of baseapp are created in `commit`,
+//! all the copying runs in the background:
+//! the files that may be written in place are detached from the ledger first,
+//! a snapshot is dropped if any of them has changed since the commit.
+//!
+//! `list_snapshots`, `load_snapshot_chunk` and the `Restorer` follow the
+//! `ListSnapshots`/`LoadSnapshotChunk`/`OfferSnapshot`/`ApplySnapshotChunk` methods of ABCI 0.34,
+//! but tendermint 0.33 (see `tools/download_tendermint.sh`) has no state-sync messages,
+//! so they are served through `abci_query` until tendermint is upgraded:
+//! - `/state_sync/snapshots`, the list of available snapshots in json
+//! - `/state_sync/chunk`, one chunk, the request data is `{"height":H,"index":I}`
+//!
+//! `abcid --state-sync-peers` downloads the newest snapshot from the peers,
+//! checks its app hash against a header verified by the light client,
+//! then unpacks it beside the ledger dir and renames it into place.
+//!
+//! The data of tendermint is not restored, tendermint refuses to start
+//! with an application ahead of its block store, so its data dir must be
+//! bootstrapped separately at the height of the snapshot.
+//!
+
+#[cfg(test)]
+mod test;
+
+use {
+    crate::abci::POOL,
+    abci::{RequestQuery, ResponseQuery},
+    baseapp::BaseApp,
+    config::abci::global_cfg::CFG,
+    cryptohash::sha256,
+    ruc::*,
+    serde::{Deserialize, Serialize},
+    std::{
+        convert::TryFrom,
+        fs::{self, File},
+        io::{BufReader, BufWriter, Read, Write},
+        path::{Component, Path, PathBuf},
+        str::FromStr,
+        time::{Duration, SystemTime},
+    },
+    tendermint_light_client::{
+        builder::LightClientBuilder,
+        light_client::Options,
+        store::memory::MemoryStore,
+        types::{Height, PeerId, TrustThreshold},
+    },
+};
+
+/// Query path prefix of the snapshot service
+pub const QUERY_PREFIX: &str = "/state_sync/";
+
+/// Version of the chunk encoding
+const SNAPSHOT_FORMAT: u32 = 1;
+
+const CHUNK_SIZE: usize = 16 * 1024 * 1024;
+
+const SNAPSHOT_INFO: &str = "snapshot.json";
+
+// the suffix of the staging dir of a restore, beside the ledger dir
+const RESTORE_SUFFIX: &str = ".restore";
+
+const PEER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Metadata of a snapshot, it is stored along with the chunks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub height: u64,
+    pub format: u32,
+    /// in upper-hex format
+    pub app_hash: String,
+    /// sha256 of each chunk, in hex format
+    pub chunk_hashes: Vec<String>,
+    /// sha256 of all the chunk hashes, in hex format
+    pub hash: String,
+}
+
+impl Snapshot {
+    fn compute_hash(chunk_hashes: &[String]) -> String {
+        hex::encode(sha256::hash(chunk_hashes.concat().as_bytes()).0)
+    }
+
+    /// Check the integrity of a chunk.
+    pub fn verify_chunk(&self, index: usize, chunk: &[u8]) -> Result<()> {
+        let expected = self.chunk_hashes.get(index).c(d!("chunk index overflow"))?;
+        if *expected == hex::encode(sha256::hash(chunk).0) {
+            Ok(())
+        } else {
+            Err(eg!(format!(
+                "chunk {index} of snapshot {} is broken",
+                self.height
+            )))
+        }
+    }
+
+    fn verify(&self) -> Result<()> {
+        if SNAPSHOT_FORMAT != self.format {
+            return Err(eg!(format!("unsupported snapshot format: {}", self.format)));
+        }
+        if Self::compute_hash(&self.chunk_hashes) != self.hash {
+            return Err(eg!("invalid snapshot hash"));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ChunkReq {
+    height: u64,
+    index: usize,
+}
+
+#[inline(always)]
+fn snapshot_root() -> PathBuf {
+    Path::new(&CFG.ledger_dir).join("state_sync")
+}
+
+#[inline(always)]
+fn chunk_path(dir: &Path, index: usize) -> PathBuf {
+    dir.join(format!("chunk_{index}"))
+}
+
+/// Whether a snapshot should be taken after committing this height
+#[inline(always)]
+pub fn is_snapshot_height(height: u64) -> bool {
+    0 < CFG.state_sync_itv && 0 < height && 0 == height % CFG.state_sync_itv
+}
+
+/// Take a snapshot of the ledger dir, this should be called at the end of `commit`,
+/// when there is no any pending write.
+///
+/// Only the freezing runs here, the chunks are encoded in the background.
+pub fn take_snapshot(height: u64, app_hash: &[u8], base_app: &BaseApp) -> Result<()> {
+    ledger::store::fbnc::flush_data();
+
+    let root = snapshot_root();
+    let tmp_dir = root.join(format!("{height}.tmp"));
+    let linked = freeze(Path::new(&CFG.ledger_dir), &root, &tmp_dir, |dir| {
+        base_app.create_checkpoint(dir)
+    })
+    .c(d!())?;
+
+    let app_hash = hex::encode_upper(app_hash);
+    let cap = CFG.state_sync_cap as usize;
+    POOL.spawn_ok(async move {
+        if let Err(e) =
+            pack_snapshot(&root, height, app_hash, tmp_dir.clone(), linked, cap)
+        {
+            omit!(fs::remove_dir_all(&tmp_dir));
+            tracing::error!(target: "abciapp", "state sync snapshot {height}: {e}");
+        }
+    });
+
+    Ok(())
+}
+
+// A ledger file that is hard-linked in `commit`, with its state at that time.
+struct Linked {
+    path: PathBuf,
+    len: u64,
+    modified: SystemTime,
+}
+
+impl Linked {
+    fn new(path: PathBuf, meta: &fs::Metadata) -> Result<Self> {
+        Ok(Linked {
+            path,
+            len: meta.len(),
+            modified: meta.modified().c(d!())?,
+        })
+    }
+
+    fn unchanged(&self, meta: &fs::Metadata) -> bool {
+        self.len == meta.len() && meta.modified().map_or(false, |m| m == self.modified)
+    }
+}
+
+// Hard-link the ledger files into `tmp_dir/files`,
+// the rocksdb instances of baseapp are written by `checkpoint` instead.
+//
+// The files that may be written in place later are returned,
+// rocksdb tables are never changed once written, so they are not.
+fn freeze(
+    ledger_dir: &Path,
+    root: &Path,
+    tmp_dir: &Path,
+    checkpoint: impl FnOnce(&Path) -> Result<()>,
+) -> Result<Vec<Linked>> {
+    let files_dir = tmp_dir.join("files");
+    omit!(fs::remove_dir_all(tmp_dir));
+    fs::create_dir_all(&files_dir).c(d!())?;
+
+    let excluded = BaseApp::db_dirs()
+        .iter()
+        .map(|d| ledger_dir.join(d))
+        .chain([root.to_path_buf()])
+        .collect::<Vec<_>>();
+
+    let mut linked = vec![];
+    for f in collect_files(ledger_dir, &excluded).c(d!())? {
+        let src = ledger_dir.join(&f);
+        let dst = files_dir.join(&f);
+        fs::create_dir_all(dst.parent().c(d!())?).c(d!())?;
+        fs::hard_link(&src, &dst).c(d!())?;
+        if f.extension().map_or(true, |e| e != "sst") {
+            let meta = fs::metadata(&dst).c(d!())?;
+            linked.push(Linked::new(f, &meta).c(d!())?);
+        }
+    }
+
+    checkpoint(&files_dir).c(d!())?;
+
+    Ok(linked)
+}
+
+// Replace the hard links of the files that may be written in place with copies,
+// fail if any of them has changed since it was linked.
+fn detach(files_dir: &Path, linked: &[Linked]) -> Result<()> {
+    for f in linked.iter() {
+        let link = files_dir.join(&f.path);
+        let mut copy = link.clone().into_os_string();
+        copy.push(".detached");
+
+        fs::copy(&link, &copy).c(d!())?;
+        // the link shares its inode with the ledger file, so any write shows here
+        if !f.unchanged(&fs::metadata(&link).c(d!())?) {
+            return Err(eg!(format!(
+                "{} has changed since the commit",
+                f.path.display()
+            )));
+        }
+        fs::rename(&copy, &link).c(d!())?;
+    }
+    Ok(())
+}
+
+// Encode the frozen files into chunks,
+// each file is encoded as: `[u32 path len][path][u64 data len][data]`.
+fn pack_snapshot(
+    root: &Path,
+    height: u64,
+    app_hash: String,
+    tmp_dir: PathBuf,
+    linked: Vec<Linked>,
+    cap: usize,
+) -> Result<()> {
+    let files_dir = tmp_dir.join("files");
+    detach(&files_dir, &linked).c(d!())?;
+
+    let files = collect_files(&files_dir, &[]).c(d!())?;
+    let mut writer = ChunkWriter::new(tmp_dir.clone());
+
+    for f in files.iter() {
+        let path = f.to_str().c(d!())?.as_bytes();
+        let mut data = File::open(files_dir.join(f)).c(d!())?;
+        let len = data.metadata().c(d!())?.len();
+        writer
+            .write_all(&(path.len() as u32).to_le_bytes())
+            .c(d!())?;
+        writer.write_all(path).c(d!())?;
+        writer.write_all(&len.to_le_bytes()).c(d!())?;
+        std::io::copy(&mut data, &mut writer).c(d!())?;
+    }
+    let chunk_hashes = writer.finish().c(d!())?;
+    fs::remove_dir_all(&files_dir).c(d!())?;
+
+    let snapshot = Snapshot {
+        height,
+        format: SNAPSHOT_FORMAT,
+        app_hash,
+        hash: Snapshot::compute_hash(&chunk_hashes),
+        chunk_hashes,
+    };
+    serde_json::to_vec_pretty(&snapshot)
+        .c(d!())
+        .and_then(|s| fs::write(tmp_dir.join(SNAPSHOT_INFO), s).c(d!()))?;
+
+    fs::rename(&tmp_dir, root.join(height.to_string())).c(d!())?;
+
+    // clean the oldest ones
+    let list = list_snapshots_in(root).c(d!())?;
+    for s in list.iter().skip(cap) {
+        omit!(fs::remove_dir_all(root.join(s.height.to_string())));
+    }
+
+    Ok(())
+}
+
+fn collect_files(base: &Path, excluded: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut res = vec![];
+    let mut dirs = vec![base.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir).c(d!())? {
+            let path = entry.c(d!())?.path();
+            let name = path.file_name().c(d!())?.to_string_lossy();
+            // the lock files of rocksdb should not be part of the state
+            if excluded.contains(&path) || name == "LOCK" {
+                continue;
+            }
+            if path.is_dir() {
+                dirs.push(path);
+            } else {
+                res.push(path.strip_prefix(base).c(d!())?.to_path_buf());
+            }
+        }
+    }
+
+    res.sort();
+    Ok(res)
+}
+
+struct ChunkWriter {
+    dir: PathBuf,
+    buf: Vec<u8>,
+    hashes: Vec<String>,
+}
+
+impl ChunkWriter {
+    fn new(dir: PathBuf) -> Self {
+        ChunkWriter {
+            dir,
+            buf: Vec::with_capacity(CHUNK_SIZE),
+            hashes: vec![],
+        }
+    }
+
+    fn dump(&mut self) -> std::io::Result<()> {
+        let path = chunk_path(&self.dir, self.hashes.len());
+        fs::write(path, &self.buf)?;
+        self.hashes.push(hex::encode(sha256::hash(&self.buf).0));
+        self.buf.clear();
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<Vec<String>> {
+        if !self.buf.is_empty() || self.hashes.is_empty() {
+            self.dump().c(d!())?;
+        }
+        Ok(self.hashes)
+    }
+}
+
+impl Write for ChunkWriter {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        let n = data.len().min(CHUNK_SIZE - self.buf.len());
+        self.buf.extend_from_slice(&data[..n]);
+        if CHUNK_SIZE == self.buf.len() {
+            self.dump()?;
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// All the available snapshots, the newest one comes first.
+#[inline(always)]
+pub fn list_snapshots() -> Result<Vec<Snapshot>> {
+    list_snapshots_in(&snapshot_root())
+}
+
+fn list_snapshots_in(root: &Path) -> Result<Vec<Snapshot>> {
+    if !root.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut res = vec![];
+    for entry in fs::read_dir(root).c(d!())? {
+        let path = entry.c(d!())?.path().join(SNAPSHOT_INFO);
+        if let Ok(s) = read_snapshot(&path) {
+            res.push(s);
+        }
+    }
+    res.sort_by(|a, b| b.height.cmp(&a.height));
+
+    Ok(res)
+}
+
+fn read_snapshot(path: &Path) -> Result<Snapshot> {
+    fs::read(path)
+        .c(d!())
+        .and_then(|s| serde_json::from_slice(&s).c(d!()))
+}
+
+/// Read a chunk of a local snapshot.
+pub fn load_snapshot_chunk(height: u64, format: u32, index: usize) -> Result<Vec<u8>> {
+    if SNAPSHOT_FORMAT != format {
+        return Err(eg!(format!("unsupported snapshot format: {format}")));
+    }
+    fs::read(chunk_path(&snapshot_root().join(height.to_string()), index)).c(d!())
+}
+
+/// Serve the snapshots to the peers.
+pub fn query(req: &RequestQuery) -> ResponseQuery {
+    let mut resp = ResponseQuery::new();
+
+    let res = match req.path.trim_start_matches(QUERY_PREFIX) {
+        "snapshots" => list_snapshots().and_then(|l| serde_json::to_vec(&l).c(d!())),
+        "chunk" => serde_json::from_slice::<ChunkReq>(&req.data)
+            .c(d!())
+            .and_then(|r| {
+                load_snapshot_chunk(r.height, SNAPSHOT_FORMAT, r.index).c(d!())
+            }),
+        p => Err(eg!(format!("unknown path: {p}"))),
+    };
+
+    match res {
+        Ok(v) => resp.value = v,
+        Err(e) => {
+            resp.code = 1;
+            resp.log = e.to_string();
+        }
+    }
+
+    resp
+}
+
+/// The restoring side of state sync,
+/// a snapshot is offered first, then its chunks are applied one by one.
+///
+/// The chunks are kept and unpacked in a staging dir beside the ledger dir,
+/// which is renamed into place after everything has been verified and written,
+/// so a failed restore leaves the ledger dir untouched.
+pub struct Restorer {
+    ledger_dir: PathBuf,
+    staging: PathBuf,
+    snapshot: Option<Snapshot>,
+    applied: Vec<bool>,
+}
+
+impl Restorer {
+    /// The ledger dir should be empty.
+    pub fn new(ledger_dir: &Path) -> Result<Self> {
+        let root = ledger_dir.join("state_sync");
+        fs::create_dir_all(ledger_dir).c(d!())?;
+        for entry in fs::read_dir(ledger_dir).c(d!())? {
+            if entry.c(d!())?.path() != root {
+                return Err(eg!("the ledger dir is not empty"));
+            }
+        }
+
+        let mut staging = ledger_dir.as_os_str().to_owned();
+        staging.push(RESTORE_SUFFIX);
+
+        Ok(Restorer {
+            ledger_dir: ledger_dir.to_path_buf(),
+            staging: PathBuf::from(staging),
+            snapshot: None,
+            applied: vec![],
+        })
+    }
+
+    /// Accept a snapshot whose app hash matches the trusted one,
+    /// the chunks of a previous offer are dropped.
+    pub fn offer_snapshot(
+        &mut self,
+        snapshot: &Snapshot,
+        trusted_app_hash: &str,
+    ) -> Result<()> {
+        snapshot.verify().c(d!())?;
+        if !snapshot.app_hash.eq_ignore_ascii_case(trusted_app_hash) {
+            return Err(eg!(format!(
+                "app hash mismatch at height {}: {} != {}",
+                snapshot.height, snapshot.app_hash, trusted_app_hash
+            )));
+        }
+
+        omit!(fs::remove_dir_all(&self.staging));
+        fs::create_dir_all(&self.staging).c(d!())?;
+        self.applied = vec![false; snapshot.chunk_hashes.len()];
+        self.snapshot = Some(snapshot.clone());
+
+        Ok(())
+    }
+
+    /// Verify and store a chunk of the offered snapshot,
+    /// return `true` once the snapshot has been installed.
+    pub fn apply_snapshot_chunk(&mut self, index: usize, chunk: &[u8]) -> Result<bool> {
+        let snapshot = self.snapshot.as_ref().c(d!("no snapshot is offered"))?;
+        snapshot.verify_chunk(index, chunk).c(d!())?;
+
+        fs::write(chunk_path(&self.staging, index), chunk).c(d!())?;
+        self.applied[index] = true;
+
+        if self.applied.iter().all(|a| *a) {
+            let target = self.staging.join("ledger");
+            let installed = self.unpack(&target).and_then(|_| {
+                fs::remove_dir_all(&self.ledger_dir).c(d!())?;
+                fs::rename(&target, &self.ledger_dir).c(d!())
+            });
+            if installed.is_err() {
+                omit!(fs::remove_dir_all(&target));
+            }
+            installed.c(d!())?;
+
+            fs::remove_dir_all(&self.staging).c(d!())?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    fn unpack(&self, target: &Path) -> Result<()> {
+        omit!(fs::remove_dir_all(target));
+        let snapshot = self.snapshot.as_ref().c(d!())?;
+        let chunks = (0..snapshot.chunk_hashes.len())
+            .map(|i| File::open(chunk_path(&self.staging, i)))
+            .collect::<std::io::Result<Vec<_>>>()
+            .c(d!())?;
+        let mut reader = BufReader::new(
+            chunks
+                .into_iter()
+                .fold(Box::new(std::io::empty()) as Box<dyn Read>, |acc, f| {
+                    Box::new(acc.chain(f))
+                }),
+        );
+
+        let mut len = [0u8; 4];
+        while read_exact_or_eof(&mut reader, &mut len).c(d!())? {
+            let mut path = vec![0u8; u32::from_le_bytes(len) as usize];
+            reader.read_exact(&mut path).c(d!())?;
+            let path = PathBuf::from(String::from_utf8(path).c(d!())?);
+            if path.is_absolute()
+                || path.components().any(|c| matches!(c, Component::ParentDir))
+            {
+                return Err(eg!(format!("invalid path: {}", path.display())));
+            }
+
+            let mut size = [0u8; 8];
+            reader.read_exact(&mut size).c(d!())?;
+            let size = u64::from_le_bytes(size);
+
+            let dst = target.join(path);
+            fs::create_dir_all(dst.parent().c(d!())?).c(d!())?;
+            let mut f = BufWriter::new(File::create(dst).c(d!())?);
+            let n = std::io::copy(&mut (&mut reader).take(size), &mut f).c(d!())?;
+            if n != size {
+                return Err(eg!("unexpected end of snapshot"));
+            }
+            f.flush().c(d!())?;
+        }
+
+        Ok(())
+    }
+}
+
+// `false` if no more data
+fn read_exact_or_eof(r: &mut impl Read, buf: &mut [u8]) -> Result<bool> {
+    let mut n = 0;
+    while n < buf.len() {
+        match r.read(&mut buf[n..]).c(d!())? {
+            0 if 0 == n => return Ok(false),
+            0 => return Err(eg!("unexpected end of snapshot")),
+            i => n += i,
+        }
+    }
+    Ok(true)
+}
+
+/// The trusted root of the light client.
+pub struct TrustOptions {
+    pub height: u64,
+    /// hash of the header at `height`, in hex format
+    pub hash: String,
+    pub period: Duration,
+}
+
+/// Restore the newest snapshot served by the peers into an empty ledger dir,
+/// `peers` are the tendermint RPC addresses, such as `http://1.2.3.4:26657`.
+pub fn sync_from_peers(
+    peers: &[String],
+    trust: &TrustOptions,
+    ledger_dir: &str,
+) -> Result<Snapshot> {
+    let mut restorer = Restorer::new(Path::new(ledger_dir)).c(d!())?;
+
+    // the same snapshot is usually served by several peers
+    let mut offers: Vec<(Snapshot, Vec<&String>)> = vec![];
+    for peer in peers.iter() {
+        let list = query_peer(peer, "snapshots", &[])
+            .and_then(|v| serde_json::from_slice::<Vec<Snapshot>>(&v).c(d!()));
+        match list {
+            Ok(list) => {
+                for s in list {
+                    match offers.iter_mut().find(|(o, _)| *o == s) {
+                        Some((_, providers)) => providers.push(peer),
+                        None => offers.push((s, vec![peer])),
+                    }
+                }
+            }
+            Err(e) => e.print(None),
+        }
+    }
+    offers.sort_by(|a, b| b.0.height.cmp(&a.0.height));
+
+    for (snapshot, providers) in offers.iter() {
+        let offered = verified_app_hash(providers[0], trust, snapshot.height)
+            .and_then(|app_hash| restorer.offer_snapshot(snapshot, &app_hash));
+        if let Err(e) = offered {
+            e.print(None);
+            continue;
+        }
+
+        for index in 0..snapshot.chunk_hashes.len() {
+            let req = serde_json::to_vec(&ChunkReq {
+                height: snapshot.height,
+                index,
+            })
+            .c(d!())?;
+
+            // a broken chunk is fetched again from the next provider
+            let applied = providers
+                .iter()
+                .cycle()
+                .skip(index)
+                .take(providers.len())
+                .any(|peer| {
+                    match query_peer(peer, "chunk", &req)
+                        .and_then(|chunk| restorer.apply_snapshot_chunk(index, &chunk))
+                    {
+                        Ok(_) => true,
+                        Err(e) => {
+                            e.print(None);
+                            false
+                        }
+                    }
+                });
+            if !applied {
+                return Err(eg!(format!(
+                    "chunk {index} of snapshot {} is not available",
+                    snapshot.height
+                )));
+            }
+        }
+
+        return Ok(snapshot.clone());
+    }
+
+    Err(eg!("no acceptable snapshot from the peers"))
+}
+
+fn query_peer(peer: &str, path: &str, data: &[u8]) -> Result<Vec<u8>> {
+    let resp = attohttpc::get(format!("{}/abci_query", peer.trim_end_matches('/')))
+        .param("path", format!("\"{QUERY_PREFIX}{path}\""))
+        .param("data", format!("0x{}", hex::encode(data)))
+        .timeout(PEER_TIMEOUT)
+        .send()
+        .c(d!())?
+        .json::<serde_json::Value>()
+        .c(d!())?;
+
+    let resp = &resp["result"]["response"];
+    if 0 != resp["code"].as_u64().unwrap_or(0) {
+        return Err(eg!(format!("{peer}: {}", resp["log"])));
+    }
+    base64::decode(resp["value"].as_str().unwrap_or_default()).c(d!())
+}
+
+// The app hash committed at `height` is recorded in the header of the next height.
+fn verified_app_hash(peer: &str, trust: &TrustOptions, height: u64) -> Result<String> {
+    let status = attohttpc::get(format!("{}/status", peer.trim_end_matches('/')))
+        .timeout(PEER_TIMEOUT)
+        .send()
+        .c(d!())?
+        .json::<serde_json::Value>()
+        .c(d!())?;
+    let peer_id = status["result"]["node_info"]["id"]
+        .as_str()
+        .c(d!("invalid status"))
+        .and_then(|id| PeerId::from_str(id).c(d!()))?;
+
+    let options = Options {
+        trust_threshold: TrustThreshold::default(),
+        trusting_period: trust.period,
+        clock_drift: Duration::from_secs(5),
+    };
+    let mut instance = LightClientBuilder::prod(
+        peer_id,
+        tendermint_rpc::HttpClient::new(peer).c(d!())?,
+        Box::new(MemoryStore::new()),
+        options,
+        Some(PEER_TIMEOUT),
+    )
+    .trust_primary_at(
+        Height::try_from(trust.height).c(d!())?,
+        trust.hash.parse().c(d!("invalid trust hash"))?,
+    )
+    .c(d!())?
+    .build();
+
+    let block = instance
+        .light_client
+        .verify_to_target(Height::try_from(height + 1).c(d!())?, &mut instance.state)
+        .c(d!())?;
+
+    Ok(hex::encode_upper(
+        block.signed_header.header.app_hash.value(),
+    ))
+}
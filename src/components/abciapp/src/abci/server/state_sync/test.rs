@@ -0,0 +1,109 @@
+use super::*;
+
+fn write(path: &Path, data: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, data).unwrap();
+}
+
+#[test]
+fn snapshot_and_restore() {
+    let ledger_dir = tempfile::tempdir().unwrap();
+    let ledger_dir = ledger_dir.path();
+    let root = ledger_dir.join("state_sync");
+    write(&ledger_dir.join("status.json"), "{}");
+    write(&ledger_dir.join("__bnc__/utxo"), "utxo");
+    // the live db is never copied, only its checkpoint is
+    write(&ledger_dir.join("state.db/CURRENT"), "live");
+    write(&ledger_dir.join("state.db/LOCK"), "");
+
+    let tmp_dir = root.join("10.tmp");
+    let linked = freeze(ledger_dir, &root, &tmp_dir, |dir| {
+        write(&dir.join("state.db/CURRENT"), "checkpoint");
+        Ok(())
+    })
+    .unwrap();
+    // replacing a file after the commit does not touch its link
+    fs::remove_file(ledger_dir.join("status.json")).unwrap();
+    write(&ledger_dir.join("status.json"), "{\"height\":11}");
+    pack_snapshot(&root, 10, "AB01".to_owned(), tmp_dir, linked, 3).unwrap();
+
+    let list = list_snapshots_in(&root).unwrap();
+    assert_eq!(1, list.len());
+    let snapshot = &list[0];
+    assert_eq!(10, snapshot.height);
+    let chunks = (0..snapshot.chunk_hashes.len())
+        .map(|i| fs::read(chunk_path(&root.join("10"), i)).unwrap())
+        .collect::<Vec<_>>();
+
+    let target = tempfile::tempdir().unwrap();
+    let mut restorer = Restorer::new(target.path()).unwrap();
+    assert!(restorer.apply_snapshot_chunk(0, &chunks[0]).is_err());
+    assert!(restorer.offer_snapshot(snapshot, "AB02").is_err());
+    restorer.offer_snapshot(snapshot, "ab01").unwrap();
+    assert!(restorer.apply_snapshot_chunk(0, b"broken").is_err());
+
+    let last = chunks.len() - 1;
+    for (i, chunk) in chunks.iter().enumerate() {
+        assert_eq!(i == last, restorer.apply_snapshot_chunk(i, chunk).unwrap());
+    }
+
+    let read = |p: &str| fs::read_to_string(target.path().join(p)).unwrap();
+    assert_eq!("{}", read("status.json"));
+    assert_eq!("utxo", read("__bnc__/utxo"));
+    assert_eq!("checkpoint", read("state.db/CURRENT"));
+    assert!(!target.path().join("state.db/LOCK").exists());
+    assert!(!restorer.staging.exists());
+}
+
+#[test]
+fn written_after_commit() {
+    let ledger_dir = tempfile::tempdir().unwrap();
+    let ledger_dir = ledger_dir.path();
+    let root = ledger_dir.join("state_sync");
+    write(&ledger_dir.join("__bnc__/utxo"), "utxo");
+
+    let tmp_dir = root.join("10.tmp");
+    let linked = freeze(ledger_dir, &root, &tmp_dir, |_| Ok(())).unwrap();
+    write(&ledger_dir.join("__bnc__/utxo"), "utxo, spent");
+
+    assert!(pack_snapshot(&root, 10, "AB01".to_owned(), tmp_dir, linked, 3).is_err());
+    assert!(list_snapshots_in(&root).unwrap().is_empty());
+}
+
+#[test]
+fn broken_snapshot() {
+    // a chunk that escapes the ledger dir after a valid file
+    let mut chunk = vec![];
+    for (path, data) in [("status.json", "{}"), ("../escaped", "x")] {
+        chunk.extend_from_slice(&(path.len() as u32).to_le_bytes());
+        chunk.extend_from_slice(path.as_bytes());
+        chunk.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        chunk.extend_from_slice(data.as_bytes());
+    }
+    let chunk_hashes = vec![hex::encode(sha256::hash(&chunk).0)];
+    let snapshot = Snapshot {
+        height: 10,
+        format: SNAPSHOT_FORMAT,
+        app_hash: "AB01".to_owned(),
+        hash: Snapshot::compute_hash(&chunk_hashes),
+        chunk_hashes,
+    };
+
+    let target = tempfile::tempdir().unwrap();
+    let mut restorer = Restorer::new(target.path()).unwrap();
+    restorer.offer_snapshot(&snapshot, "AB01").unwrap();
+    assert!(restorer.apply_snapshot_chunk(0, &chunk).is_err());
+
+    // nothing is half-written into the ledger dir
+    assert_eq!(0, fs::read_dir(target.path()).unwrap().count());
+    assert!(!restorer.staging.join("ledger").exists());
+}
+
+#[test]
+fn restore_into_non_empty_dir() {
+    let target = tempfile::tempdir().unwrap();
+    assert!(Restorer::new(target.path()).is_ok());
+
+    write(&target.path().join("status.json"), "{}");
+    assert!(Restorer::new(target.path()).is_err());
+}
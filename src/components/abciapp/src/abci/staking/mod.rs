@@ -453,13 +453,12 @@ pub fn system_mint_pay(
         account_mint
             .iter()
             .map(|mint| {
-                MintEntry::new(
-                    MintKind::Other,
-                    mint.target,
-                    None,
-                    mint.amount,
-                    mint.asset,
-                )
+                let kind = if mint.asset == ASSET_TYPE_FRA {
+                    MintKind::Other
+                } else {
+                    MintKind::FromAccount
+                };
+                MintEntry::new(kind, mint.target, None, mint.amount, mint.asset)
             })
             .collect::<Vec<MintEntry>>()
     } else {
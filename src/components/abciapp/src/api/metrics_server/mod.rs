@@ -0,0 +1,42 @@
+//!
+//! # Prometheus metrics listener
+//!
+
+use {
+    actix_web::{error, web, App, HttpResponse, HttpServer},
+    prometheus::{Encoder, TextEncoder},
+    ruc::*,
+    tracing::info,
+};
+
+/// Encode all registered metrics in the prometheus text format
+async fn metrics() -> actix_web::Result<HttpResponse> {
+    let encoder = TextEncoder::new();
+    let mut buf = vec![];
+    encoder
+        .encode(&prometheus::gather(), &mut buf)
+        .map_err(error::ErrorInternalServerError)?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(encoder.format_type())
+        .body(buf))
+}
+
+/// Structures exposed to the outside world
+pub struct MetricsApi;
+
+impl MetricsApi {
+    /// Create metrics api, serving `/metrics`
+    pub fn create(host: &str, port: u16) -> Result<MetricsApi> {
+        let _ = actix_rt::System::new("findora metrics");
+
+        HttpServer::new(|| App::new().route("/metrics", web::get().to(metrics)))
+            .bind(format!("{host}:{port}"))
+            .c(d!())?
+            .run();
+
+        info!("Metrics server started");
+
+        Ok(MetricsApi)
+    }
+}
@@ -2,6 +2,9 @@
 //! # Services provided by api
 //!
 
+/// Provide prometheus metrics of the node
+pub mod metrics_server;
+
 /// Provide query service for ledgerState
 pub mod query_server;
 
@@ -7,7 +7,10 @@ pub mod submission_api;
 use {
     fp_utils::tx::EVM_TX_TAG,
     ledger::{
-        data_model::{BlockEffect, Transaction, TxnEffect, TxnSID, TxnTempSID, TxoSID},
+        data_model::{
+            error_code::TxResult, BlockEffect, Transaction, TxnEffect, TxnSID,
+            TxnTempSID, TxoSID,
+        },
         store::LedgerState,
     },
     parking_lot::RwLock,
@@ -205,7 +208,7 @@ where
 
     /// The transaction will be applied to the effect_block after a series of judgments,
     /// and will be classified as pending or rejected depending on the result of the processing.
-    pub fn cache_transaction(&mut self, txn: Transaction) -> Result<TxnHandle> {
+    pub fn cache_transaction(&mut self, txn: Transaction) -> TxResult<TxnHandle> {
         // Begin a block if the previous one has been commited
         if self.all_commited() {
             self.begin_block();
@@ -216,12 +219,7 @@ where
         let ledger = self.committed_state.read();
         let handle = TxnHandle::new(&txn);
         let temp_sid = TxnEffect::compute_effect(txn.clone())
-            .c(d!("Failed to compute txn effect"))
-            .and_then(|txn_effect| {
-                ledger
-                    .apply_transaction(&mut block, txn_effect)
-                    .c(d!("Failed to apply transaction"))
-            });
+            .and_then(|txn_effect| ledger.apply_transaction(&mut block, txn_effect));
         match temp_sid {
             Ok(temp_sid) => {
                 self.pending_txns.push((temp_sid, handle.clone(), txn));
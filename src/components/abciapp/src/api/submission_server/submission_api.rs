@@ -7,7 +7,7 @@ use {
     actix_cors::Cors,
     actix_web::{error, middleware, web, App, HttpServer},
     finutils::api::NetworkRoute,
-    ledger::data_model::Transaction,
+    ledger::data_model::{Transaction, TxnSimulation},
     parking_lot::RwLock,
     rand_core::{CryptoRng, RngCore},
     ruc::*,
@@ -53,6 +53,32 @@ where
         })
 }
 
+/// Check a transaction against the committed state without submitting it,
+/// returns its effect or the reason why it would be rejected
+pub async fn simulate_transaction<RNG, TF>(
+    data: web::Data<Arc<RwLock<SubmissionServer<RNG, TF>>>>,
+    body: web::Json<Transaction>,
+) -> StdResult<web::Json<TxnSimulation>, actix_web::error::Error>
+where
+    RNG: RngCore + CryptoRng,
+    TF: TxnForward + Sync + Send,
+{
+    let tx = body.into_inner();
+
+    let ledger = data.read().borrowable_ledger_state();
+    let res = ledger.read().simulate_transaction(tx);
+    let simulation = match res {
+        Ok(summary) => TxnSimulation::Accepted(summary),
+        Err(e) => TxnSimulation::Rejected {
+            code: e.code,
+            codespace: e.code.codespace().to_owned(),
+            log: e.msg,
+        },
+    };
+
+    Ok(web::Json(simulation))
+}
+
 /// Queries the status of a transaction by its handle. Returns either a not committed message or a
 /// serialized TxnStatus.
 pub async fn txn_status<RNG, TF>(
@@ -84,6 +110,7 @@ pub struct SubmissionApi;
 #[allow(missing_docs)]
 pub enum SubmissionRoutes {
     SubmitTransaction,
+    SimulateTransaction,
     TxnStatus,
     Ping,
     Version,
@@ -93,6 +120,7 @@ impl NetworkRoute for SubmissionRoutes {
     fn route(&self) -> String {
         let endpoint = match *self {
             SubmissionRoutes::SubmitTransaction => "submit_transaction",
+            SubmissionRoutes::SimulateTransaction => "simulate_transaction",
             SubmissionRoutes::TxnStatus => "txn_status",
             SubmissionRoutes::Ping => "ping",
             SubmissionRoutes::Version => "version",
@@ -123,6 +151,10 @@ impl SubmissionApi {
                     &SubmissionRoutes::SubmitTransaction.route(),
                     web::post().to(submit_transaction::<RNG, TF>),
                 )
+                .route(
+                    &SubmissionRoutes::SimulateTransaction.route(),
+                    web::post().to(simulate_transaction::<RNG, TF>),
+                )
                 .route(&SubmissionRoutes::Ping.route(), web::get().to(ping))
                 .route(&SubmissionRoutes::Version.route(), web::get().to(version))
                 .route(
@@ -85,6 +85,8 @@ fn node_command() -> Result<()> {
         (CFG.snapshot_list, "--snapshot-list"),
         (CFG.snapshot_rollback, "--snapshot-rollback"),
         (CFG.arc_fresh, "--arc-fresh"),
+        (CFG.arc_archive, "--arc-archive"),
+        (CFG.enable_metrics, "--enable-metrics"),
     ] {
         if condition {
             abcid.arg(action);
@@ -100,6 +102,11 @@ fn node_command() -> Result<()> {
     convert_arg!(snapshot_algo);
     convert_arg!(snapshot_rollback_to);
     convert_arg!(snapshot_rollback_to_exact);
+    convert_arg!(metrics_port);
+    convert_arg!(web3_rpc_config);
+    convert_arg!(prune_keep_blocks);
+    convert_arg!(state_sync_itv);
+    convert_arg!(state_sync_cap);
 
     let mut abcid_child = abcid
         .stdin(Stdio::null())
@@ -204,6 +211,123 @@ fn init_command() -> Result<()> {
     Ok(())
 }
 
+fn prune_command() -> Result<()> {
+    let keep_blocks = CFG
+        .prune_keep_blocks
+        .as_deref()
+        .c(d!("missing `--keep-blocks`"))?;
+
+    let mut abcid = Command::new(format!("/tmp/abcid_{}", *SUFFIX));
+    abcid
+        .arg("--ledger-dir")
+        .arg(&CFG.ledger_dir)
+        .arg("--prune-keep-blocks")
+        .arg(keep_blocks)
+        .arg("--prune-and-exit");
+    if let Some(f) = CFG.checkpoint_file.as_deref() {
+        abcid.arg("--checkpoint-file").arg(f);
+    }
+
+    let status = abcid
+        .stdin(Stdio::null())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()
+        .c(d!())?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(eg!(format!("pruning failed: {status}")))
+    }
+}
+
+fn export_evm_state_command() -> Result<()> {
+    let height = CFG
+        .export_evm_state_height
+        .as_deref()
+        .c(d!("missing `--height`"))?;
+
+    let mut abcid = Command::new(format!("/tmp/abcid_{}", *SUFFIX));
+    abcid
+        .arg("--ledger-dir")
+        .arg(&CFG.ledger_dir)
+        .arg("--export-evm-state")
+        .arg(height);
+    if let Some(f) = CFG.export_evm_state_output.as_deref() {
+        abcid.arg("--export-evm-state-output").arg(f);
+    }
+    if let Some(f) = CFG.checkpoint_file.as_deref() {
+        abcid.arg("--checkpoint-file").arg(f);
+    }
+
+    let status = abcid
+        .stdin(Stdio::null())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()
+        .c(d!())?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(eg!(format!("exporting failed: {status}")))
+    }
+}
+
+fn backfill_log_index_command() -> Result<()> {
+    let mut abcid = Command::new(format!("/tmp/abcid_{}", *SUFFIX));
+    abcid
+        .arg("--ledger-dir")
+        .arg(&CFG.ledger_dir)
+        .arg("--backfill-log-index");
+    if let Some(f) = CFG.checkpoint_file.as_deref() {
+        abcid.arg("--checkpoint-file").arg(f);
+    }
+
+    let status = abcid
+        .stdin(Stdio::null())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()
+        .c(d!())?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(eg!(format!("backfilling failed: {status}")))
+    }
+}
+
+fn migrate_archive_command() -> Result<()> {
+    let mut abcid = Command::new(format!("/tmp/abcid_{}", *SUFFIX));
+    abcid
+        .arg("--ledger-dir")
+        .arg(&CFG.ledger_dir)
+        .arg("--migrate-archive");
+    if let Some(interval) = CFG.arc_history.1 {
+        abcid
+            .arg("--arc-history")
+            .arg(format!("{},{}", CFG.arc_history.0, interval));
+    } else {
+        abcid
+            .arg("--arc-history")
+            .arg(format!("{}", CFG.arc_history.0));
+    }
+    if let Some(f) = CFG.checkpoint_file.as_deref() {
+        abcid.arg("--checkpoint-file").arg(f);
+    }
+
+    let status = abcid
+        .stdin(Stdio::null())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()
+        .c(d!())?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(eg!(format!("migrating failed: {status}")))
+    }
+}
+
 fn pack() -> Result<()> {
     let bin_path_orig = get_bin_path().c(d!())?;
     let bin_name = bin_path_orig.file_name().c(d!())?.to_str().c(d!())?;
@@ -302,8 +426,20 @@ fn main() {
     let res = match CFG.command.as_str() {
         "init" => unpack().c(d!()).and_then(|_| init_command().c(d!())),
         "node" => unpack().c(d!()).and_then(|_| node_command().c(d!())),
+        "prune" => unpack().c(d!()).and_then(|_| prune_command().c(d!())),
+        "export-evm-state" => unpack()
+            .c(d!())
+            .and_then(|_| export_evm_state_command().c(d!())),
+        "backfill-log-index" => unpack()
+            .c(d!())
+            .and_then(|_| backfill_log_index_command().c(d!())),
+        "migrate-archive" => unpack()
+            .c(d!())
+            .and_then(|_| migrate_archive_command().c(d!())),
         "pack" => pack().c(d!()),
-        _ => Err(eg!("The available options are 'node'/'init'")),
+        _ => Err(eg!(
+            "The available options are 'node'/'init'/'prune'/'export-evm-state'/'backfill-log-index'/'migrate-archive'"
+        )),
     };
 
     pnk!(res);
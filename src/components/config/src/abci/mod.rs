@@ -4,10 +4,12 @@ use {
     ruc::*,
     serde::{Deserialize, Serialize},
     std::{
+        collections::BTreeMap,
         convert::TryFrom,
         env,
         fs::{self, File},
         io::{ErrorKind, Read, Write},
+        net::IpAddr,
         path::Path,
     },
     toml,
@@ -156,6 +158,51 @@ pub struct CheckPointConfig {
 
     #[serde(default = "def_check_tx_size_height")]
     pub check_tx_size_height: i64,
+
+    // EIP-1559 style base fee of EVM transactions, adjusted per block.
+    #[serde(default = "def_base_fee_height")]
+    pub base_fee_height: i64,
+
+    // Deposit the priority tips above the base fee to the block proposer instead
+    // of burning them since `base_fee_height`, the base fee is always burned.
+    #[serde(default = "def_base_fee_to_proposer")]
+    pub base_fee_to_proposer: bool,
+
+    // Allow `ConvertAccount` operations marked `native` to credit non-confidential
+    // custom assets to balances kept by the xhub module, other conversions
+    // still go through the prism bridge contract.
+    #[serde(default = "def_native_asset_bridge_height")]
+    pub native_asset_bridge_height: i64,
+
+    // Keep a snapshot of the staking state in the EVM state, updated at the
+    // start of each block, read by contracts through the staking precompile.
+    #[serde(default = "def_staking_precompile_height")]
+    pub staking_precompile_height: i64,
+
+    // Enforce the credential rules of custom assets, transactions carrying
+    // credential proofs or defining such assets are rejected before this height.
+    #[serde(default = "def_credential_rule_height")]
+    pub credential_rule_height: i64,
+
+    // Enable the issuer updates and minters of custom assets,
+    // such operations are rejected before this height.
+    #[serde(default = "def_issuer_update_height")]
+    pub issuer_update_height: i64,
+
+    // Enable the memos encrypted for the recipients of transactions, such memos
+    // are rejected before this height, and do not count in the fee or size rules.
+    #[serde(default = "def_encrypted_memo_height")]
+    pub encrypted_memo_height: i64,
+
+    // Route the actions of the modules registered by name,
+    // such actions are rejected before this height.
+    #[serde(default = "def_module_action_height")]
+    pub module_action_height: i64,
+
+    // Sponsored EVM transactions and sponsor policies,
+    // such actions are rejected before this height.
+    #[serde(default = "def_sponsored_tx_height")]
+    pub sponsored_tx_height: i64,
 }
 
 fn def_fix_check_replay() -> u64 {
@@ -241,6 +288,42 @@ fn def_check_tx_size_height() -> i64 {
     DEFAULT_CHECKPOINT_CONFIG.check_tx_size_height
 }
 
+fn def_base_fee_height() -> i64 {
+    DEFAULT_CHECKPOINT_CONFIG.base_fee_height
+}
+
+fn def_base_fee_to_proposer() -> bool {
+    DEFAULT_CHECKPOINT_CONFIG.base_fee_to_proposer
+}
+
+fn def_native_asset_bridge_height() -> i64 {
+    DEFAULT_CHECKPOINT_CONFIG.native_asset_bridge_height
+}
+
+fn def_staking_precompile_height() -> i64 {
+    DEFAULT_CHECKPOINT_CONFIG.staking_precompile_height
+}
+
+fn def_credential_rule_height() -> i64 {
+    DEFAULT_CHECKPOINT_CONFIG.credential_rule_height
+}
+
+fn def_issuer_update_height() -> i64 {
+    DEFAULT_CHECKPOINT_CONFIG.issuer_update_height
+}
+
+fn def_encrypted_memo_height() -> i64 {
+    DEFAULT_CHECKPOINT_CONFIG.encrypted_memo_height
+}
+
+fn def_module_action_height() -> i64 {
+    DEFAULT_CHECKPOINT_CONFIG.module_action_height
+}
+
+fn def_sponsored_tx_height() -> i64 {
+    DEFAULT_CHECKPOINT_CONFIG.sponsored_tx_height
+}
+
 #[cfg(feature = "debug_env")]
 lazy_static! {
     static ref DEFAULT_CHECKPOINT_CONFIG: CheckPointConfig = CheckPointConfig {
@@ -286,7 +369,16 @@ lazy_static! {
         evm_staking_inital_height: 128,
         evm_staking_address: "0x321DF28026D01858906D322533900aD3435eE964".to_owned(),
         utxo_fee_height: 0,
-        check_tx_size_height: 0
+        check_tx_size_height: 0,
+        base_fee_height: 0,
+        base_fee_to_proposer: false,
+        native_asset_bridge_height: 0,
+        staking_precompile_height: 0,
+        credential_rule_height: 0,
+        issuer_update_height: 0,
+        encrypted_memo_height: 0,
+        module_action_height: 0,
+        sponsored_tx_height: 0,
     };
 }
 
@@ -622,7 +714,16 @@ lazy_static! {
         evm_staking_inital_height: 4636000,
         evm_staking_address: "0x38d49e3bd5144059c9f3bA10CF7306E84155B603".to_owned(),
         utxo_fee_height: 5713000,
-        check_tx_size_height: 5713000
+        check_tx_size_height: 5713000,
+        base_fee_height: i64::MAX,
+        base_fee_to_proposer: false,
+        native_asset_bridge_height: i64::MAX,
+        staking_precompile_height: i64::MAX,
+        credential_rule_height: i64::MAX,
+        issuer_update_height: i64::MAX,
+        encrypted_memo_height: i64::MAX,
+        module_action_height: i64::MAX,
+        sponsored_tx_height: i64::MAX,
     };
 }
 
@@ -659,6 +760,40 @@ impl CheckPointConfig {
     }
 }
 
+/// Limits of the web3 RPC service, all of them are disabled by default.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Web3RpcConfig {
+    /// Tokens refilled per second into the bucket of each client,
+    /// `0` disables rate limiting.
+    pub rate_limit_per_sec: u64,
+    /// Capacity of the bucket of each client.
+    pub rate_limit_burst: u64,
+    /// Tokens consumed by a call of a method, unlisted methods cost `1`,
+    /// e.g. `eth_getLogs = 20`.
+    pub method_costs: BTreeMap<String, u64>,
+    /// If not empty, only these methods can be called,
+    /// a trailing `*` matches every method with that prefix.
+    pub allowed_methods: Vec<String>,
+    /// Methods that can never be called, e.g. `debug_*`.
+    pub denied_methods: Vec<String>,
+    /// Maximum number of calls in a batch request, `0` means unlimited.
+    pub max_batch_size: usize,
+    /// Maximum size in bytes of the response of a call, `0` means unlimited.
+    pub max_response_size: usize,
+    /// Reverse proxies whose `X-Forwarded-For`/`X-Real-IP` headers are trusted,
+    /// clients are identified by their peer address otherwise.
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl Web3RpcConfig {
+    /// load limits of the web3 RPC service from a toml file.
+    pub fn from_file(file_path: &str) -> Result<Web3RpcConfig> {
+        let content = fs::read_to_string(file_path).c(d!(file_path))?;
+        toml::from_str(&content).c(d!())
+    }
+}
+
 #[derive(Debug)]
 pub struct ABCIConfig {
     pub abci_host: String,
@@ -751,7 +886,7 @@ impl ABCIConfig {
 }
 
 pub mod global_cfg {
-    use crate::abci::CheckPointConfig;
+    use crate::abci::{CheckPointConfig, Web3RpcConfig};
     #[cfg(target_os = "linux")]
     use btm::BtmCfg;
     #[cfg(not(test))]
@@ -776,6 +911,7 @@ pub mod global_cfg {
         pub enable_enterprise_web3: bool,
         pub arc_history: (u16, Option<u16>),
         pub arc_fresh: bool,
+        pub arc_archive: bool,
         pub tendermint_host: String,
         pub tendermint_port: u16,
         pub submission_service_port: u16,
@@ -786,9 +922,24 @@ pub mod global_cfg {
         pub enable_eth_api_secondary: bool,
         pub evm_http_port: u16,
         pub evm_ws_port: u16,
+        pub enable_metrics: bool,
+        pub metrics_port: u16,
+        pub web3_rpc: Web3RpcConfig,
         pub tendermint_node_self_addr: Option<String>,
         pub tendermint_node_key_config_path: Option<String>,
         pub ledger_dir: String,
+        pub prune_keep_blocks: Option<u64>,
+        pub prune_and_exit: bool,
+        pub export_evm_state: Option<u64>,
+        pub export_evm_state_output: Option<String>,
+        pub backfill_log_index: bool,
+        pub migrate_archive: bool,
+        pub state_sync_itv: u64,
+        pub state_sync_cap: u64,
+        pub state_sync_peers: Vec<String>,
+        pub state_sync_trust_height: u64,
+        pub state_sync_trust_hash: String,
+        pub state_sync_trust_period: u64,
         #[cfg(target_os = "linux")]
         pub btmcfg: BtmCfg,
         pub checkpoint: CheckPointConfig,
@@ -813,6 +964,7 @@ pub mod global_cfg {
             .arg_from_usage("--enable-enterprise-web3 'enable enterprise-web3'")
             .arg_from_usage("--arc-history=[EVM archive node tracing history, format \"PERIOD,INTERVAL\" in days]")
             .arg_from_usage("--arc-fresh 'EVM archive node with fresh tracing history'")
+            .arg_from_usage("--arc-archive 'EVM archive node keeping the state of all heights within about u32::MAX blocks, the interval of `--arc-history` is still used for snapshots'")
             .arg_from_usage("--tendermint-host=[Tendermint IP]")
             .arg_from_usage("--tendermint-port=[Tendermint Port]")
             .arg_from_usage("--submission-service-port=[Submission Service Port]")
@@ -823,10 +975,25 @@ pub mod global_cfg {
             .arg_from_usage("--enable-eth-api-secondary")
             .arg_from_usage("--evm-http-port=[EVM Web3 Http Port]")
             .arg_from_usage("--evm-ws-port=[EVM Web3 WS Port]")
+            .arg_from_usage("--enable-metrics 'expose prometheus metrics of abci, ledger and evm'")
+            .arg_from_usage("--metrics-port=[Prometheus Metrics Port]")
+            .arg_from_usage("--web3-rpc-config=[Path] 'limits of the web3 rpc service, in toml format'")
             .arg_from_usage("--tendermint-node-self-addr=[Address] 'the address of your tendermint node, in upper-hex format'")
             .arg_from_usage("--tendermint-node-key-config-path=[Path] 'such as: ${HOME}/.tendermint/config/priv_validator_key.json'")
             .arg_from_usage("-d, --ledger-dir=[Path]")
             .arg_from_usage("--checkpoint-file=[Path]")
+            .arg_from_usage("--prune-keep-blocks=[Blocks] 'only keep the transactions of the latest N blocks, and older ones that still own unspent outputs'")
+            .arg_from_usage("--prune-and-exit 'prune the ledger storage once according to `--prune-keep-blocks`, then exit'")
+            .arg_from_usage("--export-evm-state=[Height] 'export the EVM state at a height as a genesis app state, then exit'")
+            .arg_from_usage("--export-evm-state-output=[Path] 'where to write the exported EVM state, default to `./evm_state.json`'")
+            .arg_from_usage("--backfill-log-index 'index the logs of the existing EVM blocks for `eth_getLogs`, then exit'")
+            .arg_from_usage("--migrate-archive 'turn the EVM state of a node with `--arc-history` into an archive for `--arc-archive`, then exit'")
+            .arg_from_usage("--state-sync-itv=[Interval] 'take an application snapshot for state sync every N blocks, disabled if missing'")
+            .arg_from_usage("--state-sync-cap=[Capacity] 'the maximum number of application snapshots that will be stored, default to 3'")
+            .arg_from_usage("--state-sync-peers=[Addrs] 'restore the newest application snapshot from these tendermint RPC addresses into an empty ledger dir, then exit, separated by comma'")
+            .arg_from_usage("--state-sync-trust-height=[Height] 'height of a trusted header for the light client of state sync'")
+            .arg_from_usage("--state-sync-trust-hash=[Hash] 'hash of the trusted header for the light client of state sync'")
+            .arg_from_usage("--state-sync-trust-period=[Secs] 'trusting period of the light client of state sync, default to 7 days'")
             .arg_from_usage("--enable-snapshot 'global switch for enabling snapshot functions'")
             .arg_from_usage("--snapshot-list 'list all available snapshots in the form of block height'")
             .arg_from_usage("--snapshot-target=[TargetPath] 'a data volume containing both ledger data and tendermint data'")
@@ -891,6 +1058,7 @@ pub mod global_cfg {
             }
         };
         let arf = m.is_present("arc-fresh");
+        let ara = m.is_present("arc-archive") || env::var("ARC_ARCHIVE").is_ok();
         let th = m
             .value_of("tendermint-host")
             .map(|v| v.to_owned())
@@ -957,6 +1125,85 @@ pub mod global_cfg {
             .unwrap_or_else(|| "8546".to_owned())
             .parse::<u16>()
             .c(d!())?;
+        let em = m.is_present("enable-metrics") || env::var("ENABLE_METRICS").is_ok();
+        let mp = m
+            .value_of("metrics-port")
+            .map(|v| v.to_owned())
+            .or_else(|| env::var("METRICS_PORT").ok())
+            .unwrap_or_else(|| "9615".to_owned())
+            .parse::<u16>()
+            .c(d!())?;
+        let web3_rpc = m
+            .value_of("web3-rpc-config")
+            .map(|v| v.to_owned())
+            .or_else(|| env::var("WEB3_RPC_CONFIG").ok())
+            .map(|path| Web3RpcConfig::from_file(&path))
+            .transpose()
+            .c(d!())?
+            .unwrap_or_default();
+        let pkb = m
+            .value_of("prune-keep-blocks")
+            .map(|v| v.to_owned())
+            .or_else(|| env::var("PRUNE_KEEP_BLOCKS").ok())
+            .map(|v| v.parse::<u64>().c(d!("invalid prune-keep-blocks")))
+            .transpose()?;
+        let pae = m.is_present("prune-and-exit");
+        if pae && pkb.is_none() {
+            return Err(eg!("`--prune-and-exit` requires `--prune-keep-blocks`"));
+        }
+        // the api cache keeps the full history, it can not be built on a pruned ledger
+        if pkb.is_some() && (eqs || env::var("FINDORAD_KEEP_HIST").is_ok()) {
+            return Err(eg!(
+                "`--prune-keep-blocks` conflicts with `--enable-query-service` and `FINDORAD_KEEP_HIST`"
+            ));
+        }
+        let ees = m
+            .value_of("export-evm-state")
+            .map(|v| v.parse::<u64>().c(d!("invalid export-evm-state")))
+            .transpose()?;
+        let eeso = m.value_of("export-evm-state-output").map(|v| v.to_owned());
+        let ssi = m
+            .value_of("state-sync-itv")
+            .map(|v| v.to_owned())
+            .or_else(|| env::var("STATE_SYNC_ITV").ok())
+            .unwrap_or_else(|| "0".to_owned())
+            .parse::<u64>()
+            .c(d!())?;
+        let ssc = m
+            .value_of("state-sync-cap")
+            .map(|v| v.to_owned())
+            .or_else(|| env::var("STATE_SYNC_CAP").ok())
+            .unwrap_or_else(|| "3".to_owned())
+            .parse::<u64>()
+            .c(d!())?;
+        let state_sync_peers = m
+            .value_of("state-sync-peers")
+            .map(|v| {
+                v.split(',')
+                    .map(|p| p.trim().to_owned())
+                    .filter(|p| !p.is_empty())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let sst_height = m
+            .value_of("state-sync-trust-height")
+            .map(|v| v.parse::<u64>().c(d!("invalid state-sync-trust-height")))
+            .transpose()?;
+        let sst_hash = m.value_of("state-sync-trust-hash").map(|v| v.to_owned());
+        let sst_period = m
+            .value_of("state-sync-trust-period")
+            .unwrap_or("604800")
+            .parse::<u64>()
+            .c(d!("invalid state-sync-trust-period"))?;
+        let (sst_height, sst_hash) = match (sst_height, sst_hash) {
+            (Some(h), Some(hash)) => (h, hash),
+            _ if !state_sync_peers.is_empty() => {
+                return Err(eg!(
+                    "`--state-sync-peers` requires `--state-sync-trust-height` and `--state-sync-trust-hash`"
+                ));
+            }
+            _ => (0, String::new()),
+        };
         let checkpoint_path = m
             .value_of("checkpoint-file")
             .map(|v| v.to_owned())
@@ -968,6 +1215,7 @@ pub mod global_cfg {
             enable_enterprise_web3,
             arc_history: arh,
             arc_fresh: arf,
+            arc_archive: ara,
             tendermint_host: th,
             tendermint_port: tp,
             submission_service_port: ssp,
@@ -978,9 +1226,24 @@ pub mod global_cfg {
             enable_eth_api_secondary,
             evm_http_port: ehp,
             evm_ws_port: ewp,
+            enable_metrics: em,
+            metrics_port: mp,
+            web3_rpc,
             tendermint_node_self_addr: tnsa,
             tendermint_node_key_config_path: tnkcp,
             ledger_dir: ld,
+            prune_keep_blocks: pkb,
+            prune_and_exit: pae,
+            export_evm_state: ees,
+            export_evm_state_output: eeso,
+            backfill_log_index: m.is_present("backfill-log-index"),
+            migrate_archive: m.is_present("migrate-archive"),
+            state_sync_itv: ssi,
+            state_sync_cap: ssc,
+            state_sync_peers,
+            state_sync_trust_height: sst_height,
+            state_sync_trust_hash: sst_hash,
+            state_sync_trust_period: sst_period,
             #[cfg(target_os = "linux")]
             btmcfg: parse_btmcfg(&m).c(d!())?,
             checkpoint: CheckPointConfig::from_file(&checkpoint_path).unwrap(),
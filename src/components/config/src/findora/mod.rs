@@ -155,12 +155,16 @@ pub mod config {
         pub tendermint_port: u16,
         pub arc_history: (u16, Option<u16>),
         pub arc_fresh: bool,
+        pub arc_archive: bool,
         pub submission_service_port: u16,
         pub ledger_service_port: u16,
         pub enable_query_service: bool,
         pub enable_eth_api_service: bool,
         pub enable_eth_api_secondary: bool,
         pub disable_eth_empty_blocks: bool,
+        pub enable_metrics: bool,
+        pub metrics_port: Option<String>,
+        pub web3_rpc_config: Option<String>,
         pub no_fast_sync: bool,
         pub tendermint_node_self_addr: Option<String>,
         pub tendermint_node_key_config_path: Option<String>,
@@ -180,6 +184,11 @@ pub mod config {
         pub snapshot_rollback: bool,
         pub snapshot_rollback_to: Option<String>,
         pub snapshot_rollback_to_exact: Option<String>,
+        pub prune_keep_blocks: Option<String>,
+        pub export_evm_state_height: Option<String>,
+        pub export_evm_state_output: Option<String>,
+        pub state_sync_itv: Option<String>,
+        pub state_sync_cap: Option<String>,
     }
 
     fn get_config() -> Result<Config> {
@@ -191,6 +200,7 @@ pub mod config {
                 .arg_from_usage("-c, --config=[FILE] 'Path to $TMHOM/config/config.toml'")
                 .arg_from_usage("--arc-history=[EVM archive node tracing history, format \"PERIOD,INTERVAL\" in days]")
                 .arg_from_usage("--arc-fresh 'EVM archive node with fresh tracing history'")
+                .arg_from_usage("--arc-archive 'EVM archive node keeping the state of all heights within about u32::MAX blocks, the interval of `--arc-history` is still used for snapshots'")
                 .arg_from_usage("-H, --tendermint-host=[Tendermint Node IP]")
                 .arg_from_usage("-P, --tendermint-port=[Tendermint Node Port]")
                 .arg_from_usage("--submission-service-port=[Submission Service Port]")
@@ -199,6 +209,9 @@ pub mod config {
                 .arg_from_usage("--enable-eth-api-service")
                 .arg_from_usage("--enable-eth-api-secondary")
                 .arg_from_usage("--disable-eth-empty-blocks")
+                .arg_from_usage("--enable-metrics 'expose prometheus metrics of abci, ledger and evm'")
+                .arg_from_usage("--metrics-port=[Prometheus Metrics Port]")
+                .arg_from_usage("--web3-rpc-config=[Path] 'limits of the web3 rpc service, in toml format'")
                 .arg_from_usage("-N, --no-fast-sync")
                 .arg_from_usage("--tendermint-node-self-addr=[Address] 'the address of your tendermint node, in upper-hex format'")
                 .arg_from_usage("--tendermint-node-key-config-path=[Path] 'such as: ${HOME}/.tendermint/config/priv_validator_key.json'")
@@ -216,6 +229,9 @@ pub mod config {
                 .arg_from_usage("--snapshot-rollback 'rollback to the last available snapshot'")
                 .arg_from_usage("-r, --snapshot-rollback-to=[Height] 'rollback to a custom height, will try the closest smaller height if the target does not exist'")
                 .arg_from_usage("--checkpoint-file=[Path]")
+                .arg_from_usage("--prune-keep-blocks=[Blocks] 'only keep the transactions of the latest N blocks, and older ones that still own unspent outputs'")
+                .arg_from_usage("--state-sync-itv=[Interval] 'take an application snapshot for state sync every N blocks, disabled if missing'")
+                .arg_from_usage("--state-sync-cap=[Capacity] 'the maximum number of application snapshots that will be stored, default to 3'")
                 .arg_from_usage("-R, --snapshot-rollback-to-exact=[Height] 'rollback to a custom height exactly, an error will be reported if the target does not exist'");
 
             let init = SubCommand::with_name("init")
@@ -231,6 +247,42 @@ pub mod config {
                     "-b, --base-dir=[DIR] 'The root directory for tendermint config, aka $TENDERMINT_HOME'",
                 );
 
+            let prune = SubCommand::with_name("prune")
+                .about("Prune the ledger storage of a stopped node, then exit.")
+                .arg_from_usage("--keep-blocks=<Blocks> 'keep the transactions of the latest N blocks'")
+                .arg_from_usage("-d, --ledger-dir=[Path]")
+                .arg_from_usage("--checkpoint-file=[Path]")
+                .arg_from_usage(
+                    "-b, --base-dir=[DIR] 'The root directory for tendermint config, aka $TENDERMINT_HOME'",
+                );
+
+            let export_evm_state = SubCommand::with_name("export-evm-state")
+                .about("Export the EVM state of a stopped node at a height as a genesis app state, then exit.")
+                .arg_from_usage("--height=<Height> 'the height of the exported state'")
+                .arg_from_usage("-o, --output=[Path] 'default to `./evm_state.json`'")
+                .arg_from_usage("-d, --ledger-dir=[Path]")
+                .arg_from_usage("--checkpoint-file=[Path]")
+                .arg_from_usage(
+                    "-b, --base-dir=[DIR] 'The root directory for tendermint config, aka $TENDERMINT_HOME'",
+                );
+
+            let backfill_log_index = SubCommand::with_name("backfill-log-index")
+                .about("Index the logs of the existing EVM blocks of a stopped node for `eth_getLogs`, then exit.")
+                .arg_from_usage("-d, --ledger-dir=[Path]")
+                .arg_from_usage("--checkpoint-file=[Path]")
+                .arg_from_usage(
+                    "-b, --base-dir=[DIR] 'The root directory for tendermint config, aka $TENDERMINT_HOME'",
+                );
+
+            let migrate_archive = SubCommand::with_name("migrate-archive")
+                .about("Turn the EVM state of a stopped node into an archive for `--arc-archive`, then exit.")
+                .arg_from_usage("--arc-history=[EVM archive node tracing history, format \"PERIOD,INTERVAL\" in days]")
+                .arg_from_usage("-d, --ledger-dir=[Path]")
+                .arg_from_usage("--checkpoint-file=[Path]")
+                .arg_from_usage(
+                    "-b, --base-dir=[DIR] 'The root directory for tendermint config, aka $TENDERMINT_HOME'",
+                );
+
             let pack = SubCommand::with_name("pack");
 
             App::new("findorad")
@@ -239,6 +291,10 @@ pub mod config {
                 .about("An ABCI node implementation of FindoraNetwork.")
                 .subcommand(node)
                 .subcommand(init)
+                .subcommand(prune)
+                .subcommand(export_evm_state)
+                .subcommand(backfill_log_index)
+                .subcommand(migrate_archive)
                 .subcommand(pack)
                 .arg(Arg::with_name("_a").long("ignored").hidden(true))
                 .arg(Arg::with_name("_b").long("nocapture").hidden(true))
@@ -322,6 +378,7 @@ pub mod config {
             }
         };
         let arf = m.is_present("arc-fresh");
+        let ara = m.is_present("arc-archive") || env::var("ARC_ARCHIVE").is_ok();
         let ssp = m
             .value_of("submission-service-port")
             .map(|v| v.to_owned())
@@ -346,6 +403,15 @@ pub mod config {
 
         let deeb = m.is_present("disable-eth-empty-blocks")
             || env::var("DISABLE_ETH_EMPTY_BLOCKS").is_ok();
+        let em = m.is_present("enable-metrics") || env::var("ENABLE_METRICS").is_ok();
+        let mp = m
+            .value_of("metrics-port")
+            .map(|v| v.to_owned())
+            .or_else(|| env::var("METRICS_PORT").ok());
+        let wrc = m
+            .value_of("web3-rpc-config")
+            .map(|v| v.to_owned())
+            .or_else(|| env::var("WEB3_RPC_CONFIG").ok());
         let nfs = m.is_present("no-fast-sync") || env::var("NO_FAST_SYNC").is_ok();
         let tnsa = m
             .value_of("tendermint-node-self-addr")
@@ -362,6 +428,11 @@ pub mod config {
                 env::var("LEDGER_DIR")
                     .unwrap_or_else(|_| format!("{}/__findora__", &tdir))
             });
+        let pkb = m
+            .value_of("prune-keep-blocks")
+            .or_else(|| m.value_of("keep-blocks"))
+            .map(|v| v.to_owned())
+            .or_else(|| env::var("PRUNE_KEEP_BLOCKS").ok());
         let cpf = m
             .value_of("checkpoint-file")
             .map(|v| v.to_owned())
@@ -390,12 +461,16 @@ pub mod config {
             tendermint_port: tp,
             arc_history: arh,
             arc_fresh: arf,
+            arc_archive: ara,
             submission_service_port: ssp,
             ledger_service_port: lsp,
             enable_query_service: eqs,
             enable_eth_api_service: eeas,
             enable_eth_api_secondary,
             disable_eth_empty_blocks: deeb,
+            enable_metrics: em,
+            metrics_port: mp,
+            web3_rpc_config: wrc,
             no_fast_sync: nfs,
             tendermint_node_self_addr: tnsa,
             tendermint_node_key_config_path: tnkcp,
@@ -420,6 +495,17 @@ pub mod config {
             snapshot_rollback_to_exact: m
                 .value_of("snapshot-rollback-to-exact")
                 .map(|v| v.to_owned()),
+            prune_keep_blocks: pkb,
+            export_evm_state_height: m.value_of("height").map(|v| v.to_owned()),
+            export_evm_state_output: m.value_of("output").map(|v| v.to_owned()),
+            state_sync_itv: m
+                .value_of("state-sync-itv")
+                .map(|v| v.to_owned())
+                .or_else(|| env::var("STATE_SYNC_ITV").ok()),
+            state_sync_cap: m
+                .value_of("state-sync-cap")
+                .map(|v| v.to_owned())
+                .or_else(|| env::var("STATE_SYNC_CAP").ok()),
         };
 
         Ok(res)
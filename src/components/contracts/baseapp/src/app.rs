@@ -1,18 +1,26 @@
-use crate::extensions::SignedExtra;
+use crate::{extensions::SignedExtra, txpool::Admission};
 use abci::*;
 use config::abci::global_cfg::CFG;
 use enterprise_web3::{
     Setter, PENDING_CODE_MAP, PENDING_STATE_UPDATE_LIST, REDIS_CLIENT,
     REMOVE_PENDING_CODE_MAP, REMOVE_PENDING_STATE_UPDATE_LIST,
 };
-use fp_core::context::RunTxMode;
+use ethereum::TransactionV0 as EthereumTransaction;
+use fp_core::context::{Context, RunTxMode};
 use fp_evm::BlockId;
+use fp_traits::{
+    account::AccountAsset,
+    evm::{AddressMapping, EthereumAddressMapping},
+};
 use fp_types::{
-    actions::xhub::NonConfidentialOutput, assemble::convert_unchecked_transaction,
+    actions::{ethereum::Action as EthereumAction, xhub::NonConfidentialOutput, Action},
+    assemble::{convert_unchecked_transaction, UncheckedTransaction},
+    error_code::{TxErrorCode, TxErrorExt, TxResult, TX_ERROR_EVENT},
 };
 use fp_utils::tx::EvmRawTxWrapper;
+use ledger::staking::Staking;
 use module_evm::utils::{deposit_asset_event_topic_str, parse_deposit_asset_event};
-use primitive_types::U256;
+use primitive_types::{H160, U256};
 use ruc::*;
 use std::{mem::take, ops::DerefMut};
 use tracing::{debug, error, info};
@@ -76,19 +84,32 @@ impl crate::BaseApp {
             tx
         } else {
             info!(target: "baseapp", "Transaction evm tag check failed");
-            resp.code = 1;
+            set_check_tx_code(&mut resp, TxErrorCode::EvmTagMismatch);
             resp.log = String::from("Transaction evm tag check failed");
             return resp;
         };
 
         if let Ok(tx) = convert_unchecked_transaction::<SignedExtra>(raw_tx) {
-            let check_fn = |mode: RunTxMode| {
-                let ctx = {
-                    let mut ctx = self.check_state.clone();
-                    ctx.run_mode = mode;
-                    ctx
-                };
-                let result = self.modules.process_tx::<SignedExtra>(ctx, tx.clone());
+            // The transaction is moved into `check_fn`, so consult the pool first.
+            let admission = match req.get_field_type() {
+                CheckTxType::New => self.pool_admission(&tx.function),
+                CheckTxType::Recheck => Ok(None),
+            };
+            let replaced = req.get_field_type() == CheckTxType::Recheck
+                && self.is_replaced(&tx.function);
+            let rechecked = match req.get_field_type() {
+                CheckTxType::Recheck if !replaced => self
+                    .pool_sender(&tx.function)
+                    .map(|(sender, etx)| (sender, etx.nonce, etx.hash())),
+                _ => None,
+            };
+            let check_fn = |mut ctx: Context, mode: RunTxMode| {
+                ctx.run_mode = mode;
+                let result = Self::precheck(&ctx, &tx).and_then(|_| {
+                    self.modules
+                        .process_tx::<SignedExtra>(ctx, tx.clone())
+                        .tx_err(TxErrorCode::EvmCheckFailed)
+                });
                 match result {
                     Ok(ar) => {
                         if CFG.enable_enterprise_web3 {
@@ -140,22 +161,74 @@ impl crate::BaseApp {
                                 }
                             }
                         }
-                        resp.code = ar.code;
                         if ar.code != 0 {
                             info!(target: "baseapp", "Transaction check error, action result {ar:?}");
+                            set_check_tx_code(
+                                &mut resp,
+                                TxErrorCode::from_action_result(ar.code),
+                            );
                             resp.log = ar.log;
                         }
                     }
                     Err(e) => {
                         info!(target: "baseapp", "Transaction check error: {e}");
-                        resp.code = 1;
+                        set_check_tx_code(&mut resp, e.code);
                         resp.log = format!("Transaction check error: {e}");
                     }
                 }
             };
             match req.get_field_type() {
-                CheckTxType::New => check_fn(RunTxMode::Check),
-                CheckTxType::Recheck => check_fn(RunTxMode::ReCheck),
+                CheckTxType::New => match admission {
+                    Ok(None) => check_fn(self.check_state.clone(), RunTxMode::Check),
+                    Ok(Some((sender, etx, Admission::Next))) => {
+                        check_fn(self.check_state.clone(), RunTxMode::Check);
+                        if resp.code == 0 {
+                            self.tx_pool.write().insert_pending(sender, etx);
+                        }
+                    }
+                    // The nonce is not the next one of the check state,
+                    // so only validate it against the committed state.
+                    Ok(Some((sender, etx, admission))) => {
+                        check_fn(
+                            self.check_state.copy_with_new_state(),
+                            RunTxMode::ReCheck,
+                        );
+                        if resp.code == 0 {
+                            if Admission::Replace == admission {
+                                self.tx_pool.write().replace(sender, etx);
+                            } else {
+                                resp.log = format!(
+                                    "TxQueued: origin: {:?}, nonce {} is waiting for the previous ones",
+                                    sender, etx.nonce
+                                );
+                                set_check_tx_code(&mut resp, TxErrorCode::TxQueued);
+                                self.tx_pool.write().queue(sender, etx);
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        info!(target: "baseapp", "Transaction check error: {e}");
+                        set_check_tx_code(&mut resp, e.code);
+                        resp.log = format!("Transaction check error: {e}");
+                    }
+                },
+                CheckTxType::Recheck => {
+                    if replaced {
+                        set_check_tx_code(&mut resp, TxErrorCode::TxReplaced);
+                        resp.log =
+                            String::from("TxReplaced: replaced by a higher gas price");
+                    } else {
+                        check_fn(self.check_state.clone(), RunTxMode::ReCheck);
+                        // Tendermint drops the transaction, so does the pool.
+                        match rechecked {
+                            Some((sender, nonce, hash)) if resp.code != 0 => self
+                                .tx_pool
+                                .write()
+                                .remove_pending(&sender, &nonce, &hash),
+                            _ => {}
+                        }
+                    }
+                }
             }
         } else {
             info!(target: "baseapp", "Could not unpack transaction");
@@ -163,6 +236,70 @@ impl crate::BaseApp {
         resp
     }
 
+    // The checks of `process_tx` whose rejections are reported with a specific code,
+    // everything else is left to `process_tx` itself.
+    fn precheck(ctx: &Context, tx: &UncheckedTransaction<SignedExtra>) -> TxResult<()> {
+        match &tx.function {
+            Action::Ethereum(action) => {
+                module_ethereum::App::<crate::BaseApp>::check_action(ctx, action)
+            }
+            _ => match &tx.signature {
+                Some((who, _, (nonce, fee))) => {
+                    nonce.check(ctx, who)?;
+                    fee.check(ctx, who)
+                }
+                None => Ok(()),
+            },
+        }
+    }
+
+    // The sender of an EVM transaction, `None` for other transactions
+    // and invalid signatures.
+    fn pool_sender<'a>(
+        &self,
+        action: &'a Action,
+    ) -> Option<(H160, &'a EthereumTransaction)> {
+        let etx = match action {
+            Action::Ethereum(EthereumAction::Transact(etx)) => etx,
+            _ => return None,
+        };
+        module_ethereum::App::<crate::BaseApp>::recover_signer_fast(
+            &self.check_state,
+            etx,
+        )
+        .map(|sender| (sender, etx))
+    }
+
+    // How an EVM transaction is admitted by the pool, `None` for other transactions.
+    #[allow(clippy::type_complexity)]
+    fn pool_admission(
+        &self,
+        action: &Action,
+    ) -> TxResult<Option<(H160, EthereumTransaction, Admission)>> {
+        // Invalid signatures are reported by the normal check.
+        let (sender, etx) = match self.pool_sender(action) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+        let nonce = module_account::App::<crate::BaseApp>::nonce(
+            &self.check_state,
+            &EthereumAddressMapping::convert_to_account_id(sender),
+        );
+        self.tx_pool
+            .read()
+            .admission(&sender, nonce, etx)
+            .map(|admission| Some((sender, etx.clone(), admission)))
+    }
+
+    // Whether an EVM transaction has been replaced in the pool.
+    fn is_replaced(&self, action: &Action) -> bool {
+        if let Action::Ethereum(EthereumAction::Transact(etx)) = action {
+            self.tx_pool.write().take_replaced(&etx.hash())
+        } else {
+            false
+        }
+    }
+
     /// init_chain implements the ABCI interface.
     pub fn init_chain(&mut self, req: &RequestInitChain) -> ResponseInitChain {
         let mut init_header: Header = Default::default();
@@ -173,6 +310,13 @@ impl crate::BaseApp {
         Self::update_state(&mut self.deliver_state, init_header.clone(), vec![]);
         Self::update_state(&mut self.check_state, init_header, vec![]);
 
+        // load the pre-funded accounts and pre-deployed contracts, if any
+        if !req.app_state_bytes.is_empty() {
+            pnk!(self
+                .modules
+                .init_genesis(&self.deliver_state, &req.app_state_bytes));
+        }
+
         ResponseInitChain::default()
     }
 
@@ -196,6 +340,28 @@ impl crate::BaseApp {
         ResponseBeginBlock::default()
     }
 
+    /// Export the staking state of the UTXO ledger to the deliver state,
+    /// only the entries changed since the last exported block are written.
+    pub fn export_staking(
+        &mut self,
+        staking: &Staking,
+        circulating_supply: u64,
+        return_rate: [u128; 2],
+    ) -> Result<()> {
+        let ctx = &self.deliver_state;
+        let diff = self
+            .staking_snapshot
+            .get_or_insert_with(|| module_evm::App::<Self>::staking_snapshot(ctx))
+            .update(staking, circulating_supply, return_rate);
+
+        let ret = module_evm::App::<Self>::apply_staking_diff(ctx, diff).c(d!());
+        if ret.is_err() {
+            // reload it from the state on the next block
+            self.staking_snapshot = None;
+        }
+        ret
+    }
+
     pub fn deliver_tx(
         &mut self,
         req: &RequestDeliverTx,
@@ -209,6 +375,7 @@ impl crate::BaseApp {
             info!(target: "baseapp", "Transaction deliver tx unwrap evm tag failed");
             resp.code = 1;
             resp.log = String::from("Transaction deliver tx unwrap evm tag failed");
+            set_deliver_tx_error(&mut resp, TxErrorCode::EvmTagMismatch);
             return (resp, non_confidential_outputs);
         };
 
@@ -279,6 +446,12 @@ impl crate::BaseApp {
                     resp.gas_wanted = ar.gas_wanted as i64;
                     resp.gas_used = ar.gas_used as i64;
                     resp.events = protobuf::RepeatedField::from_vec(ar.events);
+                    if 0 != resp.code {
+                        set_deliver_tx_error(
+                            &mut resp,
+                            TxErrorCode::from_action_result(resp.code),
+                        );
+                    }
                     let td_height = self.deliver_state.block_header().height;
                     if td_height > CFG.checkpoint.prismxx_inital_height && 0 == resp.code
                     {
@@ -332,6 +505,10 @@ impl crate::BaseApp {
                                             Err(e) => {
                                                 resp.code = 1;
                                                 resp.log = e.to_string();
+                                                set_deliver_tx_error(
+                                                    &mut resp,
+                                                    TxErrorCode::Internal,
+                                                );
                                             }
                                         }
                                     }
@@ -346,12 +523,14 @@ impl crate::BaseApp {
                     error!(target: "baseapp", "Ethereum transaction deliver error: {e}");
                     resp.code = 1;
                     resp.log = format!("Ethereum transaction deliver error: {e}");
+                    set_deliver_tx_error(&mut resp, TxErrorCode::EvmCheckFailed);
                     (resp, non_confidential_outputs)
                 }
             }
         } else {
             resp.code = 1;
             resp.log = String::from("Failed to convert transaction when deliver tx!");
+            set_deliver_tx_error(&mut resp, TxErrorCode::InvalidFormat);
             (resp, non_confidential_outputs)
         }
     }
@@ -380,6 +559,15 @@ impl crate::BaseApp {
         // Cache data are dropped and cleared in check_state
         self.check_state = self.deliver_state.copy_with_new_state();
 
+        // Drop the EVM transactions included in the block from the pool.
+        let check_state = &self.check_state;
+        self.tx_pool.write().prune(|sender| {
+            module_account::App::<crate::BaseApp>::nonce(
+                check_state,
+                &EthereumAddressMapping::convert_to_account_id(*sender),
+            )
+        });
+
         let block_height = self.deliver_state.block_header().height as u64;
 
         self.deliver_state
@@ -415,3 +603,35 @@ impl crate::BaseApp {
         res
     }
 }
+
+/// Report the code of a rejected `check_tx`.
+#[inline(always)]
+pub fn set_check_tx_code(resp: &mut ResponseCheckTx, code: TxErrorCode) {
+    resp.code = code.code();
+    resp.codespace = code.codespace().to_owned();
+}
+
+/// Report the code of a failed `deliver_tx` in its `codespace` and a `tx_error` event,
+/// the `code` itself is part of the consensus state and kept as is.
+pub fn set_deliver_tx_error(resp: &mut ResponseDeliverTx, code: TxErrorCode) {
+    resp.codespace = code.codespace().to_owned();
+
+    let mut pair = Pair::new();
+    pair.set_key(b"code".to_vec());
+    pair.set_value(code.code().to_string().into_bytes());
+    let mut event = Event::new();
+    event.field_type = TX_ERROR_EVENT.to_owned();
+    event.attributes.push(pair);
+    resp.events.push(event);
+}
+
+/// The code reported by `set_deliver_tx_error`.
+pub fn deliver_tx_error(resp: &ResponseDeliverTx) -> Option<TxErrorCode> {
+    resp.events
+        .iter()
+        .rev()
+        .find(|e| e.field_type == TX_ERROR_EVENT)
+        .and_then(|e| e.attributes.first())
+        .and_then(|p| std::str::from_utf8(&p.value).ok()?.parse().ok())
+        .and_then(TxErrorCode::from_code)
+}
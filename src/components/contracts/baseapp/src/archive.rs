@@ -0,0 +1,123 @@
+//! Archive mode of the chain state.
+//!
+//! A windowed node only keeps the versions of the latest `--arc-history` days,
+//! an archive node keeps the versions of all the heights since it turned into
+//! an archive, read through the periodic full snapshots of the chain state.
+//!
+//! There is no separate archive storage, an archive is the same chain state
+//! with a version window of `archive_window`, so:
+//! - the versions are still pruned once the chain is about `u32::MAX` blocks
+//!   past `since`, which is some two thousand years of 15 seconds blocks;
+//! - the versions pruned before the migration can't be restored;
+//! - the disk usage grows with every block, as nothing is compacted;
+//! - reading an old height replays up to `interval` versions from the
+//!   closest snapshot, so a smaller interval trades disk for latency.
+
+use ruc::*;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+const ARCHIVE_INFO: &str = "archive.json";
+
+/// The version window of an archive, about `u32::MAX` blocks
+/// rounded down to a multiple of the snapshot interval.
+pub fn archive_window(interval: u64) -> u64 {
+    let max = u32::MAX as u64;
+    if interval == 0 {
+        max
+    } else {
+        max / interval * interval
+    }
+}
+
+/// Recorded once a node turns into an archive.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveInfo {
+    /// The earliest height whose versions are kept.
+    pub since: u64,
+    /// The snapshot interval of the chain state, which can't be changed afterwards.
+    pub interval: u64,
+}
+
+impl ArchiveInfo {
+    pub fn load(basedir: &Path) -> Result<Option<Self>> {
+        let path = basedir.join(ARCHIVE_INFO);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let data = fs::read(path).c(d!())?;
+        serde_json::from_slice(&data)
+            .c(d!("invalid archive info"))
+            .map(Some)
+    }
+
+    // Written aside then renamed, a node never sees a partial archive info.
+    pub fn save(&self, basedir: &Path) -> Result<()> {
+        let data = serde_json::to_vec_pretty(self).c(d!())?;
+        let tmp = basedir.join(format!("{ARCHIVE_INFO}.tmp"));
+        fs::write(&tmp, data).c(d!())?;
+        fs::rename(tmp, basedir.join(ARCHIVE_INFO)).c(d!())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::BaseApp;
+    use config::abci::global_cfg::CFG;
+    use std::{env::temp_dir, path::PathBuf, time::SystemTime};
+
+    fn temp_basedir() -> PathBuf {
+        let time = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = temp_dir().join(format!("temp-findora-archive-{time}"));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_archive_window() {
+        assert_eq!(archive_window(0), u32::MAX as u64);
+        assert_eq!(archive_window(240) % 240, 0);
+        assert!(u32::MAX as u64 - archive_window(240) < 240);
+    }
+
+    #[test]
+    fn test_migrate_to_archive() {
+        let basedir = temp_basedir();
+        assert!(BaseApp::migrate_to_archive(&basedir, (1, None)).is_err());
+
+        let range = {
+            let app = BaseApp::new(&basedir, false, (1, None), false).unwrap();
+            assert!(app.archive.is_none());
+            for height in 1..=3 {
+                app.deliver_state.state.write().commit(height).unwrap();
+            }
+            let range = app.chain_state.read().get_ver_range().unwrap();
+            range
+        };
+
+        //The earliest kept version is recorded along with the snapshot interval
+        let archive = BaseApp::migrate_to_archive(&basedir, (1, None)).unwrap();
+        assert_eq!(archive.since, range.start);
+        assert_eq!(archive.interval, 240);
+        assert_eq!(ArchiveInfo::load(&basedir).unwrap(), Some(archive.clone()));
+        assert_eq!(
+            BaseApp::migrate_to_archive(&basedir, (1, None)).unwrap(),
+            archive
+        );
+
+        //A primary node prunes the versions unless started as an archive
+        if !CFG.arc_archive {
+            assert!(BaseApp::new(&basedir, false, (1, None), false).is_err());
+        }
+
+        //A secondary node follows the archive of its primary
+        let app =
+            BaseApp::new_with_secondary(&basedir, false, (1, None), false).unwrap();
+        assert_eq!(app.archive, Some(archive));
+        assert_eq!(app.opts.ver_window, archive_window(240));
+    }
+}
@@ -4,8 +4,14 @@ use fp_core::{
     context::Context,
     transaction::{ActionResult, SignedExtension},
 };
-use fp_traits::account::{AccountAsset, FeeCalculator};
-use fp_types::crypto::Address;
+use fp_traits::{
+    account::{AccountAsset, FeeCalculator},
+    evm::FeeCalculator as EvmFeeCalculator,
+};
+use fp_types::{
+    crypto::Address,
+    error_code::{TxErrorCode, TxResult},
+};
 use primitive_types::U256;
 use ruc::*;
 use serde::{Deserialize, Serialize};
@@ -19,18 +25,14 @@ impl CheckNonce {
     pub fn new(nonce: U256) -> Self {
         CheckNonce(nonce)
     }
-}
 
-impl SignedExtension for CheckNonce {
-    type AccountId = Address;
-    type Pre = ();
-
-    fn validate(&self, ctx: &Context, who: &Self::AccountId) -> Result<()> {
+    /// The nonce must not be used yet.
+    pub fn check(&self, ctx: &Context, who: &Address) -> TxResult<()> {
         let nonce = module_account::App::<BaseApp>::nonce(ctx, who);
 
         if self.0 < nonce {
             #[cfg(not(feature = "benchmark"))]
-            return Err(eg!(format!(
+            return Err(TxErrorCode::InvalidNonce.error(format!(
                 "InvalidNonce, expected: {}, actual: {}",
                 nonce, self.0
             )));
@@ -38,6 +40,15 @@ impl SignedExtension for CheckNonce {
 
         Ok(())
     }
+}
+
+impl SignedExtension for CheckNonce {
+    type AccountId = Address;
+    type Pre = ();
+
+    fn validate(&self, ctx: &Context, who: &Self::AccountId) -> Result<()> {
+        self.check(ctx, who).c(d!())
+    }
 
     fn pre_execute(self, ctx: &Context, who: &Self::AccountId) -> Result<Self::Pre> {
         let nonce = module_account::App::<BaseApp>::nonce(ctx, who);
@@ -62,19 +73,26 @@ impl CheckFee {
     pub fn new(fee: Option<U256>) -> Self {
         CheckFee(fee)
     }
-}
 
-impl SignedExtension for CheckFee {
-    type AccountId = Address;
-    type Pre = (Address, U256);
-
-    fn validate(&self, ctx: &Context, who: &Self::AccountId) -> Result<()> {
+    /// The minimum fee scales with the base fee of EVM transactions.
+    pub fn min_fee(ctx: &Context) -> U256 {
         let min_fee = <BaseApp as module_account::Config>::FeeCalculator::min_fee();
+        let base_fee =
+            <BaseApp as module_ethereum::Config>::FeeCalculator::base_fee_per_gas(ctx);
+        let min_gas_price =
+            <BaseApp as module_ethereum::Config>::FeeCalculator::min_gas_price();
+        min_fee.saturating_mul(base_fee) / min_gas_price
+    }
+
+    /// The fee must not be too low and be affordable.
+    pub fn check(&self, ctx: &Context, who: &Address) -> TxResult<()> {
+        let min_fee = Self::min_fee(ctx);
         let tx_fee = match self.0 {
             None => min_fee,
             Some(fee) => {
                 if fee < min_fee {
-                    return Err(eg!("The transaction fee is too low."));
+                    return Err(TxErrorCode::GasPriceOutOfRange
+                        .error("The transaction fee is too low."));
                 }
                 fee
             }
@@ -83,14 +101,24 @@ impl SignedExtension for CheckFee {
         // check tx fee
         let amount = module_account::App::<BaseApp>::balance(ctx, who);
         if amount < tx_fee {
-            return Err(eg!("Insufficient balance for transaction fee."));
+            return Err(TxErrorCode::InsufficientBalance
+                .error("Insufficient balance for transaction fee."));
         }
 
         Ok(())
     }
+}
+
+impl SignedExtension for CheckFee {
+    type AccountId = Address;
+    type Pre = (Address, U256);
+
+    fn validate(&self, ctx: &Context, who: &Self::AccountId) -> Result<()> {
+        self.check(ctx, who).c(d!())
+    }
 
     fn pre_execute(self, ctx: &Context, who: &Self::AccountId) -> Result<Self::Pre> {
-        let min_fee = <BaseApp as module_account::Config>::FeeCalculator::min_fee();
+        let min_fee = Self::min_fee(ctx);
         let tx_fee = match self.0 {
             None => min_fee,
             Some(fee) => {
@@ -101,6 +129,10 @@ impl SignedExtension for CheckFee {
             }
         };
         module_account::App::<BaseApp>::burn(ctx, who, tx_fee)?;
+        module_evm::App::<BaseApp>::deposit_tip_to_proposer(
+            ctx,
+            tx_fee.saturating_sub(min_fee),
+        )?;
         Ok((who.clone(), tx_fee))
     }
 
@@ -6,13 +6,18 @@
 #![allow(clippy::field_reassign_with_default)]
 
 mod app;
+pub mod archive;
 pub mod extensions;
 mod modules;
 mod notify;
 mod staking;
+pub mod txpool;
+
+pub use app::{deliver_tx_error, set_check_tx_code, set_deliver_tx_error};
 
 use crate::modules::ModuleManager;
 use abci::Header;
+use archive::{archive_window, ArchiveInfo};
 use config::abci::global_cfg::CFG;
 use ethereum::BlockV0 as Block;
 use evm_precompile::{self, FindoraPrecompiles};
@@ -28,11 +33,15 @@ use fp_evm::BlockId;
 use fp_traits::{
     account::{AccountAsset, FeeCalculator},
     base::BaseProvider,
-    evm::{EthereumAddressMapping, EthereumDecimalsMapping},
+    evm::{
+        EthereumAddressMapping, EthereumDecimalsMapping,
+        FeeCalculator as EvmFeeCalculator,
+    },
 };
 use fp_types::{actions::xhub::NonConfidentialOutput, actions::Action, crypto::Address};
 use lazy_static::lazy_static;
 use ledger::data_model::Transaction as FindoraTransaction;
+use ledger::staking::evm::StakingSnapshot;
 use ledger::LEDGER_TENDERMINT_BLOCK_HEIGHT;
 use notify::*;
 use parking_lot::RwLock;
@@ -43,6 +52,8 @@ use std::sync::atomic::Ordering;
 use std::{borrow::BorrowMut, path::Path, sync::Arc};
 use storage::state::{ChainState, ChainStateOpts};
 use tracing::info;
+use txpool::TxPool;
+use zei::xfr::structs::AssetType;
 
 lazy_static! {
     /// An identifier that distinguishes different EVM chains.
@@ -84,6 +95,12 @@ pub struct BaseApp {
     pub modules: ModuleManager,
     /// New Block event notify
     pub event_notify: Arc<Notifications<BlockId>>,
+    /// EVM transactions accepted by check_tx
+    pub tx_pool: Arc<RwLock<TxPool>>,
+    /// Set if all the versions of the chain state are kept
+    pub archive: Option<ArchiveInfo>,
+    /// The staking snapshot last exported to the deliver state
+    pub staking_snapshot: Option<StakingSnapshot>,
 }
 
 impl module_template::Config for BaseApp {}
@@ -101,6 +118,23 @@ impl module_account::Config for BaseApp {
     type FeeCalculator = StableTxFee;
 }
 
+/// Gas prices of EVM transactions, the minimum follows the base fee of module_ethereum.
+pub struct DynamicGasPrice;
+
+impl EvmFeeCalculator for DynamicGasPrice {
+    fn min_gas_price() -> U256 {
+        <() as EvmFeeCalculator>::min_gas_price()
+    }
+
+    fn max_gas_price() -> U256 {
+        <() as EvmFeeCalculator>::max_gas_price()
+    }
+
+    fn base_fee_per_gas(ctx: &Context) -> U256 {
+        module_ethereum::App::<BaseApp>::base_fee_per_gas(ctx)
+    }
+}
+
 parameter_types! {
     pub ChainId: u64 = *EVM_CAHIN_ID;
     pub const BlockHashCount: u32 = 256;
@@ -135,7 +169,7 @@ impl module_ethereum::Config for BaseApp {
     type BlockHashCount = BlockHashCount;
     type ChainId = ChainId;
     type DecimalsMapping = EthereumDecimalsMapping;
-    type FeeCalculator = ();
+    type FeeCalculator = DynamicGasPrice;
     type Runner = module_evm::runtime::runner::ActionRunner<Self>;
 }
 
@@ -162,7 +196,7 @@ impl module_evm::Config for BaseApp {
     type BlockHashMapping = module_ethereum::App<Self>;
     type ChainId = ChainId;
     type DecimalsMapping = EthereumDecimalsMapping;
-    type FeeCalculator = ();
+    type FeeCalculator = DynamicGasPrice;
     type Precompiles = (
         evm_precompile_basic::ECRecover,
         evm_precompile_basic::Sha256,
@@ -198,18 +232,11 @@ impl BaseApp {
 
         // Creates a fresh chain state db and history db
         let fdb_path = basedir.join(CHAIN_STATE_PATH);
+        let is_new = !fdb_path.exists();
         let fdb = FinDB::open(fdb_path.as_path())?;
 
-        let opts = ChainStateOpts {
-            name: Some("findora_db".to_owned()),
-            ver_window: BLOCKS_IN_DAY * arc_history.0 as u64,
-            cleanup_aux: is_fresh,
-            interval: arc_history
-                .1
-                .map_or(SNAPSHOT_INTERVAL * arc_history.0 as u64, |v| {
-                    BLOCKS_IN_DAY * v as u64
-                }),
-        };
+        let (opts, archive) =
+            Self::chain_state_opts(basedir, arc_history, is_fresh, is_new, false)?;
         let chain_state = Arc::new(RwLock::new(ChainState::create_with_opts(
             fdb,
             opts.clone(),
@@ -243,6 +270,9 @@ impl BaseApp {
                 ..Default::default()
             },
             event_notify: Arc::new(Notifications::new()),
+            tx_pool: Default::default(),
+            archive,
+            staking_snapshot: None,
         })
     }
     pub fn new_with_secondary(
@@ -263,16 +293,8 @@ impl BaseApp {
         let fdb =
             FinDB::open_as_secondary(fdb_path.as_path(), fdb_secondary_path.as_path())?;
 
-        let opts = ChainStateOpts {
-            name: Some("findora_db".to_owned()),
-            ver_window: BLOCKS_IN_DAY * arc_history.0 as u64,
-            cleanup_aux: is_fresh,
-            interval: arc_history
-                .1
-                .map_or(SNAPSHOT_INTERVAL * arc_history.0 as u64, |v| {
-                    BLOCKS_IN_DAY * v as u64
-                }),
-        };
+        let (opts, archive) =
+            Self::chain_state_opts(basedir, arc_history, is_fresh, false, true)?;
         let chain_state = Arc::new(RwLock::new(ChainState::create_with_opts(
             fdb,
             opts.clone(),
@@ -308,6 +330,9 @@ impl BaseApp {
                 ..Default::default()
             },
             event_notify: Arc::new(Notifications::new()),
+            tx_pool: Default::default(),
+            archive,
+            staking_snapshot: None,
         })
     }
     pub fn secondary_catch_up_primary(&mut self) -> Result<()> {
@@ -322,6 +347,30 @@ impl BaseApp {
             .secondary_catch_up_primary()
             .map_err(|e| eg!("chain_db secondary_catch_up_primary fail:{}", e))
     }
+
+    /// Create rocksdb checkpoints of the chain state and the history under `dir`,
+    /// the sst files are hard-linked, so it is cheap enough to be called in `commit`.
+    pub fn create_checkpoint(&self, dir: &Path) -> Result<()> {
+        self.chain_state
+            .read()
+            .snapshot(dir.join(CHAIN_STATE_PATH))
+            .map_err(|e| eg!("chain_state checkpoint fail:{}", e))?;
+        self.chain_db
+            .read()
+            .snapshot(dir.join(CHAIN_HISTORY_DATA_PATH))
+            .map_err(|e| eg!("chain_db checkpoint fail:{}", e))
+    }
+
+    /// The rocksdb directories under the basedir, covered by `create_checkpoint`.
+    pub fn db_dirs() -> [&'static str; 4] {
+        [
+            CHAIN_STATE_PATH,
+            CHAIN_HISTORY_DATA_PATH,
+            CHAIN_STATE_SECONDARY_PATH,
+            CHAIN_HISTORY_SECONDARY_DATA_PATH,
+        ]
+    }
+
     pub fn derive_app(&self) -> Self {
         let chain_state = self.chain_state.clone();
         let chain_db = self.chain_db.clone();
@@ -336,11 +385,131 @@ impl BaseApp {
             chain_db: chain_db.clone(),
             check_state: Context::new(chain_state.clone(), chain_db.clone()),
             deliver_state: Context::new(chain_state, chain_db),
-            modules: ModuleManager::default(),
+            modules: self.modules.clone(),
             event_notify: self.event_notify.clone(),
+            tx_pool: self.tx_pool.clone(),
+            archive: self.archive.clone(),
+            staking_snapshot: None,
+        }
+    }
+
+    // The options of the windowed chain state from `--arc-history`.
+    fn windowed_opts(arc_history: (u16, Option<u16>), is_fresh: bool) -> ChainStateOpts {
+        ChainStateOpts {
+            name: Some("findora_db".to_owned()),
+            ver_window: BLOCKS_IN_DAY * arc_history.0 as u64,
+            cleanup_aux: is_fresh,
+            interval: arc_history
+                .1
+                .map_or(SNAPSHOT_INTERVAL * arc_history.0 as u64, |v| {
+                    BLOCKS_IN_DAY * v as u64
+                }),
         }
     }
 
+    // The options of the archive chain state, see `archive` for its limits.
+    fn archive_opts(archive: &ArchiveInfo) -> ChainStateOpts {
+        ChainStateOpts {
+            name: Some("findora_db".to_owned()),
+            ver_window: archive_window(archive.interval),
+            cleanup_aux: false,
+            interval: archive.interval,
+        }
+    }
+
+    // The options of the chain state, which keeps all versions in archive mode.
+    // A new node turns into an archive directly, an existing one has to be migrated.
+    // A secondary node follows its primary, which is the one pruning versions.
+    fn chain_state_opts(
+        basedir: &Path,
+        arc_history: (u16, Option<u16>),
+        is_fresh: bool,
+        is_new: bool,
+        is_secondary: bool,
+    ) -> Result<(ChainStateOpts, Option<ArchiveInfo>)> {
+        let opts = Self::windowed_opts(arc_history, is_fresh);
+        let archive = ArchiveInfo::load(basedir)?;
+
+        if is_secondary {
+            return Ok(match archive {
+                Some(archive) => (Self::archive_opts(&archive), Some(archive)),
+                None => (opts, None),
+            });
+        }
+        if !CFG.arc_archive {
+            if archive.is_some() {
+                return Err(eg!(
+                    "the chain state is an archive, its versions would be pruned without `--arc-archive`"
+                ));
+            }
+            return Ok((opts, None));
+        }
+
+        if is_fresh {
+            return Err(eg!(
+                "`--arc-fresh` would drop the versions kept by `--arc-archive`"
+            ));
+        }
+        let archive = match archive {
+            Some(archive) => archive,
+            None if is_new => {
+                let archive = ArchiveInfo {
+                    since: 0,
+                    interval: opts.interval,
+                };
+                archive.save(basedir)?;
+                archive
+            }
+            None => {
+                return Err(eg!(
+                    "the chain state is not an archive yet, please migrate it with `--migrate-archive` first"
+                ))
+            }
+        };
+        if archive.interval != opts.interval {
+            return Err(eg!(format!(
+                "the snapshot interval of the archive is {} blocks, but {} blocks are set by `--arc-history`",
+                archive.interval, opts.interval
+            )));
+        }
+
+        Ok((Self::archive_opts(&archive), Some(archive)))
+    }
+
+    /// Turn the chain state of a stopped windowed node into an archive,
+    /// the versions that have been pruned can't be restored.
+    /// The chain state is reopened with the archive window before the archive
+    /// info is recorded, so an interrupted migration can simply be run again.
+    pub fn migrate_to_archive(
+        basedir: &Path,
+        arc_history: (u16, Option<u16>),
+    ) -> Result<ArchiveInfo> {
+        if let Some(archive) = ArchiveInfo::load(basedir)? {
+            return Ok(archive);
+        }
+
+        let fdb_path = basedir.join(CHAIN_STATE_PATH);
+        if !fdb_path.exists() {
+            return Err(eg!(format!("chain state not found in {basedir:?}")));
+        }
+        let fdb = FinDB::open(fdb_path.as_path())?;
+        let mut archive = ArchiveInfo {
+            since: 0,
+            interval: Self::windowed_opts(arc_history, false).interval,
+        };
+        let chain_state =
+            ChainState::create_with_opts(fdb, Self::archive_opts(&archive), false);
+        let range = chain_state.get_ver_range()?;
+        if range.is_empty() {
+            return Err(eg!(format!("no version is kept in {basedir:?}")));
+        }
+        drop(chain_state);
+
+        archive.since = range.start;
+        archive.save(basedir)?;
+        Ok(archive)
+    }
+
     //Migrate any pre-existing data from one database to the other if necessary
     pub fn migrate_initial_db(
         state_merkle: Arc<RwLock<ChainState<FinDB>>>,
@@ -393,6 +562,11 @@ impl Executable for BaseApp {
             Action::Template(action) => {
                 module_template::App::<Self>::execute(origin, action, ctx)
             }
+            // Routed by `ModuleManager::process_tx` before reaching here.
+            Action::Module(action) => Err(eg!(format!(
+                "Invalid action module route: {}!",
+                action.module
+            ))),
         }
     }
 }
@@ -424,6 +598,32 @@ impl BaseApp {
         self.check_state.state_at(height)
     }
 
+    /// Index the logs of the EVM blocks stored before the log index of `eth_getLogs`,
+    /// returns the number of the indexed blocks.
+    pub fn backfill_log_index(&self) -> Result<u64> {
+        let ctx = Context::new(self.chain_state.clone(), self.chain_db.clone());
+        self.modules.ethereum_module.backfill_log_index(&ctx)
+    }
+
+    /// Export the state of all modules at `height` as a genesis app state,
+    /// only the heights within the archive history are available.
+    pub fn export_genesis(&self, height: u64) -> Result<Vec<u8>> {
+        let ctx = self.create_context_at(height).ok_or_else(|| {
+            eg!(format!("the state at height {height} is unavailable"))
+        })?;
+        self.modules.export_genesis(&ctx)
+    }
+
+    /// Register a module besides the built-in ones, which routes queries, genesis
+    /// and `Action::Module` to it by name. It should be called right after construction,
+    /// in the same order on all nodes.
+    pub fn register_module<M: fp_core::module::PluggableModule + 'static>(
+        &mut self,
+        module: M,
+    ) -> Result<()> {
+        self.modules.register(module)
+    }
+
     /// retrieve the context for the txBytes and other memoized values.
     pub fn retrieve_context(&mut self, mode: RunTxMode) -> &mut Context {
         let ctx = if mode == RunTxMode::Deliver {
@@ -478,6 +678,12 @@ impl BaseApp {
     pub fn consume_mint(&mut self) -> Option<Vec<NonConfidentialOutput>> {
         module_xhub::App::<BaseApp>::consume_mint(&self.deliver_state)
     }
+
+    /// Total balance of a custom asset converted to accounts, in native units.
+    pub fn native_asset_supply(&self, asset: AssetType) -> Result<U256> {
+        let ctx = self.create_query_context(Some(0), false)?;
+        Ok(module_xhub::App::<BaseApp>::asset_supply(&ctx, asset))
+    }
 }
 
 impl BaseProvider for BaseApp {
@@ -565,4 +771,44 @@ impl BaseProvider for BaseApp {
             None
         }
     }
+
+    fn base_fee_per_gas(&self) -> U256 {
+        if let Ok(ctx) = self.create_query_context(None, false) {
+            module_ethereum::App::<Self>::base_fee_per_gas(&ctx)
+        } else {
+            DynamicGasPrice::min_gas_price()
+        }
+    }
+
+    fn block_base_fee_per_gas(&self, block_number: U256) -> Option<U256> {
+        if let Ok(ctx) = self.create_query_context(Some(0), false) {
+            module_ethereum::App::<Self>::block_base_fee_per_gas(&ctx, block_number)
+        } else {
+            None
+        }
+    }
+
+    fn log_index_from(&self) -> Option<u64> {
+        if let Ok(ctx) = self.create_query_context(Some(0), false) {
+            module_ethereum::App::<Self>::log_index_from(&ctx)
+        } else {
+            None
+        }
+    }
+
+    fn filter_log_blocks(
+        &self,
+        addresses: Option<&[H160]>,
+        topics: &[Option<Vec<H256>>],
+        from: u64,
+        to: u64,
+    ) -> Vec<u64> {
+        if let Ok(ctx) = self.create_query_context(Some(0), false) {
+            module_ethereum::App::<Self>::filter_log_blocks(
+                &ctx, addresses, topics, from, to,
+            )
+        } else {
+            vec![]
+        }
+    }
 }
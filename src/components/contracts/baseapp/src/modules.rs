@@ -3,7 +3,7 @@ use abci::*;
 use config::abci::global_cfg::CFG;
 use fp_core::{
     context::Context,
-    module::AppModule,
+    module::{AppModule, AppModuleBasic, PluggableModule},
     transaction::{
         ActionResult, Applyable, Executable, SignedExtension, ValidateUnsigned,
     },
@@ -19,20 +19,84 @@ use ledger::{
     data_model::{Transaction as FindoraTransaction, ASSET_TYPE_FRA},
 };
 use module_ethereum::storage::{TransactionIndex, DELIVER_PENDING_TRANSACTIONS};
+use parking_lot::RwLock;
 use ruc::*;
 use serde::Serialize;
+use serde_json::Value;
+use std::{collections::BTreeMap, sync::Arc};
+use tracing::warn;
+
+/// Names of the modules hardcoded in `ModuleManager`.
+const BUILTIN_MODULES: [&str; 4] = [
+    module_account::MODULE_NAME,
+    module_ethereum::MODULE_NAME,
+    module_evm::MODULE_NAME,
+    module_xhub::MODULE_NAME,
+];
+
+type RegisteredModule = Arc<RwLock<Box<dyn PluggableModule>>>;
 
-#[derive(Default, Clone)]
 pub struct ModuleManager {
     // Ordered module list
     pub(crate) account_module: module_account::App<BaseApp>,
     pub(crate) ethereum_module: module_ethereum::App<BaseApp>,
     pub(crate) evm_module: module_evm::App<BaseApp>,
     pub(crate) xhub_module: module_xhub::App<BaseApp>,
-    pub(crate) template_module: module_template::App<BaseApp>,
+    // Modules registered by name, run after the hardcoded ones in registration order.
+    pub(crate) registry: Vec<RegisteredModule>,
+}
+
+impl Default for ModuleManager {
+    fn default() -> Self {
+        let mut manager = ModuleManager {
+            account_module: Default::default(),
+            ethereum_module: Default::default(),
+            evm_module: Default::default(),
+            xhub_module: Default::default(),
+            registry: vec![],
+        };
+        pnk!(manager.register(module_template::App::<BaseApp>::default()));
+        manager
+    }
+}
+
+impl Clone for ModuleManager {
+    // The registered modules are cloned into new instances instead of being shared,
+    // so that a derived app never locks the modules of the consensus app.
+    fn clone(&self) -> Self {
+        ModuleManager {
+            account_module: self.account_module.clone(),
+            ethereum_module: self.ethereum_module.clone(),
+            evm_module: self.evm_module.clone(),
+            xhub_module: self.xhub_module.clone(),
+            registry: self
+                .registry
+                .iter()
+                .map(|m| Arc::new(RwLock::new(m.read().clone_module())))
+                .collect(),
+        }
+    }
 }
 
 impl ModuleManager {
+    /// Register a module by its name, the registration order is the execution order
+    /// of the registered modules, so it must be the same on all nodes.
+    pub fn register<M: PluggableModule + 'static>(&mut self, module: M) -> Result<()> {
+        let name = module.module_name();
+        if BUILTIN_MODULES.contains(&name.as_str()) || self.registered(&name).is_some() {
+            return Err(eg!(format!("module {name} is already registered")));
+        }
+        let module: Box<dyn PluggableModule> = Box::new(module);
+        self.registry.push(Arc::new(RwLock::new(module)));
+        Ok(())
+    }
+
+    fn registered(&self, name: &str) -> Option<&RegisteredModule> {
+        self.registry
+            .iter()
+            .find(|m| m.read().module_name() == name)
+    }
+
     pub fn query(
         &self,
         ctx: Context,
@@ -46,7 +110,7 @@ impl ModuleManager {
             return resp;
         }
 
-        // Note: adding new modules may need to be updated.
+        // Note: adding new built-in modules may need to be updated.
         let module_name = path.remove(0);
         if module_name == module_account::MODULE_NAME {
             self.account_module.query_route(ctx, path, req)
@@ -56,8 +120,8 @@ impl ModuleManager {
             self.evm_module.query_route(ctx, path, req)
         } else if module_name == module_xhub::MODULE_NAME {
             self.xhub_module.query_route(ctx, path, req)
-        } else if module_name == module_template::MODULE_NAME {
-            self.template_module.query_route(ctx, path, req)
+        } else if let Some(module) = self.registered(module_name) {
+            module.read().query_route(ctx, path, req)
         } else {
             resp.code = 1;
             resp.log = format!("Invalid query module route: {module_name}!");
@@ -66,12 +130,14 @@ impl ModuleManager {
     }
 
     pub fn begin_block(&mut self, ctx: &mut Context, req: &RequestBeginBlock) {
-        // Note: adding new modules need to be updated.
+        // Note: adding new built-in modules need to be updated.
         self.account_module.begin_block(ctx, req);
         self.ethereum_module.begin_block(ctx, req);
         self.evm_module.begin_block(ctx, req);
         self.xhub_module.begin_block(ctx, req);
-        self.template_module.begin_block(ctx, req);
+        for module in self.registry.iter() {
+            module.write().begin_block(ctx, req);
+        }
     }
 
     pub fn end_block(
@@ -81,7 +147,7 @@ impl ModuleManager {
         ff_addr_balance: u64,
     ) -> ResponseEndBlock {
         let mut resp: ResponseEndBlock = Default::default();
-        // Note: adding new modules need to be updated.
+        // Note: adding new built-in modules need to be updated.
         self.account_module.end_block(ctx, req, ff_addr_balance);
         let mresp = self.evm_module.end_block(ctx, req, 0);
         if !mresp.validator_updates.is_empty() {
@@ -90,10 +156,124 @@ impl ModuleManager {
 
         self.ethereum_module.end_block(ctx, req, 0);
         self.xhub_module.end_block(ctx, req, 0);
-        self.template_module.end_block(ctx, req, 0);
+        for module in self.registry.iter() {
+            module.write().end_block(ctx, req, 0);
+        }
         resp
     }
 
+    /// Validate and load the genesis app state, which is a JSON object keyed by module name,
+    /// modules that are missing from it are left untouched, and unknown keys are ignored.
+    pub fn init_genesis(&self, ctx: &Context, app_state: &[u8]) -> Result<()> {
+        let mut state = match serde_json::from_slice::<Value>(app_state)
+            .c(d!("invalid genesis app state"))?
+        {
+            Value::Null => return Ok(()),
+            Value::String(s) if s.is_empty() => return Ok(()),
+            Value::Object(m) => m.into_iter().collect::<BTreeMap<_, _>>(),
+            _ => return Err(eg!("genesis app state must be a JSON object")),
+        };
+
+        // Note: adding new built-in modules need to be updated.
+        let account = Self::genesis_of(&mut state, module_account::MODULE_NAME)?;
+        let ethereum = Self::genesis_of(&mut state, module_ethereum::MODULE_NAME)?;
+        let evm = Self::genesis_of(&mut state, module_evm::MODULE_NAME)?;
+        let xhub = Self::genesis_of(&mut state, module_xhub::MODULE_NAME)?;
+        let registered = self
+            .registry
+            .iter()
+            .map(|m| Self::genesis_of(&mut state, &m.read().module_name()))
+            .collect::<Result<Vec<_>>>()?;
+        for name in state.keys() {
+            warn!(target: "baseapp", "unknown genesis module: {}, ignored", name);
+        }
+
+        if let Some(genesis) = account.as_ref() {
+            self.account_module.validate_genesis(genesis).c(d!())?;
+        }
+        if let Some(genesis) = ethereum.as_ref() {
+            self.ethereum_module.validate_genesis(genesis).c(d!())?;
+        }
+        if let Some(genesis) = evm.as_ref() {
+            self.evm_module.validate_genesis(genesis).c(d!())?;
+        }
+        if let Some(genesis) = xhub.as_ref() {
+            self.xhub_module.validate_genesis(genesis).c(d!())?;
+        }
+        for (module, genesis) in self.registry.iter().zip(registered.iter()) {
+            if let Some(genesis) = genesis {
+                module.read().validate_genesis(genesis).c(d!())?;
+            }
+        }
+
+        if let Some(genesis) = account.as_ref() {
+            self.account_module.init_genesis(ctx, genesis).c(d!())?;
+        }
+        if let Some(genesis) = ethereum.as_ref() {
+            self.ethereum_module.init_genesis(ctx, genesis).c(d!())?;
+        }
+        if let Some(genesis) = evm.as_ref() {
+            self.evm_module.init_genesis(ctx, genesis).c(d!())?;
+        }
+        if let Some(genesis) = xhub.as_ref() {
+            self.xhub_module.init_genesis(ctx, genesis).c(d!())?;
+        }
+        for (module, genesis) in self.registry.iter().zip(registered.iter()) {
+            if let Some(genesis) = genesis {
+                module.read().init_genesis(ctx, genesis).c(d!())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Export the state of all modules in the format of `init_genesis`.
+    pub fn export_genesis(&self, ctx: &Context) -> Result<Vec<u8>> {
+        // Note: adding new built-in modules need to be updated.
+        let mut state = vec![
+            (
+                module_account::MODULE_NAME.to_owned(),
+                self.account_module.export_genesis(ctx).c(d!())?,
+            ),
+            (
+                module_ethereum::MODULE_NAME.to_owned(),
+                self.ethereum_module.export_genesis(ctx).c(d!())?,
+            ),
+            (
+                module_evm::MODULE_NAME.to_owned(),
+                self.evm_module.export_genesis(ctx).c(d!())?,
+            ),
+            (
+                module_xhub::MODULE_NAME.to_owned(),
+                self.xhub_module.export_genesis(ctx).c(d!())?,
+            ),
+        ];
+        for module in self.registry.iter() {
+            let module = module.read();
+            state.push((module.module_name(), module.export_genesis(ctx).c(d!())?));
+        }
+
+        let state = state
+            .into_iter()
+            .map(|(name, genesis)| {
+                serde_json::from_slice::<Value>(&genesis)
+                    .c(d!())
+                    .map(|v| (name, v))
+            })
+            .collect::<Result<BTreeMap<_, _>>>()?;
+
+        serde_json::to_vec_pretty(&state).c(d!())
+    }
+
+    fn genesis_of(
+        state: &mut BTreeMap<String, Value>,
+        name: &str,
+    ) -> Result<Option<Vec<u8>>> {
+        state
+            .remove(name)
+            .map(|v| serde_json::to_vec(&v).c(d!()))
+            .transpose()
+    }
+
     pub fn process_tx<
         Extra: Clone + Serialize + SignedExtension<AccountId = Address>,
     >(
@@ -108,6 +288,23 @@ impl ModuleManager {
                 module_ethereum::App<BaseApp>,
                 Extra,
             >(&ctx, action, checked),
+            actions::Action::Module(action) => {
+                if ctx.header.height < CFG.checkpoint.module_action_height {
+                    return Err(eg!("Action::Module is not enabled"));
+                }
+                let module = self.registered(&action.module).cloned().c(d!(format!(
+                    "Invalid action module route: {}!",
+                    action.module
+                )))?;
+                Self::dispatch::<ModuleCall, ModuleRouter, Extra>(
+                    &ctx,
+                    ModuleCall {
+                        module,
+                        data: action.data,
+                    },
+                    checked,
+                )
+            }
             _ => Self::dispatch::<actions::Action, BaseApp, Extra>(
                 &ctx,
                 tx.function,
@@ -121,9 +318,19 @@ impl ModuleManager {
         ctx: &Context,
         tx: &FindoraTransaction,
     ) -> Result<Option<H256>> {
-        let (from, owner, amount, asset, lowlevel) =
+        let (from, owner, amount, asset, lowlevel, native) =
             check_convert_account(tx, ctx.header.height)?;
 
+        if native {
+            return module_xhub::App::<BaseApp>::deposit_asset(
+                ctx,
+                &Address::from(owner),
+                asset,
+                amount,
+            )
+            .map(|_| None);
+        }
+
         if CFG.checkpoint.prismxx_inital_height < ctx.header.height {
             let mut pending_txs = DELIVER_PENDING_TRANSACTIONS.lock().c(d!())?;
             // if let Some(pending_txs)
@@ -206,3 +413,40 @@ impl ModuleManager {
         Ok(ActionResult::default())
     }
 }
+
+/// The action of a registered module along with the module it is routed to.
+struct ModuleCall {
+    module: RegisteredModule,
+    data: Vec<u8>,
+}
+
+/// Dispatches the actions of registered modules,
+/// unsigned transactions are rejected like the ones of `BaseApp`.
+struct ModuleRouter;
+
+impl ValidateUnsigned for ModuleRouter {
+    type Call = ModuleCall;
+
+    fn pre_execute(_ctx: &Context, _call: &Self::Call) -> Result<()> {
+        Ok(())
+    }
+
+    fn validate_unsigned(_ctx: &Context, _call: &Self::Call) -> Result<()> {
+        Err(eg!(
+            "Could not find an unsigned validator for the unsigned transaction"
+        ))
+    }
+}
+
+impl Executable for ModuleRouter {
+    type Origin = Address;
+    type Call = ModuleCall;
+
+    fn execute(
+        origin: Option<Self::Origin>,
+        call: Self::Call,
+        ctx: &Context,
+    ) -> Result<ActionResult> {
+        call.module.read().execute(origin, &call.data, ctx)
+    }
+}
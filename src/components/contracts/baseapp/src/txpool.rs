@@ -0,0 +1,368 @@
+//! Per-sender bookkeeping of the EVM transactions accepted by check_tx.
+//!
+//! Tendermint's mempool only knows about raw bytes, so the pool tracks what it
+//! holds per sender and nonce, to support replace-by-fee and to hold transactions
+//! with a nonce gap until the gap is filled.
+
+use ethereum::TransactionV0 as Transaction;
+use fp_types::error_code::{TxErrorCode, TxResult};
+use primitive_types::{H160, H256, U256};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Minimum gas price increase, in percent, for a transaction to replace
+/// a pending one with the same sender and nonce.
+pub const PRICE_BUMP_PERCENT: u64 = 10;
+/// Maximum number of future-nonce transactions held for a sender.
+pub const MAX_QUEUED_PER_SENDER: usize = 16;
+/// Maximum number of future-nonce transactions held in total.
+pub const MAX_QUEUED: usize = 4096;
+
+/// How a transaction is admitted by the pool.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Admission {
+    /// The next nonce of the sender, goes through the normal check.
+    Next,
+    /// Replaces the pending transaction with the same nonce.
+    Replace,
+    /// Held until the nonce gap is filled.
+    Queue,
+}
+
+#[derive(Clone, Debug)]
+pub struct PoolTransaction {
+    pub hash: H256,
+    pub transaction: Transaction,
+}
+
+impl From<Transaction> for PoolTransaction {
+    fn from(transaction: Transaction) -> Self {
+        PoolTransaction {
+            hash: transaction.hash(),
+            transaction,
+        }
+    }
+}
+
+type SenderQueue = BTreeMap<U256, PoolTransaction>;
+
+#[derive(Default)]
+pub struct TxPool {
+    /// Transactions in the tendermint mempool, by sender and nonce.
+    pending: BTreeMap<H160, SenderQueue>,
+    /// Transactions waiting for a nonce gap, by sender and nonce.
+    queued: BTreeMap<H160, SenderQueue>,
+    queued_cnt: usize,
+    /// Hashes of replaced transactions, to be dropped on recheck.
+    replaced: HashMap<H256, (H160, U256)>,
+    /// Queued transactions whose gap is filled, to be broadcast again.
+    promoted: Vec<Transaction>,
+}
+
+impl TxPool {
+    /// The next nonce of the sender, considering its pending transactions.
+    pub fn next_nonce(&self, sender: &H160, nonce: U256) -> U256 {
+        self.pending
+            .get(sender)
+            .and_then(|txs| txs.keys().next_back())
+            .map(|last| nonce.max(last.saturating_add(U256::one())))
+            .unwrap_or(nonce)
+    }
+
+    /// Decide how a transaction of the sender is admitted,
+    /// `nonce` is the nonce of the sender in the check state.
+    pub fn admission(
+        &self,
+        sender: &H160,
+        nonce: U256,
+        transaction: &Transaction,
+    ) -> TxResult<Admission> {
+        let expected = self.next_nonce(sender, nonce);
+        if transaction.nonce == expected {
+            return Ok(Admission::Next);
+        }
+
+        if transaction.nonce < expected {
+            let pending = self
+                .pending
+                .get(sender)
+                .and_then(|txs| txs.get(&transaction.nonce));
+            return match pending {
+                Some(old) if old.hash != transaction.hash() => {
+                    Self::ensure_bump(old, transaction).map(|_| Admission::Replace)
+                }
+                // Below the committed nonce, or a duplicate,
+                // the normal check reports it.
+                _ => Ok(Admission::Next),
+            };
+        }
+
+        let queued = self.queued.get(sender);
+        if let Some(old) = queued.and_then(|txs| txs.get(&transaction.nonce)) {
+            if old.hash != transaction.hash() {
+                Self::ensure_bump(old, transaction)?;
+            }
+            return Ok(Admission::Queue);
+        }
+        if queued.map(|txs| txs.len()).unwrap_or(0) >= MAX_QUEUED_PER_SENDER {
+            return Err(TxErrorCode::TxPoolFull.error(format!(
+                "TxPoolFull: origin: {sender:?} has {MAX_QUEUED_PER_SENDER} queued transactions"
+            )));
+        }
+        if self.queued_cnt >= MAX_QUEUED {
+            return Err(TxErrorCode::TxPoolFull
+                .error(format!("TxPoolFull: {MAX_QUEUED} transactions are queued")));
+        }
+        Ok(Admission::Queue)
+    }
+
+    fn ensure_bump(old: &PoolTransaction, new: &Transaction) -> TxResult<()> {
+        let min_price = old
+            .transaction
+            .gas_price
+            .saturating_mul(U256::from(100 + PRICE_BUMP_PERCENT))
+            / U256::from(100);
+        if new.gas_price < min_price {
+            return Err(TxErrorCode::ReplacementUnderpriced.error(format!(
+                "ReplacementUnderpriced: got gas price {}, but at least {} is required to replace {:?}",
+                new.gas_price, min_price, old.hash
+            )));
+        }
+        Ok(())
+    }
+
+    /// Record a transaction accepted into the mempool, promoting the queued
+    /// transaction with the following nonce if any.
+    pub fn insert_pending(&mut self, sender: H160, transaction: Transaction) {
+        let next = transaction.nonce.saturating_add(U256::one());
+        self.pending
+            .entry(sender)
+            .or_default()
+            .insert(transaction.nonce, transaction.into());
+
+        if let Some(tx) = self.take_queued(&sender, &next) {
+            self.promoted.push(tx.transaction);
+        }
+    }
+
+    /// Record a transaction replacing the pending one with the same nonce.
+    pub fn replace(&mut self, sender: H160, transaction: Transaction) {
+        let nonce = transaction.nonce;
+        let old = self
+            .pending
+            .entry(sender)
+            .or_default()
+            .insert(nonce, transaction.into());
+        if let Some(old) = old {
+            self.replaced.insert(old.hash, (sender, nonce));
+        }
+    }
+
+    /// Hold a transaction until its nonce gap is filled.
+    pub fn queue(&mut self, sender: H160, transaction: Transaction) {
+        let old = self
+            .queued
+            .entry(sender)
+            .or_default()
+            .insert(transaction.nonce, transaction.into());
+        if old.is_none() {
+            self.queued_cnt += 1;
+        }
+    }
+
+    /// Drop a pending transaction rejected on recheck,
+    /// unless it has been replaced by another one with the same nonce.
+    pub fn remove_pending(&mut self, sender: &H160, nonce: &U256, hash: &H256) {
+        if let Some(txs) = self.pending.get_mut(sender) {
+            if txs.get(nonce).map(|tx| &tx.hash == hash).unwrap_or(false) {
+                txs.remove(nonce);
+            }
+            if txs.is_empty() {
+                self.pending.remove(sender);
+            }
+        }
+    }
+
+    /// Whether the transaction has been replaced, it's forgotten once asked.
+    pub fn take_replaced(&mut self, hash: &H256) -> bool {
+        self.replaced.remove(hash).is_some()
+    }
+
+    /// Take the transactions promoted from the queue.
+    pub fn take_promoted(&mut self) -> Vec<Transaction> {
+        std::mem::take(&mut self.promoted)
+    }
+
+    fn take_queued(&mut self, sender: &H160, nonce: &U256) -> Option<PoolTransaction> {
+        let txs = self.queued.get_mut(sender)?;
+        let tx = txs.remove(nonce)?;
+        if txs.is_empty() {
+            self.queued.remove(sender);
+        }
+        self.queued_cnt -= 1;
+        Some(tx)
+    }
+
+    /// Drop the transactions included in blocks, `nonce_of` gets the
+    /// committed nonce of a sender.
+    pub fn prune(&mut self, nonce_of: impl Fn(&H160) -> U256) {
+        let senders = self
+            .pending
+            .keys()
+            .chain(self.queued.keys())
+            .copied()
+            .collect::<BTreeSet<_>>();
+        for sender in senders {
+            let nonce = nonce_of(&sender);
+            if let Some(txs) = self.pending.get_mut(&sender) {
+                *txs = txs.split_off(&nonce);
+                if txs.is_empty() {
+                    self.pending.remove(&sender);
+                }
+            }
+            if let Some(txs) = self.queued.get_mut(&sender) {
+                let kept = txs.split_off(&nonce);
+                self.queued_cnt -= txs.len();
+                *txs = kept;
+                if txs.is_empty() {
+                    self.queued.remove(&sender);
+                }
+            }
+            // The gap may be filled by transactions from other nodes.
+            let next = self.next_nonce(&sender, nonce);
+            if let Some(tx) = self.take_queued(&sender, &next) {
+                self.promoted.push(tx.transaction);
+            }
+        }
+        self.replaced
+            .retain(|_, (sender, n)| *n >= nonce_of(sender));
+    }
+
+    pub fn pending(&self) -> &BTreeMap<H160, SenderQueue> {
+        &self.pending
+    }
+
+    pub fn queued(&self) -> &BTreeMap<H160, SenderQueue> {
+        &self.queued
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ethereum::{TransactionAction, TransactionSignature};
+
+    fn tx(nonce: u64, gas_price: u64) -> Transaction {
+        Transaction {
+            nonce: U256::from(nonce),
+            gas_price: U256::from(gas_price),
+            gas_limit: U256::from(21000),
+            action: TransactionAction::Create,
+            value: U256::zero(),
+            input: vec![],
+            signature: TransactionSignature::new(
+                27,
+                H256::repeat_byte(1),
+                H256::repeat_byte(2),
+            )
+            .unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_replacement() {
+        let mut pool = TxPool::default();
+        let sender = H160::repeat_byte(1);
+        let old = tx(0, 100);
+        pool.insert_pending(sender, old.clone());
+
+        //The same nonce needs a price bump
+        assert_eq!(
+            pool.admission(&sender, U256::zero(), &old).unwrap(),
+            Admission::Next
+        );
+        let err = pool
+            .admission(&sender, U256::zero(), &tx(0, 109))
+            .unwrap_err();
+        assert_eq!(err.code, TxErrorCode::ReplacementUnderpriced);
+        let new = tx(0, 110);
+        assert_eq!(
+            pool.admission(&sender, U256::zero(), &new).unwrap(),
+            Admission::Replace
+        );
+
+        //The replaced transaction is dropped on recheck, once
+        pool.replace(sender, new.clone());
+        assert_eq!(pool.pending()[&sender][&U256::zero()].hash, new.hash());
+        assert!(pool.take_replaced(&old.hash()));
+        assert!(!pool.take_replaced(&old.hash()));
+
+        //A failed recheck of the replaced one keeps the new one
+        pool.remove_pending(&sender, &U256::zero(), &old.hash());
+        assert!(pool.pending().contains_key(&sender));
+        pool.remove_pending(&sender, &U256::zero(), &new.hash());
+        assert!(pool.pending().is_empty());
+    }
+
+    #[test]
+    fn test_queue_and_promotion() {
+        let mut pool = TxPool::default();
+        let sender = H160::repeat_byte(1);
+
+        //A nonce gap is queued
+        assert_eq!(
+            pool.admission(&sender, U256::zero(), &tx(1, 100)).unwrap(),
+            Admission::Queue
+        );
+        pool.queue(sender, tx(1, 100));
+        pool.queue(sender, tx(2, 100));
+        assert_eq!(pool.queued()[&sender].len(), 2);
+        assert!(pool.take_promoted().is_empty());
+
+        //Filling the gap promotes the next one
+        pool.insert_pending(sender, tx(0, 100));
+        let promoted = pool.take_promoted();
+        assert_eq!(promoted.len(), 1);
+        assert_eq!(promoted[0].nonce, U256::one());
+        assert_eq!(pool.queued()[&sender].len(), 1);
+        assert!(pool.take_promoted().is_empty());
+
+        //The per sender limit is enforced
+        let other = H160::repeat_byte(2);
+        for nonce in 1..=MAX_QUEUED_PER_SENDER as u64 {
+            pool.queue(other, tx(nonce, 100));
+        }
+        let err = pool
+            .admission(
+                &other,
+                U256::zero(),
+                &tx(MAX_QUEUED_PER_SENDER as u64 + 1, 100),
+            )
+            .unwrap_err();
+        assert_eq!(err.code, TxErrorCode::TxPoolFull);
+    }
+
+    #[test]
+    fn test_prune() {
+        let mut pool = TxPool::default();
+        let sender = H160::repeat_byte(1);
+        pool.insert_pending(sender, tx(0, 100));
+        pool.insert_pending(sender, tx(1, 100));
+        pool.replace(sender, tx(1, 200));
+        pool.queue(sender, tx(3, 100));
+        pool.queue(sender, tx(4, 100));
+
+        //Included transactions are dropped, a filled gap promotes the queue
+        pool.prune(|_| U256::from(3));
+        assert!(pool.pending().is_empty());
+        let promoted = pool.take_promoted();
+        assert_eq!(promoted.len(), 1);
+        assert_eq!(promoted[0].nonce, U256::from(3));
+        assert_eq!(pool.queued()[&sender].len(), 1);
+        assert!(!pool.take_replaced(&tx(1, 100).hash()));
+
+        //Everything below the committed nonce is dropped
+        pool.prune(|_| U256::from(5));
+        assert!(pool.queued().is_empty());
+        assert_eq!(pool.queued_cnt, 0);
+    }
+}
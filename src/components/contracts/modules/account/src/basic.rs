@@ -1,6 +1,51 @@
-use super::{App, Config, MODULE_NAME};
-use fp_core::module::AppModuleBasic;
-use ruc::Result;
+use super::{storage::*, App, Config, MODULE_NAME};
+use fp_core::{account::SmartAccount, context::Context, module::AppModuleBasic};
+use fp_storage::BorrowMut;
+use fp_types::crypto::Address;
+use primitive_types::{H160, U256};
+use ruc::*;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, str::FromStr};
+
+/// Genesis state of the account module.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenesisConfig {
+    /// Accounts keyed by address, `0x` prefixed hex for EVM addresses
+    /// and bech32 (`fra1...`) for native addresses.
+    /// Balances are in the 18 decimals of the EVM.
+    #[serde(default)]
+    pub accounts: BTreeMap<String, SmartAccount>,
+    /// Summed from `accounts` if missing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_issuance: Option<U256>,
+}
+
+impl GenesisConfig {
+    // Parse the accounts and get the total issuance.
+    fn parse(genesis: &[u8]) -> Result<(Vec<(Address, SmartAccount)>, U256)> {
+        let genesis =
+            serde_json::from_slice::<Self>(genesis).c(d!("invalid account genesis"))?;
+
+        let mut sum = U256::zero();
+        let mut accounts = Vec::with_capacity(genesis.accounts.len());
+        for (addr, account) in genesis.accounts {
+            sum = sum
+                .checked_add(account.balance)
+                .and_then(|s| s.checked_add(account.reserved))
+                .c(d!("total issuance overflow"))?;
+            accounts.push((parse_address(&addr).c(d!())?, account));
+        }
+
+        let total_issuance = genesis.total_issuance.unwrap_or(sum);
+        if total_issuance < sum {
+            return Err(eg!(format!(
+                "total issuance {total_issuance} is less than the sum of balances {sum}"
+            )));
+        }
+
+        Ok((accounts, total_issuance))
+    }
+}
 
 impl<C: Config> AppModuleBasic for App<C> {
     fn name() -> String {
@@ -8,18 +53,51 @@ impl<C: Config> AppModuleBasic for App<C> {
     }
 
     fn default_genesis(&self) -> Vec<u8> {
-        todo!()
+        pnk!(serde_json::to_vec(&GenesisConfig::default()))
+    }
+
+    fn init_genesis(&self, ctx: &Context, genesis: &[u8]) -> Result<()> {
+        let (accounts, total_issuance) = GenesisConfig::parse(genesis).c(d!())?;
+        for (who, account) in accounts.iter() {
+            AccountStore::insert(ctx.state.write().borrow_mut(), who, account)
+                .c(d!())?;
+        }
+        TotalIssuance::put(ctx.state.write().borrow_mut(), &total_issuance).c(d!())
     }
 
-    fn init_genesis(&self) {
-        todo!()
+    fn validate_genesis(&self, genesis: &[u8]) -> Result<()> {
+        GenesisConfig::parse(genesis).c(d!()).map(|_| ())
     }
 
-    fn validate_genesis(&self) -> Result<()> {
-        todo!()
+    fn export_genesis(&self, ctx: &Context) -> Result<Vec<u8>> {
+        let state = ctx.state.read();
+        let genesis = GenesisConfig {
+            accounts: AccountStore::iterate(&state)
+                .into_iter()
+                .map(|(who, account)| (format_address(&who), account))
+                .collect(),
+            total_issuance: TotalIssuance::get(&state),
+        };
+        serde_json::to_vec(&genesis).c(d!())
     }
+}
+
+fn parse_address(addr: &str) -> Result<Address> {
+    if addr.starts_with("0x") {
+        H160::from_str(addr)
+            .map(Address::from)
+            .c(d!(format!("invalid EVM address: {addr}")))
+    } else {
+        Address::from_str(addr).map_err(|_| eg!(format!("invalid address: {addr}")))
+    }
+}
 
-    fn export_genesis(&self) {
-        todo!()
+// EVM addresses are mapped to `evm:` + H160 + zero paddings.
+fn format_address(who: &Address) -> String {
+    let bytes: &[u8] = who.as_ref();
+    if bytes.starts_with(b"evm:") && bytes[24..].iter().all(|b| *b == 0) {
+        format!("{:?}", H160::from_slice(&bytes[4..24]))
+    } else {
+        who.to_string()
     }
 }
@@ -10,6 +10,7 @@ mod impls;
 mod tests;
 
 use abci::{RequestQuery, ResponseQuery};
+pub use basic::GenesisConfig;
 use fp_core::{context::Context, module::AppModule};
 use fp_traits::{
     account::{AccountAsset, FeeCalculator},
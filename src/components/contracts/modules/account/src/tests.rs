@@ -1,7 +1,7 @@
 use crate::storage::*;
 use crate::App;
 use fin_db::{FinDB, RocksDB};
-use fp_core::{account::SmartAccount, context::Context};
+use fp_core::{account::SmartAccount, context::Context, module::AppModuleBasic};
 use fp_storage::{Borrow, BorrowMut};
 use fp_traits::account::AccountAsset;
 use fp_types::crypto::Address;
@@ -376,3 +376,51 @@ fn test_account_refund() {
     assert_eq!(App::<()>::balance(&ctx, &address), 1200.into());
     assert_eq!(App::<()>::reserved_balance(&ctx, &address), 300.into());
 }
+
+#[test]
+fn test_account_genesis() {
+    //Setup db
+    let ctx = setup();
+    let app = App::<()>::default();
+
+    //One EVM account and one native account
+    let mut prng = ChaChaRng::from_entropy();
+    let key = XfrKeyPair::generate(&mut prng);
+    let native = Address::from(key.pub_key);
+    let evm = Address::from(primitive_types::H160::repeat_byte(1));
+    let genesis = format!(
+        r#"{{"accounts": {{
+            "0x0101010101010101010101010101010101010101": {{"balance": "0x64"}},
+            "{native}": {{"balance": "0xc8", "nonce": "0x1"}}
+        }}}}"#
+    );
+
+    assert!(app.validate_genesis(genesis.as_bytes()).is_ok());
+    assert!(app.init_genesis(&ctx, genesis.as_bytes()).is_ok());
+    assert_eq!(App::<()>::balance(&ctx, &evm), 100.into());
+    assert_eq!(App::<()>::nonce(&ctx, &native), 1.into());
+    assert_eq!(App::<()>::total_issuance(&ctx), 300.into());
+
+    //Export the committed state
+    assert!(ctx.state.write().commit(1).is_ok());
+    let exported = app.export_genesis(&ctx).unwrap();
+    assert_eq!(
+        serde_json::from_slice::<crate::GenesisConfig>(&exported).unwrap(),
+        serde_json::from_slice::<crate::GenesisConfig>(
+            format!(
+                r#"{{"accounts": {{
+                    "0x0101010101010101010101010101010101010101": {{"balance": "0x64"}},
+                    "{native}": {{"balance": "0xc8", "nonce": "0x1"}}
+                }}, "total_issuance": "0x12c"}}"#
+            )
+            .as_bytes()
+        )
+        .unwrap()
+    );
+
+    //The total issuance can not be less than the sum of balances
+    let genesis = r#"{"accounts": {
+        "0x0101010101010101010101010101010101010101": {"balance": "0x64"}
+    }, "total_issuance": "0x1"}"#;
+    assert!(app.validate_genesis(genesis.as_bytes()).is_err());
+}
@@ -1,6 +1,13 @@
 use super::{App, Config, MODULE_NAME};
-use fp_core::module::AppModuleBasic;
-use ruc::Result;
+use fp_core::{context::Context, module::AppModuleBasic};
+use ruc::*;
+use serde::{Deserialize, Serialize};
+
+/// Genesis state of the ethereum module, which is empty
+/// since the blocks, receipts and transaction statuses can not be
+/// carried over to a new chain.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenesisConfig {}
 
 impl<C: Config> AppModuleBasic for App<C> {
     fn name() -> String {
@@ -8,18 +15,20 @@ impl<C: Config> AppModuleBasic for App<C> {
     }
 
     fn default_genesis(&self) -> Vec<u8> {
-        todo!()
+        pnk!(serde_json::to_vec(&GenesisConfig::default()))
     }
 
-    fn init_genesis(&self) {
-        todo!()
+    fn init_genesis(&self, _ctx: &Context, genesis: &[u8]) -> Result<()> {
+        self.validate_genesis(genesis).c(d!())
     }
 
-    fn validate_genesis(&self) -> Result<()> {
-        todo!()
+    fn validate_genesis(&self, genesis: &[u8]) -> Result<()> {
+        serde_json::from_slice::<GenesisConfig>(genesis)
+            .c(d!("invalid ethereum genesis"))
+            .map(|_| ())
     }
 
-    fn export_genesis(&self) {
-        todo!()
+    fn export_genesis(&self, _ctx: &Context) -> Result<Vec<u8>> {
+        serde_json::to_vec(&GenesisConfig::default()).c(d!())
     }
 }
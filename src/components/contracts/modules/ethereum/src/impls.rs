@@ -1,5 +1,5 @@
 use crate::storage::*;
-use crate::{App, Config, ContractLog, TransactionExecuted};
+use crate::{App, Config, ContractLog, LogBlock, TransactionExecuted};
 use config::abci::global_cfg::CFG;
 use enterprise_web3::{TxState, BLOCK, RECEIPTS, TXS, WEB3_SERVICE_START_HEIGHT};
 use ethereum::{
@@ -10,23 +10,45 @@ use ethereum_types::{Bloom, BloomInput, H160, H256, H64, U256};
 use evm::{ExitFatal, ExitReason};
 use fp_core::{
     context::{Context, RunTxMode},
+    ensure,
     macros::Get,
     module::AppModuleBasic,
     transaction::ActionResult,
 };
 use fp_events::Event;
 use fp_evm::{BlockId, CallOrCreateInfo, Runner, TransactionStatus};
-use fp_storage::BorrowMut;
+use fp_storage::{BorrowMut, IterOrder};
+use fp_traits::{
+    account::AccountAsset,
+    evm::{AddressMapping, FeeCalculator},
+};
 use fp_types::crypto::Address;
 use fp_types::{
-    actions::evm as EvmAction,
-    crypto::{secp256k1_ecdsa_recover, HA256},
+    actions::{
+        ethereum::{Action, SignedSponsorPolicy, SponsoredTransaction},
+        evm as EvmAction,
+    },
+    crypto::{secp256k1_ecdsa_recover, HA160, HA256},
+    error_code::{TxErrorCode, TxResult},
 };
 use fp_utils::{proposer_converter, timestamp_converter};
 use ruc::*;
 use sha3::{Digest, Keccak256};
+use std::collections::{BTreeMap, BTreeSet};
 use tracing::{debug, info};
 
+/// Blocks of the log index committed at once by backfilling.
+const LOG_INDEX_BACKFILL_BATCH: u64 = 1000;
+
+/// Bounds the change of the base fee per block to 1/8, as EIP-1559.
+const BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
+
+/// The gas target of a block is its gas limit divided by this, as EIP-1559.
+const ELASTICITY_MULTIPLIER: u64 = 2;
+
+/// Gas charged for setting a sponsor policy.
+const SPONSOR_POLICY_GAS: u64 = 21000;
+
 impl<C: Config> App<C> {
     pub fn recover_signer_fast(
         ctx: &Context,
@@ -77,6 +99,256 @@ impl<C: Config> App<C> {
         )))
     }
 
+    /// Recover the fee payer of a sponsored transaction.
+    pub fn recover_fee_payer(sponsored: &SponsoredTransaction) -> Option<H160> {
+        Self::recover_address(
+            &sponsored.fee_payer_signature,
+            &sponsored.fee_payer_message(),
+        )
+    }
+
+    /// Recover the fee payer who signed a sponsor policy.
+    pub fn recover_policy_signer(signed: &SignedSponsorPolicy) -> Option<H160> {
+        Self::recover_address(&signed.signature, &signed.message())
+    }
+
+    // Recover the signer of a 65 bytes signature of `r`, `s` and `v`.
+    fn recover_address(signature: &[u8], msg: &[u8; 32]) -> Option<H160> {
+        let sig: [u8; 65] = signature.try_into().ok()?;
+        let pubkey = secp256k1_ecdsa_recover(&sig, msg).ok()?;
+        Some(H160::from(H256::from_slice(
+            Keccak256::digest(&pubkey).as_slice(),
+        )))
+    }
+
+    /// The stateful checks of an action before it is executed,
+    /// the same as `validate_unsigned` but the rejections are typed.
+    pub fn check_action(ctx: &Context, call: &Action) -> TxResult<()> {
+        if !matches!(call, Action::Transact(_))
+            && ctx.header.height < CFG.checkpoint.sponsored_tx_height
+        {
+            return Err(TxErrorCode::SponsorRejected
+                .error("SponsorNotEnabled: sponsored transactions are not enabled"));
+        }
+        let (transaction, fee_payer) = match call {
+            Action::Transact(transaction) => (transaction, None),
+            Action::SponsoredTransact(sponsored) => (
+                &sponsored.transaction,
+                Some(Self::validate_sponsorship(ctx, sponsored)?),
+            ),
+            Action::SetSponsorPolicy(signed) => {
+                return Self::validate_sponsor_policy(ctx, signed).map(|_| ());
+            }
+        };
+        if let Some(chain_id) = transaction.signature.chain_id() {
+            if chain_id != C::ChainId::get() {
+                return Err(TxErrorCode::EvmCheckFailed.error(format!(
+                    "InvalidChainId, got {}, but expected {}",
+                    chain_id,
+                    C::ChainId::get()
+                )));
+            }
+        } else {
+            return Err(TxErrorCode::EvmCheckFailed.error("Must provide chainId"));
+        }
+
+        let origin = Self::recover_signer_fast(ctx, transaction).ok_or_else(|| {
+            TxErrorCode::InvalidSignature.error("ExecuteTransaction: InvalidSignature")
+        })?;
+
+        // Same as go ethereum, Min gas limit is 21000.
+        if transaction.gas_limit < U256::from(21000)
+            || transaction.gas_limit > C::BlockGasLimit::get()
+        {
+            return Err(TxErrorCode::EvmCheckFailed.error(format!(
+                "InvalidGasLimit: got {}, the gas limit must be in range [21000, {}]",
+                transaction.gas_limit,
+                C::BlockGasLimit::get()
+            )));
+        }
+        let min_gas_price = Self::base_fee_per_gas(ctx);
+        let mut flag = false;
+        if ctx.header.height < CFG.checkpoint.max_gas_price_limit {
+            if transaction.gas_price < min_gas_price {
+                flag = true;
+            }
+        } else if transaction.gas_price < min_gas_price
+            || transaction.gas_price > C::FeeCalculator::max_gas_price()
+        {
+            flag = true;
+        }
+
+        if flag {
+            return Err(TxErrorCode::GasPriceOutOfRange.error(format!(
+                "InvalidGasPrice: got {}, but the minimum gas price is {}, max gas price is {}",
+                transaction.gas_price,
+                min_gas_price, C::FeeCalculator::max_gas_price()
+            )));
+        }
+
+        let account_id = C::AddressMapping::convert_to_account_id(origin);
+        let account =
+            C::AccountAsset::account_of(ctx, &account_id, None).unwrap_or_default();
+        let nonce = account.nonce;
+        let balance = account.balance;
+
+        if transaction.nonce < nonce {
+            #[cfg(not(feature = "benchmark"))]
+            return Err(TxErrorCode::InvalidNonce.error(format!(
+                "InvalidNonce: origin: {:?}, got {}, but expected {}",
+                origin, transaction.nonce, nonce
+            )));
+        }
+
+        // The fee of a sponsored transaction is checked against its fee payer.
+        let fee = transaction.gas_price.saturating_mul(transaction.gas_limit);
+        let total_payment = if fee_payer.is_some() {
+            transaction.value
+        } else {
+            transaction.value.saturating_add(fee)
+        };
+        if balance < total_payment {
+            return Err(TxErrorCode::InsufficientBalance.error(format!(
+                "InsufficientBalance, origin: {origin:?}, actual balance {balance}, but expected payment {total_payment}",
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Check a sponsored transaction against the policy and the balance
+    /// of its fee payer, returns the fee payer.
+    pub fn validate_sponsorship(
+        ctx: &Context,
+        sponsored: &SponsoredTransaction,
+    ) -> TxResult<H160> {
+        if sponsored.chain_id != C::ChainId::get() {
+            return Err(TxErrorCode::SponsorRejected.error(format!(
+                "InvalidChainId, got {}, but expected {}",
+                sponsored.chain_id,
+                C::ChainId::get()
+            )));
+        }
+        let fee_payer = Self::recover_fee_payer(sponsored).ok_or_else(|| {
+            TxErrorCode::SponsorRejected.error("InvalidFeePayer: invalid signature")
+        })?;
+        let transaction = &sponsored.transaction;
+        let sender = Self::recover_signer_fast(ctx, transaction).ok_or_else(|| {
+            TxErrorCode::InvalidSignature.error("ExecuteTransaction: InvalidSignature")
+        })?;
+        if fee_payer == sender {
+            return Err(TxErrorCode::SponsorRejected
+                .error("InvalidFeePayer: the sender can't sponsor itself"));
+        }
+
+        let fee = transaction.gas_price.saturating_mul(transaction.gas_limit);
+        let policy = SponsorPolicies::get(&ctx.state.read(), &HA160(fee_payer))
+            .unwrap_or_default();
+        if !policy.allowed_senders.is_empty()
+            && !policy.allowed_senders.contains(&sender)
+        {
+            return Err(TxErrorCode::SponsorRejected.error(format!(
+                "SponsorNotAllowed: fee payer {fee_payer:?} doesn't sponsor {sender:?}"
+            )));
+        }
+        if let Some(limit) = policy.spending_limit {
+            let spent = SponsorSpent::get(&ctx.state.read(), &HA160(fee_payer))
+                .unwrap_or_default();
+            if spent.saturating_add(fee) > limit {
+                return Err(TxErrorCode::SponsorRejected.error(format!(
+                    "SponsorLimitExceeded: fee payer {fee_payer:?} has spent {spent} of {limit}, but the fee is up to {fee}"
+                )));
+            }
+        }
+
+        let balance = C::AccountAsset::balance(
+            ctx,
+            &C::AddressMapping::convert_to_account_id(fee_payer),
+        );
+        if balance < fee {
+            return Err(TxErrorCode::InsufficientBalance.error(format!(
+                "InsufficientBalance, fee payer: {fee_payer:?}, actual balance {balance}, but expected fee {fee}"
+            )));
+        }
+
+        Ok(fee_payer)
+    }
+
+    /// Check a sponsor policy and the balance of its fee payer for the fee,
+    /// returns the fee payer.
+    pub fn validate_sponsor_policy(
+        ctx: &Context,
+        signed: &SignedSponsorPolicy,
+    ) -> TxResult<H160> {
+        if signed.chain_id != C::ChainId::get() {
+            return Err(TxErrorCode::SponsorRejected.error(format!(
+                "InvalidChainId, got {}, but expected {}",
+                signed.chain_id,
+                C::ChainId::get()
+            )));
+        }
+        let fee_payer = Self::recover_policy_signer(signed).ok_or_else(|| {
+            TxErrorCode::SponsorRejected.error("InvalidFeePayer: invalid signature")
+        })?;
+
+        let account_id = C::AddressMapping::convert_to_account_id(fee_payer);
+        let account =
+            C::AccountAsset::account_of(ctx, &account_id, None).unwrap_or_default();
+        if signed.nonce < account.nonce {
+            return Err(TxErrorCode::InvalidNonce.error(format!(
+                "InvalidNonce: origin: {:?}, got {}, but expected {}",
+                fee_payer, signed.nonce, account.nonce
+            )));
+        }
+        let fee = Self::sponsor_policy_fee(ctx);
+        if account.balance < fee {
+            return Err(TxErrorCode::InsufficientBalance.error(format!(
+                "InsufficientBalance, origin: {:?}, actual balance {}, but expected payment {}",
+                fee_payer, account.balance, fee
+            )));
+        }
+
+        Ok(fee_payer)
+    }
+
+    /// Set the policy of a fee payer, the fee paid under its previous policy is reset.
+    pub fn do_set_sponsor_policy(
+        ctx: &Context,
+        signed: SignedSponsorPolicy,
+    ) -> Result<ActionResult> {
+        let fee_payer = Self::validate_sponsor_policy(ctx, &signed).c(d!())?;
+        let account_id = C::AddressMapping::convert_to_account_id(fee_payer);
+
+        let nonce = C::AccountAsset::nonce(ctx, &account_id);
+        ensure!(
+            signed.nonce == nonce,
+            format!(
+                "InvalidNonce, expected: {}, actual: {}",
+                nonce, signed.nonce
+            )
+        );
+        C::AccountAsset::inc_nonce(ctx, &account_id)?;
+        C::AccountAsset::burn(ctx, &account_id, Self::sponsor_policy_fee(ctx))?;
+
+        let fee_payer = HA160(fee_payer);
+        SponsorPolicies::insert(
+            ctx.state.write().borrow_mut(),
+            &fee_payer,
+            &signed.policy,
+        )?;
+        SponsorSpent::remove(ctx.state.write().borrow_mut(), &fee_payer);
+
+        Ok(ActionResult {
+            source: Some(account_id),
+            ..Default::default()
+        })
+    }
+
+    // Setting a sponsor policy costs as much as a plain transfer.
+    fn sponsor_policy_fee(ctx: &Context) -> U256 {
+        Self::base_fee_per_gas(ctx).saturating_mul(U256::from(SPONSOR_POLICY_GAS))
+    }
+
     pub fn store_block(&mut self, ctx: &mut Context, block_number: U256) -> Result<()> {
         let mut transactions: Vec<Transaction> = Vec::new();
         let mut statuses: Vec<TransactionStatus> = Vec::new();
@@ -139,6 +411,11 @@ impl<C: Config> App<C> {
         let block = Block::new(partial_header, transactions, ommers);
         let block_hash = HA256::new(block.header.hash());
 
+        let base_fee = Self::base_fee_per_gas(ctx);
+        if ctx.header.height >= CFG.checkpoint.base_fee_height {
+            Self::adjust_base_fee(ctx, base_fee, block.header.gas_used)?;
+        }
+
         CurrentBlockNumber::put(ctx.db.write().borrow_mut(), &block_number)?;
         BlockHash::insert(ctx.db.write().borrow_mut(), &block_number, &block_hash)?;
         if is_store_block {
@@ -153,6 +430,13 @@ impl<C: Config> App<C> {
                 &statuses,
             )?;
             CurrentBlock::insert(ctx.db.write().borrow_mut(), &block_hash, &block)?;
+            if ctx.header.height >= CFG.checkpoint.base_fee_height {
+                BlockBaseFeePerGas::insert(
+                    ctx.db.write().borrow_mut(),
+                    &block_number,
+                    &base_fee,
+                )?;
+            }
 
             if CFG.enable_enterprise_web3
                 && block_number.as_u64() > *WEB3_SERVICE_START_HEIGHT
@@ -200,7 +484,12 @@ impl<C: Config> App<C> {
         Ok(())
     }
 
-    pub fn do_transact(ctx: &Context, transaction: Transaction) -> Result<ActionResult> {
+    /// Execute an EVM transaction, its fee is paid by `fee_payer` if sponsored.
+    pub fn do_transact(
+        ctx: &Context,
+        transaction: Transaction,
+        fee_payer: Option<H160>,
+    ) -> Result<ActionResult> {
         debug!(target: "ethereum", "transact ethereum transaction: {:?}", transaction);
 
         let mut events = vec![];
@@ -220,6 +509,7 @@ impl<C: Config> App<C> {
         };
 
         let gas_limit = transaction.gas_limit;
+        let transaction_gas_price = transaction.gas_price;
 
         let execute_ret = Self::execute_transaction(
             ctx,
@@ -229,6 +519,7 @@ impl<C: Config> App<C> {
             transaction.gas_limit,
             Some(transaction.gas_price),
             Some(transaction.nonce),
+            fee_payer,
             transaction.action,
         );
 
@@ -254,6 +545,7 @@ impl<C: Config> App<C> {
                         contract_address: None,
                         logs: vec![],
                         logs_bloom: Bloom::default(),
+                        fee_payer,
                     };
                     let used_gas = U256::zero();
 
@@ -277,6 +569,7 @@ impl<C: Config> App<C> {
                                     Self::logs_bloom(info.logs, &mut bloom);
                                     bloom
                                 },
+                                fee_payer,
                             },
                             info.used_gas,
                         ),
@@ -295,6 +588,7 @@ impl<C: Config> App<C> {
                                     Self::logs_bloom(info.logs, &mut bloom);
                                     bloom
                                 },
+                                fee_payer,
                             },
                             info.used_gas,
                         ),
@@ -402,9 +696,18 @@ impl<C: Config> App<C> {
             },
         ));
 
+        if let Some(fee_payer) = fee_payer {
+            let fee_payer = HA160(fee_payer);
+            let spent = SponsorSpent::get(&ctx.state.read(), &fee_payer)
+                .unwrap_or_default()
+                .saturating_add(used_gas.saturating_mul(transaction_gas_price));
+            SponsorSpent::insert(ctx.state.write().borrow_mut(), &fee_payer, &spent)?;
+        }
+
         Ok(ActionResult {
             code,
             source: Some(Address::from(source)),
+            fee_payer: fee_payer.map(Address::from),
             data: info
                 .as_ref()
                 .and_then(|i| serde_json::to_vec(&i).ok())
@@ -426,6 +729,7 @@ impl<C: Config> App<C> {
         gas_limit: U256,
         gas_price: Option<U256>,
         nonce: Option<U256>,
+        fee_payer: Option<H160>,
         action: ethereum::TransactionAction,
     ) -> Result<(Option<H160>, Option<H160>, CallOrCreateInfo)> {
         match action {
@@ -440,6 +744,7 @@ impl<C: Config> App<C> {
                         gas_limit: gas_limit.low_u64(),
                         gas_price,
                         nonce,
+                        fee_payer,
                     },
                     C::config(),
                 )?;
@@ -456,6 +761,7 @@ impl<C: Config> App<C> {
                         gas_limit: gas_limit.low_u64(),
                         gas_price,
                         nonce,
+                        fee_payer,
                     },
                     C::config(),
                 )?;
@@ -522,6 +828,185 @@ impl<C: Config> App<C> {
         TransactionIndex::get(&ctx.db.read(), &HA256::new(hash))
     }
 
+    /// The base fee per gas of the current block, it's the minimum gas price of
+    /// EVM transactions and scales the minimum fee of native transactions.
+    pub fn base_fee_per_gas(ctx: &Context) -> U256 {
+        BaseFeePerGas::get(&ctx.state.read())
+            .unwrap_or_else(C::FeeCalculator::min_gas_price)
+    }
+
+    /// The base fee per gas of a stored block, `None` for blocks before the base fee.
+    pub fn block_base_fee_per_gas(ctx: &Context, block_number: U256) -> Option<U256> {
+        BlockBaseFeePerGas::get(&ctx.db.read(), &block_number)
+    }
+
+    /// The first block covered by the log index.
+    pub fn log_index_from(ctx: &Context) -> Option<u64> {
+        LogIndexFrom::get(&ctx.db.read())
+    }
+
+    /// The numbers of the blocks within `[from, to]` which may have logs matching a filter,
+    /// in descending order.
+    ///
+    /// `addresses` are the allowed emitters and `topics[i]` are the allowed topics at
+    /// position `i`, `None` is a wildcard. Only the committed index is looked up.
+    pub fn filter_log_blocks(
+        ctx: &Context,
+        addresses: Option<&[H160]>,
+        topics: &[Option<Vec<H256>>],
+        from: u64,
+        to: u64,
+    ) -> Vec<u64> {
+        if from > to {
+            return vec![];
+        }
+
+        let db = ctx.db.read();
+        let (lower, upper) = (LogBlock(from), LogBlock(to.saturating_add(1)));
+        let blocks_of = |keys: Vec<HA256>| -> BTreeSet<u64> {
+            keys.iter()
+                .flat_map(|k| {
+                    LogIndex::iterate_range(&db, k, &lower, &upper, IterOrder::Asc)
+                })
+                .map(|(number, _)| number.0)
+                .collect()
+        };
+
+        let constraints = addresses
+            .map(|a| {
+                a.iter()
+                    .map(|a| log_index_key(None, a.as_bytes()))
+                    .collect::<Vec<_>>()
+            })
+            .into_iter()
+            .chain(topics.iter().enumerate().filter_map(|(i, t)| {
+                t.as_ref().map(|t| {
+                    t.iter()
+                        .map(|t| log_index_key(Some(i), t.as_bytes()))
+                        .collect::<Vec<_>>()
+                })
+            }));
+
+        let mut candidates: Option<BTreeSet<u64>> = None;
+        for keys in constraints {
+            let blocks = blocks_of(keys);
+            candidates = Some(match candidates {
+                Some(c) => c.intersection(&blocks).copied().collect(),
+                None => blocks,
+            });
+        }
+
+        candidates
+            .map(|c| c.into_iter().rev().collect())
+            .unwrap_or_else(|| (from..=to).rev().collect())
+    }
+
+    /// Index the logs of the blocks stored before the log index, from the newest one
+    /// down to the first EVM block. It commits every `LOG_INDEX_BACKFILL_BATCH` blocks,
+    /// so an interrupted backfilling resumes where it stopped.
+    ///
+    /// Returns the number of the indexed blocks.
+    pub fn backfill_log_index(&self, ctx: &Context) -> Result<u64> {
+        let end = match LogIndexFrom::get(&ctx.db.read()) {
+            Some(n) => n,
+            None => Self::current_block_number(ctx)
+                .map(|n| n.as_u64() + 1)
+                .unwrap_or_default(),
+        };
+        let first = CFG.checkpoint.evm_first_block_height as u64;
+        let height = ctx.db.read().height().c(d!())?;
+
+        let mut cnt = 0;
+        for number in (first..end).rev() {
+            if let Some(statuses) = self
+                .current_transaction_statuses(ctx, Some(BlockId::Number(number.into())))
+            {
+                Self::index_logs(ctx, number, &statuses).c(d!())?;
+            }
+            LogIndexFrom::put(ctx.db.write().borrow_mut(), &number).c(d!())?;
+
+            cnt += 1;
+            if cnt % LOG_INDEX_BACKFILL_BATCH == 0 {
+                ctx.db.write().commit(height).c(d!())?;
+                info!(target: "ethereum", "logs of block {} and later have been indexed", number);
+            }
+        }
+        ctx.db.write().commit(height).c(d!())?;
+
+        Ok(cnt)
+    }
+
+    /// Index the logs of the block just stored by `store_block`,
+    /// the blocks which are not stored have nothing to index.
+    pub fn index_block_logs(&self, ctx: &Context, block_number: U256) -> Result<()> {
+        let statuses = match self
+            .current_transaction_statuses(ctx, Some(BlockId::Number(block_number)))
+        {
+            Some(statuses) => statuses,
+            None => return Ok(()),
+        };
+
+        Self::index_logs(ctx, block_number.as_u64(), &statuses).c(d!())?;
+        if LogIndexFrom::get(&ctx.db.read()).is_none() {
+            LogIndexFrom::put(ctx.db.write().borrow_mut(), &block_number.as_u64())
+                .c(d!())?;
+        }
+        Ok(())
+    }
+
+    /// Index the addresses and the topics of the logs of a block.
+    pub fn index_logs(
+        ctx: &Context,
+        block_number: u64,
+        statuses: &[TransactionStatus],
+    ) -> Result<()> {
+        let mut counts = BTreeMap::<HA256, u32>::new();
+        for log in statuses.iter().flat_map(|s| s.logs.iter()) {
+            *counts
+                .entry(log_index_key(None, log.address.as_bytes()))
+                .or_default() += 1;
+            for (i, topic) in log.topics.iter().enumerate() {
+                *counts
+                    .entry(log_index_key(Some(i), topic.as_bytes()))
+                    .or_default() += 1;
+            }
+        }
+
+        for (key, count) in counts.iter() {
+            LogIndex::insert(
+                ctx.db.write().borrow_mut(),
+                key,
+                &LogBlock(block_number),
+                count,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Adjust the base fee of the next block by how far the gas used is from the target,
+    /// within the bounds of the gas price.
+    pub fn adjust_base_fee(ctx: &Context, base_fee: U256, gas_used: U256) -> Result<()> {
+        let target = C::BlockGasLimit::get() / ELASTICITY_MULTIPLIER;
+        let next = if target.is_zero() || gas_used == target {
+            base_fee
+        } else if gas_used > target {
+            let delta = base_fee.saturating_mul(gas_used - target)
+                / target
+                / BASE_FEE_MAX_CHANGE_DENOMINATOR;
+            base_fee.saturating_add(delta.max(U256::one()))
+        } else {
+            let delta = base_fee.saturating_mul(target - gas_used)
+                / target
+                / BASE_FEE_MAX_CHANGE_DENOMINATOR;
+            base_fee.saturating_sub(delta)
+        };
+        let next = next
+            .max(C::FeeCalculator::min_gas_price())
+            .min(C::FeeCalculator::max_gas_price());
+
+        BaseFeePerGas::put(ctx.state.write().borrow_mut(), &next).c(d!())
+    }
+
     fn logs_bloom(logs: Vec<ethereum::Log>, bloom: &mut Bloom) {
         for log in logs {
             bloom.accrue(BloomInput::Raw(&log.address[..]));
@@ -554,3 +1039,11 @@ impl<C: Config> App<C> {
         Ok(())
     }
 }
+
+// The key of an address (`position` is `None`) or a topic at `position` in the log index.
+fn log_index_key(position: Option<usize>, data: &[u8]) -> HA256 {
+    let tag = position.map_or(0, |i| i as u8 + 1);
+    HA256::new(H256::from_slice(
+        Keccak256::digest(&[&[tag][..], data].concat()).as_slice(),
+    ))
+}
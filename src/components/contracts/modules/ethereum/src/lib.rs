@@ -54,14 +54,37 @@ pub trait Config {
     }
 }
 
+/// The number of a block in the log index,
+/// it's zero-padded in the keys so that they are in the order of the numbers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LogBlock(pub u64);
+
+impl std::fmt::Display for LogBlock {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:020}", self.0)
+    }
+}
+
+impl std::str::FromStr for LogBlock {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        s.parse().map(LogBlock)
+    }
+}
+
 pub mod storage {
+    use super::LogBlock;
     use ethereum::{
         BlockV0 as Block, ReceiptV0 as Receipt, TransactionV0 as Transaction,
     };
     use ethereum_types::U256;
     use fp_evm::TransactionStatus;
     use fp_storage::*;
-    use fp_types::crypto::HA256;
+    use fp_types::{
+        actions::ethereum::SponsorPolicy,
+        crypto::{HA160, HA256},
+    };
     use lazy_static::lazy_static;
     use std::sync::Mutex;
 
@@ -78,6 +101,19 @@ pub mod storage {
     generate_storage!(Ethereum, CurrentReceipts => Map<HA256, Vec<Receipt>>);
     // The ethereum history transaction statuses with block number.
     generate_storage!(Ethereum, CurrentTransactionStatuses => Map<HA256, Vec<TransactionStatus>>);
+    // The blocks with logs emitted by an address or with a topic at a position,
+    // and the number of such logs, keyed by `log_index_key`.
+    generate_storage!(Ethereum, LogIndex => DoubleMap<HA256, LogBlock, u32>);
+    // The first block covered by the log index, older blocks are indexed by backfilling.
+    generate_storage!(Ethereum, LogIndexFrom => Value<u64>);
+    // The base fee per gas of the next block, adjusted at the end of every block.
+    generate_storage!(Ethereum, BaseFeePerGas => Value<U256>);
+    // The base fee per gas of the stored blocks.
+    generate_storage!(Ethereum, BlockBaseFeePerGas => Map<U256, U256>);
+    // The policies of fee payers on the transactions they sponsor.
+    generate_storage!(Ethereum, SponsorPolicies => Map<HA160, SponsorPolicy>);
+    // The fee paid by fee payers under their current policies.
+    generate_storage!(Ethereum, SponsorSpent => Map<HA160, U256>);
 
     // The following data is stored in in-memory array
     // Current building block's transactions and receipts.
@@ -137,6 +173,7 @@ impl<C: Config> AppModule for App<C> {
         _ff_addr_balance: u64,
     ) -> ResponseEndBlock {
         let _ = ruc::info!(self.store_block(ctx, U256::from(req.height)));
+        let _ = ruc::info!(self.index_block_logs(ctx, U256::from(req.height)));
         Default::default()
     }
 }
@@ -153,7 +190,13 @@ impl<C: Config> Executable for App<C> {
         ensure!(origin.is_none(), "InvalidTransaction: IllegalOrigin");
 
         match call {
-            Action::Transact(tx) => Self::do_transact(ctx, tx),
+            Action::Transact(tx) => Self::do_transact(ctx, tx, None),
+            Action::SponsoredTransact(sponsored) => {
+                let fee_payer = Self::recover_fee_payer(&sponsored)
+                    .ok_or_else(|| eg!("InvalidFeePayer: invalid signature"))?;
+                Self::do_transact(ctx, sponsored.transaction, Some(fee_payer))
+            }
+            Action::SetSponsorPolicy(signed) => Self::do_set_sponsor_policy(ctx, signed),
         }
     }
 }
@@ -162,12 +205,24 @@ impl<C: Config> ValidateUnsigned for App<C> {
     type Call = Action;
 
     fn pre_execute(ctx: &Context, call: &Self::Call) -> Result<()> {
+        // The balance and the policy of the fee payer may have changed since check_tx.
+        if let Action::SponsoredTransact(sponsored) = call {
+            Self::validate_sponsorship(ctx, sponsored).c(d!())?;
+        }
+
         if ctx.header.height >= CFG.checkpoint.evm_checktx_nonce
             && ctx.run_mode == RunTxMode::Check
         {
-            let Action::Transact(transaction) = call;
-            let origin = Self::recover_signer_fast(ctx, transaction)
-                .ok_or_else(|| eg!("ExecuteTransaction: InvalidSignature"))?;
+            let origin = match call {
+                Action::Transact(transaction) => {
+                    Self::recover_signer_fast(ctx, transaction)
+                }
+                Action::SponsoredTransact(sponsored) => {
+                    Self::recover_signer_fast(ctx, &sponsored.transaction)
+                }
+                Action::SetSponsorPolicy(signed) => Self::recover_policy_signer(signed),
+            }
+            .ok_or_else(|| eg!("ExecuteTransaction: InvalidSignature"))?;
             let account_id = C::AddressMapping::convert_to_account_id(origin);
             C::AccountAsset::inc_nonce(ctx, &account_id)?;
         }
@@ -176,74 +231,7 @@ impl<C: Config> ValidateUnsigned for App<C> {
     }
 
     fn validate_unsigned(ctx: &Context, call: &Self::Call) -> Result<()> {
-        let Action::Transact(transaction) = call;
-        if let Some(chain_id) = transaction.signature.chain_id() {
-            if chain_id != C::ChainId::get() {
-                return Err(eg!(format!(
-                    "InvalidChainId, got {}, but expected {}",
-                    chain_id,
-                    C::ChainId::get()
-                )));
-            }
-        } else {
-            return Err(eg!("Must provide chainId".to_string()));
-        }
-
-        let origin = Self::recover_signer_fast(ctx, transaction)
-            .ok_or_else(|| eg!("ExecuteTransaction: InvalidSignature"))?;
-
-        // Same as go ethereum, Min gas limit is 21000.
-        if transaction.gas_limit < U256::from(21000)
-            || transaction.gas_limit > C::BlockGasLimit::get()
-        {
-            return Err(eg!(format!(
-                "InvalidGasLimit: got {}, the gas limit must be in range [21000, {}]",
-                transaction.gas_limit,
-                C::BlockGasLimit::get()
-            )));
-        }
-        let mut flag = false;
-        if ctx.header.height < CFG.checkpoint.max_gas_price_limit {
-            if transaction.gas_price < C::FeeCalculator::min_gas_price() {
-                flag = true;
-            }
-        } else if transaction.gas_price < C::FeeCalculator::min_gas_price()
-            || transaction.gas_price > C::FeeCalculator::max_gas_price()
-        {
-            flag = true;
-        }
-
-        if flag {
-            return Err(eg!(format!(
-                "InvalidGasPrice: got {}, but the minimum gas price is {}, max gas price is {}",
-                transaction.gas_price,
-                C::FeeCalculator::min_gas_price(), C::FeeCalculator::max_gas_price()
-            )));
-        }
-
-        let account_id = C::AddressMapping::convert_to_account_id(origin);
-        let account =
-            C::AccountAsset::account_of(ctx, &account_id, None).unwrap_or_default();
-        let nonce = account.nonce;
-        let balance = account.balance;
-
-        if transaction.nonce < nonce {
-            #[cfg(not(feature = "benchmark"))]
-            return Err(eg!(format!(
-                "InvalidNonce: origin: {:?}, got {}, but expected {}",
-                origin, transaction.nonce, nonce
-            )));
-        }
-
-        let fee = transaction.gas_price.saturating_mul(transaction.gas_limit);
-        let total_payment = transaction.value.saturating_add(fee);
-        if balance < total_payment {
-            return Err(eg!(format!(
-                "InsufficientBalance, origin: {origin:?}, actual balance {balance}, but expected payment {total_payment}",
-            )));
-        }
-
-        Ok(())
+        Self::check_action(ctx, call).c(d!())
     }
 
     fn post_execute(ctx: &Context, result: &ActionResult) -> Result<()> {
@@ -282,7 +270,7 @@ impl<C: Config> ValidateUnsigned for App<C> {
                 }
             }
 
-            if let Some(who) = result.source.as_ref() {
+            if let Some(who) = result.fee_payer.as_ref().or(result.source.as_ref()) {
                 // if gas_used is zero, withdraw would do noting.
                 let _ = C::AccountAsset::withdraw(ctx, who, U256::from(result.gas_used));
             }
@@ -1,11 +1,16 @@
 use baseapp::BaseApp;
-use ethereum::{TransactionAction, TransactionSignature, TransactionV0};
+use config::abci::global_cfg::CFG;
+use ethereum::{Log, TransactionAction, TransactionSignature, TransactionV0};
 use fin_db::{FinDB, RocksDB};
 use fp_core::context::Context;
+use fp_evm::{BlockId, TransactionStatus};
 use fp_storage::{Borrow, BorrowMut, RwLock};
 use fp_types::crypto::HA256;
-use fp_types::{H256, U256};
-use module_ethereum::storage::TransactionIndex;
+use fp_types::{H160, H256, U256};
+use module_ethereum::storage::{
+    BaseFeePerGas, BlockHash, CurrentBlockNumber, CurrentTransactionStatuses,
+    LogIndexFrom, TransactionIndex,
+};
 use sha3::{Digest, Keccak256};
 use std::{env::temp_dir, sync::Arc, time::SystemTime};
 use storage::state::ChainState;
@@ -80,3 +85,175 @@ fn test_eth_db_migrate_txn_index() {
         assert_eq!(value.unwrap(), txn.1);
     }
 }
+
+type Ethereum = module_ethereum::App<BaseApp>;
+
+// A block with one log of `address` with `topics`.
+fn statuses(address: H160, topics: Vec<H256>) -> Vec<TransactionStatus> {
+    vec![TransactionStatus {
+        logs: vec![Log {
+            address,
+            topics,
+            data: vec![],
+        }],
+        ..Default::default()
+    }]
+}
+
+#[test]
+fn test_log_index() {
+    let ctx = setup();
+    let (a, b) = (H160::repeat_byte(0xaa), H160::repeat_byte(0xbb));
+    let (t0, t1) = (H256::repeat_byte(1), H256::repeat_byte(2));
+
+    //Blocks 1 to 12: `a` logs `t0` in the odd ones, `b` logs `t0, t1` in the even ones
+    for n in 1..=12u64 {
+        let s = if n % 2 == 1 {
+            statuses(a, vec![t0])
+        } else {
+            statuses(b, vec![t0, t1])
+        };
+        Ethereum::index_logs(&ctx, n, &s).unwrap();
+    }
+    ctx.db.write().commit(1).unwrap();
+
+    let filter = |addresses: Option<&[H160]>, topics: &[Option<Vec<H256>>], from, to| {
+        Ethereum::filter_log_blocks(&ctx, addresses, topics, from, to)
+    };
+
+    //The range is kept in numeric order across the digits
+    assert_eq!(filter(Some(&[a][..]), &[], 1, 12), vec![11, 9, 7, 5, 3, 1]);
+    assert_eq!(filter(Some(&[a][..]), &[], 9, 11), vec![11, 9]);
+    assert_eq!(filter(Some(&[a, b][..]), &[], 8, 10), vec![10, 9, 8]);
+    assert_eq!(filter(Some(&[b][..]), &[], 11, 100), vec![12]);
+    assert!(filter(Some(&[b][..]), &[], 13, 100).is_empty());
+    assert!(filter(Some(&[b][..]), &[], 10, 9).is_empty());
+
+    //An address alone, which never logged anything
+    assert!(filter(Some(&[H160::repeat_byte(0xcc)][..]), &[], 1, 12).is_empty());
+
+    //Topics are matched by position, and combined with the addresses
+    assert_eq!(filter(None, &[Some(vec![t0])], 3, 6), vec![6, 5, 4, 3]);
+    assert_eq!(filter(None, &[None, Some(vec![t1])], 3, 6), vec![6, 4]);
+    assert!(filter(None, &[Some(vec![t1])], 1, 12).is_empty());
+    assert!(filter(Some(&[a][..]), &[None, Some(vec![t1])], 1, 12).is_empty());
+    assert!(filter(None, &[Some(vec![t0]), Some(vec![t1])], 1, 1).is_empty());
+
+    //Wildcards at any position, alternatives within a position
+    assert_eq!(
+        filter(None, &[Some(vec![t0]), None], 3, 6),
+        vec![6, 5, 4, 3]
+    );
+    assert_eq!(
+        filter(None, &[None, Some(vec![t0, t1]), None], 3, 6),
+        vec![6, 4]
+    );
+    assert_eq!(
+        filter(Some(&[a, b][..]), &[Some(vec![t1, t0])], 11, 12),
+        vec![12, 11]
+    );
+    assert!(filter(None, &[None, None, Some(vec![t0])], 1, 12).is_empty());
+
+    //Nothing in a reversed range
+    assert!(filter(None, &[Some(vec![t0])], 6, 3).is_empty());
+    assert!(filter(None, &[None], 6, 3).is_empty());
+
+    //No constraint means all blocks
+    assert_eq!(filter(None, &[None], 2, 4), vec![4, 3, 2]);
+}
+
+#[test]
+fn test_index_block_logs() {
+    let ctx = setup();
+    let app = Ethereum::default();
+    let a = H160::repeat_byte(0xaa);
+    let t0 = H256::repeat_byte(1);
+
+    //A block which is not stored has nothing to index
+    app.index_block_logs(&ctx, U256::from(7)).unwrap();
+    assert_eq!(Ethereum::log_index_from(&ctx), None);
+
+    //The stored blocks are indexed, the index starts at the first one
+    for n in 8..10u64 {
+        let hash = HA256::new(H256::from_low_u64_be(n + 1));
+        BlockHash::insert(ctx.db.write().borrow_mut(), &U256::from(n), &hash).unwrap();
+        CurrentTransactionStatuses::insert(
+            ctx.db.write().borrow_mut(),
+            &hash,
+            &statuses(a, vec![t0]),
+        )
+        .unwrap();
+        app.index_block_logs(&ctx, U256::from(n)).unwrap();
+    }
+    ctx.db.write().commit(1).unwrap();
+    assert_eq!(Ethereum::log_index_from(&ctx), Some(8));
+    assert_eq!(
+        Ethereum::filter_log_blocks(&ctx, None, &[Some(vec![t0])], 1, 100),
+        vec![9, 8]
+    );
+}
+
+#[test]
+fn test_backfill_log_index() {
+    let ctx = setup();
+    let app = Ethereum::default();
+    let a = H160::repeat_byte(0xaa);
+    let t0 = H256::repeat_byte(1);
+
+    //Blocks stored before the log index
+    let first = CFG.checkpoint.evm_first_block_height as u64;
+    for n in first..first + 5 {
+        let hash = HA256::new(H256::from_low_u64_be(n + 1));
+        BlockHash::insert(ctx.db.write().borrow_mut(), &U256::from(n), &hash).unwrap();
+        CurrentTransactionStatuses::insert(
+            ctx.db.write().borrow_mut(),
+            &hash,
+            &statuses(a, vec![t0]),
+        )
+        .unwrap();
+    }
+    CurrentBlockNumber::put(ctx.db.write().borrow_mut(), &U256::from(first + 4))
+        .unwrap();
+    ctx.db.write().commit(1).unwrap();
+    assert!(app
+        .current_transaction_statuses(&ctx, Some(BlockId::Number(first.into())))
+        .is_some());
+
+    //The newer blocks were indexed, the backfilling resumes below them
+    LogIndexFrom::put(ctx.db.write().borrow_mut(), &(first + 3)).unwrap();
+    assert_eq!(app.backfill_log_index(&ctx).unwrap(), 3);
+    assert_eq!(Ethereum::log_index_from(&ctx), Some(first));
+    assert_eq!(
+        Ethereum::filter_log_blocks(&ctx, Some(&[a][..]), &[], first, first + 4),
+        vec![first + 2, first + 1, first]
+    );
+
+    //Nothing is left
+    assert_eq!(app.backfill_log_index(&ctx).unwrap(), 0);
+}
+
+#[test]
+fn test_adjust_base_fee() {
+    let ctx = setup();
+    let limit = baseapp::BlockGasLimit::get();
+    let min = U256::from(100_0000_0000_u64);
+    let base_fee = min * 4;
+    let adjust = |gas_used: U256| {
+        module_ethereum::App::<BaseApp>::adjust_base_fee(&ctx, base_fee, gas_used)
+            .unwrap();
+        BaseFeePerGas::get(&ctx.state.read()).unwrap()
+    };
+
+    //A full block raises the base fee by 1/8
+    assert_eq!(adjust(limit), base_fee * 9 / 8);
+
+    //An empty block lowers the base fee by 1/8
+    assert_eq!(adjust(U256::zero()), base_fee * 7 / 8);
+
+    //A block at the target keeps the base fee
+    assert_eq!(adjust(limit / 2), base_fee);
+
+    //The base fee never drops below the minimum gas price
+    module_ethereum::App::<BaseApp>::adjust_base_fee(&ctx, min, U256::zero()).unwrap();
+    assert_eq!(BaseFeePerGas::get(&ctx.state.read()), Some(min));
+}
@@ -4,20 +4,31 @@
 
 use abci::*;
 use baseapp::{BaseApp, ChainId};
+use config::abci::global_cfg::CFG;
 use ethereum_types::{H160, U256};
+use fp_core::context::Context;
 use fp_mocks::*;
+use fp_storage::BorrowMut;
 use fp_traits::{account::AccountAsset, evm::FeeCalculator};
 use fp_types::{
-    actions::{ethereum::Action as EthereumAction, Action},
+    actions::{
+        ethereum::{Action as EthereumAction, SponsorPolicy, SponsoredTransaction},
+        Action,
+    },
     assemble::UncheckedTransaction,
+    crypto::HA160,
+    error_code::TxErrorCode,
 };
 use fp_utils::tx::EvmRawTxWrapper;
+use module_ethereum::storage::{SponsorPolicies, SponsorSpent};
+use std::collections::BTreeSet;
 
 #[test]
 fn run_all_tests() {
     test_abci_check_tx();
     test_abci_begin_block();
     test_abci_deliver_tx();
+    test_abci_deliver_sponsored_tx();
     test_abci_end_block();
     test_abci_commit();
     test_abci_query()
@@ -132,6 +143,105 @@ fn test_abci_deliver_tx() {
     );
 }
 
+fn test_abci_deliver_sponsored_tx() {
+    let balance_of = |who| {
+        module_account::App::<BaseApp>::balance(
+            &BASE_APP.lock().unwrap().deliver_state,
+            who,
+        )
+    };
+    let alice_balance = balance_of(&ALICE_ECDSA.account_id);
+    let bob_balance = balance_of(&BOB_ECDSA.account_id);
+
+    // Bob transfers 1 to Alice, who pays the fee.
+    let transaction = UnsignedTransaction {
+        nonce: U256::zero(),
+        gas_price: <BaseApp as module_ethereum::Config>::FeeCalculator::min_gas_price(),
+        gas_limit: U256::from(0x100000),
+        action: ethereum::TransactionAction::Call(ALICE_ECDSA.address),
+        value: 1.into(),
+        input: Vec::new(),
+    }
+    .sign(&BOB_ECDSA.private_key, ChainId::get());
+    let mut sponsored = SponsoredTransaction {
+        transaction,
+        chain_id: ChainId::get(),
+        fee_payer_signature: vec![],
+    };
+    sponsored.fee_payer_signature =
+        sign_message(&sponsored.fee_payer_message(), &ALICE_ECDSA.private_key);
+
+    let ctx = BASE_APP.lock().unwrap().deliver_state.copy_with_state();
+    let check = |ctx: &Context, sponsored: &SponsoredTransaction| {
+        module_ethereum::App::<BaseApp>::check_action(
+            ctx,
+            &EthereumAction::SponsoredTransact(sponsored.clone()),
+        )
+    };
+    if ctx.header.height < CFG.checkpoint.sponsored_tx_height {
+        assert_eq!(
+            check(&ctx, &sponsored).unwrap_err().code,
+            TxErrorCode::SponsorRejected
+        );
+        return;
+    }
+
+    // The fee payer signs for one chain only.
+    let mut other_chain = sponsored.clone();
+    other_chain.chain_id += 1;
+    assert_eq!(
+        check(&ctx, &other_chain).unwrap_err().code,
+        TxErrorCode::SponsorRejected
+    );
+
+    // Senders out of the allowlist are rejected.
+    let alice = HA160(ALICE_ECDSA.address);
+    let mut policy = SponsorPolicy {
+        allowed_senders: BTreeSet::from([H160::repeat_byte(1)]),
+        spending_limit: None,
+    };
+    SponsorPolicies::insert(ctx.state.write().borrow_mut(), &alice, &policy).unwrap();
+    let err = check(&ctx, &sponsored).unwrap_err();
+    assert!(err.msg.starts_with("SponsorNotAllowed"), "{}", err.msg);
+
+    // So are fees over the spending limit.
+    let fee = sponsored
+        .transaction
+        .gas_price
+        .saturating_mul(sponsored.transaction.gas_limit);
+    policy.allowed_senders.insert(BOB_ECDSA.address);
+    policy.spending_limit = Some(fee);
+    SponsorPolicies::insert(ctx.state.write().borrow_mut(), &alice, &policy).unwrap();
+    assert!(check(&ctx, &sponsored).is_ok());
+    SponsorSpent::insert(ctx.state.write().borrow_mut(), &alice, &U256::one()).unwrap();
+    let err = check(&ctx, &sponsored).unwrap_err();
+    assert!(err.msg.starts_with("SponsorLimitExceeded"), "{}", err.msg);
+
+    let mut req = RequestDeliverTx::default();
+    let tx = serde_json::to_vec(&UncheckedTransaction::<()>::new_unsigned(
+        Action::Ethereum(EthereumAction::SponsoredTransact(sponsored)),
+    ))
+    .unwrap();
+    req.tx = EvmRawTxWrapper::wrap(&tx);
+    let (resp, _) = BASE_APP.lock().unwrap().deliver_tx(&req);
+    assert_eq!(
+        resp.code, 0,
+        "deliver sponsored tx failed, code: {}, log: {}",
+        resp.code, resp.log
+    );
+
+    assert_eq!(
+        balance_of(&ALICE_ECDSA.account_id),
+        alice_balance
+            .saturating_sub(base_transfer_fee())
+            .saturating_add(1.into())
+    );
+    assert_eq!(
+        balance_of(&BOB_ECDSA.account_id),
+        bob_balance.saturating_sub(1.into())
+    );
+}
+
 fn test_abci_end_block() {
     let mut req = RequestEndBlock::default();
     req.height = 3;
@@ -158,15 +268,29 @@ fn test_abci_query() {
         .unwrap()
         .create_query_context(Some(3), false)
         .unwrap();
+    // Alice paid the fee of the sponsored transfer of 1 from Bob, if enabled.
+    let (alice, bob) = if 3 < CFG.checkpoint.sponsored_tx_height {
+        (
+            U256::from(100_0000_0000_0000_0000_u64)
+                .saturating_sub(base_transfer_fee())
+                .saturating_sub(10.into()),
+            U256::from(10),
+        )
+    } else {
+        (
+            U256::from(100_0000_0000_0000_0000_u64)
+                .saturating_sub(base_transfer_fee() * 2)
+                .saturating_sub(9.into()),
+            U256::from(9),
+        )
+    };
     assert_eq!(
         module_account::App::<BaseApp>::balance(&ctx, &ALICE_ECDSA.account_id),
-        U256::from(100_0000_0000_0000_0000_u64)
-            .saturating_sub(base_transfer_fee())
-            .saturating_sub(10.into())
+        alice
     );
 
     assert_eq!(
         module_account::App::<BaseApp>::balance(&ctx, &BOB_ECDSA.account_id),
-        10.into()
+        bob
     );
 }
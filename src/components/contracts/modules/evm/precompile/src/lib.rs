@@ -1,3 +1,4 @@
+use config::abci::global_cfg::CFG;
 use ethereum_types::H160;
 use evm::{executor::stack::PrecompileSet, Context};
 use evm_precompile_ed25519_verify::Ed25519Verify;
@@ -10,6 +11,7 @@ use evm_precompile_blake2::Blake2F;
 use evm_precompile_bn128::{Bn128Add, Bn128Mul, Bn128Pairing};
 use evm_precompile_frc20::FRC20;
 use evm_precompile_modexp::Modexp;
+use evm_precompile_staking::Staking;
 use fp_core::context::Context as Context2;
 use module_evm::precompile::PrecompileId;
 use module_evm::Config;
@@ -83,6 +85,11 @@ where
             a if a == H160::from_low_u64_be(Ed25519Verify::contract_id()) => {
                 Some(Ed25519Verify::execute(input, target_gas, context, ctx))
             }
+            a if a == H160::from_low_u64_be(Staking::contract_id())
+                && ctx.header.height >= CFG.checkpoint.staking_precompile_height =>
+            {
+                Some(Staking::execute(input, target_gas, context, ctx))
+            }
             //a if a == H160::from_low_u64_be(EthPairing::contract_id()) => {
             //    Some(EthPairing::execute(input, target_gas, context, ctx))
             //}
@@ -0,0 +1,178 @@
+#[cfg(test)]
+mod tests;
+
+use ethereum_types::{H256, U256};
+use evm::{
+    executor::stack::{PrecompileFailure, PrecompileOutput},
+    Context, ExitSucceed,
+};
+use evm_precompile_utils::{
+    Address, EvmDataReader, EvmDataWriter, EvmResult, Gasometer,
+};
+use fp_types::crypto::{HA160, HA256};
+use module_evm::{
+    precompile::{FinState, Precompile, PrecompileId, PrecompileResult},
+    storage::{StakingDelegations, StakingGlobal, StakingValidators},
+};
+use tracing::debug;
+
+const GAS_VALIDATOR_INFO: u64 = 2000;
+const GAS_DELEGATION_INFO: u64 = 2500;
+const GAS_GLOBAL_STAKING: u64 = 1000;
+
+/// Read-only access to the staking state of the UTXO ledger,
+/// answered from the snapshot written to the EVM state at the start of each block,
+/// so historical calls see the staking state of their height.
+///
+/// All amounts are in FRA units, unknown validators or delegators
+/// are answered with zeros.
+pub struct Staking;
+
+impl PrecompileId for Staking {
+    fn contract_id() -> u64 {
+        0x2004
+    }
+}
+
+#[evm_precompile_utils::generate_function_selector]
+#[derive(Debug, PartialEq, Eq, num_enum::TryFromPrimitive, num_enum::IntoPrimitive)]
+pub enum Call {
+    ValidatorInfo = "validatorInfo(address)",
+    DelegationInfo = "delegationInfo(bytes32,address)",
+    GlobalStaking = "globalStaking()",
+}
+
+impl Precompile for Staking {
+    fn execute(
+        input: &[u8],
+        target_gas: Option<u64>,
+        _context: &Context,
+        state: &FinState,
+    ) -> PrecompileResult {
+        let mut input = EvmDataReader::new(input);
+        let selector = input
+            .read_selector::<Call>()
+            .map_err(|e| PrecompileFailure::Error { exit_status: e })?;
+
+        let res = match selector {
+            Call::ValidatorInfo => Self::validator_info(state, input, target_gas),
+            Call::DelegationInfo => Self::delegation_info(state, input, target_gas),
+            Call::GlobalStaking => Self::global_staking(state, input, target_gas),
+        };
+        res.map_err(|e| PrecompileFailure::Error { exit_status: e })
+    }
+}
+
+impl Staking {
+    /// Returns the power, the commission rate, the number of delegators
+    /// and whether it signed the last block of a current validator.
+    ///
+    /// function validatorInfo(address tdAddr) returns
+    ///     (uint256 power, uint64 rateNum, uint64 rateDen, uint64 delegators, bool signed);
+    fn validator_info(
+        state: &FinState,
+        mut input: EvmDataReader,
+        target_gas: Option<u64>,
+    ) -> EvmResult<PrecompileOutput> {
+        let mut gasometer = Gasometer::new(target_gas);
+        gasometer.record_cost(GAS_VALIDATOR_INFO)?;
+
+        input.expect_arguments(1)?;
+        let td_addr: Address = input.read()?;
+
+        let v = StakingValidators::get(&state.state.read(), &HA160(td_addr.0))
+            .unwrap_or_default();
+
+        debug!(target: "evm", "Staking#validatorInfo: {:?} => {:?}", td_addr.0, v);
+
+        Ok(PrecompileOutput {
+            exit_status: ExitSucceed::Returned,
+            cost: gasometer.used_gas(),
+            output: EvmDataWriter::new()
+                .write(U256::from(v.power))
+                .write(v.commission_rate[0])
+                .write(v.commission_rate[1])
+                .write(v.delegator_cnt)
+                .write(v.signed_last_block)
+                .build(),
+            logs: vec![],
+        })
+    }
+
+    /// Returns the principal delegated to a current validator, the total principal
+    /// and the rewards of a delegator, identified by its 32-byte public key.
+    ///
+    /// function delegationInfo(bytes32 delegator, address tdAddr) returns
+    ///     (uint256 principal, uint256 totalPrincipal, uint256 rewards);
+    fn delegation_info(
+        state: &FinState,
+        mut input: EvmDataReader,
+        target_gas: Option<u64>,
+    ) -> EvmResult<PrecompileOutput> {
+        let mut gasometer = Gasometer::new(target_gas);
+        gasometer.record_cost(GAS_DELEGATION_INFO)?;
+
+        input.expect_arguments(2)?;
+        let delegator: H256 = input.read()?;
+        let td_addr: Address = input.read()?;
+
+        let d = StakingDelegations::get(&state.state.read(), &HA256::new(delegator))
+            .unwrap_or_default();
+        let principal = d
+            .principals
+            .iter()
+            .find(|(a, _)| a.as_slice() == td_addr.0.as_bytes())
+            .map(|(_, am)| *am)
+            .unwrap_or_default();
+
+        debug!(
+            target: "evm",
+            "Staking#delegationInfo: {:?}, {:?} => {}, {:?}",
+            delegator, td_addr.0, principal, d
+        );
+
+        Ok(PrecompileOutput {
+            exit_status: ExitSucceed::Returned,
+            cost: gasometer.used_gas(),
+            output: EvmDataWriter::new()
+                .write(U256::from(principal))
+                .write(U256::from(d.total_principal))
+                .write(U256::from(d.rewards))
+                .build(),
+            logs: vec![],
+        })
+    }
+
+    /// Returns the global delegation, the circulating supply of FRA,
+    /// the block rewards rate and the height of the snapshot.
+    ///
+    /// function globalStaking() returns (uint256 delegation, uint256 circulatingSupply,
+    ///     uint256 rateNum, uint256 rateDen, uint64 height);
+    fn global_staking(
+        state: &FinState,
+        input: EvmDataReader,
+        target_gas: Option<u64>,
+    ) -> EvmResult<PrecompileOutput> {
+        let mut gasometer = Gasometer::new(target_gas);
+        gasometer.record_cost(GAS_GLOBAL_STAKING)?;
+
+        input.expect_arguments(0)?;
+
+        let g = StakingGlobal::get(&state.state.read()).unwrap_or_default();
+
+        debug!(target: "evm", "Staking#globalStaking: height {}", g.height);
+
+        Ok(PrecompileOutput {
+            exit_status: ExitSucceed::Returned,
+            cost: gasometer.used_gas(),
+            output: EvmDataWriter::new()
+                .write(U256::from(g.global_delegation))
+                .write(U256::from(g.circulating_supply))
+                .write(U256::from(g.return_rate[0]))
+                .write(U256::from(g.return_rate[1]))
+                .write(g.height)
+                .build(),
+            logs: vec![],
+        })
+    }
+}
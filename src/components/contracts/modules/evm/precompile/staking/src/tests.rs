@@ -0,0 +1,146 @@
+use crate::*;
+use baseapp::BaseApp;
+use ethereum_types::H160;
+use fp_mocks::create_temp_db_path;
+use ledger::staking::evm::{
+    DelegationSnapshot, GlobalSnapshot, StakingDiff, ValidatorSnapshot,
+};
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaChaRng;
+use sha3::{Digest, Keccak256};
+use zei::{serialization::ZeiFromToBytes, xfr::sig::XfrKeyPair};
+
+fn selector(sig: &str) -> u32 {
+    let hash = Keccak256::digest(sig.as_bytes());
+    u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]])
+}
+
+fn global(height: u64) -> GlobalSnapshot {
+    GlobalSnapshot {
+        height,
+        global_delegation: 1000,
+        circulating_supply: 2000,
+        return_rate: [3, 100],
+    }
+}
+
+#[test]
+fn selectors() {
+    assert_eq!(
+        Call::ValidatorInfo as u32,
+        selector("validatorInfo(address)")
+    );
+    assert_eq!(
+        Call::DelegationInfo as u32,
+        selector("delegationInfo(bytes32,address)")
+    );
+    assert_eq!(Call::GlobalStaking as u32, selector("globalStaking()"));
+}
+
+#[test]
+fn read_snapshot() {
+    let app = BaseApp::new(&create_temp_db_path(), false, (1, None), false).unwrap();
+    let ctx = &app.deliver_state;
+
+    let mut prng = ChaChaRng::from_entropy();
+    let pk = XfrKeyPair::generate(&mut prng).pub_key;
+    let delegator = H256::from_slice(&pk.zei_to_bytes());
+    let td_addr = H160::from_low_u64_be(1);
+
+    let validator = ValidatorSnapshot {
+        power: 400,
+        commission_rate: [1, 100],
+        delegator_cnt: 2,
+        signed_last_block: true,
+    };
+    let delegation = DelegationSnapshot {
+        principals: vec![(td_addr.as_bytes().to_vec(), 300)],
+        total_principal: 500,
+        rewards: 7,
+    };
+    let diff = StakingDiff {
+        global: global(1),
+        validators: vec![(td_addr.as_bytes().to_vec(), Some(validator))],
+        delegations: vec![(pk, Some(delegation.clone()))],
+    };
+    module_evm::App::<BaseApp>::apply_staking_diff(ctx, diff).unwrap();
+    ctx.state.write().commit(1).unwrap();
+
+    // the validator leaves and the rewards grow in the next block
+    let diff = StakingDiff {
+        global: global(2),
+        validators: vec![(td_addr.as_bytes().to_vec(), None)],
+        delegations: vec![(
+            pk,
+            Some(DelegationSnapshot {
+                rewards: 9,
+                ..delegation
+            }),
+        )],
+    };
+    module_evm::App::<BaseApp>::apply_staking_diff(ctx, diff).unwrap();
+    ctx.state.write().commit(2).unwrap();
+
+    let validator_info = |state: &FinState| {
+        let input = EvmDataWriter::new().write(Address(td_addr)).build();
+        Staking::validator_info(state, EvmDataReader::new(&input), None)
+            .unwrap()
+            .output
+    };
+    let delegation_info = |state: &FinState| {
+        let input = EvmDataWriter::new()
+            .write(delegator)
+            .write(Address(td_addr))
+            .build();
+        Staking::delegation_info(state, EvmDataReader::new(&input), None)
+            .unwrap()
+            .output
+    };
+    let global_staking = |state: &FinState| {
+        Staking::global_staking(state, EvmDataReader::new(&[]), None)
+            .unwrap()
+            .output
+    };
+
+    // historical calls see the state of their height
+    let past = app.create_context_at(1).unwrap();
+    let expected = EvmDataWriter::new()
+        .write(U256::from(400))
+        .write(1u64)
+        .write(100u64)
+        .write(2u64)
+        .write(true)
+        .build();
+    assert_eq!(validator_info(&past), expected);
+    let expected = EvmDataWriter::new()
+        .write(U256::from(300))
+        .write(U256::from(500))
+        .write(U256::from(7))
+        .build();
+    assert_eq!(delegation_info(&past), expected);
+    let expected = EvmDataWriter::new()
+        .write(U256::from(1000))
+        .write(U256::from(2000))
+        .write(U256::from(3))
+        .write(U256::from(100))
+        .write(1u64)
+        .build();
+    assert_eq!(global_staking(&past), expected);
+
+    // unknown validators are answered with zeros
+    assert_eq!(validator_info(ctx), vec![0u8; 32 * 5]);
+    let expected = EvmDataWriter::new()
+        .write(U256::from(300))
+        .write(U256::from(500))
+        .write(U256::from(9))
+        .build();
+    assert_eq!(delegation_info(ctx), expected);
+    let expected = EvmDataWriter::new()
+        .write(U256::from(1000))
+        .write(U256::from(2000))
+        .write(U256::from(3))
+        .write(U256::from(100))
+        .write(2u64)
+        .build();
+    assert_eq!(global_staking(ctx), expected);
+}
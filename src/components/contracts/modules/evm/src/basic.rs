@@ -1,6 +1,46 @@
-use super::{App, Config, MODULE_NAME};
-use fp_core::module::AppModuleBasic;
-use ruc::Result;
+use super::{storage::*, App, Config, MODULE_NAME};
+use ethereum_types::{H160, H256};
+use fp_core::{context::Context, module::AppModuleBasic};
+use fp_storage::BorrowMut;
+use fp_types::crypto::{HA160, HA256};
+use ruc::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Genesis state of a contract account,
+/// its balance and nonce belong to the account module.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenesisAccount {
+    /// `0x` prefixed hex of the runtime bytecode
+    #[serde(default)]
+    pub code: String,
+    #[serde(default)]
+    pub storage: BTreeMap<H256, H256>,
+}
+
+/// Genesis state of the evm module.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenesisConfig {
+    #[serde(default)]
+    pub accounts: BTreeMap<H160, GenesisAccount>,
+}
+
+impl GenesisConfig {
+    // Parse the accounts and decode their code.
+    fn parse(genesis: &[u8]) -> Result<Vec<(HA160, Vec<u8>, BTreeMap<H256, H256>)>> {
+        let genesis =
+            serde_json::from_slice::<Self>(genesis).c(d!("invalid evm genesis"))?;
+        genesis
+            .accounts
+            .into_iter()
+            .map(|(addr, account)| {
+                let code = hex::decode(account.code.trim_start_matches("0x"))
+                    .c(d!(format!("invalid code of {addr:?}")))?;
+                Ok((HA160(addr), code, account.storage))
+            })
+            .collect()
+    }
+}
 
 impl<C: Config> AppModuleBasic for App<C> {
     fn name() -> String {
@@ -8,18 +48,48 @@ impl<C: Config> AppModuleBasic for App<C> {
     }
 
     fn default_genesis(&self) -> Vec<u8> {
-        todo!()
+        pnk!(serde_json::to_vec(&GenesisConfig::default()))
     }
 
-    fn init_genesis(&self) {
-        todo!()
+    fn init_genesis(&self, ctx: &Context, genesis: &[u8]) -> Result<()> {
+        for (addr, code, storage) in GenesisConfig::parse(genesis).c(d!())? {
+            if !code.is_empty() {
+                AccountCodes::insert_bytes(ctx.state.write().borrow_mut(), &addr, code)
+                    .c(d!())?;
+            }
+            for (index, value) in storage.iter() {
+                AccountStorages::insert(
+                    ctx.state.write().borrow_mut(),
+                    &addr,
+                    &HA256::new(*index),
+                    value,
+                )
+                .c(d!())?;
+            }
+        }
+        Ok(())
     }
 
-    fn validate_genesis(&self) -> Result<()> {
-        todo!()
+    fn validate_genesis(&self, genesis: &[u8]) -> Result<()> {
+        GenesisConfig::parse(genesis).c(d!()).map(|_| ())
     }
 
-    fn export_genesis(&self) {
-        todo!()
+    fn export_genesis(&self, ctx: &Context) -> Result<Vec<u8>> {
+        let state = ctx.state.read();
+        let accounts = AccountCodes::iterate_bytes(&state)
+            .into_iter()
+            .map(|(addr, code)| {
+                let storage = AccountStorages::iterate_prefix(&state, &addr)
+                    .into_iter()
+                    .map(|(index, value)| (index.h256(), value))
+                    .collect();
+                let account = GenesisAccount {
+                    code: format!("0x{}", hex::encode(code)),
+                    storage,
+                };
+                (addr.0, account)
+            })
+            .collect();
+        serde_json::to_vec(&GenesisConfig { accounts }).c(d!())
     }
 }
@@ -1,5 +1,6 @@
 use crate::storage::*;
 use crate::{App, Config};
+use config::abci::global_cfg::CFG;
 use ethereum_types::{H160, H256, U256};
 use fp_core::context::Context;
 use fp_evm::Account;
@@ -10,7 +11,9 @@ use fp_traits::{
 };
 use fp_types::crypto::{HA160, HA256};
 use fp_utils::proposer_converter;
+use ledger::staking::evm::{StakingDiff, StakingSnapshot};
 use ruc::Result;
+use zei::{serialization::ZeiFromToBytes, xfr::sig::XfrPublicKey};
 
 impl<C: Config> App<C> {
     /// Check whether an account is empty.
@@ -85,6 +88,72 @@ impl<C: Config> App<C> {
         // TODO
         proposer_converter(ctx.header.proposer_address.clone()).unwrap_or_default()
     }
+
+    /// Deposit the priority tip of a burned fee to the block proposer,
+    /// if tips are routed to proposers since the base fee.
+    /// The base fee is always burned, so is the tip of a block without proposer.
+    pub fn deposit_tip_to_proposer(ctx: &Context, tip: U256) -> Result<()> {
+        if ctx.header.height < CFG.checkpoint.base_fee_height
+            || !CFG.checkpoint.base_fee_to_proposer
+            || tip.is_zero()
+        {
+            return Ok(());
+        }
+        match proposer_converter(ctx.header.proposer_address.clone()) {
+            Some(proposer) => C::AccountAsset::mint(
+                ctx,
+                &C::AddressMapping::convert_to_account_id(proposer),
+                tip,
+            ),
+            None => Ok(()),
+        }
+    }
+
+    /// Load the staking snapshot kept in the EVM state.
+    pub fn staking_snapshot(ctx: &Context) -> StakingSnapshot {
+        let state = ctx.state.read();
+        StakingSnapshot {
+            global: StakingGlobal::get(&state).unwrap_or_default(),
+            validators: StakingValidators::iterate(&state)
+                .into_iter()
+                .map(|(addr, v)| (addr.0.as_bytes().to_vec(), v))
+                .collect(),
+            delegations: StakingDelegations::iterate(&state)
+                .into_iter()
+                .filter_map(|(pk, d)| {
+                    XfrPublicKey::zei_from_bytes(pk.h256().as_bytes())
+                        .ok()
+                        .map(|pk| (pk, d))
+                })
+                .collect(),
+        }
+    }
+
+    /// Write the changes of the staking snapshot to the EVM state.
+    pub fn apply_staking_diff(ctx: &Context, diff: StakingDiff) -> Result<()> {
+        for (addr, v) in diff.validators {
+            if addr.len() != H160::len_bytes() {
+                continue;
+            }
+            let addr = HA160(H160::from_slice(&addr));
+            match v {
+                Some(v) => {
+                    StakingValidators::insert(ctx.state.write().borrow_mut(), &addr, &v)?
+                }
+                None => StakingValidators::remove(ctx.state.write().borrow_mut(), &addr),
+            }
+        }
+        for (pk, d) in diff.delegations {
+            let pk = HA256::new(H256::from_slice(pk.zei_to_bytes().as_slice()));
+            match d {
+                Some(d) => {
+                    StakingDelegations::insert(ctx.state.write().borrow_mut(), &pk, &d)?
+                }
+                None => StakingDelegations::remove(ctx.state.write().borrow_mut(), &pk),
+            }
+        }
+        StakingGlobal::put(ctx.state.write().borrow_mut(), &diff.global)
+    }
 }
 
 /// Implements the transaction payment for a module implementing the `Currency`
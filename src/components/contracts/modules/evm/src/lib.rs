@@ -11,6 +11,7 @@ pub mod system_contracts;
 pub mod utils;
 
 use abci::{RequestQuery, ResponseQuery};
+pub use basic::{GenesisAccount, GenesisConfig};
 use config::abci::global_cfg::CFG;
 use ethabi::Token;
 use ethereum::{
@@ -79,11 +80,18 @@ pub mod storage {
     use ethereum_types::H256;
     use fp_storage::*;
     use fp_types::crypto::{HA160, HA256};
+    use ledger::staking::evm::{DelegationSnapshot, GlobalSnapshot, ValidatorSnapshot};
 
     // The code corresponding to the contract account.
     generate_storage!(EVM, AccountCodes => Map<HA160, Vec<u8>>);
     // Storage root hash related to the contract account.
     generate_storage!(EVM, AccountStorages => DoubleMap<HA160, HA256, H256>);
+    // The staking snapshot of the UTXO ledger, read by the staking precompile.
+    generate_storage!(EVM, StakingGlobal => Value<GlobalSnapshot>);
+    // Tendermint address => current validator.
+    generate_storage!(EVM, StakingValidators => Map<HA160, ValidatorSnapshot>);
+    // Delegator pubkey => delegation.
+    generate_storage!(EVM, StakingDelegations => Map<HA256, DelegationSnapshot>);
 }
 pub struct ValidatorParam {
     pub td_addr: H160,
@@ -283,6 +291,7 @@ impl<C: Config> App<C> {
             contract_address: Some(to),
             logs,
             logs_bloom,
+            fee_payer: None,
         };
 
         let receipt = Receipt {
@@ -995,6 +1004,7 @@ impl<C: Config> App<C> {
                 Self::logs_bloom(logs, &mut bloom);
                 bloom
             },
+            fee_payer: None,
         };
         tracing::info!("generate TransactionStatus: {:?}", status);
 
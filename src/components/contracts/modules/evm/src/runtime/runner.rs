@@ -31,6 +31,7 @@ impl<C: Config> ActionRunner<C> {
         gas_limit: u64,
         gas_price: Option<U256>,
         nonce: Option<U256>,
+        fee_payer: Option<H160>,
         config: &'config evm::Config,
         precompiles: &'precompiles C::PrecompilesType,
         f: F,
@@ -49,7 +50,7 @@ impl<C: Config> ActionRunner<C> {
         let gas_price = match gas_price {
             Some(gas_price) => {
                 ensure!(
-                    gas_price >= C::FeeCalculator::min_gas_price(),
+                    gas_price >= C::FeeCalculator::base_fee_per_gas(ctx),
                     "GasPriceTooLow"
                 );
                 if ctx.header.height > CFG.checkpoint.max_gas_price_limit {
@@ -92,11 +93,22 @@ impl<C: Config> ActionRunner<C> {
             let _ = nonce;
         }
 
+        // The fee is paid by the `source` account if not sponsored.
+        let fee_payer = fee_payer.unwrap_or(source);
+
         if !config.estimate {
-            ensure!(source_account.balance >= total_payment, "BalanceLow");
+            if fee_payer == source {
+                ensure!(source_account.balance >= total_payment, "BalanceLow");
+            } else {
+                ensure!(source_account.balance >= value, "BalanceLow");
+                ensure!(
+                    App::<C>::account_basic(ctx, &fee_payer).balance >= total_fee,
+                    "FeePayerBalanceLow"
+                );
+            }
 
-            // Deduct fee from the `source` account.
-            App::<C>::withdraw_fee(ctx, &source, total_fee)?;
+            // Deduct fee from the `fee_payer` account.
+            App::<C>::withdraw_fee(ctx, &fee_payer, total_fee)?;
         }
 
         // Execute the EVM call.
@@ -116,8 +128,13 @@ impl<C: Config> ActionRunner<C> {
         );
 
         if !config.estimate {
-            // Refund fees to the `source` account if deducted more before,
-            App::<C>::correct_and_deposit_fee(ctx, &source, actual_fee, total_fee)?;
+            // Refund fees to the `fee_payer` account if deducted more before,
+            App::<C>::correct_and_deposit_fee(ctx, &fee_payer, actual_fee, total_fee)?;
+            // The base fee is burned, the priority tip goes to the proposer.
+            let tip = gas_price
+                .saturating_sub(C::FeeCalculator::base_fee_per_gas(ctx))
+                .saturating_mul(used_gas);
+            App::<C>::deposit_tip_to_proposer(ctx, tip)?;
         }
 
         let state = executor.into_state();
@@ -224,6 +241,7 @@ impl<C: Config> Runner for ActionRunner<C> {
             args.gas_limit,
             args.gas_price,
             args.nonce,
+            args.fee_payer,
             config,
             &precompiles,
             |executor| {
@@ -250,6 +268,7 @@ impl<C: Config> Runner for ActionRunner<C> {
             args.gas_limit,
             args.gas_price,
             args.nonce,
+            args.fee_payer,
             config,
             &precompiles,
             |executor| {
@@ -289,6 +308,7 @@ impl<C: Config> Runner for ActionRunner<C> {
             args.gas_limit,
             args.gas_price,
             args.nonce,
+            args.fee_payer,
             config,
             &precompiles,
             |executor| {
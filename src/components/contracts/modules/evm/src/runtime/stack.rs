@@ -274,7 +274,7 @@ impl<'context, 'vicinity, 'config, C: Config> Backend
     }
 
     fn block_base_fee_per_gas(&self) -> U256 {
-        C::FeeCalculator::min_gas_price()
+        C::FeeCalculator::base_fee_per_gas(self.ctx)
     }
 }
 
@@ -0,0 +1,81 @@
+use baseapp::BaseApp;
+use ethereum_types::{H160, H256};
+use fp_mocks::create_temp_db_path;
+use module_evm::{storage::*, GenesisAccount, GenesisConfig};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+fn new_app() -> BaseApp {
+    BaseApp::new(create_temp_db_path().as_path(), false, (0, None), false).unwrap()
+}
+
+// Load `genesis` into a new app and export it from the committed state.
+fn import_export(genesis: &[u8]) -> (BaseApp, Value) {
+    let app = new_app();
+    app.modules
+        .init_genesis(&app.deliver_state, genesis)
+        .unwrap();
+    app.deliver_state.state.write().commit(1).unwrap();
+
+    let exported = app.modules.export_genesis(&app.deliver_state).unwrap();
+    (app, serde_json::from_slice(&exported).unwrap())
+}
+
+#[test]
+fn evm_genesis_round_trip() {
+    let contract = H160::repeat_byte(0x11);
+    let genesis = br#"{
+        "evm": {"accounts": {
+            "0x1111111111111111111111111111111111111111": {
+                "code": "0x6080604052",
+                "storage": {
+                    "0x0000000000000000000000000000000000000000000000000000000000000001":
+                    "0x000000000000000000000000000000000000000000000000000000000000002a"
+                }
+            }
+        }},
+        "account": {"accounts": {
+            "0x1111111111111111111111111111111111111111": {"balance": "0x64"}
+        }},
+        "unknown": {}
+    }"#;
+
+    //Unknown modules are ignored, the code is loaded as the runtime reads it
+    let (app, exported) = import_export(genesis);
+    let ctx = &app.deliver_state;
+    assert_eq!(
+        AccountCodes::get_bytes(&ctx.state.read(), &contract.into()),
+        Some(hex::decode("6080604052").unwrap())
+    );
+    assert!(exported.get("unknown").is_none());
+
+    let evm = serde_json::from_value::<GenesisConfig>(exported["evm"].clone()).unwrap();
+    assert_eq!(
+        evm,
+        GenesisConfig {
+            accounts: BTreeMap::from([(
+                contract,
+                GenesisAccount {
+                    code: "0x6080604052".to_owned(),
+                    storage: BTreeMap::from([(
+                        H256::from_low_u64_be(1),
+                        H256::from_low_u64_be(42)
+                    )]),
+                }
+            )]),
+        }
+    );
+
+    //The exported genesis is loaded into the same state
+    let (_, reexported) = import_export(&serde_json::to_vec(&exported).unwrap());
+    assert_eq!(exported, reexported);
+
+    //Modules missing from the genesis are left untouched
+    let (_, exported) = import_export(br#"{"account": {"accounts": {}}}"#);
+    assert!(
+        serde_json::from_value::<GenesisConfig>(exported["evm"].clone())
+            .unwrap()
+            .accounts
+            .is_empty()
+    );
+}
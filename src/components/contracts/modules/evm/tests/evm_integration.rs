@@ -288,6 +288,7 @@ fn test_balance_of_with_eth_call(contract: ERC20, who: H160) -> U256 {
         gas_limit: DEFAULT_GAS_LIMIT,
         gas_price: None,
         nonce: None,
+        fee_payer: None,
     };
 
     let info = <BaseApp as module_ethereum::Config>::Runner::call(
@@ -1,6 +1,15 @@
-use super::{App, Config, MODULE_NAME};
-use fp_core::module::AppModuleBasic;
-use ruc::Result;
+use super::{storage::*, App, Config, MODULE_NAME};
+use fp_core::{context::Context, module::AppModuleBasic};
+use fp_storage::BorrowMut;
+use ruc::*;
+use serde::{Deserialize, Serialize};
+
+/// Genesis state of the template module.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenesisConfig {
+    #[serde(default)]
+    pub value: u64,
+}
 
 impl<C: Config> AppModuleBasic for App<C> {
     fn name() -> String {
@@ -8,18 +17,23 @@ impl<C: Config> AppModuleBasic for App<C> {
     }
 
     fn default_genesis(&self) -> Vec<u8> {
-        todo!()
+        pnk!(serde_json::to_vec(&GenesisConfig::default()))
     }
 
-    fn init_genesis(&self) {
-        todo!()
+    fn init_genesis(&self, ctx: &Context, genesis: &[u8]) -> Result<()> {
+        let genesis = serde_json::from_slice::<GenesisConfig>(genesis)
+            .c(d!("invalid template genesis"))?;
+        ValueStore::put(ctx.state.write().borrow_mut(), &genesis.value).c(d!())
     }
 
-    fn validate_genesis(&self) -> Result<()> {
-        todo!()
+    fn validate_genesis(&self, genesis: &[u8]) -> Result<()> {
+        serde_json::from_slice::<GenesisConfig>(genesis)
+            .c(d!("invalid template genesis"))
+            .map(|_| ())
     }
 
-    fn export_genesis(&self) {
-        todo!()
+    fn export_genesis(&self, ctx: &Context) -> Result<Vec<u8>> {
+        let value = ValueStore::get(&ctx.state.read()).unwrap_or_default();
+        serde_json::to_vec(&GenesisConfig { value }).c(d!())
     }
 }
@@ -6,14 +6,14 @@ mod basic;
 pub use crate::storage::*;
 use fp_core::{
     context::Context,
-    module::AppModule,
+    module::{ActionCodec, AppModule},
     transaction::{ActionResult, Executable},
 };
 // use fp_storage::{hash::StoragePrefixKey, Deref, StatelessStore};
 use abci::{RequestQuery, ResponseQuery};
 use fp_storage::BorrowMut;
 use fp_types::{actions::template::Action, crypto::Address};
-use ruc::Result;
+use ruc::*;
 use std::marker::PhantomData;
 
 pub const MODULE_NAME: &str = "template";
@@ -88,3 +88,9 @@ impl<C: Config> Executable for App<C> {
         }
     }
 }
+
+impl<C: Config> ActionCodec for App<C> {
+    fn decode_action(data: &[u8]) -> Result<Self::Call> {
+        serde_json::from_slice(data).c(d!("invalid template action"))
+    }
+}
@@ -2,9 +2,13 @@
 
 //! Template module integration tests.
 use abci::*;
+use config::abci::global_cfg::CFG;
 use fp_mocks::*;
 use fp_traits::account::{AccountAsset, FeeCalculator};
-use fp_types::{actions::template::Action as TemplateAction, actions::Action, U256};
+use fp_types::{
+    actions::module::Action as ModuleAction,
+    actions::template::Action as TemplateAction, actions::Action, U256,
+};
 use fp_utils::tx::EvmRawTxWrapper;
 use module_template::ValueStore;
 pub use std::borrow::Borrow;
@@ -26,6 +30,7 @@ fn run_all_tests() {
     test_abci_commit();
     test_abci_query();
     test_abci_check_tx_with_bad_nonce();
+    test_abci_deliver_module_tx();
 }
 
 fn test_abci_info() {
@@ -190,3 +195,60 @@ fn test_abci_check_tx_with_bad_nonce() {
         resp.log
     );
 }
+
+fn test_abci_deliver_module_tx() {
+    let mut req = RequestBeginBlock::default();
+    req.hash = b"test".to_vec();
+    let mut header = Header::default();
+    header.height = 3;
+    req.set_header(header);
+    let _ = BASE_APP.lock().unwrap().begin_block(&req);
+
+    let mut req = RequestDeliverTx::default();
+    // Routed to the registered template module by name.
+    let function = Action::Module(ModuleAction {
+        module: module_template::MODULE_NAME.to_owned(),
+        data: serde_json::to_vec(&TemplateAction::SetValue(20)).unwrap(),
+    });
+    let tx =
+        serde_json::to_vec(&build_signed_transaction(function, &ALICE_XFR, U256::one()))
+            .unwrap();
+    req.tx = EvmRawTxWrapper::wrap(&tx);
+    let (resp, _) = BASE_APP.lock().unwrap().deliver_tx(&req);
+
+    if 3 < CFG.checkpoint.module_action_height {
+        assert!(
+            resp.code != 0 && resp.log.contains("Action::Module is not enabled"),
+            "resp log: {}",
+            resp.log
+        );
+        return;
+    }
+    assert_eq!(
+        resp.code, 0,
+        "deliver tx failed, code: {}, log: {}",
+        resp.code, resp.log
+    );
+    assert_eq!(u64::from_be_bytes(resp.data.try_into().unwrap()), 20);
+    assert_eq!(
+        ValueStore::get(BASE_APP.lock().unwrap().deliver_state.state.read().borrow()),
+        Some(20)
+    );
+
+    // Unknown modules are rejected
+    let mut req = RequestCheckTx::default();
+    let function = Action::Module(ModuleAction {
+        module: "unknown".to_owned(),
+        data: vec![],
+    });
+    let tx =
+        serde_json::to_vec(&build_signed_transaction(function, &ALICE_XFR, U256::one()))
+            .unwrap();
+    req.tx = EvmRawTxWrapper::wrap(&tx);
+    let resp = BASE_APP.lock().unwrap().check_tx(&req);
+    assert!(
+        resp.code != 0 && resp.log.contains("Invalid action module route"),
+        "resp log: {}",
+        resp.log
+    );
+}
@@ -1,6 +1,36 @@
-use super::{App, Config, MODULE_NAME};
-use fp_core::module::AppModuleBasic;
-use ruc::Result;
+use super::{impls::asset_key, storage::*, App, Config, MODULE_NAME};
+use fp_core::{context::Context, module::AppModuleBasic};
+use fp_storage::BorrowMut;
+use fp_types::crypto::Address;
+use ledger::data_model::AssetTypeCode;
+use primitive_types::U256;
+use ruc::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use zei::xfr::structs::AssetType;
+
+/// Genesis state of the xhub module, the pending UTXOs are not part of it
+/// since they are drained at the end of every block.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenesisConfig {
+    /// Balances of the custom assets converted from UTXOs.
+    #[serde(default)]
+    pub asset_balances: Vec<GenesisAssetBalance>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenesisAssetBalance {
+    pub owner: Address,
+    pub asset: AssetTypeCode,
+    /// In native units of the asset.
+    pub amount: U256,
+}
+
+impl GenesisConfig {
+    fn parse(genesis: &[u8]) -> Result<Self> {
+        serde_json::from_slice::<Self>(genesis).c(d!("invalid xhub genesis"))
+    }
+}
 
 impl<C: Config> AppModuleBasic for App<C> {
     fn name() -> String {
@@ -8,18 +38,47 @@ impl<C: Config> AppModuleBasic for App<C> {
     }
 
     fn default_genesis(&self) -> Vec<u8> {
-        todo!()
+        pnk!(serde_json::to_vec(&GenesisConfig::default()))
     }
 
-    fn init_genesis(&self) {
-        todo!()
+    fn init_genesis(&self, ctx: &Context, genesis: &[u8]) -> Result<()> {
+        let mut supplies = BTreeMap::new();
+        for b in GenesisConfig::parse(genesis).c(d!())?.asset_balances {
+            let key = asset_key(b.asset.val);
+            let supply = supplies.entry(key.clone()).or_insert_with(U256::zero);
+            *supply = supply
+                .checked_add(b.amount)
+                .c(d!("asset supply overflow"))?;
+            AssetBalances::insert(
+                ctx.state.write().borrow_mut(),
+                &b.owner,
+                &key,
+                &b.amount,
+            )
+            .c(d!())?;
+        }
+        for (key, supply) in supplies.iter() {
+            AssetSupplies::insert(ctx.state.write().borrow_mut(), key, supply)
+                .c(d!())?;
+        }
+        Ok(())
     }
 
-    fn validate_genesis(&self) -> Result<()> {
-        todo!()
+    fn validate_genesis(&self, genesis: &[u8]) -> Result<()> {
+        GenesisConfig::parse(genesis).c(d!()).map(|_| ())
     }
 
-    fn export_genesis(&self) {
-        todo!()
+    fn export_genesis(&self, ctx: &Context) -> Result<Vec<u8>> {
+        let asset_balances = AssetBalances::iterate(&ctx.state.read())
+            .into_iter()
+            .map(|(owner, key, amount)| GenesisAssetBalance {
+                owner,
+                asset: AssetTypeCode {
+                    val: AssetType(key.h256().0),
+                },
+                amount,
+            })
+            .collect();
+        serde_json::to_vec(&GenesisConfig { asset_balances }).c(d!())
     }
 }
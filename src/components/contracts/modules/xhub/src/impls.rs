@@ -1,14 +1,20 @@
 use crate::storage::*;
 use crate::{App, Config};
+use config::abci::global_cfg::CFG;
 use fp_core::{context::Context, ensure, transaction::ActionResult};
 use fp_storage::BorrowMut;
 use fp_traits::{account::AccountAsset, evm::DecimalsMapping};
 use fp_types::actions::xhub::NonConfidentialTransfer;
-use fp_types::{actions::xhub::NonConfidentialOutput, crypto::Address};
+use fp_types::{
+    actions::xhub::NonConfidentialOutput,
+    crypto::{Address, HA256},
+};
 use ledger::data_model::ASSET_TYPE_FRA;
-use primitive_types::U256;
+use primitive_types::{H256, U256};
 use ruc::*;
+use std::collections::BTreeMap;
 use tracing::debug;
+use zei::xfr::structs::AssetType;
 
 impl<C: Config> App<C> {
     pub fn transfer_to_nonconfidential_utxo(
@@ -17,12 +23,20 @@ impl<C: Config> App<C> {
         call: NonConfidentialTransfer,
     ) -> Result<ActionResult> {
         let mut transfer_amount = 0;
+        let mut assets = BTreeMap::new();
         for output in &call.outputs {
-            ensure!(
-                output.asset == ASSET_TYPE_FRA,
-                "Invalid asset type only support FRA"
-            );
-            transfer_amount += output.amount;
+            if output.asset == ASSET_TYPE_FRA {
+                transfer_amount += output.amount;
+            } else {
+                ensure!(
+                    ctx.header.height >= CFG.checkpoint.native_asset_bridge_height,
+                    "Invalid asset type only support FRA"
+                );
+                let sum = assets.entry(asset_key(output.asset)).or_insert(0u64);
+                *sum = sum
+                    .checked_add(output.amount)
+                    .c(d!("the transfer to UTXO amount is too large"))?;
+            }
         }
 
         debug!(target: "xhub", "transfer to UTXO {} FRA", transfer_amount);
@@ -41,13 +55,78 @@ impl<C: Config> App<C> {
             return Err(eg!("insufficient balance"));
         }
 
+        for (asset, value) in assets.iter() {
+            Self::withdraw_asset(ctx, &sender, asset, U256::from(*value)).c(d!())?;
+        }
+
         if !amount.is_zero() {
             C::AccountAsset::burn(ctx, &sender, amount)?;
+        }
+        if !amount.is_zero() || !assets.is_empty() {
             Self::add_mint(ctx, call.outputs)?;
         }
         Ok(ActionResult::default())
     }
 
+    /// Credit a custom asset converted from UTXOs to an account.
+    pub fn deposit_asset(
+        ctx: &Context,
+        who: &Address,
+        asset: AssetType,
+        amount: u64,
+    ) -> Result<()> {
+        ensure!(asset != ASSET_TYPE_FRA, "FRA is kept by the account module");
+
+        let key = asset_key(asset);
+        let amount = U256::from(amount);
+        let supply = AssetSupplies::get(&ctx.state.read(), &key)
+            .unwrap_or_default()
+            .checked_add(amount)
+            .c(d!("asset supply overflow"))?;
+        let balance = AssetBalances::get(&ctx.state.read(), who, &key)
+            .unwrap_or_default()
+            .checked_add(amount)
+            .c(d!("asset balance overflow"))?;
+
+        AssetBalances::insert(ctx.state.write().borrow_mut(), who, &key, &balance)
+            .c(d!())?;
+        AssetSupplies::insert(ctx.state.write().borrow_mut(), &key, &supply).c(d!())
+    }
+
+    fn withdraw_asset(
+        ctx: &Context,
+        who: &Address,
+        key: &HA256,
+        amount: U256,
+    ) -> Result<()> {
+        let balance = AssetBalances::get(&ctx.state.read(), who, key)
+            .unwrap_or_default()
+            .checked_sub(amount)
+            .c(d!(format!("insufficient balance of asset {key}")))?;
+        let supply = AssetSupplies::get(&ctx.state.read(), key)
+            .unwrap_or_default()
+            .checked_sub(amount)
+            .c(d!(format!("insufficient supply of asset {key}")))?;
+
+        if balance.is_zero() {
+            AssetBalances::remove(ctx.state.write().borrow_mut(), who, key);
+        } else {
+            AssetBalances::insert(ctx.state.write().borrow_mut(), who, key, &balance)
+                .c(d!())?;
+        }
+        AssetSupplies::insert(ctx.state.write().borrow_mut(), key, &supply).c(d!())
+    }
+
+    /// Balance of a custom asset of an account, in native units.
+    pub fn asset_balance(ctx: &Context, who: &Address, asset: AssetType) -> U256 {
+        AssetBalances::get(&ctx.state.read(), who, &asset_key(asset)).unwrap_or_default()
+    }
+
+    /// Total balance of a custom asset held by accounts, in native units.
+    pub fn asset_supply(ctx: &Context, asset: AssetType) -> U256 {
+        AssetSupplies::get(&ctx.state.read(), &asset_key(asset)).unwrap_or_default()
+    }
+
     pub(crate) fn add_mint(
         ctx: &Context,
         mut outputs: Vec<NonConfidentialOutput>,
@@ -65,3 +144,7 @@ impl<C: Config> App<C> {
         PendingUTXOs::take(ctx.db.write().borrow_mut())
     }
 }
+
+pub(crate) fn asset_key(asset: AssetType) -> HA256 {
+    HA256::new(H256::from(asset.0))
+}
@@ -4,6 +4,7 @@
 mod basic;
 mod impls;
 
+use abci::{RequestQuery, ResponseQuery};
 use fp_core::{
     context::Context,
     module::AppModule,
@@ -11,6 +12,7 @@ use fp_core::{
 };
 use fp_traits::{account::AccountAsset, evm::DecimalsMapping};
 use fp_types::{actions::xhub::Action, crypto::Address};
+use ledger::data_model::AssetTypeCode;
 use ruc::*;
 use std::marker::PhantomData;
 
@@ -24,13 +26,23 @@ pub trait Config {
 }
 
 mod storage {
-    use fp_types::actions::xhub::NonConfidentialOutput;
+    use fp_types::{
+        actions::xhub::NonConfidentialOutput,
+        crypto::{Address, HA256},
+    };
+    use primitive_types::U256;
 
     use fp_storage::*;
 
     // The following data is stored in non-state rocksdb
     // account balance transfer to utxo waiting to be mint.
     generate_storage!(XHub, PendingUTXOs => Value<Vec<NonConfidentialOutput>>);
+
+    // The following data is stored in state.
+    // Balances of the custom assets converted from UTXOs, in native units.
+    generate_storage!(XHub, AssetBalances => DoubleMap<Address, HA256, U256>);
+    // Sum of the balances of each custom asset.
+    generate_storage!(XHub, AssetSupplies => Map<HA256, U256>);
 }
 
 #[derive(Clone)]
@@ -46,7 +58,44 @@ impl<C: Config> Default for App<C> {
     }
 }
 
-impl<C: Config> AppModule for App<C> {}
+impl<C: Config> AppModule for App<C> {
+    fn query_route(
+        &self,
+        ctx: Context,
+        path: Vec<&str>,
+        req: &RequestQuery,
+    ) -> ResponseQuery {
+        let mut resp: ResponseQuery = Default::default();
+        if path.len() != 1 {
+            resp.code = 1;
+            resp.log = String::from("xhub: invalid query path");
+            return resp;
+        }
+        let value = match path[0] {
+            "asset-balance" => {
+                serde_json::from_slice::<(Address, AssetTypeCode)>(req.data.as_slice())
+                    .map(|(who, code)| Self::asset_balance(&ctx, &who, code.val))
+            }
+            "asset-supply" => {
+                serde_json::from_slice::<AssetTypeCode>(req.data.as_slice())
+                    .map(|code| Self::asset_supply(&ctx, code.val))
+            }
+            _ => {
+                resp.code = 1;
+                resp.log = format!("xhub: unknown query path {}", path[0]);
+                return resp;
+            }
+        };
+        match value {
+            Ok(v) => resp.value = serde_json::to_vec(&v).unwrap_or_default(),
+            Err(_) => {
+                resp.code = 1;
+                resp.log = format!("xhub: query {} with invalid params", path[0]);
+            }
+        }
+        resp
+    }
+}
 
 impl<C: Config> Executable for App<C> {
     type Origin = Address;
@@ -0,0 +1,87 @@
+use abci::RequestQuery;
+use baseapp::BaseApp;
+use config::abci::global_cfg::CFG;
+use fp_core::module::AppModule;
+use fp_mocks::{create_temp_db_path, ALICE_ECDSA, ALICE_XFR};
+use fp_traits::account::AccountAsset;
+use fp_types::{
+    actions::xhub::{NonConfidentialOutput, NonConfidentialTransfer},
+    U256,
+};
+use ledger::data_model::{AssetTypeCode, ASSET_TYPE_FRA};
+use zei::xfr::structs::AssetType;
+
+type XHub = module_xhub::App<BaseApp>;
+
+fn withdraw(amount: u64, asset: AssetType) -> NonConfidentialTransfer {
+    NonConfidentialTransfer {
+        input_value: 0,
+        outputs: vec![NonConfidentialOutput {
+            asset,
+            amount,
+            target: ALICE_XFR.get_pk(),
+            ..Default::default()
+        }],
+    }
+}
+
+#[test]
+fn test_native_asset_bridge() {
+    let app =
+        BaseApp::new(create_temp_db_path().as_path(), false, (0, None), false).unwrap();
+    let mut ctx = app.deliver_state.clone();
+    ctx.header.height = CFG.checkpoint.native_asset_bridge_height.max(0);
+    let alice = &ALICE_ECDSA.account_id;
+    let asset = AssetType([9; 32]);
+
+    //FRA is kept by the account module
+    assert!(XHub::deposit_asset(&ctx, alice, ASSET_TYPE_FRA, 10).is_err());
+
+    //Deposits are credited to the account and the supply
+    XHub::deposit_asset(&ctx, alice, asset, 10).unwrap();
+    assert_eq!(XHub::asset_balance(&ctx, alice, asset), U256::from(10));
+    assert_eq!(XHub::asset_supply(&ctx, asset), U256::from(10));
+
+    //Withdrawals are debited and minted to UTXOs
+    module_account::App::<BaseApp>::mint(&ctx, alice, U256::one()).unwrap();
+    XHub::transfer_to_nonconfidential_utxo(&ctx, alice.clone(), withdraw(4, asset))
+        .unwrap();
+    assert_eq!(XHub::asset_balance(&ctx, alice, asset), U256::from(6));
+    assert_eq!(XHub::asset_supply(&ctx, asset), U256::from(6));
+    let minted = XHub::consume_mint(&ctx).unwrap();
+    assert_eq!(minted.len(), 1);
+    assert_eq!((minted[0].asset, minted[0].amount), (asset, 4));
+
+    //Nothing is withdrawn beyond the balance
+    assert!(XHub::transfer_to_nonconfidential_utxo(
+        &ctx,
+        alice.clone(),
+        withdraw(7, asset)
+    )
+    .is_err());
+    assert_eq!(XHub::asset_supply(&ctx, asset), U256::from(6));
+    assert!(XHub::consume_mint(&ctx).is_none());
+
+    //Custom assets are not withdrawn before the bridge
+    ctx.header.height -= 1;
+    if ctx.header.height < CFG.checkpoint.native_asset_bridge_height {
+        assert!(XHub::transfer_to_nonconfidential_utxo(
+            &ctx,
+            alice.clone(),
+            withdraw(1, asset)
+        )
+        .is_err());
+    }
+
+    //The supply is queried for reconciliation, unknown paths are rejected
+    let mut req = RequestQuery::new();
+    req.data = serde_json::to_vec(&AssetTypeCode { val: asset }).unwrap();
+    let resp = XHub::default().query_route(ctx.clone(), vec!["asset-supply"], &req);
+    assert_eq!(resp.code, 0);
+    assert_eq!(
+        serde_json::from_slice::<U256>(&resp.value).unwrap(),
+        U256::from(6)
+    );
+    let resp = XHub::default().query_route(ctx, vec!["unknown"], &req);
+    assert_eq!(resp.code, 1);
+}
@@ -2,6 +2,7 @@ use primitive_types::U256;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SmartAccount {
     /// Account nonce.
     pub nonce: U256,
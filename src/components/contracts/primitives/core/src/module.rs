@@ -1,5 +1,9 @@
-use crate::context::Context;
+use crate::{
+    context::Context,
+    transaction::{ActionResult, Executable},
+};
 use abci::*;
+use fp_types::crypto::Address;
 use ruc::Result;
 
 /// AppModuleBasic is the standard form for basic non-dependant elements of an application module.
@@ -11,13 +15,13 @@ pub trait AppModuleBasic {
     fn default_genesis(&self) -> Vec<u8>;
 
     /// Performs genesis initialization for the module. It returns no validator updates.
-    fn init_genesis(&self);
+    fn init_genesis(&self, ctx: &Context, genesis: &[u8]) -> Result<()>;
 
     /// Performs genesis state validation for the module.
-    fn validate_genesis(&self) -> Result<()>;
+    fn validate_genesis(&self, genesis: &[u8]) -> Result<()>;
 
     /// Returns the exported genesis state as raw bytes for the module.
-    fn export_genesis(&self);
+    fn export_genesis(&self, ctx: &Context) -> Result<Vec<u8>>;
 }
 
 /// AppModule is the standard form for an application module
@@ -45,3 +49,126 @@ pub trait AppModule: AppModuleBasic {
         Default::default()
     }
 }
+
+/// ActionCodec decodes the raw action data of `Action::Module` into the call of the module.
+pub trait ActionCodec: Executable {
+    /// Decodes the raw action data.
+    fn decode_action(data: &[u8]) -> Result<Self::Call>;
+}
+
+/// PluggableModule is the object-safe form of an application module,
+/// which lets modules be registered into the module manager by name.
+///
+/// It is implemented for every cloneable `AppModule` with an `ActionCodec`.
+pub trait PluggableModule: Send + Sync {
+    /// Returns the module's name, which routes queries, genesis and actions to it.
+    fn module_name(&self) -> String;
+
+    /// Same as `AppModule::query_route`.
+    fn query_route(
+        &self,
+        ctx: Context,
+        path: Vec<&str>,
+        req: &RequestQuery,
+    ) -> ResponseQuery;
+
+    /// Same as `AppModule::begin_block`.
+    fn begin_block(&mut self, ctx: &mut Context, req: &RequestBeginBlock);
+
+    /// Same as `AppModule::end_block`.
+    fn end_block(
+        &mut self,
+        ctx: &mut Context,
+        req: &RequestEndBlock,
+        ff_addr_balance: u64,
+    ) -> ResponseEndBlock;
+
+    /// Same as `AppModuleBasic::default_genesis`.
+    fn default_genesis(&self) -> Vec<u8>;
+
+    /// Same as `AppModuleBasic::init_genesis`.
+    fn init_genesis(&self, ctx: &Context, genesis: &[u8]) -> Result<()>;
+
+    /// Same as `AppModuleBasic::validate_genesis`.
+    fn validate_genesis(&self, genesis: &[u8]) -> Result<()>;
+
+    /// Same as `AppModuleBasic::export_genesis`.
+    fn export_genesis(&self, ctx: &Context) -> Result<Vec<u8>>;
+
+    /// Decodes the raw action data and executes it.
+    fn execute(
+        &self,
+        origin: Option<Address>,
+        data: &[u8],
+        ctx: &Context,
+    ) -> Result<ActionResult>;
+
+    /// Clones the module into a new instance.
+    fn clone_module(&self) -> Box<dyn PluggableModule>;
+}
+
+impl<M> PluggableModule for M
+where
+    M: AppModule
+        + ActionCodec
+        + Executable<Origin = Address>
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+{
+    fn module_name(&self) -> String {
+        M::name()
+    }
+
+    fn query_route(
+        &self,
+        ctx: Context,
+        path: Vec<&str>,
+        req: &RequestQuery,
+    ) -> ResponseQuery {
+        AppModule::query_route(self, ctx, path, req)
+    }
+
+    fn begin_block(&mut self, ctx: &mut Context, req: &RequestBeginBlock) {
+        AppModule::begin_block(self, ctx, req)
+    }
+
+    fn end_block(
+        &mut self,
+        ctx: &mut Context,
+        req: &RequestEndBlock,
+        ff_addr_balance: u64,
+    ) -> ResponseEndBlock {
+        AppModule::end_block(self, ctx, req, ff_addr_balance)
+    }
+
+    fn default_genesis(&self) -> Vec<u8> {
+        AppModuleBasic::default_genesis(self)
+    }
+
+    fn init_genesis(&self, ctx: &Context, genesis: &[u8]) -> Result<()> {
+        AppModuleBasic::init_genesis(self, ctx, genesis)
+    }
+
+    fn validate_genesis(&self, genesis: &[u8]) -> Result<()> {
+        AppModuleBasic::validate_genesis(self, genesis)
+    }
+
+    fn export_genesis(&self, ctx: &Context) -> Result<Vec<u8>> {
+        AppModuleBasic::export_genesis(self, ctx)
+    }
+
+    fn execute(
+        &self,
+        origin: Option<Address>,
+        data: &[u8],
+        ctx: &Context,
+    ) -> Result<ActionResult> {
+        <M as Executable>::execute(origin, M::decode_action(data)?, ctx)
+    }
+
+    fn clone_module(&self) -> Box<dyn PluggableModule> {
+        Box::new(self.clone())
+    }
+}
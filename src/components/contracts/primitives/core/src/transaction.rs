@@ -213,6 +213,8 @@ pub struct ActionResult {
     pub code: u32,
     /// Record the source address
     pub source: Option<Address>,
+    /// Record the fee payer address if not the source
+    pub fee_payer: Option<Address>,
     /// Data is any data returned from message or handler execution.
     pub data: Vec<u8>,
     /// Log contains the log information from message or handler execution.
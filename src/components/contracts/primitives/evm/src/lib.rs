@@ -45,6 +45,9 @@ pub struct TransactionStatus {
     pub contract_address: Option<H160>,
     pub logs: Vec<Log>,
     pub logs_bloom: Bloom,
+    /// The account paid the fee if not `from`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_payer: Option<H160>,
 }
 
 pub trait Runner {
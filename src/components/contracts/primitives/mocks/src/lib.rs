@@ -104,6 +104,17 @@ pub fn generate_address(seed: u8) -> KeyPair {
     }
 }
 
+/// Sign a message hash into 65 bytes of `r`, `s` and `v`.
+pub fn sign_message(msg: &[u8; 32], key: &H256) -> Vec<u8> {
+    let (sig, recovery_id) = libsecp256k1::sign(
+        &libsecp256k1::Message::parse(msg),
+        &libsecp256k1::SecretKey::parse_slice(&key[..]).unwrap(),
+    );
+    let mut signature = sig.serialize().to_vec();
+    signature.push(recovery_id.serialize());
+    signature
+}
+
 pub struct UnsignedTransaction {
     pub nonce: U256,
     pub gas_price: U256,
@@ -20,6 +20,7 @@ mod eth;
 mod eth_filter;
 mod eth_pubsub;
 mod net;
+mod txpool;
 mod web3;
 
 pub mod types;
@@ -28,4 +29,5 @@ pub use eth::{EthApi, EthApiServer};
 pub use eth_filter::{EthFilterApi, EthFilterApiServer};
 pub use eth_pubsub::{EthPubSubApi, EthPubSubApiServer};
 pub use net::{NetApi, NetApiServer};
+pub use txpool::{TxPoolApi, TxPoolApiServer};
 pub use web3::{Web3Api, Web3ApiServer};
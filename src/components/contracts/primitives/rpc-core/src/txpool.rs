@@ -0,0 +1,14 @@
+//! Txpool rpc interface.
+use crate::types::TxPoolInspect;
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+
+pub use rpc_impl_TxPoolApi::gen_server::TxPoolApi as TxPoolApiServer;
+
+/// Txpool rpc interface.
+#[rpc(server)]
+pub trait TxPoolApi {
+    /// Returns a textual summary of the pending and queued transactions.
+    #[rpc(name = "txpool_inspect")]
+    fn inspect(&self) -> Result<TxPoolInspect>;
+}
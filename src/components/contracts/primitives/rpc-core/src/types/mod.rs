@@ -27,9 +27,11 @@ mod filter;
 mod index;
 mod log;
 mod receipt;
+mod state_range;
 mod sync;
 mod transaction;
 mod transaction_request;
+mod txpool;
 mod work;
 
 pub mod pubsub;
@@ -48,6 +50,7 @@ pub use self::filter::{
 pub use self::index::Index;
 pub use self::log::Log;
 pub use self::receipt::Receipt;
+pub use self::state_range::StateRange;
 pub use self::sync::{
     ChainStatus, EthProtocolInfo, PeerCount, PeerInfo, PeerNetworkInfo,
     PeerProtocolsInfo, Peers, PipProtocolInfo, SyncInfo, SyncStatus, TransactionStats,
@@ -57,4 +60,5 @@ pub use self::transaction::{
     Transaction,
 };
 pub use self::transaction_request::TransactionRequest;
+pub use self::txpool::{InspectSummaries, TxPoolInspect};
 pub use self::work::Work;
@@ -104,6 +104,15 @@ impl<'a> Deserialize<'a> for Params {
     }
 }
 
+/// Metadata that knows which client sent the request.
+pub trait ClientMetadata {
+    /// Identity of the remote client, such as its IP address.
+    fn client(&self) -> Option<&str>;
+
+    /// Attach the identity of the remote client.
+    fn with_client(self, client: String) -> Self;
+}
+
 /// RPC Metadata.
 ///
 /// Manages persistent session for transports that support it
@@ -112,6 +121,7 @@ impl<'a> Deserialize<'a> for Params {
 #[derive(Default, Clone)]
 pub struct Metadata {
     session: Option<Arc<Session>>,
+    client: Option<String>,
 }
 
 impl jsonrpc_core::Metadata for Metadata {}
@@ -121,11 +131,23 @@ impl PubSubMetadata for Metadata {
     }
 }
 
+impl ClientMetadata for Metadata {
+    fn client(&self) -> Option<&str> {
+        self.client.as_deref()
+    }
+
+    fn with_client(mut self, client: String) -> Self {
+        self.client = Some(client);
+        self
+    }
+}
+
 impl Metadata {
     /// Create new `Metadata` with session (Pub/Sub) support.
     pub fn new(transport: mpsc::UnboundedSender<String>) -> Self {
         Metadata {
             session: Some(Arc::new(Session::new(transport))),
+            client: None,
         }
     }
 
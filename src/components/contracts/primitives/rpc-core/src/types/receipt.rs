@@ -54,4 +54,7 @@ pub struct Receipt {
     // NOTE(niklasad1): Unknown after EIP98 rules, if it's missing then skip serializing it
     #[serde(skip_serializing_if = "Option::is_none", rename = "status")]
     pub status_code: Option<U64>,
+    /// The account paid the fee, if sponsored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_payer: Option<H160>,
 }
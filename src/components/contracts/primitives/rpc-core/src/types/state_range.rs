@@ -0,0 +1,16 @@
+//! Types of `web3_stateRange`.
+
+use ethereum_types::U256;
+use serde::Serialize;
+
+/// The heights whose state can be queried.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateRange {
+    /// Whether the versions since `earliest` are kept, within the archive window.
+    pub archive: bool,
+    /// The earliest queryable height.
+    pub earliest: U256,
+    /// The latest queryable height.
+    pub latest: U256,
+}
@@ -67,6 +67,9 @@ pub struct Transaction {
     pub r: U256,
     /// The S field of the signature.
     pub s: U256,
+    /// The account paid the fee, if sponsored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_payer: Option<H160>,
 }
 
 /// Local Transaction Status
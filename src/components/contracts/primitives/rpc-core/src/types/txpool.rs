@@ -0,0 +1,17 @@
+//! Types of the `txpool` namespace.
+
+use ethereum_types::H160;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Summaries of the transactions of a sender, keyed by nonce.
+pub type InspectSummaries = BTreeMap<String, String>;
+
+/// The result of `txpool_inspect`, in the format of geth.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct TxPoolInspect {
+    /// Transactions in the mempool.
+    pub pending: BTreeMap<H160, InspectSummaries>,
+    /// Transactions waiting for a nonce gap to be filled.
+    pub queued: BTreeMap<H160, InspectSummaries>,
+}
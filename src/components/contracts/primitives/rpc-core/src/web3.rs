@@ -21,7 +21,7 @@ use ethereum_types::H256;
 use jsonrpc_core::Result;
 use jsonrpc_derive::rpc;
 
-use crate::types::Bytes;
+use crate::types::{Bytes, StateRange};
 
 pub use rpc_impl_Web3Api::gen_server::Web3Api as Web3ApiServer;
 
@@ -35,4 +35,8 @@ pub trait Web3Api {
     /// Returns sha3 of the given data
     #[rpc(name = "web3_sha3")]
     fn sha3(&self, _: Bytes) -> Result<H256>;
+
+    /// Returns the range of heights whose state can be queried.
+    #[rpc(name = "web3_stateRange")]
+    fn state_range(&self) -> Result<StateRange>;
 }
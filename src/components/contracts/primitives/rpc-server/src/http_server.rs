@@ -0,0 +1,227 @@
+//! HTTP transport of the RPC servers.
+//!
+//! `jsonrpc-http-server` hides the address of the remote peer,
+//! which the per-client rate limits are keyed on, so requests are served by hyper directly.
+
+use crate::{policy::client_of, RpcHandler};
+use fp_rpc_core::types::pubsub::ClientMetadata;
+use futures::channel::oneshot;
+use hyper::{
+    body::HttpBody,
+    header::{self, HeaderMap, HeaderValue},
+    server::conn::AddrStream,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use parking_lot::Mutex;
+use std::{
+    convert::Infallible,
+    io,
+    net::{IpAddr, SocketAddr, TcpListener},
+    sync::Arc,
+    thread,
+};
+use tracing::error;
+
+const HEALTH_REQUEST: &str =
+    r#"{"jsonrpc":"2.0","method":"system_health","params":[],"id":1}"#;
+
+/// Stops the server once closed.
+#[derive(Clone)]
+pub struct CloseHandle(Arc<Mutex<Option<oneshot::Sender<()>>>>);
+
+impl CloseHandle {
+    /// Stop accepting requests, the pending ones are finished.
+    pub fn close(self) {
+        if let Some(tx) = self.0.lock().take() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+/// A running HTTP server.
+pub struct HttpServer {
+    close: CloseHandle,
+    thread: thread::JoinHandle<()>,
+}
+
+impl HttpServer {
+    /// Handle to stop the server.
+    pub fn close_handle(&self) -> CloseHandle {
+        self.close.clone()
+    }
+
+    /// Wait until the server is stopped.
+    pub fn wait(self) {
+        if self.thread.join().is_err() {
+            error!("the rpc http server panicked");
+        }
+    }
+}
+
+struct Settings {
+    cors: Option<Vec<String>>,
+    trusted_proxies: Vec<IpAddr>,
+    max_request_body_size: usize,
+}
+
+/// Serve `io` on `addr` with `threads` workers.
+pub fn start<M>(
+    addr: &SocketAddr,
+    threads: usize,
+    cors: Option<&Vec<String>>,
+    trusted_proxies: &[IpAddr],
+    io: RpcHandler<M>,
+    max_request_body_size: usize,
+) -> io::Result<HttpServer>
+where
+    M: pubsub::PubSubMetadata + ClientMetadata + Default + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind(addr)?;
+    listener.set_nonblocking(true)?;
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(threads.max(1))
+        .thread_name("rpc-http-worker")
+        .enable_all()
+        .build()?;
+    let server = {
+        let _guard = runtime.enter();
+        Server::from_tcp(listener)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
+    };
+
+    let io = Arc::new(io);
+    let settings = Arc::new(Settings {
+        cors: cors.cloned(),
+        trusted_proxies: trusted_proxies.to_vec(),
+        max_request_body_size,
+    });
+    let make_service = make_service_fn(move |conn: &AddrStream| {
+        let peer = conn.remote_addr();
+        let io = io.clone();
+        let settings = settings.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| {
+                handle(io.clone(), settings.clone(), peer, req)
+            }))
+        }
+    });
+
+    let (tx, rx) = oneshot::channel::<()>();
+    let thread =
+        thread::Builder::new()
+            .name("rpc-http".to_owned())
+            .spawn(move || {
+                runtime.block_on(async move {
+                    let shutdown = async {
+                        let _ = rx.await;
+                    };
+                    if let Err(e) = server
+                        .serve(make_service)
+                        .with_graceful_shutdown(shutdown)
+                        .await
+                    {
+                        error!("rpc http server error: {}", e);
+                    }
+                })
+            })?;
+
+    Ok(HttpServer {
+        close: CloseHandle(Arc::new(Mutex::new(Some(tx)))),
+        thread,
+    })
+}
+
+async fn handle<M>(
+    io: Arc<RpcHandler<M>>,
+    settings: Arc<Settings>,
+    peer: SocketAddr,
+    req: Request<Body>,
+) -> Result<Response<Body>, Infallible>
+where
+    M: pubsub::PubSubMetadata + ClientMetadata + Default + Send + Sync + 'static,
+{
+    let allow_origin = allowed_origin(settings.cors.as_deref(), req.headers());
+    let meta = M::default().with_client(client_of(
+        peer,
+        header_str(req.headers(), "x-forwarded-for"),
+        header_str(req.headers(), "x-real-ip"),
+        &settings.trusted_proxies,
+    ));
+
+    let mut resp = match (req.method(), req.uri().path()) {
+        (&Method::OPTIONS, _) => Response::new(Body::empty()),
+        (&Method::GET, "/health") => match io.handle_request(HEALTH_REQUEST, meta).await
+        {
+            Some(out) => json_response(out),
+            None => status_response(StatusCode::SERVICE_UNAVAILABLE),
+        },
+        (&Method::POST, _) => {
+            match read_body(req.into_body(), settings.max_request_body_size).await {
+                Ok(body) => match io.handle_request(&body, meta).await {
+                    Some(out) => json_response(out),
+                    None => Response::new(Body::empty()),
+                },
+                Err(status) => status_response(status),
+            }
+        }
+        _ => status_response(StatusCode::METHOD_NOT_ALLOWED),
+    };
+
+    if let Some(origin) = allow_origin {
+        let headers = resp.headers_mut();
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+        headers.insert(
+            header::ACCESS_CONTROL_ALLOW_METHODS,
+            HeaderValue::from_static("OPTIONS, POST"),
+        );
+        headers.insert(
+            header::ACCESS_CONTROL_ALLOW_HEADERS,
+            HeaderValue::from_static("Origin, Content-Type, Accept, User-Agent"),
+        );
+    }
+
+    Ok(resp)
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+fn allowed_origin(cors: Option<&[String]>, headers: &HeaderMap) -> Option<HeaderValue> {
+    let cors = cors?;
+    if cors.iter().any(|c| c == "*") {
+        return Some(HeaderValue::from_static("*"));
+    }
+    let origin = headers.get(header::ORIGIN)?;
+    let origin_str = origin.to_str().ok()?;
+    cors.iter().any(|c| c == origin_str).then(|| origin.clone())
+}
+
+async fn read_body(mut body: Body, max_size: usize) -> Result<String, StatusCode> {
+    let mut buf = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|_| StatusCode::BAD_REQUEST)?;
+        if buf.len() + chunk.len() > max_size {
+            return Err(StatusCode::PAYLOAD_TOO_LARGE);
+        }
+        buf.extend_from_slice(&chunk);
+    }
+    String::from_utf8(buf).map_err(|_| StatusCode::BAD_REQUEST)
+}
+
+fn json_response(out: String) -> Response<Body> {
+    let mut resp = Response::new(Body::from(out));
+    resp.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json; charset=utf-8"),
+    );
+    resp
+}
+
+fn status_response(status: StatusCode) -> Response<Body> {
+    let mut resp = Response::new(Body::empty());
+    *resp.status_mut() = status;
+    resp
+}
@@ -20,8 +20,11 @@
 
 #![warn(missing_docs)]
 
+mod http_server;
 mod middleware;
+mod policy;
 
+use fp_rpc_core::types::pubsub::ClientMetadata;
 use jsonrpc_core::{IoHandlerExtension, MetaIoHandler};
 use pubsub::PubSubMetadata;
 use std::io;
@@ -42,10 +45,11 @@ const HTTP_THREADS: usize = 4;
 pub type RpcHandler<T> = pubsub::PubSubHandler<T, RpcMiddleware>;
 
 pub use self::inner::*;
-pub use middleware::RpcMiddleware;
+pub use middleware::{RpcMetrics, RpcMiddleware};
+pub use policy::{client_of, RpcLimits, RpcPolicy};
 
 /// Construct rpc `IoHandler`
-pub fn rpc_handler<M: PubSubMetadata>(
+pub fn rpc_handler<M: PubSubMetadata + ClientMetadata>(
     extension: impl IoHandlerExtension<M>,
     rpc_middleware: RpcMiddleware,
 ) -> RpcHandler<M> {
@@ -80,42 +84,50 @@ mod inner {
     /// Type alias for ipc server
     pub type IpcServer = ipc::Server;
     /// Type alias for http server
-    pub type HttpServer = http::Server;
+    pub type HttpServer = http_server::HttpServer;
     /// Type alias for ws server
     pub type WsServer = ws::Server;
 
     /// Start HTTP server listening on given address.
     ///
+    /// The forwarding headers of a request are only trusted if it comes from one of
+    /// `trusted_proxies`, otherwise the client is the address of the remote peer.
+    ///
     /// **Note**: Only available if `not(target_os = "unknown")`.
-    pub fn start_http<M: pubsub::PubSubMetadata + Default + Unpin>(
+    pub fn start_http<
+        M: pubsub::PubSubMetadata
+            + ClientMetadata
+            + Default
+            + Unpin
+            + Send
+            + Sync
+            + 'static,
+    >(
         addr: &std::net::SocketAddr,
         thread_pool_size: Option<usize>,
         cors: Option<&Vec<String>>,
+        trusted_proxies: &[std::net::IpAddr],
         io: RpcHandler<M>,
         maybe_max_payload_mb: Option<usize>,
-    ) -> io::Result<http::Server> {
+    ) -> io::Result<HttpServer> {
         let max_request_body_size = maybe_max_payload_mb
             .map(|mb| mb.saturating_mul(MEGABYTE))
             .unwrap_or(RPC_MAX_PAYLOAD_DEFAULT);
 
-        http::ServerBuilder::new(io)
-            .threads(thread_pool_size.unwrap_or(HTTP_THREADS))
-            .health_api(("/health", "system_health"))
-            // .allowed_hosts(hosts_filtering(cors.is_some()))
-            .rest_api(if cors.is_some() {
-                http::RestApi::Secure
-            } else {
-                http::RestApi::Unsecure
-            })
-            .cors(map_cors::<http::AccessControlAllowOrigin>(cors))
-            .max_request_body_size(max_request_body_size)
-            .start_http(addr)
+        http_server::start(
+            addr,
+            thread_pool_size.unwrap_or(HTTP_THREADS),
+            cors,
+            trusted_proxies,
+            io,
+            max_request_body_size,
+        )
     }
 
     /// Start IPC server listening on given path.
     ///
     /// **Note**: Only available if `not(target_os = "unknown")`.
-    pub fn start_ipc<M: pubsub::PubSubMetadata + Default>(
+    pub fn start_ipc<M: pubsub::PubSubMetadata + ClientMetadata + Default>(
         addr: &str,
         io: RpcHandler<M>,
     ) -> io::Result<ipc::Server> {
@@ -133,7 +145,9 @@ mod inner {
     ///
     /// **Note**: Only available if `not(target_os = "unknown")`.
     pub fn start_ws<
-        M: pubsub::PubSubMetadata + From<futures::channel::mpsc::UnboundedSender<String>>,
+        M: pubsub::PubSubMetadata
+            + ClientMetadata
+            + From<futures::channel::mpsc::UnboundedSender<String>>,
     >(
         addr: &std::net::SocketAddr,
         max_connections: Option<usize>,
@@ -145,7 +159,8 @@ mod inner {
             .map(|mb| mb.saturating_mul(MEGABYTE))
             .unwrap_or(RPC_MAX_PAYLOAD_DEFAULT);
         ws::ServerBuilder::with_meta_extractor(io, |context: &ws::RequestContext| {
-            context.sender().into()
+            // the remote address is not exposed, so every connection is a client
+            M::from(context.sender()).with_client(format!("ws:{}", context.session_id))
         })
         .max_payload(rpc_max_payload)
         .max_connections(max_connections.unwrap_or(WS_MAX_CONNECTIONS))
@@ -18,32 +18,113 @@
 
 //! Middleware for RPC requests.
 
-use futures::{future::Either, Future};
+use crate::policy::RpcPolicy;
+use fp_rpc_core::types::pubsub::ClientMetadata;
+use futures::{
+    future::{self, Either},
+    Future, FutureExt,
+};
 use jsonrpc_core::{
-    FutureOutput, FutureResponse, Metadata, Middleware as RequestMiddleware, Request,
-    Response,
+    Call, Error, ErrorCode, FutureOutput, FutureResponse, Id, Metadata,
+    Middleware as RequestMiddleware, Output, Request, Response, Version,
+};
+use prometheus::{
+    exponential_buckets, Error as PrometheusError, HistogramOpts, HistogramVec,
+    IntCounterVec, Opts, Registry,
 };
+use std::{sync::Arc, time::Instant};
+
+/// Label used for calls to methods that are not registered.
+const UNKNOWN_METHOD: &str = "unknown";
+
+/// EIP-1474 error code of a method that is not available.
+const METHOD_NOT_SUPPORTED: i64 = -32004;
+
+/// EIP-1474 error code of a request that exceeds a defined limit.
+const LIMIT_EXCEEDED: i64 = -32005;
+
+/// Metrics for RPC middleware
+#[derive(Debug, Clone)]
+pub struct RpcMetrics {
+    requests_started: IntCounterVec,
+    calls_time: HistogramVec,
+    calls_finished: IntCounterVec,
+}
+
+impl RpcMetrics {
+    /// Create an instance of metrics, registered in `metrics_registry`.
+    ///
+    /// Returns `None` if no registry is given.
+    pub fn new(
+        metrics_registry: Option<&Registry>,
+    ) -> Result<Option<Self>, PrometheusError> {
+        let registry = match metrics_registry {
+            Some(r) => r,
+            None => return Ok(None),
+        };
+
+        let requests_started = IntCounterVec::new(
+            Opts::new(
+                "rpc_requests_started",
+                "Number of RPC requests (not calls) received by the server.",
+            ),
+            &["protocol"],
+        )?;
+        let calls_time = HistogramVec::new(
+            HistogramOpts::new(
+                "rpc_calls_time",
+                "Total time [s] of processed RPC calls",
+            )
+            .buckets(exponential_buckets(0.001, 4.0, 9)?),
+            &["protocol", "method"],
+        )?;
+        let calls_finished = IntCounterVec::new(
+            Opts::new(
+                "rpc_calls_finished",
+                "Number of processed RPC calls (unique un-batched requests)",
+            ),
+            &["protocol", "method", "is_error"],
+        )?;
+
+        registry.register(Box::new(requests_started.clone()))?;
+        registry.register(Box::new(calls_time.clone()))?;
+        registry.register(Box::new(calls_finished.clone()))?;
+
+        Ok(Some(RpcMetrics {
+            requests_started,
+            calls_time,
+            calls_finished,
+        }))
+    }
+}
 
 /// Middleware for RPC calls
-pub struct RpcMiddleware;
+pub struct RpcMiddleware {
+    metrics: Option<RpcMetrics>,
+    policy: Option<Arc<RpcPolicy>>,
+    transport_label: String,
+}
 
 impl RpcMiddleware {
     /// Create an instance of middleware.
     ///
     /// - `metrics`: Will be used to report statistics.
+    /// - `policy`: Will be used to reject calls, shared by all transports.
     /// - `transport_label`: The label that is used when reporting the statistics.
-    pub fn new() -> Self {
-        RpcMiddleware {}
+    pub fn new(
+        metrics: Option<RpcMetrics>,
+        policy: Option<Arc<RpcPolicy>>,
+        transport_label: &str,
+    ) -> Self {
+        RpcMiddleware {
+            metrics,
+            policy,
+            transport_label: String::from(transport_label),
+        }
     }
 }
 
-impl Default for RpcMiddleware {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-impl<M: Metadata> RequestMiddleware<M> for RpcMiddleware {
+impl<M: Metadata + ClientMetadata> RequestMiddleware<M> for RpcMiddleware {
     type Future = FutureResponse;
     type CallFuture = FutureOutput;
 
@@ -57,6 +138,125 @@ impl<M: Metadata> RequestMiddleware<M> for RpcMiddleware {
         F: Fn(Request, M) -> X + Send + Sync,
         X: Future<Output = Option<Response>> + Send + 'static,
     {
+        if let Some(ref metrics) = self.metrics {
+            metrics
+                .requests_started
+                .with_label_values(&[self.transport_label.as_str()])
+                .inc();
+        }
+
+        if let Request::Batch(ref calls) = request {
+            if let Some(max) = self.policy.as_ref().and_then(|p| p.max_batch_size()) {
+                if calls.len() > max {
+                    let output = Output::from(
+                        Err(Error {
+                            code: ErrorCode::InvalidRequest,
+                            message: format!("Batch size exceeds limit: {max}"),
+                            data: None,
+                        }),
+                        Id::Null,
+                        Some(Version::V2),
+                    );
+                    return Either::Left(Box::pin(future::ready(Some(
+                        Response::Single(output),
+                    ))));
+                }
+            }
+        }
+
         Either::Right(next(request, meta))
     }
+
+    fn on_call<F, X>(&self, call: Call, meta: M, next: F) -> Either<FutureOutput, X>
+    where
+        F: Fn(Call, M) -> X + Send + Sync,
+        X: Future<Output = Option<Output>> + Send + 'static,
+    {
+        if self.metrics.is_none() && self.policy.is_none() {
+            return Either::Right(next(call, meta));
+        }
+
+        let (method, id, jsonrpc) = match call {
+            Call::MethodCall(ref c) => (c.method.clone(), Some(c.id.clone()), c.jsonrpc),
+            Call::Notification(ref n) => (n.method.clone(), None, n.jsonrpc),
+            Call::Invalid { .. } => return Either::Right(next(call, meta)),
+        };
+
+        if let Some(ref policy) = self.policy {
+            let rejection = if !policy.is_allowed(&method) {
+                Some(Error {
+                    code: ErrorCode::ServerError(METHOD_NOT_SUPPORTED),
+                    message: format!("Method not supported: {method}"),
+                    data: None,
+                })
+            } else if !policy.try_acquire(meta.client(), &method) {
+                Some(Error {
+                    code: ErrorCode::ServerError(LIMIT_EXCEEDED),
+                    message: "Rate limit exceeded".to_owned(),
+                    data: None,
+                })
+            } else {
+                None
+            };
+
+            if let Some(error) = rejection {
+                // notifications never get a response
+                let output = id.map(|id| Output::from(Err(error), id, jsonrpc));
+                return Either::Left(Box::pin(future::ready(output)));
+            }
+        }
+
+        let metrics = self.metrics.clone();
+        let max_response_size = self.policy.as_ref().and_then(|p| p.max_response_size());
+        let transport_label = self.transport_label.clone();
+        let start = Instant::now();
+
+        Either::Left(Box::pin(next(call, meta).map(move |output| {
+            if let Some(metrics) = metrics {
+                // Avoid unbounded label values caused by calls to nonexistent methods.
+                let method = match output {
+                    Some(Output::Failure(ref f))
+                        if f.error.code == ErrorCode::MethodNotFound =>
+                    {
+                        UNKNOWN_METHOD
+                    }
+                    _ => method.as_str(),
+                };
+                let is_error = matches!(output, Some(Output::Failure(_)));
+
+                metrics
+                    .calls_time
+                    .with_label_values(&[transport_label.as_str(), method])
+                    .observe(start.elapsed().as_secs_f64());
+                metrics
+                    .calls_finished
+                    .with_label_values(&[
+                        transport_label.as_str(),
+                        method,
+                        if is_error { "true" } else { "false" },
+                    ])
+                    .inc();
+            }
+
+            match (output, max_response_size) {
+                (Some(output), Some(max)) => {
+                    let size = serde_json::to_vec(&output).map_or(0, |v| v.len());
+                    if size > max {
+                        Some(Output::from(
+                            Err(Error {
+                                code: ErrorCode::ServerError(LIMIT_EXCEEDED),
+                                message: format!("Response size exceeds limit: {max}"),
+                                data: None,
+                            }),
+                            output.id().clone(),
+                            output.version(),
+                        ))
+                    } else {
+                        Some(output)
+                    }
+                }
+                (output, _) => output,
+            }
+        })))
+    }
 }
@@ -0,0 +1,241 @@
+//! Access policy of RPC calls: method allow/deny lists and per-client rate limits.
+
+use lru::LruCache;
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    time::Instant,
+};
+
+/// Upper bound of tracked clients, the least recently seen ones are evicted beyond it.
+const MAX_TRACKED_CLIENTS: usize = 65536;
+
+/// Limits applied to RPC calls, everything is disabled by default.
+#[derive(Debug, Clone, Default)]
+pub struct RpcLimits {
+    /// Tokens refilled per second into the bucket of each client, `0` disables rate limiting.
+    pub rate_limit_per_sec: u64,
+    /// Capacity of the bucket of each client.
+    pub rate_limit_burst: u64,
+    /// Tokens consumed by a call of a method, unlisted methods cost `1`.
+    pub method_costs: HashMap<String, u64>,
+    /// If not empty, only these methods can be called.
+    pub allowed_methods: Vec<String>,
+    /// Methods that can never be called.
+    pub denied_methods: Vec<String>,
+    /// Maximum number of calls in a batch request, `0` means unlimited.
+    pub max_batch_size: usize,
+    /// Maximum size in bytes of a serialized call output, `0` means unlimited.
+    pub max_response_size: usize,
+}
+
+struct Bucket {
+    tokens: f64,
+    last: Instant,
+}
+
+/// Enforces [`RpcLimits`], shared by all transports.
+pub struct RpcPolicy {
+    limits: RpcLimits,
+    buckets: Mutex<LruCache<String, Bucket>>,
+}
+
+impl RpcPolicy {
+    /// Create a policy from the given limits.
+    pub fn new(limits: RpcLimits) -> Self {
+        RpcPolicy {
+            limits,
+            buckets: Mutex::new(LruCache::new(MAX_TRACKED_CLIENTS)),
+        }
+    }
+
+    /// Maximum number of calls in a batch request, if limited.
+    pub fn max_batch_size(&self) -> Option<usize> {
+        Some(self.limits.max_batch_size).filter(|n| *n > 0)
+    }
+
+    /// Maximum size of a serialized call output, if limited.
+    pub fn max_response_size(&self) -> Option<usize> {
+        Some(self.limits.max_response_size).filter(|n| *n > 0)
+    }
+
+    /// Whether `method` can be called at all.
+    ///
+    /// A pattern ending with `*` matches every method with that prefix, e.g. `debug_*`.
+    pub fn is_allowed(&self, method: &str) -> bool {
+        let matches = |pattern: &String| match pattern.strip_suffix('*') {
+            Some(prefix) => method.starts_with(prefix),
+            None => method == pattern,
+        };
+
+        (self.limits.allowed_methods.is_empty()
+            || self.limits.allowed_methods.iter().any(matches))
+            && !self.limits.denied_methods.iter().any(matches)
+    }
+
+    /// Take the cost of `method` from the bucket of `client`,
+    /// returns `false` if the client has run out of tokens.
+    ///
+    /// Calls from clients without an identity share one bucket.
+    pub fn try_acquire(&self, client: Option<&str>, method: &str) -> bool {
+        if 0 == self.limits.rate_limit_per_sec {
+            return true;
+        }
+
+        let rate = self.limits.rate_limit_per_sec as f64;
+        let burst = self.limits.rate_limit_burst.max(1) as f64;
+        let cost = self.limits.method_costs.get(method).copied().unwrap_or(1) as f64;
+        let now = Instant::now();
+
+        let client = client.unwrap_or_default();
+        let mut buckets = self.buckets.lock();
+        if !buckets.contains(client) {
+            // the least recently seen client is evicted once the cache is full
+            buckets.put(
+                client.to_owned(),
+                Bucket {
+                    tokens: burst,
+                    last: now,
+                },
+            );
+        }
+        let bucket = match buckets.get_mut(client) {
+            Some(b) => b,
+            None => return false,
+        };
+        bucket.tokens = (bucket.tokens
+            + now.duration_since(bucket.last).as_secs_f64() * rate)
+            .min(burst);
+        bucket.last = now;
+
+        if bucket.tokens < cost {
+            return false;
+        }
+        bucket.tokens -= cost;
+        true
+    }
+}
+
+/// Identity of the client of a request received from `peer`.
+///
+/// The forwarding headers are only trusted if `peer` is one of `trusted_proxies`,
+/// the client is then the last address of `X-Forwarded-For` that is not a trusted proxy,
+/// or `X-Real-IP` if there is no such header.
+pub fn client_of(
+    peer: SocketAddr,
+    forwarded_for: Option<&str>,
+    real_ip: Option<&str>,
+    trusted_proxies: &[IpAddr],
+) -> String {
+    let peer = peer.ip();
+    if !trusted_proxies.contains(&peer) {
+        return peer.to_string();
+    }
+
+    let forwarded = forwarded_for.map(|v| {
+        v.rsplit(',')
+            .map(|a| a.trim().parse::<IpAddr>().ok())
+            .find(|a| a.map_or(true, |a| !trusted_proxies.contains(&a)))
+            .flatten()
+    });
+    let client = match forwarded {
+        Some(client) => client,
+        None => real_ip.and_then(|v| v.trim().parse::<IpAddr>().ok()),
+    };
+    client.unwrap_or(peer).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn method_lists() {
+        let policy = RpcPolicy::new(RpcLimits {
+            denied_methods: vec!["debug_*".to_owned(), "eth_sign".to_owned()],
+            ..Default::default()
+        });
+        assert!(policy.is_allowed("eth_call"));
+        assert!(policy.is_allowed("eth_signTransaction"));
+        assert!(!policy.is_allowed("eth_sign"));
+        assert!(!policy.is_allowed("debug_traceTransaction"));
+
+        let policy = RpcPolicy::new(RpcLimits {
+            allowed_methods: vec!["eth_*".to_owned()],
+            denied_methods: vec!["eth_getLogs".to_owned()],
+            ..Default::default()
+        });
+        assert!(policy.is_allowed("eth_call"));
+        assert!(!policy.is_allowed("eth_getLogs"));
+        assert!(!policy.is_allowed("net_version"));
+    }
+
+    #[test]
+    fn token_bucket() {
+        let policy = RpcPolicy::new(RpcLimits {
+            rate_limit_per_sec: 1,
+            rate_limit_burst: 10,
+            method_costs: [("eth_getLogs".to_owned(), 4)].into_iter().collect(),
+            ..Default::default()
+        });
+
+        assert!(policy.try_acquire(Some("1.1.1.1"), "eth_getLogs"));
+        assert!(policy.try_acquire(Some("1.1.1.1"), "eth_getLogs"));
+        assert!(!policy.try_acquire(Some("1.1.1.1"), "eth_getLogs"));
+        assert!(policy.try_acquire(Some("1.1.1.1"), "eth_call"));
+
+        // buckets are per client
+        assert!(policy.try_acquire(Some("2.2.2.2"), "eth_getLogs"));
+        assert!(policy.try_acquire(None, "eth_getLogs"));
+    }
+
+    #[test]
+    fn bounded_buckets() {
+        let policy = RpcPolicy::new(RpcLimits {
+            rate_limit_per_sec: 1,
+            rate_limit_burst: 1,
+            ..Default::default()
+        });
+
+        assert!(policy.try_acquire(Some("0"), "eth_call"));
+        for i in 1..=MAX_TRACKED_CLIENTS {
+            assert!(policy.try_acquire(Some(&i.to_string()), "eth_call"));
+        }
+        assert_eq!(policy.buckets.lock().len(), MAX_TRACKED_CLIENTS);
+
+        // the empty bucket of the least recently seen client has been evicted
+        assert!(policy.try_acquire(Some("0"), "eth_call"));
+        // while the others are kept
+        assert!(!policy.try_acquire(Some(&MAX_TRACKED_CLIENTS.to_string()), "eth_call"));
+    }
+
+    #[test]
+    fn client_identity() {
+        let proxy: IpAddr = "10.0.0.1".parse().unwrap();
+        let direct: SocketAddr = "1.1.1.1:5000".parse().unwrap();
+        let proxied: SocketAddr = "10.0.0.1:5000".parse().unwrap();
+
+        // headers of direct clients are ignored
+        assert_eq!(
+            client_of(direct, Some("2.2.2.2"), None, &[proxy]),
+            "1.1.1.1"
+        );
+        assert_eq!(client_of(direct, None, Some("2.2.2.2"), &[]), "1.1.1.1");
+
+        // the last untrusted hop of a trusted proxy
+        assert_eq!(
+            client_of(proxied, Some("3.3.3.3, 2.2.2.2, 10.0.0.1"), None, &[proxy]),
+            "2.2.2.2"
+        );
+        assert_eq!(
+            client_of(proxied, None, Some("2.2.2.2"), &[proxy]),
+            "2.2.2.2"
+        );
+        assert_eq!(
+            client_of(proxied, Some("bogus"), None, &[proxy]),
+            "10.0.0.1"
+        );
+        assert_eq!(client_of(proxied, None, None, &[proxy]), "10.0.0.1");
+    }
+}
@@ -14,6 +14,7 @@ pub use serde::{de::DeserializeOwned, Serialize};
 pub use std::borrow::{Borrow, BorrowMut};
 pub use std::ops::{Deref, DerefMut};
 pub use std::sync::Arc;
+pub use storage::db::IterOrder;
 pub use storage::store::traits::StatelessStore;
 
 const DB_SEPARATOR: &str = "_";
@@ -3,7 +3,7 @@ use crate::*;
 use config::abci::global_cfg::CFG;
 use ruc::*;
 use std::str::FromStr;
-use storage::db::MerkleDB;
+use storage::db::{IterOrder, MerkleDB};
 use storage::state::State;
 use storage::store::Prefix;
 
@@ -60,6 +60,17 @@ where
         Key2::from_str(last_key).map_err(|_| eg!("key convert to string err"))
     }
 
+    pub fn parse_keys_for(key_list: Vec<&str>) -> Result<(Key1, Key2)> {
+        let mut keys = key_list.iter().rev();
+        let (k2, k1) = keys
+            .next()
+            .zip(keys.next())
+            .ok_or(eg!("parse key failed with less than two keys"))?;
+        let k1 = Key1::from_str(k1).map_err(|_| eg!("key convert to string err"))?;
+        let k2 = Key2::from_str(k2).map_err(|_| eg!("key convert to string err"))?;
+        Ok((k1, k2))
+    }
+
     /// Does the value (explicitly) exist in storage?
     pub fn contains_key<D: MerkleDB>(state: &State<D>, k1: &Key1, k2: &Key2) -> bool {
         Instance::exists(state, Self::build_key_for(k1, k2).as_slice()).unwrap()
@@ -113,6 +124,59 @@ where
     }
 
     /// Iter over all value of the storage.
+    pub fn iterate<D: MerkleDB>(state: &State<D>) -> Vec<(Key1, Key2, Value)> {
+        let prefix_key: Vec<u8> =
+            [Self::module_prefix(), Self::storage_prefix()].concat();
+        let prefix = Prefix::new(prefix_key.as_ref());
+
+        let kv_map = Instance::iter_cur(state, prefix);
+
+        let mut res = Vec::new();
+        for (k, v) in kv_map {
+            let key_str = String::from_utf8_lossy(k.as_slice()).to_string();
+            let key_list: Vec<_> = key_str.split(DB_SEPARATOR).collect();
+
+            let keys = Self::parse_keys_for(key_list);
+            let raw_value = serde_json::from_slice::<Value>(v.as_slice()).ok();
+
+            if let (Ok((k1, k2)), Some(v)) = (keys, raw_value) {
+                res.push((k1, k2, v))
+            }
+        }
+        res
+    }
+
+    /// Iter over the committed values under the first key whose second keys are
+    /// within `[lower, upper)`, which must serialize in the order of the second keys.
+    pub fn iterate_range<D: MerkleDB>(
+        state: &State<D>,
+        k1: &Key1,
+        lower: &Key2,
+        upper: &Key2,
+        order: IterOrder,
+    ) -> Vec<(Key2, Value)> {
+        let mut res = Vec::new();
+        state.iterate(
+            &Self::build_key_for(k1, lower),
+            &Self::build_key_for(k1, upper),
+            order,
+            &mut |(k, v)| {
+                let key_str = String::from_utf8_lossy(k.as_slice()).to_string();
+                let key_list: Vec<_> = key_str.split(DB_SEPARATOR).collect();
+
+                let key = Self::parse_key_for(key_list);
+                let raw_value = serde_json::from_slice::<Value>(v.as_slice()).ok();
+
+                if let (Ok(k), Some(v)) = (key, raw_value) {
+                    res.push((k, v))
+                }
+                false
+            },
+        );
+        res
+    }
+
+    /// Iter over all value under the first key.
     pub fn iterate_prefix<D: MerkleDB>(
         state: &State<D>,
         k1: &Key1,
@@ -170,4 +170,20 @@ where
         }
         res
     }
+
+    /// Iter over all serialized value of the storage, the counterpart of `insert_bytes`.
+    pub fn iterate_bytes<D: MerkleDB>(state: &State<D>) -> Vec<(Key, Vec<u8>)> {
+        let prefix_key: Vec<u8> =
+            [Self::module_prefix(), Self::storage_prefix()].concat();
+        let prefix = Prefix::new(prefix_key.as_ref());
+
+        Instance::iter_cur(state, prefix)
+            .into_iter()
+            .filter_map(|(k, v)| {
+                let key_str = String::from_utf8_lossy(k.as_slice()).to_string();
+                let key_list: Vec<_> = key_str.split(DB_SEPARATOR).collect();
+                Self::parse_key_for(key_list).ok().map(|k| (k, v))
+            })
+            .collect()
+    }
 }
@@ -32,4 +32,18 @@ pub trait BaseProvider {
         index: H256,
         height: Option<u64>,
     ) -> Option<H256>;
+
+    fn base_fee_per_gas(&self) -> U256;
+
+    fn block_base_fee_per_gas(&self, block_number: U256) -> Option<U256>;
+
+    fn log_index_from(&self) -> Option<u64>;
+
+    fn filter_log_blocks(
+        &self,
+        addresses: Option<&[H160]>,
+        topics: &[Option<Vec<H256>>],
+        from: u64,
+        to: u64,
+    ) -> Vec<u64>;
 }
@@ -52,6 +52,12 @@ pub trait FeeCalculator {
     /// Return the minimal required gas price.
     fn min_gas_price() -> U256;
     fn max_gas_price() -> U256;
+
+    /// Return the base fee per gas of the current block,
+    /// which is the minimal required gas price if it is not adjusted.
+    fn base_fee_per_gas(_ctx: &Context) -> U256 {
+        Self::min_gas_price()
+    }
 }
 
 impl FeeCalculator for () {
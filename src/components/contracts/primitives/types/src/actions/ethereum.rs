@@ -1,7 +1,80 @@
 use ethereum::TransactionV0 as Transaction;
+use primitive_types::{H160, U256};
 use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::collections::BTreeSet;
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Action {
     Transact(Transaction),
+    /// A transaction whose fee is paid by a fee payer.
+    SponsoredTransact(SponsoredTransaction),
+    /// Set the policy of a fee payer on the transactions it sponsors.
+    SetSponsorPolicy(SignedSponsorPolicy),
+}
+
+/// An EVM transaction along with the signature of the account paying its fee.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SponsoredTransaction {
+    pub transaction: Transaction,
+    /// The EVM chain id, to prevent replay on other chains
+    /// even if the transaction doesn't carry it.
+    pub chain_id: u64,
+    /// Signature of the fee payer over `fee_payer_message`,
+    /// 65 bytes of `r`, `s` and `v`.
+    pub fee_payer_signature: Vec<u8>,
+}
+
+impl SponsoredTransaction {
+    /// The message signed by the fee payer, bound to the chain id and the hash
+    /// of the signed transaction so that it can't be attached to another one.
+    pub fn fee_payer_message(&self) -> [u8; 32] {
+        let mut data = b"FindoraSponsoredTransaction".to_vec();
+        data.extend_from_slice(&self.chain_id.to_be_bytes());
+        data.extend_from_slice(self.transaction.hash().as_bytes());
+        keccak256(&data)
+    }
+}
+
+/// What a fee payer agrees to pay for.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SponsorPolicy {
+    /// Senders that can be sponsored, anyone if empty.
+    #[serde(default)]
+    pub allowed_senders: BTreeSet<H160>,
+    /// The total fee paid under the policy, unlimited if `None`.
+    #[serde(default)]
+    pub spending_limit: Option<U256>,
+}
+
+/// A sponsor policy signed by its fee payer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedSponsorPolicy {
+    pub policy: SponsorPolicy,
+    /// The EVM chain id, to prevent replay on other chains.
+    pub chain_id: u64,
+    /// The nonce of the fee payer, to prevent replay.
+    pub nonce: U256,
+    /// Signature of the fee payer over `message`, 65 bytes of `r`, `s` and `v`.
+    pub signature: Vec<u8>,
+}
+
+impl SignedSponsorPolicy {
+    /// The message signed by the fee payer.
+    pub fn message(&self) -> [u8; 32] {
+        let mut nonce = [0u8; 32];
+        self.nonce.to_big_endian(&mut nonce);
+
+        let mut data = b"FindoraSponsorPolicy".to_vec();
+        data.extend_from_slice(&self.chain_id.to_be_bytes());
+        data.extend_from_slice(&nonce);
+        data.extend(serde_json::to_vec(&self.policy).unwrap_or_default());
+        keccak256(&data)
+    }
+}
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(Keccak256::digest(data).as_slice());
+    hash
 }
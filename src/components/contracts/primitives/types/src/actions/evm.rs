@@ -17,6 +17,9 @@ pub struct Call {
     pub gas_limit: u64,
     pub gas_price: Option<U256>,
     pub nonce: Option<U256>,
+    /// The account paying the fee instead of `source`.
+    #[serde(default)]
+    pub fee_payer: Option<H160>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -27,6 +30,9 @@ pub struct Create {
     pub gas_limit: u64,
     pub gas_price: Option<U256>,
     pub nonce: Option<U256>,
+    /// The account paying the fee instead of `source`.
+    #[serde(default)]
+    pub fee_payer: Option<H160>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -38,4 +44,7 @@ pub struct Create2 {
     pub gas_limit: u64,
     pub gas_price: Option<U256>,
     pub nonce: Option<U256>,
+    /// The account paying the fee instead of `source`.
+    #[serde(default)]
+    pub fee_payer: Option<H160>,
 }
@@ -1,5 +1,6 @@
 pub mod ethereum;
 pub mod evm;
+pub mod module;
 pub mod template;
 pub mod xhub;
 
@@ -11,4 +12,5 @@ pub enum Action {
     Evm(evm::Action),
     XHub(xhub::Action),
     Template(template::Action),
+    Module(module::Action),
 }
@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+
+/// Action of a module registered into the module manager by name,
+/// `data` is decoded by the `ActionCodec` of that module.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Action {
+    pub module: String,
+    pub data: Vec<u8>,
+}
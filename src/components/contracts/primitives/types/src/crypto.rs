@@ -63,9 +63,7 @@ impl FromStr for Address32 {
         let v = bech32::decode(s)
             .and_then(|d| Vec::<u8>::from_base32(&d.1))
             .map_err(|_e| ())?;
-        let mut address_32 = Address32::default();
-        address_32.0.copy_from_slice(v.as_slice());
-        Ok(address_32)
+        Address32::try_from(v.as_slice())
     }
 }
 
@@ -0,0 +1,278 @@
+//!
+//! # Error codes of rejected transactions
+//!
+//! Every code is stable and unique across all codespaces,
+//! `1` and `2` keep their historical meanings.
+//!
+//! `check_tx` returns them in `ResponseCheckTx.code` and `codespace`.
+//! The `code` of `deliver_tx` is part of the consensus state and is left as is,
+//! the code of a failed one is reported in its `codespace` and a `tx_error` event.
+//!
+
+use {
+    serde::{Deserialize, Serialize},
+    std::fmt,
+};
+
+/// Codespace of the checks done by the ABCI layer itself.
+pub const CODESPACE_ABCI: &str = "abci";
+/// Codespace of `TxnEffect`/`BlockEffect` and `LedgerStatus` validation.
+pub const CODESPACE_LEDGER: &str = "ledger";
+/// Codespace of the `check_run` of staking operations.
+pub const CODESPACE_STAKING: &str = "staking";
+/// Codespace of the baseapp and EVM modules.
+pub const CODESPACE_EVM: &str = "evm";
+
+/// Type of the event reporting the code of a failed `deliver_tx`.
+pub const TX_ERROR_EVENT: &str = "tx_error";
+
+/// Error codes of rejected transactions.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub enum TxErrorCode {
+    // abci
+    Internal = 1,
+    EvmDisabled = 2,
+    TxTooLarge = 101,
+    TooManySignatures = 102,
+    InvalidInAbci = 103,
+    HistoricalTx = 104,
+    InvalidFormat = 105,
+    UnknownTx = 106,
+    UnexpectedTxKind = 107,
+
+    // ledger
+    InvalidTx = 200,
+    LedgerRejected = 201,
+    FeeTooLow = 202,
+    InvalidSignature = 203,
+    SeqIdAhead = 204,
+    SeqIdExpired = 205,
+    ReplayedTx = 206,
+    InputSpent = 207,
+    InputMismatch = 208,
+    NonTransferableAsset = 209,
+    AssetAlreadyDefined = 210,
+    IssuanceKeyMismatch = 211,
+    InvalidIssuanceSeqNum = 212,
+    AssetCapExceeded = 213,
+    BlockConflict = 214,
+    MissingCredential = 215,
+    InvalidMemo = 216,
+
+    // staking
+    StakingRejected = 300,
+
+    // evm
+    EvmTagMismatch = 400,
+    EvmCheckFailed = 401,
+    InvalidNonce = 402,
+    GasPriceOutOfRange = 403,
+    InsufficientBalance = 404,
+    EvmExecFailed = 405,
+    EvmError = 406,
+    EvmReverted = 407,
+    EvmFatal = 408,
+    EvmStateError = 409,
+    TxQueued = 410,
+    ReplacementUnderpriced = 411,
+    TxPoolFull = 412,
+    TxReplaced = 413,
+    SponsorRejected = 414,
+}
+
+impl TxErrorCode {
+    /// All codes of the registry.
+    pub const ALL: [TxErrorCode; 42] = [
+        TxErrorCode::Internal,
+        TxErrorCode::EvmDisabled,
+        TxErrorCode::TxTooLarge,
+        TxErrorCode::TooManySignatures,
+        TxErrorCode::InvalidInAbci,
+        TxErrorCode::HistoricalTx,
+        TxErrorCode::InvalidFormat,
+        TxErrorCode::UnknownTx,
+        TxErrorCode::UnexpectedTxKind,
+        TxErrorCode::InvalidTx,
+        TxErrorCode::LedgerRejected,
+        TxErrorCode::FeeTooLow,
+        TxErrorCode::InvalidSignature,
+        TxErrorCode::SeqIdAhead,
+        TxErrorCode::SeqIdExpired,
+        TxErrorCode::ReplayedTx,
+        TxErrorCode::InputSpent,
+        TxErrorCode::InputMismatch,
+        TxErrorCode::NonTransferableAsset,
+        TxErrorCode::AssetAlreadyDefined,
+        TxErrorCode::IssuanceKeyMismatch,
+        TxErrorCode::InvalidIssuanceSeqNum,
+        TxErrorCode::AssetCapExceeded,
+        TxErrorCode::BlockConflict,
+        TxErrorCode::MissingCredential,
+        TxErrorCode::InvalidMemo,
+        TxErrorCode::StakingRejected,
+        TxErrorCode::EvmTagMismatch,
+        TxErrorCode::EvmCheckFailed,
+        TxErrorCode::InvalidNonce,
+        TxErrorCode::GasPriceOutOfRange,
+        TxErrorCode::InsufficientBalance,
+        TxErrorCode::EvmExecFailed,
+        TxErrorCode::EvmError,
+        TxErrorCode::EvmReverted,
+        TxErrorCode::EvmFatal,
+        TxErrorCode::EvmStateError,
+        TxErrorCode::TxQueued,
+        TxErrorCode::ReplacementUnderpriced,
+        TxErrorCode::TxPoolFull,
+        TxErrorCode::TxReplaced,
+        TxErrorCode::SponsorRejected,
+    ];
+
+    /// The numeric code.
+    #[inline(always)]
+    pub fn code(self) -> u32 {
+        self as u32
+    }
+
+    /// Lookup a code by its numeric value.
+    pub fn from_code(code: u32) -> Option<TxErrorCode> {
+        Self::ALL.iter().copied().find(|c| c.code() == code)
+    }
+
+    /// The codespace this code belongs to.
+    pub fn codespace(self) -> &'static str {
+        match self.code() {
+            200..=299 => CODESPACE_LEDGER,
+            300..=399 => CODESPACE_STAKING,
+            400..=499 => CODESPACE_EVM,
+            _ => CODESPACE_ABCI,
+        }
+    }
+
+    /// Whether the same transfer may succeed if it is rebuilt or resubmitted later,
+    /// e.g. with a fresh nonce, a higher fee or after the balance is topped up.
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            TxErrorCode::FeeTooLow
+                | TxErrorCode::SeqIdAhead
+                | TxErrorCode::SeqIdExpired
+                | TxErrorCode::BlockConflict
+                | TxErrorCode::InvalidNonce
+                | TxErrorCode::GasPriceOutOfRange
+                | TxErrorCode::InsufficientBalance
+                | TxErrorCode::EvmStateError
+                | TxErrorCode::ReplacementUnderpriced
+                | TxErrorCode::TxPoolFull
+        )
+    }
+
+    /// A short snake_case name, suitable for metric labels.
+    pub fn name(self) -> &'static str {
+        match self {
+            TxErrorCode::Internal => "internal",
+            TxErrorCode::EvmDisabled => "evm_disabled",
+            TxErrorCode::TxTooLarge => "tx_too_large",
+            TxErrorCode::TooManySignatures => "too_many_signatures",
+            TxErrorCode::InvalidInAbci => "invalid_in_abci",
+            TxErrorCode::HistoricalTx => "historical_tx",
+            TxErrorCode::InvalidFormat => "invalid_format",
+            TxErrorCode::UnknownTx => "unknown_tx",
+            TxErrorCode::UnexpectedTxKind => "unexpected_tx_kind",
+            TxErrorCode::InvalidTx => "invalid_tx",
+            TxErrorCode::LedgerRejected => "ledger_rejected",
+            TxErrorCode::FeeTooLow => "fee_too_low",
+            TxErrorCode::InvalidSignature => "invalid_signature",
+            TxErrorCode::SeqIdAhead => "seq_id_ahead",
+            TxErrorCode::SeqIdExpired => "seq_id_expired",
+            TxErrorCode::ReplayedTx => "replayed_tx",
+            TxErrorCode::InputSpent => "input_spent",
+            TxErrorCode::InputMismatch => "input_mismatch",
+            TxErrorCode::NonTransferableAsset => "non_transferable_asset",
+            TxErrorCode::AssetAlreadyDefined => "asset_already_defined",
+            TxErrorCode::IssuanceKeyMismatch => "issuance_key_mismatch",
+            TxErrorCode::InvalidIssuanceSeqNum => "invalid_issuance_seq_num",
+            TxErrorCode::AssetCapExceeded => "asset_cap_exceeded",
+            TxErrorCode::BlockConflict => "block_conflict",
+            TxErrorCode::MissingCredential => "missing_credential",
+            TxErrorCode::InvalidMemo => "invalid_memo",
+            TxErrorCode::StakingRejected => "staking_rejected",
+            TxErrorCode::EvmTagMismatch => "evm_tag_mismatch",
+            TxErrorCode::EvmCheckFailed => "evm_check_failed",
+            TxErrorCode::InvalidNonce => "invalid_nonce",
+            TxErrorCode::GasPriceOutOfRange => "gas_price_out_of_range",
+            TxErrorCode::InsufficientBalance => "insufficient_balance",
+            TxErrorCode::EvmExecFailed => "evm_exec_failed",
+            TxErrorCode::EvmError => "evm_error",
+            TxErrorCode::EvmReverted => "evm_reverted",
+            TxErrorCode::EvmFatal => "evm_fatal",
+            TxErrorCode::EvmStateError => "evm_state_error",
+            TxErrorCode::TxQueued => "tx_queued",
+            TxErrorCode::ReplacementUnderpriced => "replacement_underpriced",
+            TxErrorCode::TxPoolFull => "tx_pool_full",
+            TxErrorCode::TxReplaced => "tx_replaced",
+            TxErrorCode::SponsorRejected => "sponsor_rejected",
+        }
+    }
+
+    /// Reject a transaction with this code.
+    pub fn error(self, msg: impl fmt::Display) -> TxError {
+        TxError {
+            code: self,
+            msg: msg.to_string(),
+        }
+    }
+
+    /// Map the `ActionResult.code` of a failed EVM action.
+    pub fn from_action_result(code: u32) -> TxErrorCode {
+        match code {
+            1 => TxErrorCode::EvmExecFailed,
+            2 => TxErrorCode::EvmError,
+            3 => TxErrorCode::EvmReverted,
+            4 => TxErrorCode::EvmFatal,
+            0xff => TxErrorCode::EvmStateError,
+            _ => TxErrorCode::EvmCheckFailed,
+        }
+    }
+}
+
+/// A rejected transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxError {
+    /// The code reported to clients.
+    pub code: TxErrorCode,
+    /// Why the transaction is rejected.
+    pub msg: String,
+}
+
+impl fmt::Display for TxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.msg)
+    }
+}
+
+impl std::error::Error for TxError {}
+
+/// Result of the validation of a transaction.
+pub type TxResult<T> = std::result::Result<T, TxError>;
+
+/// Reject a transaction with a code on any error.
+pub trait TxErrorExt<T> {
+    /// Turn the error into a [TxError] with `code`.
+    fn tx_err(self, code: TxErrorCode) -> TxResult<T>;
+}
+
+impl<T, E: fmt::Display> TxErrorExt<T> for std::result::Result<T, E> {
+    #[inline(always)]
+    fn tx_err(self, code: TxErrorCode) -> TxResult<T> {
+        self.map_err(|e| code.error(e))
+    }
+}
+
+impl<T> TxErrorExt<T> for Option<T> {
+    #[inline(always)]
+    fn tx_err(self, code: TxErrorCode) -> TxResult<T> {
+        self.ok_or_else(|| code.error(code.name()))
+    }
+}
@@ -6,4 +6,5 @@ pub use primitive_types::{H160, H256, U256};
 pub mod actions;
 pub mod assemble;
 pub mod crypto;
+pub mod error_code;
 pub mod transaction;
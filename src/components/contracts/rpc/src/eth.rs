@@ -14,17 +14,18 @@ use fp_evm::{BlockId, Runner, TransactionStatus};
 use fp_rpc_core::types::{
     Block, BlockNumber, BlockTransactions, Bytes, CallRequest, Filter, FilteredParams,
     Index, Log, Receipt, Rich, RichBlock, SyncStatus, Transaction, TransactionRequest,
-    Work,
+    VariadicValue, Work,
 };
 use fp_rpc_core::EthApi;
 use fp_traits::{
     base::BaseProvider,
-    evm::{AddressMapping, EthereumAddressMapping, FeeCalculator},
+    evm::{AddressMapping, EthereumAddressMapping},
 };
 use fp_types::{
     actions,
     actions::evm::{Call, Create},
     assemble::UncheckedTransaction,
+    error_code::TxErrorCode,
 };
 use fp_utils::{ecdsa::SecpPair, tx::EvmRawTxWrapper};
 use hex_literal::hex;
@@ -375,6 +376,7 @@ impl EthApi for EthApiImpl {
                         gas_limit: gas_limit.as_u64(),
                         gas_price,
                         nonce,
+                        fee_payer: None,
                     };
 
                     let info = <BaseApp as module_ethereum::Config>::Runner::call(
@@ -397,6 +399,7 @@ impl EthApi for EthApiImpl {
                         gas_limit: gas_limit.as_u64(),
                         gas_price,
                         nonce,
+                        fee_payer: None,
                     };
 
                     let info = <BaseApp as module_ethereum::Config>::Runner::create(
@@ -452,8 +455,16 @@ impl EthApi for EthApiImpl {
     }
 
     fn gas_price(&self) -> BoxFuture<Result<U256>> {
+        let account_base_app = self.account_base_app.clone();
+
+        let task =
+            spawn_blocking(move || Ok(account_base_app.read().base_fee_per_gas()));
+
         Box::pin(async move {
-            Ok(<BaseApp as module_evm::Config>::FeeCalculator::min_gas_price())
+            match task.await {
+                Ok(r) => r,
+                Err(e) => Err(convert_join_error_to_rpc_error(e)),
+            }
         })
     }
 
@@ -523,11 +534,15 @@ impl EthApi for EthApiImpl {
 
             match (block, statuses) {
                 (Some(block), Some(statuses)) => {
+                    let base_fee = account_base_app
+                        .read()
+                        .block_base_fee_per_gas(block.header.number);
                     let blk = rich_block_build(
                         block,
                         statuses.into_iter().map(Some).collect(),
                         Some(hash),
                         full,
+                        base_fee,
                     );
 
                     match blk {
@@ -575,12 +590,16 @@ impl EthApi for EthApiImpl {
             match (block, statuses) {
                 (Some(block), Some(statuses)) => {
                     let hash = block.header.hash();
+                    let base_fee = account_base_app
+                        .read()
+                        .block_base_fee_per_gas(block.header.number);
 
                     let blk = rich_block_build(
                         block,
                         statuses.into_iter().map(Some).collect(),
                         Some(hash),
                         full,
+                        base_fee,
                     );
 
                     match blk {
@@ -750,7 +769,10 @@ impl EthApi for EthApiImpl {
 
             match resp {
                 Ok(resp) => {
-                    if resp.code != Code::Ok {
+                    // Queued transactions are broadcast once the nonce gap is filled.
+                    if resp.code != Code::Ok
+                        && resp.code.value() != TxErrorCode::TxQueued.code()
+                    {
                         return Err(convert_error_to_rpc_error(resp));
                     }
 
@@ -887,6 +909,7 @@ impl EthApi for EthApiImpl {
                             gas_limit,
                             gas_price,
                             nonce,
+                            fee_payer: None,
                         };
 
                         let info = <BaseApp as module_ethereum::Config>::Runner::call(
@@ -911,6 +934,7 @@ impl EthApi for EthApiImpl {
                             gas_limit,
                             gas_price,
                             nonce,
+                            fee_payer: None,
                         };
 
                         let info = <BaseApp as module_ethereum::Config>::Runner::create(
@@ -1197,6 +1221,7 @@ impl EthApi for EthApiImpl {
                         status_code: Some(U64::from(receipt.state_root.to_low_u64_be())),
                         logs_bloom: receipt.logs_bloom,
                         state_root: None,
+                        fee_payer: status.fee_payer,
                     }));
                 }
                 _ => Ok(None),
@@ -1342,6 +1367,7 @@ fn rich_block_build(
     statuses: Vec<Option<TransactionStatus>>,
     hash: Option<H256>,
     full_transactions: bool,
+    base_fee_per_gas: Option<U256>,
 ) -> Result<RichBlock> {
     if block.transactions.len() > statuses.len() {
         return Err(internal_err("block transaction statuses statue error!!!"));
@@ -1408,6 +1434,7 @@ fn rich_block_build(
                 }
             },
             size: Some(U256::from(rlp::encode(&block).len() as u32)),
+            base_fee_per_gas,
         },
         extra_info: BTreeMap::new(),
     })
@@ -1465,6 +1492,7 @@ fn transaction_build(
         v: U256::from(transaction.signature.v()),
         r: U256::from(transaction.signature.r().as_bytes()),
         s: U256::from(transaction.signature.s().as_bytes()),
+        fee_payer: status.as_ref().and_then(|status| status.fee_payer),
     }
 }
 
@@ -1489,7 +1517,9 @@ fn filter_range_logs(
     from: U256,
     to: U256,
 ) -> Result<()> {
-    let mut current = to;
+    if from > to {
+        return Ok(());
+    }
 
     let topics_input = if filter.topics.is_some() {
         let filtered_params = FilteredParams::new(Some(filter.clone()));
@@ -1500,8 +1530,8 @@ fn filter_range_logs(
     let address_bloom_filter = FilteredParams::addresses_bloom_filter(&filter.address);
     let topics_bloom_filter = FilteredParams::topics_bloom_filter(&topics_input);
 
-    while current >= from {
-        let id = BlockId::Number(current);
+    for current in log_block_candidates(&app, filter, from.as_u64(), to.as_u64()) {
+        let id = BlockId::Number(current.into());
         let block = app.read().current_block(Some(id.clone()));
 
         if let Some(block) = block {
@@ -1530,15 +1560,77 @@ fn filter_range_logs(
             warn!(target: "eth_rpc", "max_past_logs reached at block {:?}", current);
             break;
         }
-        if current == U256::zero() {
-            break;
-        } else {
-            current = current.saturating_sub(U256::one());
-        }
     }
     Ok(())
 }
 
+/// The numbers of the blocks within `[from, to]` which may have logs matching `filter`,
+/// in descending order.
+///
+/// If the filter has any address or topic, the blocks covered by the log index are
+/// looked up from it, the older ones are scanned one by one.
+pub fn log_block_candidates(
+    app: &Arc<RwLock<BaseApp>>,
+    filter: &Filter,
+    from: u64,
+    to: u64,
+) -> Box<dyn Iterator<Item = u64>> {
+    let (addresses, topics) = log_index_params(filter);
+    let indexed_from = if addresses.is_some() || topics.iter().any(Option::is_some) {
+        app.read().log_index_from()
+    } else {
+        None
+    };
+
+    merge_log_blocks(indexed_from, from, to, |from, to| {
+        app.read()
+            .filter_log_blocks(addresses.as_deref(), &topics, from, to)
+    })
+}
+
+// The blocks from `indexed_from` on are looked up by `lookup` within the range,
+// the older ones are scanned one by one.
+fn merge_log_blocks(
+    indexed_from: Option<u64>,
+    from: u64,
+    to: u64,
+    lookup: impl FnOnce(u64, u64) -> Vec<u64>,
+) -> Box<dyn Iterator<Item = u64>> {
+    match indexed_from {
+        Some(n) if from <= to && n <= to => {
+            let indexed = lookup(from.max(n), to);
+            Box::new(indexed.into_iter().chain((from..n).rev()))
+        }
+        _ => Box::new((from..=to).rev()),
+    }
+}
+
+// The addresses and the topics by position of a filter in the form of the log index,
+// `None` is a wildcard.
+#[allow(clippy::type_complexity)]
+fn log_index_params(filter: &Filter) -> (Option<Vec<H160>>, Vec<Option<Vec<H256>>>) {
+    let addresses = match &filter.address {
+        Some(VariadicValue::Single(a)) => Some(vec![*a]),
+        Some(VariadicValue::Multiple(a)) if !a.is_empty() => Some(a.clone()),
+        _ => None,
+    };
+
+    let position = |t: &Option<VariadicValue<Option<H256>>>| match t {
+        Some(VariadicValue::Single(Some(t))) => Some(vec![*t]),
+        Some(VariadicValue::Multiple(t)) if !t.is_empty() => {
+            t.iter().copied().collect::<Option<Vec<_>>>()
+        }
+        _ => None,
+    };
+    let topics = match &filter.topics {
+        Some(VariadicValue::Single(t)) => vec![position(t)],
+        Some(VariadicValue::Multiple(t)) => t.iter().map(position).collect(),
+        _ => vec![],
+    };
+
+    (addresses, topics)
+}
+
 pub fn filter_block_logs<'a>(
     ret: &'a mut Vec<Log>,
     filter: &'a Filter,
@@ -1660,6 +1752,7 @@ fn dummy_block(height: u64, full: bool) -> Rich<Block> {
         uncles: vec![],
         transactions,
         size: Some(U256::from(0x1_u32)),
+        base_fee_per_gas: None,
     };
 
     Rich {
@@ -1667,3 +1760,66 @@ fn dummy_block(height: u64, full: bool) -> Rich<Block> {
         extra_info: BTreeMap::new(),
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn params(filter: &str) -> (Option<Vec<H160>>, Vec<Option<Vec<H256>>>) {
+        log_index_params(&serde_json::from_str::<Filter>(filter).unwrap())
+    }
+
+    #[test]
+    fn test_log_index_params() {
+        let (a, b) = (H160::repeat_byte(0xaa), H160::repeat_byte(0xbb));
+        let (t0, t1) = (H256::repeat_byte(1), H256::repeat_byte(2));
+
+        assert_eq!(params("{}"), (None, vec![]));
+        assert_eq!(
+            params(&format!(r#"{{"address": "{a:?}", "topics": "{t0:?}"}}"#)),
+            (Some(vec![a]), vec![Some(vec![t0])])
+        );
+        assert_eq!(
+            params(&format!(r#"{{"address": ["{a:?}", "{b:?}"]}}"#)),
+            (Some(vec![a, b]), vec![])
+        );
+
+        //Null and empty positions are wildcards, so is a null alternative
+        assert_eq!(
+            params(&format!(
+                r#"{{"topics": [null, "{t1:?}", ["{t0:?}", "{t1:?}"], [], ["{t0:?}", null]]}}"#
+            )),
+            (
+                None,
+                vec![None, Some(vec![t1]), Some(vec![t0, t1]), None, None]
+            )
+        );
+    }
+
+    #[test]
+    fn test_merge_log_blocks() {
+        let merge = |indexed_from, from, to| {
+            merge_log_blocks(indexed_from, from, to, |from, to| {
+                (from..=to).filter(|n| n % 3 == 0).rev().collect()
+            })
+            .collect::<Vec<_>>()
+        };
+        let unused =
+            |_, _| -> Vec<u64> { panic!("the index is not covering the range") };
+
+        //The indexed blocks come first, then all the blocks before the index
+        assert_eq!(merge(Some(5), 1, 10), vec![9, 6, 4, 3, 2, 1]);
+        assert_eq!(merge(Some(5), 6, 10), vec![9, 6]);
+
+        //Ranges before the index are scanned in full
+        assert_eq!(merge(None, 1, 3), vec![3, 2, 1]);
+        assert_eq!(
+            merge_log_blocks(Some(5), 1, 4, unused).collect::<Vec<_>>(),
+            vec![4, 3, 2, 1]
+        );
+
+        //Nothing in a reversed range
+        assert!(merge_log_blocks(Some(5), 10, 6, unused).next().is_none());
+        assert!(merge(None, 10, 6).is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{filter_block_logs, internal_err};
+use crate::{filter_block_logs, internal_err, log_block_candidates};
 use baseapp::BaseApp;
 use ethereum::BlockV0 as EthereumBlock;
 use ethereum_types::{H256, U256};
@@ -124,8 +124,7 @@ impl EthFilterApiImpl {
             FilteredParams::addresses_bloom_filter(&filter.address);
         let topics_bloom_filter = FilteredParams::topics_bloom_filter(&topics_input);
 
-        let mut current = to;
-        while current >= from {
+        for current in log_block_candidates(&self.account_base_app, filter, from, to) {
             let block = self
                 .block_data_cache
                 .current_block(&self.account_base_app, current.into());
@@ -163,7 +162,6 @@ impl EthFilterApiImpl {
                     max_duration.as_secs()
                 )));
             }
-            current = current.saturating_sub(1);
         }
         Ok(())
     }
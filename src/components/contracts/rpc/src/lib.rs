@@ -5,17 +5,23 @@ mod eth;
 mod eth_filter;
 mod eth_pubsub;
 mod net;
+mod txpool;
 mod utils;
 mod web3;
 
 use baseapp::BaseApp;
-use eth::filter_block_logs;
+use config::abci::global_cfg::CFG;
+use eth::{filter_block_logs, log_block_candidates};
 use evm::{ExitError, ExitReason};
 use fp_rpc_core::types::pubsub::Metadata;
 use fp_rpc_core::{
-    EthApiServer, EthFilterApiServer, EthPubSubApiServer, NetApiServer, Web3ApiServer,
+    EthApiServer, EthFilterApiServer, EthPubSubApiServer, NetApiServer, TxPoolApiServer,
+    Web3ApiServer,
+};
+use fp_rpc_server::{
+    rpc_handler, start_http, start_ws, RpcHandler, RpcLimits, RpcMetrics, RpcMiddleware,
+    RpcPolicy,
 };
-use fp_rpc_server::{rpc_handler, start_http, start_ws, RpcHandler, RpcMiddleware};
 use fp_utils::ecdsa::SecpPair;
 use jsonrpc_core::types::error::{Error, ErrorCode};
 use parking_lot::RwLock;
@@ -40,7 +46,24 @@ pub fn start_web3_service(
     let dev_signer = "zebra paddle unveil toilet weekend space gorilla lesson relief useless arrive picture";
     let signers = vec![SecpPair::from_phrase(dev_signer, None).unwrap().0];
 
-    let io = || -> RpcHandler<Metadata> {
+    let rpc_metrics = if CFG.enable_metrics {
+        RpcMetrics::new(Some(prometheus::default_registry()))
+            .expect("Unable to register web3 rpc metrics")
+    } else {
+        None
+    };
+
+    let rpc_policy = Some(Arc::new(RpcPolicy::new(RpcLimits {
+        rate_limit_per_sec: CFG.web3_rpc.rate_limit_per_sec,
+        rate_limit_burst: CFG.web3_rpc.rate_limit_burst,
+        method_costs: CFG.web3_rpc.method_costs.clone().into_iter().collect(),
+        allowed_methods: CFG.web3_rpc.allowed_methods.clone(),
+        denied_methods: CFG.web3_rpc.denied_methods.clone(),
+        max_batch_size: CFG.web3_rpc.max_batch_size,
+        max_response_size: CFG.web3_rpc.max_response_size,
+    })));
+
+    let io = |transport_label: &str| -> RpcHandler<Metadata> {
         rpc_handler(
             (
                 eth::EthApiImpl::new(
@@ -57,13 +80,16 @@ pub fn start_web3_service(
                 )
                 .to_delegate(),
                 net::NetApiImpl::new().to_delegate(),
-                web3::Web3ApiImpl::new().to_delegate(),
+                web3::Web3ApiImpl::new(app.clone()).to_delegate(),
                 eth_pubsub::EthPubSubApiImpl::new(app2.clone()).to_delegate(),
+                txpool::TxPoolApiImpl::new(app.clone()).to_delegate(),
             ),
-            RpcMiddleware::new(),
+            RpcMiddleware::new(rpc_metrics.clone(), rpc_policy.clone(), transport_label),
         )
     };
 
+    txpool::spawn_promotion(tendermint_rpc.clone(), app.clone());
+
     let logical_cpus = rucv4::max!(num_cpus::get(), 8);
     let thread_pool_size =
         std::env::var("WEB3_MAX_HTTP_THREADS").map_or(Some(logical_cpus), |id| {
@@ -78,7 +104,8 @@ pub fn start_web3_service(
         &evm_http.parse().unwrap(),
         thread_pool_size,
         Some(&vec!["*".to_string()]),
-        io(),
+        &CFG.web3_rpc.trusted_proxies,
+        io("http"),
         None,
     )
     .map(|s| waiting::HttpServer(Some(s)))
@@ -88,7 +115,7 @@ pub fn start_web3_service(
         &evm_ws.parse().unwrap(),
         None,
         Some(&vec!["*".to_string()]),
-        io(),
+        io("ws"),
         None,
     )
     .map(|s| waiting::WsServer(Some(s)))
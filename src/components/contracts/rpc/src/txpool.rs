@@ -0,0 +1,111 @@
+use baseapp::{extensions::SignedExtra, txpool::PoolTransaction, BaseApp};
+use ethereum::TransactionAction;
+use ethereum_types::{H160, U256};
+use fp_rpc_core::types::{InspectSummaries, TxPoolInspect};
+use fp_rpc_core::TxPoolApi;
+use fp_types::{actions, assemble::UncheckedTransaction};
+use fp_utils::tx::EvmRawTxWrapper;
+use jsonrpc_core::Result;
+use parking_lot::RwLock;
+use std::{collections::BTreeMap, sync::Arc, thread, time::Duration};
+use tendermint_rpc::{Client, HttpClient};
+use tracing::{debug, error, warn};
+
+/// Interval to broadcast the queued transactions whose nonce gap is filled.
+const PROMOTE_INTERVAL: Duration = Duration::from_secs(1);
+
+pub struct TxPoolApiImpl {
+    account_base_app: Arc<RwLock<BaseApp>>,
+}
+
+impl TxPoolApiImpl {
+    pub fn new(account_base_app: Arc<RwLock<BaseApp>>) -> Self {
+        Self { account_base_app }
+    }
+
+    fn summaries(
+        txs: &BTreeMap<H160, BTreeMap<U256, PoolTransaction>>,
+    ) -> BTreeMap<H160, InspectSummaries> {
+        txs.iter()
+            .map(|(sender, txs)| {
+                let summaries = txs
+                    .iter()
+                    .map(|(nonce, tx)| (nonce.to_string(), Self::summary(tx)))
+                    .collect();
+                (*sender, summaries)
+            })
+            .collect()
+    }
+
+    fn summary(tx: &PoolTransaction) -> String {
+        let tx = &tx.transaction;
+        let to = match tx.action {
+            TransactionAction::Call(to) => format!("{to:?}"),
+            TransactionAction::Create => "contract creation".to_owned(),
+        };
+        format!(
+            "{}: {} wei + {} gas × {} wei",
+            to, tx.value, tx.gas_limit, tx.gas_price
+        )
+    }
+}
+
+impl TxPoolApi for TxPoolApiImpl {
+    fn inspect(&self) -> Result<TxPoolInspect> {
+        let tx_pool = self.account_base_app.read().tx_pool.clone();
+        let tx_pool = tx_pool.read();
+        Ok(TxPoolInspect {
+            pending: Self::summaries(tx_pool.pending()),
+            queued: Self::summaries(tx_pool.queued()),
+        })
+    }
+}
+
+/// Broadcast the queued transactions once their nonce gap is filled,
+/// they have been kept out of the tendermint mempool until then.
+pub fn spawn_promotion(tendermint_rpc: String, app: Arc<RwLock<BaseApp>>) {
+    let tx_pool = app.read().tx_pool.clone();
+    thread::spawn(move || {
+        let rt = match tokio::runtime::Runtime::new() {
+            Ok(rt) => rt,
+            Err(e) => {
+                error!(target: "eth_rpc", "Failed to create runtime, queued transactions won't be promoted: {}", e);
+                return;
+            }
+        };
+        let client = match HttpClient::new(tendermint_rpc.as_str()) {
+            Ok(client) => client,
+            Err(e) => {
+                error!(target: "eth_rpc", "Invalid tendermint rpc {}, queued transactions won't be promoted: {}", tendermint_rpc, e);
+                return;
+            }
+        };
+        loop {
+            thread::sleep(PROMOTE_INTERVAL);
+            let promoted = tx_pool.write().take_promoted();
+            for transaction in promoted {
+                let hash = transaction.hash();
+                let function = actions::Action::Ethereum(
+                    actions::ethereum::Action::Transact(transaction),
+                );
+                let txn = match serde_json::to_vec(
+                    &UncheckedTransaction::<SignedExtra>::new_unsigned(function),
+                ) {
+                    Ok(txn) => EvmRawTxWrapper::wrap(&txn),
+                    Err(e) => {
+                        warn!(target: "eth_rpc", "Failed to encode promoted transaction {:?}: {}", hash, e);
+                        continue;
+                    }
+                };
+                match rt.block_on(client.broadcast_tx_sync(txn.into())) {
+                    Ok(resp) => {
+                        debug!(target: "eth_rpc", "Promoted transaction {:?}: {:?}", hash, resp.code)
+                    }
+                    Err(e) => {
+                        warn!(target: "eth_rpc", "Failed to broadcast promoted transaction {:?}: {}", hash, e)
+                    }
+                }
+            }
+        }
+    });
+}
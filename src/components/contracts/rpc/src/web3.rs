@@ -1,20 +1,23 @@
-use ethereum_types::H256;
-use fp_rpc_core::{types::Bytes, Web3Api};
+use crate::internal_err;
+use baseapp::BaseApp;
+use ethereum_types::{H256, U256};
+use fp_rpc_core::{
+    types::{Bytes, StateRange},
+    Web3Api,
+};
 use jsonrpc_core::Result;
+use parking_lot::RwLock;
 use rustc_version::version;
 use sha3::{Digest, Keccak256};
+use std::sync::Arc;
 
-pub struct Web3ApiImpl;
-
-impl Web3ApiImpl {
-    pub fn new() -> Self {
-        Self
-    }
+pub struct Web3ApiImpl {
+    account_base_app: Arc<RwLock<BaseApp>>,
 }
 
-impl Default for Web3ApiImpl {
-    fn default() -> Self {
-        Web3ApiImpl::new()
+impl Web3ApiImpl {
+    pub fn new(account_base_app: Arc<RwLock<BaseApp>>) -> Self {
+        Self { account_base_app }
     }
 }
 
@@ -34,4 +37,57 @@ impl Web3Api for Web3ApiImpl {
             Keccak256::digest(&input.into_vec()).as_slice(),
         ))
     }
+
+    fn state_range(&self) -> Result<StateRange> {
+        let app = self.account_base_app.read();
+        let range = app
+            .chain_state
+            .read()
+            .get_ver_range()
+            .map_err(internal_err)?;
+        Ok(StateRange {
+            archive: app.archive.is_some(),
+            earliest: U256::from(range.start),
+            // The range is [lower, upper).
+            latest: U256::from(range.end.saturating_sub(1).max(range.start)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{env::temp_dir, time::SystemTime};
+
+    #[test]
+    fn test_state_range() {
+        let time = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let basedir = temp_dir().join(format!("temp-findora-web3-{time}"));
+        std::fs::create_dir_all(&basedir).unwrap();
+
+        let app = BaseApp::new(&basedir, false, (1, None), false).unwrap();
+        for height in 1..=3 {
+            app.deliver_state.state.write().commit(height).unwrap();
+        }
+        let range = Web3ApiImpl::new(Arc::new(RwLock::new(app)))
+            .state_range()
+            .unwrap();
+        assert!(!range.archive);
+        assert!(range.earliest <= range.latest);
+        assert_eq!(range.latest, U256::from(3));
+
+        //The secondary of an archive reports it along with the earliest height
+        let archive = BaseApp::migrate_to_archive(&basedir, (1, None)).unwrap();
+        let app =
+            BaseApp::new_with_secondary(&basedir, false, (1, None), false).unwrap();
+        let range = Web3ApiImpl::new(Arc::new(RwLock::new(app)))
+            .state_range()
+            .unwrap();
+        assert!(range.archive);
+        assert_eq!(range.earliest, U256::from(archive.since));
+        assert_eq!(range.latest, U256::from(3));
+    }
 }
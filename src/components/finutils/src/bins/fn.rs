@@ -31,17 +31,26 @@ use fp_types::H160;
 
 use {
     clap::{crate_authors, load_yaml, App},
-    finutils::common::{self, evm::*},
+    finutils::common::{
+        self,
+        evm::*,
+        keystore,
+        offline::{self, UnsignedOp},
+        trace,
+    },
     fp_utils::ecdsa::SecpPair,
     globutils::wallet,
     ledger::{
-        data_model::{AssetTypeCode, FRA_DECIMALS},
+        data_model::{AssetTypeCode, CredentialRule, ASSET_TYPE_FRA, FRA_DECIMALS},
         staking::{StakerMemo, VALIDATORS_MIN},
     },
     ruc::*,
     std::{fmt, fs},
 };
 
+/// Default output file of `--build-only`
+const UNSIGNED_TX_FILE: &str = "unsigned_tx.json";
+
 fn main() {
     if let Err(e) = run() {
         tip_fail(e);
@@ -57,12 +66,136 @@ fn run() -> Result<()> {
         .author(crate_authors!())
         .get_matches();
 
+    if let Some(name) = matches
+        .value_of("account")
+        .or_else(|| matches.subcommand().1.and_then(|m| m.value_of("account")))
+    {
+        keystore::use_account(name);
+    }
+
     if matches.is_present("version") {
         println!("{}", env!("VERGEN_SHA"));
     } else if matches.is_present("genkey") {
         common::gen_key_and_print();
+    } else if let Some(m) = matches.subcommand_matches("keys") {
+        let name = m.value_of("name");
+        let index = m
+            .value_of("index")
+            .map(|i| {
+                i.parse::<u32>()
+                    .c(d!("index should be a 32-bits unsigned integer"))
+            })
+            .transpose()?
+            .unwrap_or(0);
+        if m.is_present("list") {
+            let default = keystore::get_default_account().c(d!())?;
+            for a in keystore::list_accounts().c(d!())? {
+                let mark = alt!(default.as_ref() == Some(&a.name), "*", " ");
+                let path = a
+                    .path
+                    .map(|(account, change, address)| {
+                        format!(
+                            "m/44'/{}'/{account}'/{change}/{address}",
+                            keystore::FRA_COIN_TYPE
+                        )
+                    })
+                    .unwrap_or_else(|| "imported".to_owned());
+                println!("{mark} {}\t{}\t{path}", a.name, a.pubkey);
+            }
+        } else if let Some(name) = name {
+            if m.is_present("create") {
+                let mnemonic = keystore::create_account(name, index).c(d!())?;
+                println!(
+                    "\x1b[31;01mMnemonic:\x1b[00m {mnemonic}\n\
+                    Please write it down and keep it safe, it is the only backup of the account."
+                );
+            } else if m.is_present("import") {
+                if let Some(path) = m.value_of("mnemonic-path") {
+                    let phrase = fs::read_to_string(path)
+                        .c(d!("Failed to read mnemonic file"))?;
+                    keystore::import_mnemonic(name, &phrase, index).c(d!())?;
+                } else if let Some(path) = m.value_of("seckey") {
+                    let sk =
+                        fs::read_to_string(path).c(d!("Failed to read seckey file"))?;
+                    keystore::import_seckey(name, &sk).c(d!())?;
+                } else {
+                    println!("{}", m.usage());
+                    return Ok(());
+                }
+            } else if m.is_present("select") {
+                keystore::select_default_account(name).c(d!())?;
+            } else if m.is_present("export") {
+                let pk = keystore::get_account(name).c(d!())?.get_pk().c(d!())?;
+                println!(
+                    "\x1b[31;01mAddress:\x1b[00m {}\n\x1b[31;01mPublic Key:\x1b[00m {}",
+                    wallet::public_key_to_bech32(&pk),
+                    wallet::public_key_to_base64(&pk)
+                );
+            } else {
+                println!("{}", m.usage());
+            }
+        } else {
+            println!("{}", m.usage());
+        }
     } else if let Some(m) = matches.subcommand_matches("wallet") {
-        if m.is_present("create") {
+        if let Some(m) = m.subcommand_matches("consolidate") {
+            let seckey = match m.value_of("seckey") {
+                Some(path) => {
+                    Some(fs::read_to_string(path).c(d!("Failed to read seckey file"))?)
+                }
+                None => None,
+            };
+            let asset = match m.value_of("asset") {
+                Some(code) if code.to_uppercase() == "FRA" => Some(AssetTypeCode {
+                    val: ASSET_TYPE_FRA,
+                }),
+                Some(code) => Some(AssetTypeCode::new_from_base64(code).c(d!())?),
+                None => None,
+            };
+            let max_inputs = m
+                .value_of("max-inputs")
+                .map(|n| n.parse::<usize>().c(d!("invalid max inputs")))
+                .transpose()?;
+            common::consolidate::consolidate(
+                seckey.as_deref(),
+                asset,
+                max_inputs,
+                m.is_present("dry-run"),
+            )
+            .c(d!())?;
+        } else if let Some(m) = m.subcommand_matches("memos") {
+            let seckey = match m.value_of("seckey") {
+                Some(path) => {
+                    Some(fs::read_to_string(path).c(d!("Failed to read seckey file"))?)
+                }
+                None => None,
+            };
+            let sid = m
+                .value_of("txn-sid")
+                .c(d!())?
+                .parse::<usize>()
+                .c(d!("'txn-sid' must be an integer"))?;
+            common::show_encrypted_memos(seckey.as_deref(), sid).c(d!())?;
+        } else if let (name @ ("shield" | "unshield"), Some(m)) = m.subcommand() {
+            let seckey = match m.value_of("seckey") {
+                Some(path) => {
+                    Some(fs::read_to_string(path).c(d!("Failed to read seckey file"))?)
+                }
+                None => None,
+            };
+            let token_code = match m.value_of("asset") {
+                Some(code) if code.to_uppercase() != "FRA" => {
+                    Some(AssetTypeCode::new_from_base64(code).c(d!())?)
+                }
+                _ => None,
+            };
+            let amount = m.value_of("amount").c(d!())?.parse::<u64>().c(d!())?;
+            if name == "shield" {
+                common::shield(seckey.as_deref(), amount, token_code).c(d!())?;
+            } else {
+                common::unshield(seckey.as_deref(), amount, token_code).c(d!())?;
+            }
+        } else if m.is_present("create") {
             common::gen_key_and_print();
         } else if m.is_present("show") {
             let seckey = match m.value_of("seckey") {
@@ -96,12 +229,22 @@ fn run() -> Result<()> {
         let show_info = m.is_present("info");
 
         if amount.is_some() && validator.is_some() {
-            common::delegate(
-                seckey.as_deref(),
-                amount.unwrap().parse::<u64>().c(d!())?,
-                validator.unwrap(),
-            )
-            .c(d!())?;
+            let amount = amount.unwrap().parse::<u64>().c(d!())?;
+            if m.is_present("build-only") {
+                offline::build_to_file(
+                    offline::get_signer(m.value_of("signer"), seckey.as_deref())
+                        .c(d!())?,
+                    UnsignedOp::Delegate {
+                        amount,
+                        validator: validator.unwrap().to_owned(),
+                    },
+                    m.value_of("output").unwrap_or(UNSIGNED_TX_FILE),
+                )
+                .c(d!())?;
+            } else {
+                common::delegate(seckey.as_deref(), amount, validator.unwrap())
+                    .c(d!())?;
+            }
         } else if show_info {
             common::show_delegations(seckey.as_deref()).c(d!())?;
         } else {
@@ -157,6 +300,19 @@ fn run() -> Result<()> {
                 None
             };
             let token_code = m.value_of("code");
+            let credential_rule = match m.value_of("credential-issuer") {
+                Some(path) => Some(CredentialRule {
+                    issuer: read_json(path).c(d!("Invalid credential issuer key"))?,
+                    attributes: m
+                        .values_of("credential-attr")
+                        .map(|attrs| {
+                            attrs.map(parse_credential_attr).collect::<Result<Vec<_>>>()
+                        })
+                        .transpose()?
+                        .unwrap_or_default(),
+                }),
+                None => None,
+            };
             common::create_asset(
                 seckey.as_deref(),
                 memo.unwrap(),
@@ -164,6 +320,7 @@ fn run() -> Result<()> {
                 max_units,
                 transferable,
                 token_code,
+                credential_rule,
             )
             .c(d!())?;
         } else if m.is_present("show") {
@@ -195,8 +352,30 @@ fn run() -> Result<()> {
 
             common::issue_asset(seckey.as_deref(), code.unwrap(), amount, hidden)
                 .c(d!())?;
+        } else if m.is_present("trace") {
+            let code = m.value_of("code");
+            let tracer_key = m.value_of("tracer-key");
+            if code.is_none() || tracer_key.is_none() {
+                println!("{}", m.usage());
+                return Ok(());
+            }
+            let code = AssetTypeCode::new_from_base64(code.unwrap()).c(d!())?;
+            let tracer = fs::read_to_string(tracer_key.unwrap())
+                .c(d!("Failed to read tracer key file"))
+                .and_then(|k| serde_json::from_str(&k).c(d!("Invalid tracer key")))?;
+            let format =
+                trace::ReportFormat::from_name(m.value_of("format").unwrap_or("json"))
+                    .c(d!())?;
+
+            let records = trace::trace_asset(&code, &tracer).c(d!())?;
+            let report = trace::gen_report(&records, format).c(d!())?;
+            if let Some(path) = m.value_of("output") {
+                fs::write(path, report).c(d!())?;
+            } else {
+                print!("{report}");
+            }
         } else {
-            let help = "fn asset [--create | --issue | --show]";
+            let help = "fn asset [--create | --issue | --show | --trace]";
             println!("{help}");
         }
     } else if let Some(m) = matches.subcommand_matches("staker-update") {
@@ -241,6 +420,9 @@ fn run() -> Result<()> {
     } else if let Some(m) = matches.subcommand_matches("stake") {
         let am = m.value_of("amount");
         if m.is_present("append") {
+            if m.is_present("build-only") {
+                return Err(eg!("`--build-only` is not supported by `--append`"));
+            }
             let staker = match m.value_of("staker-priv-key") {
                 Some(path) => {
                     Some(fs::read_to_string(path).c(d!("Failed to read seckey file"))?)
@@ -262,6 +444,17 @@ fn run() -> Result<()> {
                 println!(
                     "Tips: if you want to raise the power of your node, please use `fn stake --append [OPTIONS]`"
                 );
+            } else if m.is_present("build-only") {
+                let am = am
+                    .unwrap()
+                    .parse::<u64>()
+                    .c(d!("'amount' must be an integer"))?;
+                offline::build_to_file(
+                    offline::get_signer(m.value_of("signer"), None).c(d!())?,
+                    UnsignedOp::new_stake(am, cr.unwrap(), vm).c(d!())?,
+                    m.value_of("output").unwrap_or(UNSIGNED_TX_FILE),
+                )
+                .c(d!())?;
             } else {
                 common::stake(am.unwrap(), cr.unwrap(), vm, force).c(d!())?;
             }
@@ -291,7 +484,42 @@ fn run() -> Result<()> {
                 return Ok(());
             }
         };
-        common::claim(td_addr, am, seckey.as_deref()).c(d!())?;
+        if m.is_present("build-only") {
+            offline::build_to_file(
+                offline::get_signer(m.value_of("signer"), seckey.as_deref()).c(d!())?,
+                UnsignedOp::Claim {
+                    td_addr: td_addr.to_owned(),
+                    amount: None,
+                },
+                m.value_of("output").unwrap_or(UNSIGNED_TX_FILE),
+            )
+            .c(d!())?;
+        } else {
+            common::claim(td_addr, am, seckey.as_deref()).c(d!())?;
+        }
+    } else if let Some(m) = matches.subcommand_matches("tx") {
+        if let Some(sm) = m.subcommand_matches("sign") {
+            let seckey = match sm.value_of("seckey") {
+                Some(path) => {
+                    Some(fs::read_to_string(path).c(d!("Failed to read seckey file"))?)
+                }
+                None => None,
+            };
+            let file = sm.value_of("file").c(d!())?;
+            let output = sm
+                .value_of("output")
+                .map(|o| o.to_owned())
+                .unwrap_or_else(|| format!("{file}.signed"));
+            offline::sign_file(seckey.as_deref(), file, &output).c(d!())?;
+        } else if let Some(sm) = m.subcommand_matches("inspect") {
+            offline::inspect_file(sm.value_of("file").c(d!())?).c(d!())?;
+        } else if let Some(sm) = m.subcommand_matches("simulate") {
+            offline::simulate_file(sm.value_of("file").c(d!())?).c(d!())?;
+        } else if let Some(sm) = m.subcommand_matches("broadcast") {
+            offline::broadcast_file(sm.value_of("file").c(d!())?).c(d!())?;
+        } else {
+            println!("{}", m.usage());
+        }
     } else if let Some(m) = matches.subcommand_matches("show") {
         let basic = m.is_present("basic");
         common::show(basic).c(d!())?;
@@ -335,16 +563,86 @@ fn run() -> Result<()> {
             } else {
                 None
             };
-            common::transfer_asset(
-                f.as_deref(),
-                t,
-                None,
-                token_code,
-                am.unwrap(),
-                m.is_present("confidential-amount"),
-                m.is_present("confidential-type"),
-            )
-            .c(d!())?;
+            if m.is_present("build-only") {
+                let am = am
+                    .unwrap()
+                    .parse::<u64>()
+                    .c(d!("'amount' must be an integer"))?;
+                offline::build_to_file(
+                    offline::get_signer(m.value_of("signer"), f.as_deref()).c(d!())?,
+                    UnsignedOp::Transfer {
+                        targets: vec![(t, am, None)],
+                        token_code,
+                        confidential_am: m.is_present("confidential-amount"),
+                        confidential_ty: m.is_present("confidential-type"),
+                    },
+                    m.value_of("output").unwrap_or(UNSIGNED_TX_FILE),
+                )
+                .c(d!())?;
+            } else if let Some(paths) = m.values_of("credential-proof") {
+                let proofs = paths
+                    .map(|p| read_json(p).c(d!("Invalid credential proof")))
+                    .collect::<Result<Vec<_>>>()?;
+                common::transfer_asset_with_credential_proofs(
+                    f.as_deref(),
+                    t,
+                    proofs,
+                    token_code,
+                    am.unwrap(),
+                    m.is_present("confidential-amount"),
+                )
+                .c(d!())?;
+            } else if let Some(memo) = m.value_of("encrypted-memo") {
+                common::transfer_asset_with_encrypted_memo(
+                    f.as_deref(),
+                    t,
+                    memo,
+                    token_code,
+                    am.unwrap(),
+                    m.is_present("confidential-amount"),
+                    m.is_present("confidential-type"),
+                )
+                .c(d!())?;
+            } else {
+                common::transfer_asset(
+                    f.as_deref(),
+                    t,
+                    None,
+                    token_code,
+                    am.unwrap(),
+                    m.is_present("confidential-amount"),
+                    m.is_present("confidential-type"),
+                )
+                .c(d!())?;
+            }
+        }
+    } else if let Some(m) = matches.subcommand_matches("credential-proof") {
+        let credential =
+            read_json(m.value_of("credential").c(d!())?).c(d!("Invalid credential"))?;
+        let user_sk = read_json(m.value_of("user-seckey").c(d!())?)
+            .c(d!("Invalid credential user key"))?;
+        let reveal_fields = m
+            .value_of("reveal")
+            .unwrap_or_default()
+            .split(',')
+            .map(|f| f.trim().to_owned())
+            .filter(|f| !f.is_empty())
+            .collect::<Vec<_>>();
+        let address = m
+            .value_of("address")
+            .map(|addr| {
+                wallet::public_key_from_bech32(addr).c(d!("invalid wallet address"))
+            })
+            .transpose()?;
+
+        let proof =
+            common::gen_credential_proof(&credential, &user_sk, address, &reveal_fields)
+                .c(d!())?;
+        let proof = serde_json::to_string_pretty(&proof).c(d!())?;
+        if let Some(path) = m.value_of("output") {
+            fs::write(path, proof).c(d!())?;
+        } else {
+            println!("{proof}");
         }
     } else if let Some(m) = matches.subcommand_matches("transfer-batch") {
         let f = match m.value_of("from-seckey") {
@@ -781,6 +1079,19 @@ fn run() -> Result<()> {
     Ok(())
 }
 
+fn read_json<T: serde::de::DeserializeOwned>(path: &str) -> Result<T> {
+    fs::read_to_string(path)
+        .c(d!(format!("Failed to read {path}")))
+        .and_then(|s| serde_json::from_str(&s).c(d!()))
+}
+
+// `name=value`
+fn parse_credential_attr(attr: &str) -> Result<(String, Vec<u8>)> {
+    attr.split_once('=')
+        .map(|(name, value)| (name.trim().to_owned(), value.trim().as_bytes().to_vec()))
+        .c(d!(format!("invalid credential attribute: {attr}")))
+}
+
 fn tip_fail(e: impl fmt::Display) {
     eprintln!("\n\x1b[31;01mFAIL !!!\x1b[00m");
     eprintln!(
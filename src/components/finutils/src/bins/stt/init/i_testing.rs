@@ -107,7 +107,7 @@ pub fn run_all() -> Result<()> {
         .keypair;
 
     println!(">>> Create custom asset A ...");
-    let code = create_asset_x(v0_kp, "A", 9, None, true, None).c(d!())?;
+    let code = create_asset_x(v0_kp, "A", 9, None, true, None, None).c(d!())?;
     println!(">>> Wait 1.2 block ...");
     sleep_n_block!(1.2);
 
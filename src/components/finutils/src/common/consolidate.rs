@@ -0,0 +1,464 @@
+//!
+//! Consolidation of UTXOs
+//!
+//! Merges the small UTXOs of a wallet, e.g. the rewards and the outputs of
+//! `transfer-batch`, so that later transfers need fewer inputs.
+//!
+
+use {
+    super::utils,
+    crate::txn_builder::{LargestFirst, TransactionBuilder, TransferOperationBuilder},
+    ledger::data_model::{
+        min_transfer_fee, AssetTypeCode, Transaction, TransferType, TxoRef, TxoSID,
+        ASSET_TYPE_FRA, BLACK_HOLE_PUBKEY,
+    },
+    ruc::*,
+    std::collections::BTreeMap,
+    zei::xfr::{
+        asset_record::AssetRecordType,
+        sig::XfrKeyPair,
+        structs::{AssetRecordTemplate, OpenAssetRecord},
+    },
+};
+
+/// Inputs of a consolidation transaction with nonconfidential records.
+pub const DEFAULT_MAX_INPUTS: usize = 128;
+
+/// Inputs of a consolidation transaction with confidential records,
+/// whose proofs are much larger.
+pub const DEFAULT_MAX_CONFIDENTIAL_INPUTS: usize = 16;
+
+/// The default `max_tx_bytes` of tendermint.
+pub const MAX_TX_SIZE: usize = 1024 * 1024;
+
+/// A planned consolidation transaction,
+/// which merges all its inputs into one output of the owner.
+pub struct Batch {
+    /// The asset type of the inputs
+    pub asset_type: AssetTypeCode,
+    /// The record type of the inputs and the merged output
+    pub record_type: AssetRecordType,
+    /// The UTXOs to merge
+    pub inputs: Vec<(TxoSID, OpenAssetRecord)>,
+    /// The FRA UTXOs which pay the fee of a custom asset batch
+    pub fee_inputs: Vec<(TxoSID, OpenAssetRecord)>,
+    #[allow(missing_docs)]
+    pub fee: u64,
+}
+
+impl Batch {
+    /// The sum of the inputs
+    pub fn total(&self) -> u64 {
+        self.inputs.iter().map(|(_, oar)| oar.amount).sum()
+    }
+
+    /// The amount of the merged output
+    pub fn merged(&self) -> u64 {
+        alt!(
+            self.asset_type.val == ASSET_TYPE_FRA,
+            self.total() - self.fee,
+            self.total()
+        )
+    }
+
+    /// Build and sign the consolidation transaction.
+    pub fn build(&self, kp: &XfrKeyPair, seq_id: u64) -> Result<Transaction> {
+        let mut op = TransferOperationBuilder::new();
+        op.add_output(
+            &AssetRecordTemplate::with_no_asset_tracing(
+                self.fee,
+                ASSET_TYPE_FRA,
+                AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType,
+                *BLACK_HOLE_PUBKEY,
+            ),
+            None,
+            None,
+            None,
+            None,
+        )
+        .c(d!())?
+        .add_output(
+            &AssetRecordTemplate::with_no_asset_tracing(
+                self.merged(),
+                self.asset_type.val,
+                self.record_type,
+                kp.get_pk(),
+            ),
+            None,
+            None,
+            None,
+            None,
+        )
+        .c(d!())?;
+
+        for (sid, oar) in self.inputs.iter() {
+            op.add_input(TxoRef::Absolute(*sid), oar.clone(), None, None, oar.amount)
+                .c(d!())?;
+        }
+        if self.asset_type.val != ASSET_TYPE_FRA {
+            let fee_inputs = self
+                .fee_inputs
+                .iter()
+                .map(|(sid, oar)| (TxoRef::Absolute(*sid), oar.clone()))
+                .collect();
+            op.add_inputs_by_selection(fee_inputs, self.fee, &LargestFirst, None)
+                .c(d!())?;
+        }
+
+        let op = op
+            .balance(None)
+            .c(d!())?
+            .create(TransferType::Standard)
+            .c(d!())?
+            .sign(kp)
+            .c(d!())?
+            .transaction()
+            .c(d!())?;
+
+        let mut builder = TransactionBuilder::from_seq_id(seq_id);
+        builder.add_operation(op);
+        let mut tx = builder.take_transaction();
+        tx.sign_to_map(kp);
+
+        Ok(tx)
+    }
+}
+
+/// The planned consolidation transactions of an account.
+pub struct Plan {
+    #[allow(missing_docs)]
+    pub batches: Vec<Batch>,
+    /// Custom assets whose UTXOs are not merged as the FRAs can not pay the fees,
+    /// along with the number of such UTXOs
+    pub unpaid: Vec<(AssetTypeCode, usize)>,
+}
+
+/// Plan the consolidation of the UTXOs of `kp`, only `asset` is merged if it is set.
+///
+/// UTXOs are grouped by asset type and confidentiality, every group is merged
+/// in batches of at most `max_inputs` (or the defaults) inputs.
+/// The fees of custom assets are paid by the largest FRA UTXOs,
+/// which are excluded from the consolidation of FRA.
+pub fn plan(
+    kp: &XfrKeyPair,
+    asset: Option<AssetTypeCode>,
+    max_inputs: Option<usize>,
+) -> Result<Plan> {
+    let records = utils::get_owned_records(kp).c(d!())?;
+    let height = utils::get_fee_height().c(d!())?;
+    Ok(plan_records(records, asset, max_inputs, height))
+}
+
+fn plan_records(
+    records: Vec<(TxoSID, OpenAssetRecord)>,
+    asset: Option<AssetTypeCode>,
+    max_inputs: Option<usize>,
+    height: i64,
+) -> Plan {
+    let mut groups: BTreeMap<
+        (AssetTypeCode, bool, bool),
+        Vec<(TxoSID, OpenAssetRecord)>,
+    > = BTreeMap::new();
+    for (sid, oar) in records {
+        let code = AssetTypeCode {
+            val: oar.asset_type,
+        };
+        if asset.map(|a| a != code).unwrap_or(false) && code.val != ASSET_TYPE_FRA {
+            continue;
+        }
+        let (conf_am, conf_ty) = flags(oar.get_record_type());
+        groups
+            .entry((code, conf_am, conf_ty))
+            .or_default()
+            .push((sid, oar));
+    }
+
+    let fra_key = (
+        AssetTypeCode {
+            val: ASSET_TYPE_FRA,
+        },
+        false,
+        false,
+    );
+    let mut fee_pool = groups.remove(&fra_key).unwrap_or_default();
+    fee_pool.sort_by(|a, b| b.1.amount.cmp(&a.1.amount));
+
+    let mut batches = vec![];
+    let mut unpaid = vec![];
+    for ((code, conf_am, conf_ty), inputs) in groups {
+        let is_fra = code.val == ASSET_TYPE_FRA;
+        if asset.map(|a| a != code).unwrap_or(false) {
+            continue;
+        }
+        let mut batched = chunks(inputs, conf_am || conf_ty, max_inputs).into_iter();
+        while let Some(chunk) = batched.next() {
+            // outputs: the fee, the merged one and the change of FRA
+            let fee = min_transfer_fee(height, alt!(is_fra, 2, 3), 0);
+            let fee_inputs = if is_fra {
+                vec![]
+            } else if let Some(v) = take_fee_inputs(&mut fee_pool, fee) {
+                v
+            } else {
+                let n = chunk.len() + batched.by_ref().map(|c| c.len()).sum::<usize>();
+                unpaid.push((code, n));
+                break;
+            };
+            let batch = Batch {
+                asset_type: code,
+                record_type: AssetRecordType::from_flags(conf_am, conf_ty),
+                inputs: chunk,
+                fee_inputs,
+                fee,
+            };
+            if !is_fra || batch.total() > fee {
+                batches.push(batch);
+            }
+        }
+    }
+
+    // the rest of the nonconfidential FRAs
+    if asset.map(|a| a.val == ASSET_TYPE_FRA).unwrap_or(true) {
+        fee_pool.sort_unstable_by_key(|(sid, _)| *sid);
+        let fee = min_transfer_fee(height, 2, 0);
+        for chunk in chunks(fee_pool, false, max_inputs) {
+            let batch = Batch {
+                asset_type: fra_key.0,
+                record_type:
+                    AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType,
+                inputs: chunk,
+                fee_inputs: vec![],
+                fee,
+            };
+            if batch.total() > fee {
+                batches.push(batch);
+            }
+        }
+    }
+
+    Plan { batches, unpaid }
+}
+
+/// Consolidate the UTXOs of a findora account,
+/// the planned transactions are only printed if `dry_run`.
+pub fn consolidate(
+    sk_str: Option<&str>,
+    asset: Option<AssetTypeCode>,
+    max_inputs: Option<usize>,
+    dry_run: bool,
+) -> Result<()> {
+    let kp = &super::restore_keypair_from_str_with_default(sk_str).c(d!())?;
+    let Plan { batches, unpaid } = plan(kp, asset, max_inputs).c(d!())?;
+    for (code, n) in unpaid.iter() {
+        println!(
+            "Not enough FRA to pay the fees, {} UTXOs of {} are left unmerged",
+            n,
+            code.to_base64()
+        );
+    }
+    if batches.is_empty() {
+        println!("Nothing to consolidate");
+        return Ok(());
+    }
+
+    let seq_id = utils::get_seq_id().c(d!())?;
+    let mut txs = vec![];
+    for (idx, batch) in batches.iter().enumerate() {
+        let tx = batch.build(kp, seq_id).c(d!())?;
+        let size = serde_json::to_vec(&tx).c(d!())?.len();
+        if size > MAX_TX_SIZE {
+            return Err(eg!(format!(
+                "Transaction {idx} is too large ({size} bytes), please retry with a smaller `--max-inputs`"
+            )));
+        }
+        println!(
+            "Transaction {}: merge {} UTXOs of {} ({:?}) into {}, fee: {}, size: {} bytes",
+            idx,
+            batch.inputs.len(),
+            alt!(
+                batch.asset_type.val == ASSET_TYPE_FRA,
+                "FRA".to_owned(),
+                batch.asset_type.to_base64()
+            ),
+            batch.record_type,
+            batch.merged(),
+            batch.fee,
+            size
+        );
+        txs.push(tx);
+    }
+
+    if !dry_run {
+        for tx in txs.iter() {
+            utils::send_tx(tx).c(d!())?;
+        }
+    }
+
+    Ok(())
+}
+
+fn flags(record_type: AssetRecordType) -> (bool, bool) {
+    match record_type {
+        AssetRecordType::ConfidentialAmount_ConfidentialAssetType => (true, true),
+        AssetRecordType::ConfidentialAmount_NonConfidentialAssetType => (true, false),
+        AssetRecordType::NonConfidentialAmount_ConfidentialAssetType => (false, true),
+        _ => (false, false),
+    }
+}
+
+// Split a group into batches, a batch of one input is useless.
+fn chunks(
+    inputs: Vec<(TxoSID, OpenAssetRecord)>,
+    confidential: bool,
+    max_inputs: Option<usize>,
+) -> Vec<Vec<(TxoSID, OpenAssetRecord)>> {
+    let max = max_inputs
+        .unwrap_or(alt!(
+            confidential,
+            DEFAULT_MAX_CONFIDENTIAL_INPUTS,
+            DEFAULT_MAX_INPUTS
+        ))
+        .max(2);
+
+    let mut res = vec![];
+    let mut inputs = inputs.into_iter().peekable();
+    while inputs.peek().is_some() {
+        let chunk = inputs.by_ref().take(max).collect::<Vec<_>>();
+        if chunk.len() > 1 {
+            res.push(chunk);
+        }
+    }
+    res
+}
+
+// Take the largest FRAs out of the pool to pay a fee.
+fn take_fee_inputs(
+    pool: &mut Vec<(TxoSID, OpenAssetRecord)>,
+    fee: u64,
+) -> Option<Vec<(TxoSID, OpenAssetRecord)>> {
+    let mut sum = 0;
+    let n = pool
+        .iter()
+        .take_while(|(_, oar)| {
+            let enough = sum >= fee;
+            sum += oar.amount;
+            !enough
+        })
+        .count();
+    alt!(sum >= fee, Some(pool.drain(..n).collect()), None)
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        rand_chacha::ChaChaRng,
+        rand_core::SeedableRng,
+        zei::{
+            setup::PublicParams,
+            xfr::asset_record::{build_blind_asset_record, open_blind_asset_record},
+        },
+    };
+
+    fn records(
+        kp: &XfrKeyPair,
+        code: AssetTypeCode,
+        amounts: &[u64],
+        first_sid: u64,
+    ) -> Vec<(TxoSID, OpenAssetRecord)> {
+        let mut prng = ChaChaRng::from_entropy();
+        let params = PublicParams::default();
+        amounts
+            .iter()
+            .enumerate()
+            .map(|(i, am)| {
+                let template = AssetRecordTemplate::with_no_asset_tracing(
+                    *am,
+                    code.val,
+                    AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType,
+                    kp.get_pk(),
+                );
+                let (bar, _, memo) = build_blind_asset_record(
+                    &mut prng,
+                    &params.pc_gens,
+                    &template,
+                    vec![],
+                );
+                let oar = pnk!(open_blind_asset_record(&bar, &memo, kp));
+                (TxoSID(first_sid + i as u64), oar)
+            })
+            .collect()
+    }
+
+    fn fra() -> AssetTypeCode {
+        AssetTypeCode {
+            val: ASSET_TYPE_FRA,
+        }
+    }
+
+    #[test]
+    fn test_chunks() {
+        let kp = XfrKeyPair::generate(&mut ChaChaRng::from_entropy());
+        let inputs = records(&kp, fra(), &[1; 7], 0);
+
+        let res = chunks(inputs.clone(), false, Some(3));
+        assert_eq!(res.iter().map(|c| c.len()).collect::<Vec<_>>(), vec![3, 3]);
+        // a batch of one input is dropped
+        assert_eq!(TxoSID(5), res[1].last().unwrap().0);
+
+        // at least two inputs per batch
+        assert_eq!(3, chunks(inputs.clone(), false, Some(1)).len());
+        assert_eq!(1, chunks(inputs, true, None).len());
+    }
+
+    #[test]
+    fn test_take_fee_inputs() {
+        let kp = XfrKeyPair::generate(&mut ChaChaRng::from_entropy());
+        let mut pool = records(&kp, fra(), &[50, 30, 20], 0);
+
+        let taken = take_fee_inputs(&mut pool, 60).unwrap();
+        assert_eq!(
+            taken.iter().map(|(sid, _)| *sid).collect::<Vec<_>>(),
+            vec![TxoSID(0), TxoSID(1)]
+        );
+        assert_eq!(1, pool.len());
+
+        assert!(take_fee_inputs(&mut pool, 21).is_none());
+        assert_eq!(1, pool.len());
+        assert_eq!(1, take_fee_inputs(&mut pool, 20).unwrap().len());
+        assert!(pool.is_empty());
+    }
+
+    #[test]
+    fn test_plan() {
+        let kp = XfrKeyPair::generate(&mut ChaChaRng::from_entropy());
+        let code = AssetTypeCode::gen_random();
+        let fee = min_transfer_fee(i64::MAX, 3, 0);
+
+        let mut owned = records(&kp, fra(), &[fee, fee, 5, 5, 5], 0);
+        owned.append(&mut records(&kp, code, &[1; 6], 10));
+
+        // the custom asset is paid by the two largest FRAs, one per batch
+        let Plan { batches, unpaid } =
+            plan_records(owned.clone(), Some(code), Some(3), i64::MAX);
+        assert!(unpaid.is_empty());
+        assert_eq!(2, batches.len());
+        for b in batches.iter() {
+            assert_eq!(code, b.asset_type);
+            assert_eq!(3, b.merged());
+            assert_eq!(1, b.fee_inputs.len());
+        }
+
+        // the FRA pool runs out at the third batch
+        let Plan { batches, unpaid } =
+            plan_records(owned.clone(), Some(code), Some(2), i64::MAX);
+        assert_eq!(2, batches.len());
+        assert_eq!(vec![(code, 2)], unpaid);
+
+        // the small FRAs are not worth merging with the fee
+        let Plan { batches, unpaid } = plan_records(owned, Some(fra()), None, i64::MAX);
+        assert!(unpaid.is_empty());
+        assert_eq!(1, batches.len());
+        assert_eq!(5, batches[0].inputs.len());
+        assert_eq!(2 * fee + 15 - batches[0].fee, batches[0].merged());
+    }
+}
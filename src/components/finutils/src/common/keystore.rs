@@ -0,0 +1,253 @@
+//!
+//! # Keystore of FN
+//!
+//! Multiple named accounts, each of them is derived from a mnemonic
+//! with a BIP-44 path, or imported from a raw secret key.
+//! The secrets are encrypted with a password by PBKDF2 + AES-256-GCM.
+//!
+
+use {
+    super::CFG_PATH,
+    crate::crypto::{decryption_pbkdf2_aes256gcm, encryption_pbkdf2_aes256gcm},
+    globutils::wallet::{self, BipPath},
+    lazy_static::lazy_static,
+    ruc::*,
+    serde::{Deserialize, Serialize},
+    std::{env, fs, io, sync::Mutex},
+    zei::xfr::sig::{XfrKeyPair, XfrPublicKey},
+};
+
+/// Coin type of FRA in BIP-44 paths, the same as `wallet::restore_keypair_from_mnemonic_default`.
+pub const FRA_COIN_TYPE: u32 = 917;
+
+/// The password of the keystore will be read from this env var if it is set,
+/// otherwise it will be read from the terminal.
+pub const PASSWORD_ENV: &str = "FN_KEYSTORE_PASSWORD";
+
+const PBKDF2_ITERATIONS: u32 = 100_000;
+
+lazy_static! {
+    static ref KEYSTORE_PATH: String = format!("{}/keystore", &*CFG_PATH);
+    static ref DEFAULT_ACCOUNT_FILE: String = format!("{}/default", &*KEYSTORE_PATH);
+    static ref SELECTED_ACCOUNT: Mutex<Option<String>> = Mutex::new(None);
+}
+
+/// The secret of an account.
+#[derive(Serialize, Deserialize)]
+enum Secret {
+    Mnemonic(String),
+    SecKey(String),
+}
+
+/// An account of the keystore, as it is saved on disk.
+#[derive(Serialize, Deserialize)]
+pub struct Account {
+    /// Name of the account
+    pub name: String,
+    /// Bech32-encoded public key of the account
+    pub pubkey: String,
+    /// BIP-44 path `(account, change, address)` of the key,
+    /// `None` if the account is imported from a secret key
+    pub path: Option<(u32, u32, u32)>,
+    iterations: u32,
+    // base64(salt || iv || ciphertext)
+    cipher: String,
+}
+
+impl Account {
+    fn new(name: &str, secret: &Secret, path: Option<(u32, u32, u32)>) -> Result<Self> {
+        check_name(name).c(d!())?;
+        let kp = restore_keypair(secret, path).c(d!())?;
+        let password = read_password(&format!("New password of `{name}`: ")).c(d!())?;
+        let plaintext = serde_json::to_vec(secret).c(d!())?;
+
+        Ok(Account {
+            name: name.to_owned(),
+            pubkey: wallet::public_key_to_bech32(kp.get_pk_ref()),
+            path,
+            iterations: PBKDF2_ITERATIONS,
+            cipher: base64::encode(
+                encryption_pbkdf2_aes256gcm(&plaintext, &password, PBKDF2_ITERATIONS)
+                    .c(d!())?,
+            ),
+        })
+    }
+
+    /// Decrypt the secret key of this account.
+    pub fn unlock(&self, password: &str) -> Result<XfrKeyPair> {
+        let data = base64::decode(&self.cipher).c(d!())?;
+        let plaintext = decryption_pbkdf2_aes256gcm(&data, password, self.iterations)
+            .c(d!(format!("Wrong password of `{}`", self.name)))?;
+        let secret = serde_json::from_slice::<Secret>(&plaintext).c(d!())?;
+        restore_keypair(&secret, self.path).c(d!())
+    }
+
+    /// The public key of this account.
+    pub fn get_pk(&self) -> Result<XfrPublicKey> {
+        wallet::public_key_from_bech32(&self.pubkey).c(d!())
+    }
+
+    fn save(&self) -> Result<()> {
+        fs::create_dir_all(&*KEYSTORE_PATH).c(d!())?;
+        let path = account_file(&self.name);
+        if fs::metadata(&path).is_ok() {
+            return Err(eg!(format!("Account `{}` already exists", self.name)));
+        }
+        fs::write(&path, serde_json::to_vec_pretty(self).c(d!())?).c(d!())?;
+        if get_default_account().c(d!())?.is_none() {
+            select_default_account(&self.name).c(d!())?;
+        }
+        Ok(())
+    }
+}
+
+/// Create a new account with a random mnemonic, the mnemonic is returned for backup.
+pub fn create_account(name: &str, account_index: u32) -> Result<String> {
+    let mnemonic = wallet::generate_mnemonic_custom(24, "en").c(d!())?;
+    Account::new(
+        name,
+        &Secret::Mnemonic(mnemonic.clone()),
+        Some((account_index, 0, 0)),
+    )
+    .and_then(|a| a.save())
+    .c(d!())
+    .map(|_| mnemonic)
+}
+
+/// Import an account from a mnemonic.
+pub fn import_mnemonic(name: &str, phrase: &str, account_index: u32) -> Result<()> {
+    Account::new(
+        name,
+        &Secret::Mnemonic(phrase.trim().to_owned()),
+        Some((account_index, 0, 0)),
+    )
+    .and_then(|a| a.save())
+    .c(d!())
+}
+
+/// Import an account from a base64-encoded secret key.
+pub fn import_seckey(name: &str, seckey: &str) -> Result<()> {
+    Account::new(name, &Secret::SecKey(seckey.trim().to_owned()), None)
+        .and_then(|a| a.save())
+        .c(d!())
+}
+
+/// Load an account by its name.
+pub fn get_account(name: &str) -> Result<Account> {
+    fs::read(account_file(name))
+        .c(d!(format!("Account `{name}` not found")))
+        .and_then(|b| serde_json::from_slice(&b).c(d!()))
+}
+
+/// All the accounts of the keystore, sorted by their names.
+pub fn list_accounts() -> Result<Vec<Account>> {
+    let mut accounts = vec![];
+    if let Ok(dir) = fs::read_dir(&*KEYSTORE_PATH) {
+        for entry in dir {
+            let path = entry.c(d!())?.path();
+            if path.extension().map(|e| e == "json").unwrap_or(false) {
+                let account = fs::read(&path)
+                    .c(d!())
+                    .and_then(|b| serde_json::from_slice::<Account>(&b).c(d!()))?;
+                accounts.push(account);
+            }
+        }
+    }
+    accounts.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(accounts)
+}
+
+/// Use `name` as the default account of FN.
+pub fn select_default_account(name: &str) -> Result<()> {
+    get_account(name).c(d!())?;
+    fs::write(&*DEFAULT_ACCOUNT_FILE, name).c(d!())
+}
+
+/// The default account of FN, if any.
+pub fn get_default_account() -> Result<Option<String>> {
+    match fs::read_to_string(&*DEFAULT_ACCOUNT_FILE) {
+        Ok(name) => Ok(Some(name.trim().to_owned())),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).c(d!()),
+    }
+}
+
+/// Use `name` instead of the default account in this run of FN, i.e. `fn --account <name>`.
+pub fn use_account(name: &str) {
+    *SELECTED_ACCOUNT.lock().unwrap() = Some(name.to_owned());
+}
+
+/// The account selected by `--account <name>` in this run of FN, if any.
+pub fn selected_account() -> Option<String> {
+    SELECTED_ACCOUNT.lock().unwrap().clone()
+}
+
+/// Decrypt the key of an account, with a password from the env or the terminal.
+pub fn unlock_account(name: &str) -> Result<XfrKeyPair> {
+    let account = get_account(name).c(d!())?;
+    let password = read_password(&format!("Password of `{name}`: ")).c(d!())?;
+    account.unlock(&password).c(d!())
+}
+
+fn restore_keypair(
+    secret: &Secret,
+    path: Option<(u32, u32, u32)>,
+) -> Result<XfrKeyPair> {
+    match (secret, path) {
+        (Secret::Mnemonic(phrase), Some((account, change, address))) => {
+            wallet::restore_keypair_from_mnemonic_bip44(
+                phrase,
+                "en",
+                &BipPath::new(FRA_COIN_TYPE, account, change, address),
+            )
+            .c(d!("Invalid mnemonic"))
+        }
+        (Secret::SecKey(sk), None) => {
+            wallet::restore_keypair_from_seckey_base64(sk).c(d!("Invalid secret key"))
+        }
+        _ => Err(eg!("Corrupted account")),
+    }
+}
+
+fn account_file(name: &str) -> String {
+    format!("{}/{}.json", &*KEYSTORE_PATH, name)
+}
+
+fn check_name(name: &str) -> Result<()> {
+    if !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        Ok(())
+    } else {
+        Err(eg!(
+            "Account name should only contain letters, digits, `-` and `_`"
+        ))
+    }
+}
+
+fn read_password(prompt: &str) -> Result<String> {
+    if let Ok(password) = env::var(PASSWORD_ENV) {
+        return Ok(password);
+    }
+
+    let password = rpassword::prompt_password(prompt).c(d!())?;
+    alt!(
+        password.is_empty(),
+        Err(eg!("Empty password")),
+        Ok(password)
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_check_name() {
+        assert!(check_name("alice_1-a").is_ok());
+        assert!(check_name("").is_err());
+        assert!(check_name("../a").is_err());
+    }
+}
@@ -6,13 +6,24 @@
 //! This module is the library part of FN.
 //!
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod consolidate;
+
 #[cfg(not(target_arch = "wasm32"))]
 pub mod dev;
 
 #[cfg(not(target_arch = "wasm32"))]
 pub mod ddev;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod keystore;
+
 pub mod evm;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod offline;
+
+pub mod trace;
 pub mod utils;
 
 use {
@@ -22,12 +33,14 @@ use {
             get_evm_staking_address, get_validator_memo_and_rate, mapping_address,
         },
     },
+    credentials::{CredUserSecretKey, Credential},
     globutils::wallet,
     lazy_static::lazy_static,
     ledger::{
         data_model::{
-            gen_random_keypair, AssetRules, AssetTypeCode, AssetTypePrefix, Transaction,
-            BLACK_HOLE_PUBKEY_STAKING,
+            gen_random_keypair, AssetRules, AssetTypeCode, AssetTypePrefix,
+            CredentialProof, CredentialRule, Transaction, TxnSID, TxoRef,
+            ASSET_TYPE_FRA, BLACK_HOLE_PUBKEY_STAKING,
         },
         staking::{
             check_delegation_amount, td_addr_to_bytes, td_pubkey_to_td_addr,
@@ -35,6 +48,8 @@ use {
             TendermintAddrRef,
         },
     },
+    rand_chacha::ChaChaRng,
+    rand_core::SeedableRng,
     ruc::*,
     sha3::{Digest, Keccak256},
     std::{env, fs, str::FromStr},
@@ -441,6 +456,93 @@ pub fn transfer_asset(
     .c(d!())
 }
 
+/// Same as `transfer_asset`, but with a memo that only the receiver can read
+pub fn transfer_asset_with_encrypted_memo(
+    owner_sk: Option<&str>,
+    target_addr: XfrPublicKey,
+    memo: &str,
+    token_code: Option<AssetTypeCode>,
+    am: &str,
+    confidential_am: bool,
+    confidential_ty: bool,
+) -> Result<()> {
+    let from = restore_keypair_from_str_with_default(owner_sk)?;
+    let am = am.parse::<u64>().c(d!("'amount' must be an integer"))?;
+
+    utils::transfer_with_encrypted_memo(
+        &from,
+        &target_addr,
+        am,
+        token_code,
+        confidential_am,
+        confidential_ty,
+        memo,
+    )
+    .c(d!())
+}
+
+/// Same as `transfer_asset`, but with the proofs that the receiver holds
+/// the credential required by the asset, see `CredentialProof::new`
+pub fn transfer_asset_with_credential_proofs(
+    owner_sk: Option<&str>,
+    target_addr: XfrPublicKey,
+    proofs: Vec<CredentialProof>,
+    token_code: Option<AssetTypeCode>,
+    am: &str,
+    confidential_am: bool,
+) -> Result<()> {
+    let from = restore_keypair_from_str_with_default(owner_sk)?;
+    let am = am.parse::<u64>().c(d!("'amount' must be an integer"))?;
+
+    utils::transfer_with_credential_proofs(
+        &from,
+        &target_addr,
+        am,
+        token_code,
+        confidential_am,
+        proofs,
+    )
+    .c(d!())
+}
+
+/// Prove that `address`, default to the current account,
+/// holds `credential`, with the attributes in `reveal_fields` revealed
+pub fn gen_credential_proof(
+    credential: &Credential,
+    user_sk: &CredUserSecretKey,
+    address: Option<XfrPublicKey>,
+    reveal_fields: &[String],
+) -> Result<CredentialProof> {
+    let address = match address {
+        Some(pk) => pk,
+        None => get_keypair().c(d!())?.get_pk(),
+    };
+    CredentialProof::new(
+        &mut ChaChaRng::from_entropy(),
+        user_sk,
+        credential,
+        &address,
+        reveal_fields,
+    )
+    .c(d!())
+}
+
+/// Show the encrypted memos sent to a findora account by the transaction `txn_sid`
+pub fn show_encrypted_memos(sk_str: Option<&str>, txn_sid: usize) -> Result<()> {
+    let kp = restore_keypair_from_str_with_default(sk_str)?;
+    let tx = trace::get_transaction(TxnSID(txn_sid)).c(d!())?;
+
+    let memos = tx.decrypt_memos(&kp);
+    if memos.is_empty() {
+        println!("No encrypted memo for this wallet");
+    }
+    for memo in memos {
+        println!("{memo}");
+    }
+
+    Ok(())
+}
+
 #[allow(missing_docs)]
 pub fn transfer_asset_x(
     kp: &XfrKeyPair,
@@ -522,8 +624,30 @@ pub fn get_serv_addr() -> Result<&'static str> {
     }
 }
 
-/// Get keypair from config file
+/// The keystore account to sign with, `--account` or the default account,
+/// it's an error if the default account and the mnemonic of `fn setup` both exist.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn keystore_account() -> Result<Option<String>> {
+    if let Some(name) = keystore::selected_account() {
+        return Ok(Some(name));
+    }
+    match keystore::get_default_account().c(d!())? {
+        Some(name) if MNEMONIC.is_some() => Err(eg!(format!(
+            "Both the keystore account `{}` and the mnemonic in {} exist, select the account with `--account`, or remove either of them",
+            name, &*MNEMONIC_FILE
+        ))),
+        name => Ok(name),
+    }
+}
+
+/// Get keypair from the keystore(`--account` or the default account),
+/// or from config file
 pub fn get_keypair() -> Result<XfrKeyPair> {
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(name) = keystore_account().c(d!())? {
+        return keystore::unlock_account(&name).c(d!());
+    }
+
     if let Some(m_path) = MNEMONIC.as_ref() {
         fs::read_to_string(m_path)
             .c(d!("can not read mnemonic from 'owner-mnemonic-path'"))
@@ -622,17 +746,78 @@ pub fn show_account(sk_str: Option<&str>, _asset: Option<&str>) -> Result<()> {
     //
     // println!("{}: {}", asset.unwrap_or("FRA"), balance);
 
-    let res = utils::get_asset_all(&kp)?;
+    let res = utils::get_asset_all_by_confidentiality(&kp)?;
 
-    for (k, v) in res {
+    for (k, (nonconfidential, confidential)) in res {
         let codes = k.to_base64();
 
-        println!("{codes}: {v}");
+        println!(
+            "{codes}: {} (non-confidential: {nonconfidential}, confidential: {confidential})",
+            nonconfidential + confidential
+        );
     }
 
     Ok(())
 }
 
+/// Convert `amount` of an asset (FRA by default) of a findora account
+/// into records with confidential amount and asset type
+pub fn shield(
+    sk_str: Option<&str>,
+    amount: u64,
+    token_code: Option<AssetTypeCode>,
+) -> Result<()> {
+    restore_keypair_from_str_with_default(sk_str)
+        .c(d!())
+        .and_then(|kp| convert_confidentiality_x(&kp, amount, token_code, true).c(d!()))
+}
+
+/// Convert `amount` of the confidential records of an asset (FRA by default)
+/// of a findora account into nonconfidential ones
+pub fn unshield(
+    sk_str: Option<&str>,
+    amount: u64,
+    token_code: Option<AssetTypeCode>,
+) -> Result<()> {
+    restore_keypair_from_str_with_default(sk_str)
+        .c(d!())
+        .and_then(|kp| convert_confidentiality_x(&kp, amount, token_code, false).c(d!()))
+}
+
+/// Shield (`to_confidential`) or unshield `amount` of an asset of `kp`,
+/// SEE [add_operation_convert_confidentiality](crate::txn_builder::TransactionBuilder::add_operation_convert_confidentiality)
+pub fn convert_confidentiality_x(
+    kp: &XfrKeyPair,
+    amount: u64,
+    token_code: Option<AssetTypeCode>,
+    to_confidential: bool,
+) -> Result<()> {
+    let candidates = utils::get_owned_records(kp)
+        .c(d!())?
+        .into_iter()
+        .map(|(sid, oar)| (TxoRef::Absolute(sid), oar))
+        .collect();
+    let code = token_code.unwrap_or(AssetTypeCode {
+        val: ASSET_TYPE_FRA,
+    });
+
+    let mut builder = utils::new_tx_builder().c(d!())?;
+    builder
+        .add_operation_convert_confidentiality(
+            kp,
+            candidates,
+            code,
+            amount,
+            to_confidential,
+            utils::get_fee_height().c(d!())?,
+        )
+        .c(d!())?;
+    let mut tx = builder.take_transaction();
+    tx.sign_to_map(kp);
+
+    utils::send_tx(&tx).c(d!())
+}
+
 #[inline(always)]
 #[allow(missing_docs)]
 pub fn delegate(sk_str: Option<&str>, amount: u64, validator: &str) -> Result<()> {
@@ -744,6 +929,7 @@ pub fn create_asset(
     max_units: Option<u64>,
     transferable: bool,
     token_code: Option<&str>,
+    credential_rule: Option<CredentialRule>,
 ) -> Result<()> {
     let kp = restore_keypair_from_str_with_default(sk_str)?;
 
@@ -754,11 +940,19 @@ pub fn create_asset(
             .c(d!("invalid asset code"))?
     };
 
-    create_asset_x(&kp, memo, decimal, max_units, transferable, Some(code))
-        .c(d!())
-        .map(|code| {
-            println!("type: {}", code.to_base64());
-        })
+    create_asset_x(
+        &kp,
+        memo,
+        decimal,
+        max_units,
+        transferable,
+        Some(code),
+        credential_rule,
+    )
+    .c(d!())
+    .map(|code| {
+        println!("type: {}", code.to_base64());
+    })
 }
 
 #[allow(missing_docs)]
@@ -769,6 +963,7 @@ pub fn create_asset_x(
     max_units: Option<u64>,
     transferable: bool,
     code: Option<AssetTypeCode>,
+    credential_rule: Option<CredentialRule>,
 ) -> Result<AssetTypeCode> {
     let code = code.unwrap_or_else(AssetTypeCode::gen_random);
     let asset_code = AssetTypeCode::from_prefix_and_raw_asset_type_code(
@@ -780,6 +975,7 @@ pub fn create_asset_x(
     rules.set_decimals(decimal).c(d!())?;
     rules.set_max_units(max_units);
     rules.set_transferable(transferable);
+    rules.set_credential_rule(credential_rule);
 
     let mut builder = utils::new_tx_builder().c(d!())?;
     builder
@@ -0,0 +1,510 @@
+//!
+//! Offline signing of FN transactions
+//!
+//! An online machine builds an unsigned transaction, which contains the
+//! `TransactionBuilder` of it and all the UTXOs (with their owner memos) of the signer,
+//! an air-gapped machine signs it, then the online machine broadcasts it.
+//!
+
+use {
+    super::{
+        convert_commission_rate, get_keypair, get_serv_addr, get_td_privkey,
+        get_td_pubkey, restore_keypair_from_str_with_default, utils,
+    },
+    crate::txn_builder::TransactionBuilder,
+    globutils::wallet,
+    ledger::{
+        data_model::{
+            min_transfer_fee, AssetTypeCode, Operation, Transaction, TxnSimulation,
+            TxoSID, Utxo, ASSET_TYPE_FRA, BLACK_HOLE_PUBKEY, BLACK_HOLE_PUBKEY_STAKING,
+        },
+        staking::check_delegation_amount,
+    },
+    ruc::*,
+    serde::{Deserialize, Serialize},
+    std::fs,
+    zei::xfr::{
+        asset_record::AssetRecordType,
+        sig::{XfrKeyPair, XfrPublicKey},
+        structs::OwnerMemo,
+    },
+};
+
+/// The operation of an unsigned transaction.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum UnsignedOp {
+    /// Transfer assets to some receivers
+    Transfer {
+        /// `(receiver, amount, memo)`
+        targets: Vec<(XfrPublicKey, u64, Option<String>)>,
+        /// `None` for FRA
+        token_code: Option<AssetTypeCode>,
+        #[allow(missing_docs)]
+        confidential_am: bool,
+        #[allow(missing_docs)]
+        confidential_ty: bool,
+    },
+    /// Delegate FRAs to a validator
+    Delegate {
+        #[allow(missing_docs)]
+        amount: u64,
+        /// Tendermint address of the validator
+        validator: String,
+    },
+    /// Claim rewards
+    Claim {
+        /// Hex-encoded tendermint address of the validator
+        td_addr: String,
+        /// `None` to claim all the rewards
+        amount: Option<u64>,
+    },
+    /// Stake FRAs to make the signer a validator,
+    /// the tendermint key of the validator is needed when signing
+    Stake {
+        #[allow(missing_docs)]
+        amount: u64,
+        #[allow(missing_docs)]
+        commission_rate: [u64; 2],
+        #[allow(missing_docs)]
+        memo: Option<String>,
+        /// Tendermint public key of the validator
+        td_pubkey: Vec<u8>,
+    },
+}
+
+impl UnsignedOp {
+    /// A staking operation of the local validator, like `fn stake`.
+    pub fn new_stake(
+        amount: u64,
+        commission_rate: &str,
+        memo: Option<&str>,
+    ) -> Result<Self> {
+        let commission_rate = commission_rate
+            .parse::<f64>()
+            .c(d!("commission rate must be a float number"))
+            .and_then(|cr| convert_commission_rate(cr).c(d!()))?;
+        Ok(UnsignedOp::Stake {
+            amount,
+            commission_rate,
+            memo: memo.map(|m| m.to_owned()),
+            td_pubkey: get_td_pubkey().c(d!())?,
+        })
+    }
+}
+
+/// An unsigned transaction, with everything needed to sign it offline.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct UnsignedTx {
+    /// The builder of the transaction, with the no-replay token from the network,
+    /// the operations are added when signing as all of them need the key of the signer
+    pub builder: TransactionBuilder,
+    /// The height that the ledger will check the fee at
+    pub height: i64,
+    /// The owner of the inputs, whose key is needed to sign the transaction
+    pub signer: XfrPublicKey,
+    /// The UTXOs of the signer, with their owner memos
+    pub utxos: Vec<(TxoSID, Utxo, Option<OwnerMemo>)>,
+    #[allow(missing_docs)]
+    pub op: UnsignedOp,
+}
+
+impl UnsignedTx {
+    /// Query the `seq_id` and the UTXOs of `signer` from the network.
+    pub fn build(signer: XfrPublicKey, op: UnsignedOp) -> Result<Self> {
+        if let UnsignedOp::Delegate { amount, .. } | UnsignedOp::Stake { amount, .. } =
+            &op
+        {
+            check_delegation_amount(*amount, false).c(d!())?;
+        }
+        if let UnsignedOp::Claim { td_addr, .. } = &op {
+            hex::decode(td_addr).c(d!("invalid tendermint address"))?;
+        }
+
+        let mut utxos = utils::get_owned_utxos_x(None, &signer)
+            .c(d!())?
+            .into_iter()
+            .map(|(sid, (utxo, memo))| (sid, utxo, memo))
+            .collect::<Vec<_>>();
+        utxos.sort_unstable_by_key(|(sid, _, _)| *sid);
+
+        Ok(UnsignedTx {
+            builder: TransactionBuilder::from_seq_id(utils::get_seq_id().c(d!())?),
+            height: utils::get_fee_height().c(d!())?,
+            signer,
+            utxos,
+            op,
+        })
+    }
+
+    /// Sign the transaction with the key of the signer, no network access is needed.
+    pub fn sign(&self, kp: &XfrKeyPair) -> Result<Transaction> {
+        if kp.get_pk_ref() != &self.signer {
+            return Err(eg!("The key does not match the signer of the transaction"));
+        }
+
+        let utxos = || {
+            self.utxos
+                .iter()
+                .cloned()
+                .map(|(sid, utxo, memo)| (sid, (utxo, memo)))
+        };
+        let principal_op = |amount| {
+            utils::gen_transfer_op_with_utxos(
+                kp,
+                self.height,
+                utxos(),
+                vec![(&BLACK_HOLE_PUBKEY_STAKING, amount, None)],
+                None,
+                true,
+                0,
+                false,
+                false,
+                Some(AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType),
+            )
+            .c(d!())
+        };
+
+        let mut builder = self.builder.clone();
+        match &self.op {
+            UnsignedOp::Transfer {
+                targets,
+                token_code,
+                confidential_am,
+                confidential_ty,
+            } => {
+                let op = utils::gen_transfer_op_with_utxos(
+                    kp,
+                    self.height,
+                    utxos(),
+                    targets
+                        .iter()
+                        .map(|(pk, am, memo)| (pk, *am, memo.clone()))
+                        .collect(),
+                    *token_code,
+                    true,
+                    0,
+                    *confidential_am,
+                    *confidential_ty,
+                    None,
+                )
+                .c(d!())?;
+                builder.add_operation(op);
+            }
+            UnsignedOp::Delegate { amount, validator } => {
+                builder.add_operation(principal_op(*amount).c(d!())?);
+                builder.add_operation_delegation(kp, *amount, validator.to_owned());
+            }
+            UnsignedOp::Claim { td_addr, amount } => {
+                let td_addr = hex::decode(td_addr).c(d!())?;
+                let fee_op = utils::gen_transfer_op_with_utxos(
+                    kp,
+                    self.height,
+                    utxos(),
+                    vec![],
+                    None,
+                    true,
+                    0,
+                    false,
+                    false,
+                    None,
+                )
+                .c(d!())?;
+                builder.add_operation(fee_op);
+                builder.add_operation_claim(Some(td_addr), kp, *amount);
+            }
+            UnsignedOp::Stake {
+                amount,
+                commission_rate,
+                memo,
+                td_pubkey,
+            } => {
+                let vkp = get_td_privkey().c(d!())?;
+                builder
+                    .add_operation_staking(
+                        kp,
+                        *amount,
+                        &vkp,
+                        td_pubkey.clone(),
+                        *commission_rate,
+                        memo.clone(),
+                    )
+                    .c(d!())?;
+                builder.add_operation(principal_op(*amount).c(d!())?);
+            }
+        }
+
+        let mut tx = builder.take_transaction();
+        tx.sign_to_map(kp);
+
+        Ok(tx)
+    }
+
+    /// Human-readable description of the transaction.
+    pub fn describe(&self) -> String {
+        let mut lines = vec![
+            format!(
+                "Unsigned transaction, seq_id: {}",
+                self.builder.transaction().body.no_replay_token.get_seq_id()
+            ),
+            format!("Signer: {}", wallet::public_key_to_bech32(&self.signer)),
+            format!("Inputs available: {} UTXOs", self.utxos.len()),
+        ];
+        match &self.op {
+            UnsignedOp::Transfer {
+                targets,
+                token_code,
+                confidential_am,
+                confidential_ty,
+            } => {
+                let asset = token_code
+                    .map(|c| c.to_base64())
+                    .unwrap_or_else(|| "FRA".to_owned());
+                lines.push(format!(
+                    "Transfer {asset} (confidential amount: {confidential_am}, confidential type: {confidential_ty})"
+                ));
+                for (pk, am, memo) in targets {
+                    lines.push(format!(
+                        "  -> {}: {}{}",
+                        wallet::public_key_to_bech32(pk),
+                        am,
+                        memo.as_ref().map(|m| format!(" ({m})")).unwrap_or_default()
+                    ));
+                }
+            }
+            UnsignedOp::Delegate { amount, validator } => {
+                lines.push(format!("Delegate {amount} FRA units to {validator}"));
+            }
+            UnsignedOp::Claim { td_addr, amount } => {
+                lines.push(format!(
+                    "Claim {} rewards from {td_addr}",
+                    amount
+                        .map(|am| am.to_string())
+                        .unwrap_or_else(|| "all".to_owned())
+                ));
+            }
+            UnsignedOp::Stake {
+                amount,
+                commission_rate,
+                memo,
+                ..
+            } => {
+                lines.push(format!(
+                    "Stake {amount} FRA units, commission rate: {}/{}, memo: {}",
+                    commission_rate[0],
+                    commission_rate[1],
+                    memo.as_deref().unwrap_or("")
+                ));
+            }
+        }
+        lines.push(format!("Fee: {} FRA units", self.estimate_fee()));
+        lines.join("\n")
+    }
+
+    /// The fee that will be paid when the transaction is signed.
+    pub fn estimate_fee(&self) -> u64 {
+        // outputs: the fee, the change of FRA and the others below
+        let (outputs, memos) = match &self.op {
+            UnsignedOp::Transfer {
+                targets,
+                token_code,
+                ..
+            } => (
+                targets.len() + alt!(token_code.is_some(), 1, 0),
+                targets.iter().filter(|(_, _, m)| m.is_some()).count(),
+            ),
+            UnsignedOp::Delegate { .. } | UnsignedOp::Stake { .. } => (1, 0),
+            UnsignedOp::Claim { .. } => (0, 0),
+        };
+        min_transfer_fee(self.height, outputs as u64 + 2, memos as u64)
+    }
+}
+
+/// Human-readable description of a signed transaction.
+pub fn describe_transaction(tx: &Transaction) -> String {
+    let mut lines = vec![format!(
+        "Signed transaction, seq_id: {}, signatures: {}",
+        tx.body.no_replay_token.get_seq_id(),
+        tx.pubkey_sign_map.len()
+    )];
+    let mut fee = 0;
+
+    for (idx, op) in tx.body.operations.iter().enumerate() {
+        let name = match op {
+            Operation::TransferAsset(_) => "TransferAsset",
+            Operation::IssueAsset(_) => "IssueAsset",
+            Operation::DefineAsset(_) => "DefineAsset",
+            Operation::UpdateMemo(_) => "UpdateMemo",
+            Operation::UpdateStaker(_) => "UpdateStaker",
+            Operation::Delegation(_) => "Delegation",
+            Operation::UnDelegation(_) => "UnDelegation",
+            Operation::Claim(_) => "Claim",
+            Operation::UpdateValidator(_) => "UpdateValidator",
+            Operation::Governance(_) => "Governance",
+            Operation::FraDistribution(_) => "FraDistribution",
+            Operation::MintFra(_) => "MintFra",
+            Operation::ConvertAccount(_) => "ConvertAccount",
+            Operation::ReplaceStaker(_) => "ReplaceStaker",
+            Operation::UpdateIssuer(_) => "UpdateIssuer",
+        };
+        lines.push(format!("Operation {idx}: {name}"));
+
+        if let Operation::TransferAsset(xfr) = op {
+            lines.push(format!("  inputs: {}", xfr.body.inputs.len()));
+            for out in xfr.body.transfer.outputs.iter() {
+                let asset_type = out.asset_type.get_asset_type();
+                let amount = out.amount.get_amount();
+                if out.public_key == *BLACK_HOLE_PUBKEY
+                    && asset_type == Some(ASSET_TYPE_FRA)
+                {
+                    fee += amount.unwrap_or(0);
+                }
+                let receiver = if out.public_key == *BLACK_HOLE_PUBKEY {
+                    "fee".to_owned()
+                } else if out.public_key == *BLACK_HOLE_PUBKEY_STAKING {
+                    "staking".to_owned()
+                } else {
+                    wallet::public_key_to_bech32(&out.public_key)
+                };
+                lines.push(format!(
+                    "  -> {}: {} {}",
+                    receiver,
+                    amount
+                        .map(|am| am.to_string())
+                        .unwrap_or_else(|| "<confidential>".to_owned()),
+                    match asset_type {
+                        Some(val) if val == ASSET_TYPE_FRA => "FRA".to_owned(),
+                        Some(val) => AssetTypeCode { val }.to_base64(),
+                        None => "<confidential>".to_owned(),
+                    }
+                ));
+            }
+        }
+    }
+    lines.push(format!("Fee: {fee} FRA units"));
+    lines.join("\n")
+}
+
+/// The public key of the signer of `--build-only`, no secret key is needed
+/// if the address is given or a keystore account is used.
+pub fn get_signer(addr: Option<&str>, sk_str: Option<&str>) -> Result<XfrPublicKey> {
+    if let Some(addr) = addr {
+        return wallet::public_key_from_bech32(addr)
+            .c(d!("invalid wallet address"))
+            .or_else(|e| wallet::public_key_from_base64(addr).c(d!(e)));
+    }
+    if sk_str.is_none() {
+        if let Some(name) = super::keystore_account().c(d!())? {
+            return super::keystore::get_account(&name)
+                .c(d!())
+                .and_then(|a| a.get_pk().c(d!()));
+        }
+        return get_keypair().c(d!()).map(|kp| kp.get_pk());
+    }
+    restore_keypair_from_str_with_default(sk_str)
+        .c(d!())
+        .map(|kp| kp.get_pk())
+}
+
+/// Build an unsigned transaction and save it to `path`.
+pub fn build_to_file(signer: XfrPublicKey, op: UnsignedOp, path: &str) -> Result<()> {
+    let tx = UnsignedTx::build(signer, op).c(d!())?;
+    fs::write(path, serde_json::to_vec_pretty(&tx).c(d!())?).c(d!())?;
+    println!(
+        "{}\n\nUnsigned transaction saved to {}",
+        tx.describe(),
+        path
+    );
+    Ok(())
+}
+
+/// Sign the unsigned transaction in `path` and save the result to `output`.
+pub fn sign_file(sk_str: Option<&str>, path: &str, output: &str) -> Result<()> {
+    let tx = fs::read(path)
+        .c(d!())
+        .and_then(|b| serde_json::from_slice::<UnsignedTx>(&b).c(d!()))?;
+    let kp = restore_keypair_from_str_with_default(sk_str).c(d!())?;
+    let signed = tx.sign(&kp).c(d!())?;
+    fs::write(output, serde_json::to_vec_pretty(&signed).c(d!())?).c(d!())?;
+    println!(
+        "{}\n\nSigned transaction saved to {}",
+        describe_transaction(&signed),
+        output
+    );
+    Ok(())
+}
+
+/// Print the content of an unsigned or a signed transaction file.
+pub fn inspect_file(path: &str) -> Result<()> {
+    let data = fs::read(path).c(d!())?;
+    if let Ok(tx) = serde_json::from_slice::<UnsignedTx>(&data) {
+        println!("{}", tx.describe());
+    } else {
+        let tx = serde_json::from_slice::<Transaction>(&data)
+            .c(d!("Neither an unsigned nor a signed transaction"))?;
+        println!("{}", describe_transaction(&tx));
+    }
+    Ok(())
+}
+
+/// Send the signed transaction in `path` to the network.
+pub fn broadcast_file(path: &str) -> Result<()> {
+    let tx = fs::read(path)
+        .c(d!())
+        .and_then(|b| serde_json::from_slice::<Transaction>(&b).c(d!()))?;
+    if tx.pubkey_sign_map.is_empty() {
+        return Err(eg!("The transaction has not been signed"));
+    }
+    utils::send_tx(&tx).c(d!())
+}
+
+/// Check the signed transaction in `path` against the state of the network,
+/// nothing will be submitted.
+pub fn simulate_file(path: &str) -> Result<()> {
+    let tx = fs::read(path)
+        .c(d!())
+        .and_then(|b| serde_json::from_slice::<Transaction>(&b).c(d!()))?;
+    let url = format!("{}:8669/simulate_transaction", get_serv_addr().c(d!())?);
+
+    let simulation = attohttpc::post(&url)
+        .header(attohttpc::header::CONTENT_TYPE, "application/json")
+        .bytes(&serde_json::to_vec(&tx).c(d!())?)
+        .send()
+        .c(d!(url))?
+        .error_for_status()
+        .c(d!(url))?
+        .bytes()
+        .c(d!(url))
+        .and_then(|b| serde_json::from_slice::<TxnSimulation>(&b).c(d!(url)))?;
+
+    match simulation {
+        TxnSimulation::Accepted(summary) => {
+            println!("The transaction would be accepted");
+            println!(
+                "Inputs spent: {}",
+                summary
+                    .inputs
+                    .iter()
+                    .map(|sid| sid.0.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            println!("Outputs created: {}", summary.outputs.len());
+            println!("Fee: {} FRA units", summary.fee);
+            for change in summary.staking_changes.iter() {
+                println!("Staking: {change}");
+            }
+            Ok(())
+        }
+        TxnSimulation::Rejected {
+            code,
+            codespace,
+            log,
+        } => Err(eg!(format!(
+            "The transaction would be rejected, code: {} ({}/{}), log: {}",
+            code.code(),
+            codespace,
+            code.name(),
+            log
+        ))),
+    }
+}
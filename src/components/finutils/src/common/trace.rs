@@ -0,0 +1,330 @@
+//!
+//! Asset tracing for compliance audits
+//!
+//! Walks all the transfers of an asset through the query server,
+//! and decrypts the tracer memos of them with the key of an asset tracer.
+//!
+
+use {
+    crate::common::get_serv_addr,
+    globutils::wallet,
+    ledger::data_model::{
+        AssetTypeCode, FinalizedTransaction, Operation, Transaction, TxnSID,
+    },
+    ruc::*,
+    serde::{Deserialize, Serialize},
+    std::collections::HashSet,
+    zei::xfr::{lib::trace_assets, structs::AssetTracerKeyPair},
+};
+
+/// A record of a transfer that has been decrypted by an asset tracer.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TracedRecord {
+    /// The transaction which contains the transfer
+    pub txn_sid: TxnSID,
+    /// Index of the transfer operation in the transaction
+    pub op_index: usize,
+    /// The bech32 address of the record owner
+    pub owner: String,
+    /// Base64-encoded asset type code
+    pub asset_type: String,
+    /// The decrypted amount
+    pub amount: u64,
+    /// The decrypted identity attributes of the owner,
+    /// empty if the tracing policy does not require an identity
+    pub identity: Vec<String>,
+}
+
+/// Report formats of `fn asset --trace`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    #[allow(missing_docs)]
+    Json,
+    #[allow(missing_docs)]
+    Csv,
+}
+
+impl ReportFormat {
+    /// Parse a format name, `json` or `csv`.
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "json" => Ok(ReportFormat::Json),
+            "csv" => Ok(ReportFormat::Csv),
+            _ => Err(eg!(format!("Unknown report format: {name}"))),
+        }
+    }
+}
+
+/// Retrieve the sids of all the transactions that transfer `code`,
+/// in the order of the ledger.
+pub fn get_related_transfers(code: &AssetTypeCode) -> Result<Vec<TxnSID>> {
+    let url = format!(
+        "{}:8667/get_related_xfrs/{}",
+        get_serv_addr().c(d!())?,
+        code.to_base64()
+    );
+
+    let mut sids = attohttpc::get(&url)
+        .send()
+        .c(d!(url))?
+        .error_for_status()
+        .c(d!(url))?
+        .bytes()
+        .c(d!(url))
+        .and_then(|b| serde_json::from_slice::<HashSet<TxnSID>>(&b).c(d!(url)))?
+        .into_iter()
+        .collect::<Vec<_>>();
+    sids.sort_unstable();
+
+    Ok(sids)
+}
+
+/// Retrieve a transaction by its sid.
+pub fn get_transaction(sid: TxnSID) -> Result<Transaction> {
+    let url = format!("{}:8668/txn_sid_light/{}", get_serv_addr().c(d!())?, sid.0);
+
+    attohttpc::get(&url)
+        .send()
+        .c(d!(url))?
+        .error_for_status()
+        .c(d!(url))?
+        .bytes()
+        .c(d!(url))
+        .and_then(|b| parse_transaction(&b).c(d!(url)))
+}
+
+/// Parse a response of `txn_sid_light`, which is a finalized transaction.
+fn parse_transaction(resp: &[u8]) -> Result<Transaction> {
+    serde_json::from_slice::<FinalizedTransaction>(resp)
+        .c(d!())
+        .map(|tx| tx.txn)
+}
+
+/// Decrypt the records of all transfers in `txn` that are traceable by `tracer`,
+/// records of other asset types than `code` are skipped,
+/// so are the transfers whose memos can not be decrypted by `tracer`.
+pub fn trace_transaction(
+    sid: TxnSID,
+    txn: &Transaction,
+    code: &AssetTypeCode,
+    tracer: &AssetTracerKeyPair,
+) -> Result<Vec<TracedRecord>> {
+    let mut records = vec![];
+
+    for (op_index, op) in txn.body.operations.iter().enumerate() {
+        if let Operation::TransferAsset(xfr) = op {
+            if xfr
+                .body
+                .transfer
+                .asset_tracing_memos
+                .iter()
+                .all(|m| m.is_empty())
+            {
+                continue;
+            }
+            let traced = match trace_assets(&xfr.body.transfer, tracer) {
+                Ok(traced) => traced,
+                // traced by other tracers
+                Err(_) => continue,
+            };
+            for (amount, asset_type, identity, owner) in traced {
+                if asset_type != code.val {
+                    continue;
+                }
+                records.push(TracedRecord {
+                    txn_sid: sid,
+                    op_index,
+                    owner: wallet::public_key_to_bech32(&owner),
+                    asset_type: AssetTypeCode { val: asset_type }.to_base64(),
+                    amount,
+                    identity: identity.iter().map(|attr| attr.to_string()).collect(),
+                });
+            }
+        }
+    }
+
+    Ok(records)
+}
+
+/// Walk all the transfers of `code` and decrypt them with `tracer`,
+/// the transactions that can not be retrieved are reported and skipped.
+///
+/// NOTE: the query server only indexes the transfers of nonconfidential asset types.
+pub fn trace_asset(
+    code: &AssetTypeCode,
+    tracer: &AssetTracerKeyPair,
+) -> Result<Vec<TracedRecord>> {
+    let mut records = vec![];
+    for sid in get_related_transfers(code).c(d!())? {
+        match get_transaction(sid).c(d!(format!("skip transaction {}", sid.0))) {
+            Ok(txn) => {
+                records.append(&mut trace_transaction(sid, &txn, code, tracer).c(d!())?)
+            }
+            Err(e) => e.print(None),
+        }
+    }
+    Ok(records)
+}
+
+/// Render the records as a report in the given format.
+pub fn gen_report(records: &[TracedRecord], format: ReportFormat) -> Result<String> {
+    match format {
+        ReportFormat::Json => serde_json::to_string_pretty(records).c(d!()),
+        ReportFormat::Csv => {
+            let mut report =
+                "txn_sid,op_index,owner,asset_type,amount,identity\n".to_owned();
+            for r in records {
+                report.push_str(&format!(
+                    "{},{},{},{},{},{}\n",
+                    r.txn_sid.0,
+                    r.op_index,
+                    csv_field(&r.owner),
+                    csv_field(&r.asset_type),
+                    r.amount,
+                    csv_field(&r.identity.join(" "))
+                ));
+            }
+            Ok(report)
+        }
+    }
+}
+
+/// Quote a CSV field if it contains any separator, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains(|c| matches!(c, ',' | '"' | '\n' | '\r')) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::txn_builder::TransferOperationBuilder,
+        ledger::data_model::{TransferType, TxoRef, TxoSID},
+        rand_chacha::ChaChaRng,
+        rand_core::SeedableRng,
+        zei::{
+            setup::PublicParams,
+            xfr::{
+                asset_record::{
+                    build_blind_asset_record, open_blind_asset_record, AssetRecordType,
+                },
+                sig::XfrKeyPair,
+                structs::{AssetRecordTemplate, TracingPolicies, TracingPolicy},
+            },
+        },
+    };
+
+    #[test]
+    fn test_parse_recorded_response() {
+        let txn = pnk!(parse_transaction(include_bytes!(
+            "testdata/txn_sid_light.json"
+        )));
+        assert_eq!(1, txn.body.memos.len());
+
+        let mut prng = ChaChaRng::from_entropy();
+        let tracer = AssetTracerKeyPair::generate(&mut prng);
+        let code = AssetTypeCode::gen_random();
+        assert!(pnk!(trace_transaction(TxnSID(3), &txn, &code, &tracer)).is_empty());
+
+        // a bare transaction is not a `txn_sid_light` response
+        let bare = serde_json::to_vec(&txn).unwrap();
+        assert!(parse_transaction(&bare).is_err());
+    }
+
+    #[test]
+    fn test_trace_transaction() {
+        let mut prng = ChaChaRng::from_entropy();
+        let params = PublicParams::default();
+        let code = AssetTypeCode::gen_random();
+        let alice = XfrKeyPair::generate(&mut prng);
+        let bob = XfrKeyPair::generate(&mut prng);
+        let tracer = AssetTracerKeyPair::generate(&mut prng);
+        let other = AssetTracerKeyPair::generate(&mut prng);
+
+        let mut policies = TracingPolicies::new();
+        policies.add(TracingPolicy {
+            enc_keys: tracer.enc_key.clone(),
+            asset_tracing: true,
+            identity_tracing: None,
+        });
+        let record_type =
+            AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType;
+        let template = |am, pk| {
+            AssetRecordTemplate::with_asset_tracing(
+                am,
+                code.val,
+                record_type,
+                pk,
+                policies.clone(),
+            )
+        };
+
+        let (ba, _, memo) = build_blind_asset_record(
+            &mut prng,
+            &params.pc_gens,
+            &template(100, alice.get_pk()),
+            vec![],
+        );
+        let op = pnk!(TransferOperationBuilder::new()
+            .add_input(
+                TxoRef::Absolute(TxoSID(0)),
+                pnk!(open_blind_asset_record(&ba, &memo, &alice)),
+                Some(policies.clone()),
+                None,
+                100,
+            )
+            .and_then(|b| b.add_output(
+                &template(100, bob.get_pk()),
+                Some(policies.clone()),
+                None,
+                None,
+                None
+            ))
+            .and_then(|b| b.create(TransferType::Standard))
+            .and_then(|b| b.sign(&alice))
+            .and_then(|b| b.transaction()));
+        let txn = Transaction::from_operation(op, 0);
+
+        // through the same response as the query server
+        let resp = serde_json::to_vec(&FinalizedTransaction {
+            txn,
+            tx_id: TxnSID(5),
+            txo_ids: vec![TxoSID(1)],
+            merkle_id: 5,
+        })
+        .unwrap();
+        let txn = pnk!(parse_transaction(&resp));
+
+        let records = pnk!(trace_transaction(TxnSID(5), &txn, &code, &tracer));
+        assert!(!records.is_empty());
+        assert!(records.iter().all(|r| r.amount == 100 && r.op_index == 0));
+        assert!(records
+            .iter()
+            .any(|r| r.owner == wallet::public_key_to_bech32(bob.get_pk_ref())));
+
+        assert!(pnk!(trace_transaction(TxnSID(5), &txn, &code, &other)).is_empty());
+        let code2 = AssetTypeCode::gen_random();
+        assert!(pnk!(trace_transaction(TxnSID(5), &txn, &code2, &tracer)).is_empty());
+    }
+
+    #[test]
+    fn test_csv_report() {
+        let record = TracedRecord {
+            txn_sid: TxnSID(1),
+            op_index: 0,
+            owner: "fra1abc".to_owned(),
+            asset_type: "AAAA".to_owned(),
+            amount: 10,
+            identity: vec!["Doe, \"John\"".to_owned(), "1990".to_owned()],
+        };
+        let report = pnk!(gen_report(&[record], ReportFormat::Csv));
+        assert_eq!(
+            report.lines().nth(1).unwrap(),
+            "1,0,fra1abc,AAAA,10,\"Doe, \"\"John\"\" 1990\""
+        );
+    }
+}
@@ -6,14 +6,14 @@ use {
     crate::{
         api::{DelegationInfo, ValidatorDetail},
         common::get_serv_addr,
-        txn_builder::{TransactionBuilder, TransferOperationBuilder},
+        txn_builder::{LargestFirst, TransactionBuilder, TransferOperationBuilder},
     },
     globutils::{wallet, HashOf, SignatureOf},
     ledger::{
         data_model::{
-            AssetType, AssetTypeCode, DefineAsset, Operation, StateCommitmentData,
-            Transaction, TransferType, TxoRef, TxoSID, Utxo, ASSET_TYPE_FRA,
-            BLACK_HOLE_PUBKEY, TX_FEE_MIN_V1,
+            encrypted_memo_fee, min_transfer_fee, AssetType, AssetTypeCode,
+            CredentialProof, DefineAsset, Operation, StateCommitmentData, Transaction,
+            TransferType, TxoRef, TxoSID, Utxo, ASSET_TYPE_FRA, BLACK_HOLE_PUBKEY,
         },
         staking::{
             init::get_inital_validators, StakerMemo, TendermintAddrRef, FRA_TOTAL_AMOUNT,
@@ -40,7 +40,9 @@ use {
     zei::xfr::{
         asset_record::{open_blind_asset_record, AssetRecordType},
         sig::{XfrKeyPair, XfrPublicKey},
-        structs::{AssetRecordTemplate, OwnerMemo},
+        structs::{
+            AssetRecordTemplate, OpenAssetRecord, OwnerMemo, XfrAmount, XfrAssetType,
+        },
     },
 };
 
@@ -140,6 +142,80 @@ pub fn transfer_batch(
     send_tx(&tx).c(d!())
 }
 
+/// Same as `transfer`, but attaches a memo encrypted for `target_pk`,
+/// e.g. an invoice ID, which only the recipient can read
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_with_encrypted_memo(
+    owner_kp: &XfrKeyPair,
+    target_pk: &XfrPublicKey,
+    am: u64,
+    token_code: Option<AssetTypeCode>,
+    confidential_am: bool,
+    confidential_ty: bool,
+    memo: &str,
+) -> Result<()> {
+    let mut builder = new_tx_builder().c(d!())?;
+    builder.add_encrypted_memo(target_pk, memo).c(d!())?;
+
+    let utxos = get_owned_utxos(owner_kp.get_pk_ref()).c(d!())?;
+    let op = gen_transfer_op_with_utxos(
+        owner_kp,
+        get_fee_height().c(d!())?,
+        utxos,
+        vec![(target_pk, am, None)],
+        token_code,
+        true,
+        1,
+        confidential_am,
+        confidential_ty,
+        None,
+    )
+    .c(d!())?;
+    builder.add_operation(op);
+
+    let mut tx = builder.take_transaction();
+    tx.sign_to_map(owner_kp);
+
+    send_tx(&tx).c(d!())
+}
+
+/// Same as `transfer`, but attaches the proofs that `target_pk`
+/// holds the credential required by a credential-gated asset
+pub fn transfer_with_credential_proofs(
+    owner_kp: &XfrKeyPair,
+    target_pk: &XfrPublicKey,
+    am: u64,
+    token_code: Option<AssetTypeCode>,
+    confidential_am: bool,
+    proofs: Vec<CredentialProof>,
+) -> Result<()> {
+    let mut builder = new_tx_builder().c(d!())?;
+    for proof in proofs {
+        proof.verify().c(d!())?;
+        if &proof.address != target_pk {
+            return Err(eg!("the credential proof is not made for the receiver"));
+        }
+        builder.add_credential_proof(proof);
+    }
+
+    // assets with credential rules can not be confidential
+    let op = gen_transfer_op(
+        owner_kp,
+        vec![(target_pk, am, None)],
+        token_code,
+        confidential_am,
+        false,
+        None,
+    )
+    .c(d!())?;
+    builder.add_operation(op);
+
+    let mut tx = builder.take_transaction();
+    tx.sign_to_map(owner_kp);
+
+    send_tx(&tx).c(d!())
+}
+
 /// @target_list: use `Vec` but `HashMap` ?
 ///     there might be multi entries to one address
 #[inline(always)]
@@ -191,73 +267,80 @@ pub fn gen_transfer_op_x(
 pub fn gen_transfer_op_xx(
     rpc_endpoint: Option<&str>,
     owner_kp: &XfrKeyPair,
-    mut target_list: Vec<(&XfrPublicKey, u64, Option<String>)>,
+    target_list: Vec<(&XfrPublicKey, u64, Option<String>)>,
     token_code: Option<AssetTypeCode>,
     auto_fee: bool,
     confidential_am: bool,
     confidential_ty: bool,
     balance_type: Option<AssetRecordType>,
 ) -> Result<Operation> {
-    let mut op_fee: u64 = 0;
-    if auto_fee {
-        target_list.push((&*BLACK_HOLE_PUBKEY, TX_FEE_MIN_V1, None));
-        op_fee += TX_FEE_MIN_V1;
-    }
-    let asset_type = token_code.map(|code| code.val).unwrap_or(ASSET_TYPE_FRA);
-
-    let mut trans_builder = TransferOperationBuilder::new();
+    let utxos = get_owned_utxos_x(rpc_endpoint, owner_kp.get_pk_ref()).c(d!())?;
+    gen_transfer_op_with_utxos(
+        owner_kp,
+        get_fee_height().c(d!())?,
+        utxos,
+        target_list,
+        token_code,
+        auto_fee,
+        0,
+        confidential_am,
+        confidential_ty,
+        balance_type,
+    )
+    .c(d!())
+}
 
-    let mut am = target_list.iter().map(|(_, am, _)| *am).sum();
-    if asset_type != ASSET_TYPE_FRA {
-        am -= op_fee;
+/// Same as `gen_transfer_op_xx`, but spends the given UTXOs
+/// instead of querying them from the network, used by offline signing.
+///
+/// The fee is estimated by the rules at `height`, including the `encrypted_memos`
+/// encrypted memos of the transaction, and the inputs
+/// are picked by [LargestFirst](crate::txn_builder::LargestFirst).
+#[allow(clippy::too_many_arguments)]
+pub fn gen_transfer_op_with_utxos(
+    owner_kp: &XfrKeyPair,
+    height: i64,
+    utxos: impl IntoIterator<Item = (TxoSID, (Utxo, Option<OwnerMemo>))>,
+    target_list: Vec<(&XfrPublicKey, u64, Option<String>)>,
+    token_code: Option<AssetTypeCode>,
+    auto_fee: bool,
+    encrypted_memos: u64,
+    confidential_am: bool,
+    confidential_ty: bool,
+    balance_type: Option<AssetRecordType>,
+) -> Result<Operation> {
+    let asset_type = token_code.map(|code| code.val).unwrap_or(ASSET_TYPE_FRA);
+    let am = target_list.iter().map(|(_, am, _)| *am).sum::<u64>();
+
+    // outputs: all targets, the fee, and the changes of the asset and FRA
+    let op_fee = if auto_fee {
+        let changes = alt!(asset_type == ASSET_TYPE_FRA, 1, 2);
+        let memos = target_list.iter().filter(|(_, _, m)| m.is_some()).count() as u64;
+        min_transfer_fee(height, target_list.len() as u64 + 1 + changes, memos)
+            + encrypted_memo_fee(height, encrypted_memos)
     } else {
-        // if this is a FRA asset, set op_fee to 0, because fee has been added to am already.
-        op_fee = 0;
-    }
-    let mut i_am;
-    let utxos = get_owned_utxos_x(rpc_endpoint, owner_kp.get_pk_ref())
-        .c(d!())?
-        .into_iter();
+        0
+    };
 
+    let mut asset_inputs = vec![];
+    let mut fra_inputs = vec![];
     for (sid, (utxo, owner_memo)) in utxos {
         let oar =
             open_blind_asset_record(&utxo.0.record, &owner_memo, owner_kp).c(d!())?;
-
-        if oar.asset_type != asset_type && oar.asset_type != ASSET_TYPE_FRA {
-            continue;
-        } else if oar.asset_type == ASSET_TYPE_FRA && op_fee != 0 {
-            // asset_type is a custom asset, need handle fee here
-            alt!(oar.amount < op_fee, i_am = oar.amount, i_am = op_fee);
-            op_fee -= i_am;
-
-            trans_builder
-                .add_input(TxoRef::Absolute(sid), oar, None, None, i_am)
-                .c(d!())?;
-
-            continue;
-        } else if am != 0 {
-            alt!(oar.amount < am, i_am = oar.amount, i_am = am);
-            //am = am.saturating_sub(i_am);
-            am -= i_am;
-
-            trans_builder
-                .add_input(TxoRef::Absolute(sid), oar, None, None, i_am)
-                .c(d!())?;
+        if oar.asset_type == asset_type {
+            asset_inputs.push((TxoRef::Absolute(sid), oar));
+        } else if oar.asset_type == ASSET_TYPE_FRA {
+            fra_inputs.push((TxoRef::Absolute(sid), oar));
         }
-
-        alt!(0 == am && 0 == op_fee, break);
     }
 
-    if 0 != am || 0 != op_fee {
-        return Err(eg!("insufficient balance"));
-    }
+    let mut trans_builder = TransferOperationBuilder::new();
 
     if auto_fee {
-        target_list.pop();
         trans_builder
             .add_output(
                 &AssetRecordTemplate::with_no_asset_tracing(
-                    TX_FEE_MIN_V1,
+                    op_fee,
                     ASSET_TYPE_FRA,
                     AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType,
                     *BLACK_HOLE_PUBKEY,
@@ -277,21 +360,32 @@ pub fn gen_transfer_op_xx(
         _ => AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType,
     };
 
-    let outputs = target_list.into_iter().map(|(pk, n, memo)| {
-        (
-            AssetRecordTemplate::with_no_asset_tracing(
-                n,
-                token_code.map(|code| code.val).unwrap_or(ASSET_TYPE_FRA),
-                art,
-                *pk,
-            ),
-            memo,
-        )
-    });
-
-    for (output, memo) in outputs {
+    for (pk, n, memo) in target_list.into_iter() {
         trans_builder
-            .add_output(&output, None, None, None, memo)
+            .add_output(
+                &AssetRecordTemplate::with_no_asset_tracing(n, asset_type, art, *pk),
+                None,
+                None,
+                None,
+                memo,
+            )
+            .c(d!())?;
+    }
+
+    if asset_type == ASSET_TYPE_FRA {
+        trans_builder
+            .add_inputs_by_selection(
+                asset_inputs,
+                am + op_fee,
+                &LargestFirst,
+                balance_type,
+            )
+            .c(d!())?;
+    } else {
+        trans_builder
+            .add_inputs_by_selection(asset_inputs, am, &LargestFirst, balance_type)
+            .c(d!())?
+            .add_inputs_by_selection(fra_inputs, op_fee, &LargestFirst, balance_type)
             .c(d!())?;
     }
 
@@ -421,6 +515,15 @@ pub fn get_block_height(addr: &str) -> u64 {
         .unwrap_or(0)
 }
 
+/// The height that the ledger will check the fees at, i.e. the next block of the network
+pub fn get_fee_height() -> Result<i64> {
+    let height = get_block_height(get_serv_addr().c(d!())?);
+    if 0 == height {
+        return Err(eg!("Failed to get the block height of the network"));
+    }
+    Ok(height as i64 + 1)
+}
+
 /// Retrieve current block height of the local tendermint node address
 pub fn get_local_block_height() -> u64 {
     let addr = "http://127.0.0.1";
@@ -511,13 +614,77 @@ pub fn get_asset_all(kp: &XfrKeyPair) -> Result<BTreeMap<AssetTypeCode, u64>> {
     Ok(set)
 }
 
-fn get_owned_utxos(
+/// Same as `get_asset_all`, but the balance of every asset is split into
+/// the nonconfidential part and the confidential part
+pub fn get_asset_all_by_confidentiality(
+    kp: &XfrKeyPair,
+) -> Result<BTreeMap<AssetTypeCode, (u64, u64)>> {
+    let mut set = BTreeMap::new();
+
+    for (_, oar) in get_owned_records(kp).c(d!())? {
+        let code = AssetTypeCode {
+            val: oar.asset_type,
+        };
+        let (nonconfidential, confidential) = set.entry(code).or_insert((0, 0));
+        if oar.get_record_type()
+            == AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType
+        {
+            *nonconfidential += oar.amount;
+        } else {
+            *confidential += oar.amount;
+        }
+    }
+
+    Ok(set)
+}
+
+/// Retrieve and open the Utxos of a findora keypair, sorted by their ids,
+/// the missing owner memos of confidential records are queried in one batch,
+/// records which can not be opened by `kp` are skipped
+pub fn get_owned_records(kp: &XfrKeyPair) -> Result<Vec<(TxoSID, OpenAssetRecord)>> {
+    let mut utxos = get_owned_utxos(kp.get_pk_ref())
+        .c(d!())?
+        .into_iter()
+        .collect::<Vec<_>>();
+    utxos.sort_unstable_by_key(|(sid, _)| *sid);
+
+    let missing = utxos
+        .iter()
+        .filter(|(_, (utxo, memo))| {
+            memo.is_none()
+                && (matches!(utxo.0.record.amount, XfrAmount::Confidential(_))
+                    || matches!(utxo.0.record.asset_type, XfrAssetType::Confidential(_)))
+        })
+        .map(|(sid, _)| *sid)
+        .collect::<Vec<_>>();
+    let mut memos = if missing.is_empty() {
+        BTreeMap::new()
+    } else {
+        missing
+            .iter()
+            .copied()
+            .zip(get_owner_memo_batch(&missing).c(d!())?)
+            .collect::<BTreeMap<_, _>>()
+    };
+
+    Ok(utxos
+        .into_iter()
+        .filter_map(|(sid, (utxo, memo))| {
+            let memo = memo.or_else(|| memos.remove(&sid).flatten());
+            open_blind_asset_record(&utxo.0.record, &memo, kp)
+                .ok()
+                .map(|oar| (sid, oar))
+        })
+        .collect())
+}
+
+pub(crate) fn get_owned_utxos(
     addr: &XfrPublicKey,
 ) -> Result<HashMap<TxoSID, (Utxo, Option<OwnerMemo>)>> {
     get_owned_utxos_x(None, addr).c(d!())
 }
 
-fn get_owned_utxos_x(
+pub(crate) fn get_owned_utxos_x(
     rpc_endpoint: Option<&str>,
     addr: &XfrPublicKey,
 ) -> Result<HashMap<TxoSID, (Utxo, Option<OwnerMemo>)>> {
@@ -542,7 +709,7 @@ fn get_owned_utxos_x(
 }
 
 #[inline(always)]
-fn get_seq_id() -> Result<u64> {
+pub(crate) fn get_seq_id() -> Result<u64> {
     type Resp = (
         HashOf<Option<StateCommitmentData>>,
         u64,
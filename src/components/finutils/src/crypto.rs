@@ -0,0 +1,92 @@
+//!
+//! # Password-based encryption
+//!
+//! PBKDF2-HMAC-SHA512 + AES-256-GCM, shared by the wallets and the keystore of FN.
+//!
+
+use {
+    aes_gcm::{
+        aead::{generic_array::GenericArray, Aead, NewAead},
+        Aes256Gcm,
+    },
+    rand_chacha::ChaChaRng,
+    rand_core::{RngCore, SeedableRng},
+    ring::pbkdf2,
+    ruc::*,
+    std::num::NonZeroU32,
+};
+
+/// PBKDF2 iterations of the keys encrypted by the wallets.
+pub const WALLET_PBKDF2_ITERATIONS: u32 = 32;
+
+const SALT_LEN: usize = 32;
+const IV_LEN: usize = 12;
+
+fn derive_key(password: &str, salt: &[u8], iterations: u32) -> Result<[u8; 32]> {
+    let mut key = [0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA512,
+        NonZeroU32::new(iterations).c(d!())?,
+        salt,
+        password.as_bytes(),
+        &mut key,
+    );
+    Ok(key)
+}
+
+/// Encrypt `plaintext` with `password`, the result is `salt || iv || ciphertext`.
+pub fn encryption_pbkdf2_aes256gcm(
+    plaintext: &[u8],
+    password: &str,
+    iterations: u32,
+) -> Result<Vec<u8>> {
+    let mut rng = ChaChaRng::from_entropy();
+    let mut salt = [0u8; SALT_LEN];
+    rng.fill_bytes(&mut salt);
+    let mut iv = [0u8; IV_LEN];
+    rng.fill_bytes(&mut iv);
+
+    let key = derive_key(password, &salt, iterations).c(d!())?;
+    let cipher = Aes256Gcm::new(GenericArray::from_slice(&key));
+    let ciphertext = cipher
+        .encrypt(GenericArray::from_slice(&iv), plaintext)
+        .map_err(|_| eg!("Failed to encrypt"))?;
+
+    Ok([&salt[..], &iv[..], &ciphertext[..]].concat())
+}
+
+/// Decrypt the output of `encryption_pbkdf2_aes256gcm` with `password`.
+pub fn decryption_pbkdf2_aes256gcm(
+    data: &[u8],
+    password: &str,
+    iterations: u32,
+) -> Result<Vec<u8>> {
+    if data.len() <= SALT_LEN + IV_LEN {
+        return Err(eg!("Invalid cipher"));
+    }
+    let (salt, rest) = data.split_at(SALT_LEN);
+    let (iv, ciphertext) = rest.split_at(IV_LEN);
+
+    let key = derive_key(password, salt, iterations).c(d!())?;
+    let cipher = Aes256Gcm::new(GenericArray::from_slice(&key));
+    cipher
+        .decrypt(GenericArray::from_slice(iv), ciphertext)
+        .map_err(|_| eg!("Failed to decrypt"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let data = pnk!(encryption_pbkdf2_aes256gcm(b"findora", "pass", 16));
+        assert_eq!(
+            pnk!(decryption_pbkdf2_aes256gcm(&data, "pass", 16)),
+            b"findora"
+        );
+        assert!(decryption_pbkdf2_aes256gcm(&data, "wrong", 16).is_err());
+        assert!(decryption_pbkdf2_aes256gcm(&data, "pass", 32).is_err());
+        assert!(decryption_pbkdf2_aes256gcm(&data[..SALT_LEN], "pass", 16).is_err());
+    }
+}
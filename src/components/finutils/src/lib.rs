@@ -9,4 +9,5 @@
 pub mod api;
 #[cfg(feature = "std")]
 pub mod common;
+pub mod crypto;
 pub mod txn_builder;
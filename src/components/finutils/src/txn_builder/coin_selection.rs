@@ -0,0 +1,200 @@
+//!
+//! Strategies of picking the inputs of a transfer out of the UTXOs of the sender
+//!
+
+/// A coin selection strategy, used by
+/// [add_inputs_by_selection](super::TransferOperationBuilder::add_inputs_by_selection).
+pub trait CoinSelection {
+    /// Pick some of `amounts` to pay `target`, returns their indexes,
+    /// or `None` if all of them together are not enough.
+    fn select(&self, amounts: &[u64], target: u64) -> Option<Vec<usize>>;
+}
+
+/// Spend the largest UTXOs first, the fewest inputs are used.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LargestFirst;
+
+impl CoinSelection for LargestFirst {
+    fn select(&self, amounts: &[u64], target: u64) -> Option<Vec<usize>> {
+        let mut idxs = (0..amounts.len()).collect::<Vec<_>>();
+        idxs.sort_by(|a, b| amounts[*b].cmp(&amounts[*a]));
+        take_until(amounts, idxs, target)
+    }
+}
+
+/// Search for a set of UTXOs that pays `target` with a change
+/// no more than `tolerance`, so no change output or only a tiny one is created.
+/// Falls back to [LargestFirst] if nothing is found within `max_tries` steps.
+#[derive(Clone, Copy, Debug)]
+pub struct BranchAndBound {
+    #[allow(missing_docs)]
+    pub tolerance: u64,
+    #[allow(missing_docs)]
+    pub max_tries: usize,
+}
+
+impl Default for BranchAndBound {
+    fn default() -> Self {
+        BranchAndBound {
+            tolerance: 0,
+            max_tries: 100_000,
+        }
+    }
+}
+
+impl CoinSelection for BranchAndBound {
+    fn select(&self, amounts: &[u64], target: u64) -> Option<Vec<usize>> {
+        let mut idxs = (0..amounts.len()).collect::<Vec<_>>();
+        idxs.sort_by(|a, b| amounts[*b].cmp(&amounts[*a]));
+
+        // `rest[i]`: the sum of all the candidates from `i` on
+        let mut rest = vec![0u64; idxs.len() + 1];
+        for i in (0..idxs.len()).rev() {
+            rest[i] = rest[i + 1].saturating_add(amounts[idxs[i]]);
+        }
+        if rest[0] < target {
+            return None;
+        }
+
+        let mut tries = 0;
+        let mut picked = vec![];
+        if self.search(amounts, &idxs, &rest, 0, 0, target, &mut picked, &mut tries) {
+            Some(picked)
+        } else {
+            LargestFirst.select(amounts, target)
+        }
+    }
+}
+
+impl BranchAndBound {
+    #[allow(clippy::too_many_arguments)]
+    fn search(
+        &self,
+        amounts: &[u64],
+        idxs: &[usize],
+        rest: &[u64],
+        depth: usize,
+        sum: u64,
+        target: u64,
+        picked: &mut Vec<usize>,
+        tries: &mut usize,
+    ) -> bool {
+        *tries += 1;
+        if sum >= target {
+            return sum - target <= self.tolerance;
+        }
+        if depth == idxs.len()
+            || *tries > self.max_tries
+            || sum.saturating_add(rest[depth]) < target
+        {
+            return false;
+        }
+
+        // include the current candidate first, then try to exclude it
+        picked.push(idxs[depth]);
+        let sum_with = sum.saturating_add(amounts[idxs[depth]]);
+        if self.search(
+            amounts,
+            idxs,
+            rest,
+            depth + 1,
+            sum_with,
+            target,
+            picked,
+            tries,
+        ) {
+            return true;
+        }
+        picked.pop();
+        self.search(amounts, idxs, rest, depth + 1, sum, target, picked, tries)
+    }
+}
+
+/// Spend the smallest UTXOs first, and merge all the UTXOs below `dust`
+/// into the change, at most `max_inputs` inputs are used.
+#[derive(Clone, Copy, Debug)]
+pub struct ConsolidateDust {
+    #[allow(missing_docs)]
+    pub dust: u64,
+    #[allow(missing_docs)]
+    pub max_inputs: usize,
+}
+
+impl Default for ConsolidateDust {
+    fn default() -> Self {
+        ConsolidateDust {
+            dust: 1_000_000,
+            max_inputs: 64,
+        }
+    }
+}
+
+impl CoinSelection for ConsolidateDust {
+    fn select(&self, amounts: &[u64], target: u64) -> Option<Vec<usize>> {
+        let mut idxs = (0..amounts.len()).collect::<Vec<_>>();
+        idxs.sort_by_key(|i| amounts[*i]);
+
+        let mut picked = take_until(amounts, idxs.clone(), target)?;
+        if picked.len() > self.max_inputs {
+            // too many small ones, leave the dust for the next time
+            return LargestFirst.select(amounts, target);
+        }
+        for i in idxs.into_iter().skip(picked.len()) {
+            if picked.len() >= self.max_inputs || amounts[i] >= self.dust {
+                break;
+            }
+            picked.push(i);
+        }
+        Some(picked)
+    }
+}
+
+fn take_until(amounts: &[u64], idxs: Vec<usize>, target: u64) -> Option<Vec<usize>> {
+    let mut sum = 0u64;
+    let mut picked = vec![];
+    for i in idxs {
+        if sum >= target {
+            break;
+        }
+        sum = sum.saturating_add(amounts[i]);
+        picked.push(i);
+    }
+    if sum >= target {
+        Some(picked)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn total(amounts: &[u64], idxs: &[usize]) -> u64 {
+        idxs.iter().map(|i| amounts[*i]).sum()
+    }
+
+    #[test]
+    fn test_coin_selection() {
+        let amounts = [5, 1, 30, 2, 10, 3];
+
+        let picked = LargestFirst.select(&amounts, 35).unwrap();
+        assert_eq!(picked, vec![2, 4]);
+        assert!(LargestFirst.select(&amounts, 52).is_none());
+
+        let picked = BranchAndBound::default().select(&amounts, 36).unwrap();
+        assert_eq!(total(&amounts, &picked), 36);
+        let picked = BranchAndBound::default().select(&amounts, 51).unwrap();
+        assert_eq!(total(&amounts, &picked), 51);
+        assert!(BranchAndBound::default().select(&amounts, 52).is_none());
+
+        let cd = ConsolidateDust {
+            dust: 4,
+            max_inputs: 4,
+        };
+        let picked = cd.select(&amounts, 2).unwrap();
+        assert_eq!(picked, vec![1, 3, 5]);
+        let picked = cd.select(&amounts, 45).unwrap();
+        assert!(total(&amounts, &picked) >= 45);
+    }
+}
@@ -5,7 +5,13 @@
 #![deny(warnings)]
 #![allow(clippy::needless_borrow)]
 
-use ledger::data_model::{Operation, Transaction, TransferAsset, TX_FEE_MIN_V1};
+mod coin_selection;
+
+pub use coin_selection::{BranchAndBound, CoinSelection, ConsolidateDust, LargestFirst};
+
+use ledger::data_model::{
+    encrypted_memo_fee, min_transfer_fee, Operation, Transaction, TransferAsset,
+};
 //#[cfg(not(target_arch = "wasm32"))]
 use zei::serialization::ZeiFromToBytes;
 
@@ -17,10 +23,11 @@ use {
     ledger::{
         converter::ConvertAccount,
         data_model::{
-            AssetRules, AssetTypeCode, ConfidentialMemo, DefineAsset, DefineAssetBody,
-            IndexedSignature, IssueAsset, IssueAssetBody, IssuerKeyPair,
-            IssuerPublicKey, Memo, NoReplayToken, TransactionBody, TransferAssetBody,
-            TransferType, TxOutput, TxoRef, UpdateMemo, UpdateMemoBody, ASSET_TYPE_FRA,
+            AssetRules, AssetTypeCode, ConfidentialMemo, CredentialProof, DefineAsset,
+            DefineAssetBody, EncryptedMemo, IndexedSignature, IssueAsset,
+            IssueAssetBody, IssuerKeyPair, IssuerPublicKey, Memo, NoReplayToken,
+            TransactionBody, TransferAssetBody, TransferType, TxOutput, TxoRef,
+            UpdateIssuer, UpdateIssuerBody, UpdateMemo, UpdateMemoBody, ASSET_TYPE_FRA,
             BLACK_HOLE_PUBKEY,
         },
         staking::{
@@ -112,6 +119,19 @@ impl FeeInputs {
     ) {
         self.inner.push(FeeInput { am, tr, ar, om, kp })
     }
+
+    /// Open all the records, for the APIs which take `FeeInputs` as
+    /// the candidates of coin selection, `am` is ignored
+    pub fn open(self) -> Result<Vec<(TxoRef, OpenAssetRecord)>> {
+        self.inner
+            .into_iter()
+            .map(|i| {
+                open_blind_asset_record(&i.ar.record, &i.om, &i.kp)
+                    .c(d!())
+                    .map(|oar| (i.tr, oar))
+            })
+            .collect()
+    }
 }
 
 /// An simple builder for findora transaction
@@ -169,34 +189,18 @@ impl TransactionBuilder {
             .collect()
     }
 
-    /// @param am: amount to pay
     /// @param kp: owner's XfrKeyPair
+    /// @param height: the height that the fee is checked at, SEE [estimate_fee](Self::estimate_fee)
     pub fn add_fee_relative_auto(
         &mut self,
         kp: &XfrKeyPair,
         memo: Option<String>,
+        height: i64,
     ) -> Result<&mut TransactionBuilder> {
         let mut opb = TransferOperationBuilder::default();
         let outputs = self.get_relative_outputs();
 
-        let mut am = TX_FEE_MIN_V1;
-        for i in self.txn.body.operations.iter() {
-            if let Operation::TransferAsset(transfer) = i {
-                let len = transfer.body.outputs.len() as u64;
-                if len > 3 {
-                    am += (len - 3) * 2_000_000;
-                }
-                let memo_len = transfer
-                    .body
-                    .outputs
-                    .iter()
-                    .filter(|v| v.memo.is_some())
-                    .collect::<Vec<_>>()
-                    .len() as u64;
-                am += memo_len * 2_000_000;
-                break;
-            }
-        }
+        let mut am = self.estimate_fee(height);
         for (idx, (o, om)) in outputs.into_iter().enumerate() {
             if 0 < am {
                 if let Ok(oar) = open_blind_asset_record(&o, &om, &kp) {
@@ -233,33 +237,17 @@ impl TransactionBuilder {
     }
 
     /// As the last operation of any transaction,
-    /// add a static fee to the transaction.
+    /// add the fee required at `height` to the transaction.
     pub fn add_fee(
         &mut self,
         inputs: FeeInputs,
         memo: Option<String>,
+        height: i64,
     ) -> Result<&mut TransactionBuilder> {
         let mut kps = vec![];
         let mut opb = TransferOperationBuilder::default();
 
-        let mut am = TX_FEE_MIN_V1;
-        for i in self.txn.body.operations.iter() {
-            if let Operation::TransferAsset(transfer) = i {
-                let len = transfer.body.outputs.len() as u64;
-                if len > 3 {
-                    am += (len - 3) * 2_000_000;
-                }
-                let memo_len = transfer
-                    .body
-                    .outputs
-                    .iter()
-                    .filter(|v| v.memo.is_some())
-                    .collect::<Vec<_>>()
-                    .len() as u64;
-                am += memo_len * 2_000_000;
-                break;
-            }
-        }
+        let am = self.estimate_fee(height);
 
         for i in inputs.inner.into_iter() {
             open_blind_asset_record(&i.ar.record, &i.om, &i.kp)
@@ -303,6 +291,129 @@ impl TransactionBuilder {
         .map(move |op| self.add_operation(op))
     }
 
+    /// The fee required at `height` by the rules of [check_fee](ledger::data_model::Transaction::check_fee),
+    /// according to the outputs of the first transfer and the encrypted memos in the transaction,
+    /// `i64::MAX` means the latest rules.
+    pub fn estimate_fee(&self, height: i64) -> u64 {
+        self.txn
+            .body
+            .operations
+            .iter()
+            .find_map(|op| match op {
+                Operation::TransferAsset(transfer) => Some(&transfer.body.outputs),
+                _ => None,
+            })
+            .map(|outputs| {
+                min_transfer_fee(
+                    height,
+                    outputs.len() as u64,
+                    outputs.iter().filter(|o| o.memo.is_some()).count() as u64,
+                )
+            })
+            .unwrap_or_else(|| min_transfer_fee(height, 0, 0))
+            + encrypted_memo_fee(height, self.txn.body.encrypted_memos.len() as u64)
+    }
+
+    /// Shield (`to_confidential`) or unshield `amount` of the asset `code` owned by `kp`,
+    /// that is converting it into records with confidential amount and type,
+    /// or into fully nonconfidential records, in one transfer which pays its own fee.
+    ///
+    /// The inputs are picked out of `candidates`, the UTXOs of `kp`, by [LargestFirst],
+    /// the change keeps the form of the inputs. The fee of a custom asset is paid
+    /// by the nonconfidential FRAs, while FRA pays its fee by itself,
+    /// the fee is the one required at `height`, SEE [estimate_fee](Self::estimate_fee).
+    pub fn add_operation_convert_confidentiality(
+        &mut self,
+        kp: &XfrKeyPair,
+        candidates: Vec<(TxoRef, OpenAssetRecord)>,
+        code: AssetTypeCode,
+        amount: u64,
+        to_confidential: bool,
+        height: i64,
+    ) -> Result<&mut Self> {
+        let nonconfidential =
+            AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType;
+        let target_type = alt!(
+            to_confidential,
+            AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+            nonconfidential
+        );
+        // records of any confidential form are unshielded
+        let is_source = |t: AssetRecordType| (t == nonconfidential) == to_confidential;
+
+        let is_fra = code.val == ASSET_TYPE_FRA;
+        let mut sources = vec![];
+        let mut fra_inputs = vec![];
+        for (txo, oar) in candidates {
+            if oar.get_pub_key() != kp.get_pk_ref() {
+                continue;
+            }
+            if oar.asset_type == code.val && is_source(oar.get_record_type()) {
+                sources.push((txo, oar));
+            } else if !is_fra
+                && oar.asset_type == ASSET_TYPE_FRA
+                && oar.get_record_type() == nonconfidential
+            {
+                fra_inputs.push((txo, oar));
+            }
+        }
+
+        // outputs: the fee, the converted one, and the changes of the asset and FRA
+        let fee = min_transfer_fee(height, alt!(is_fra, 3, 4), 0)
+            + encrypted_memo_fee(height, self.txn.body.encrypted_memos.len() as u64);
+
+        let mut opb = TransferOperationBuilder::new();
+        opb.add_output(
+            &AssetRecordTemplate::with_no_asset_tracing(
+                fee,
+                ASSET_TYPE_FRA,
+                nonconfidential,
+                *BLACK_HOLE_PUBKEY,
+            ),
+            None,
+            None,
+            None,
+            None,
+        )
+        .c(d!())?
+        .add_output(
+            &AssetRecordTemplate::with_no_asset_tracing(
+                amount,
+                code.val,
+                target_type,
+                kp.get_pk(),
+            ),
+            None,
+            None,
+            None,
+            None,
+        )
+        .c(d!())?;
+
+        if is_fra {
+            let total = amount.checked_add(fee).c(d!())?;
+            opb.add_inputs_by_selection(sources, total, &LargestFirst, None)
+                .c(d!())?;
+        } else {
+            opb.add_inputs_by_selection(sources, amount, &LargestFirst, None)
+                .c(d!())?
+                .add_inputs_by_selection(fra_inputs, fee, &LargestFirst, None)
+                .c(d!())?;
+        }
+
+        let op = opb
+            .balance(None)
+            .c(d!())?
+            .create(TransferType::Standard)
+            .c(d!())?
+            .sign(kp)
+            .c(d!())?
+            .transaction()
+            .c(d!())?;
+
+        Ok(self.add_operation(op))
+    }
+
     /// SEE [check_fee](ledger::data_model::Transaction::check_fee)
     #[inline(always)]
     pub fn check_fee(&self) -> bool {
@@ -390,6 +501,25 @@ impl TransactionBuilder {
         self
     }
 
+    /// Append a memo encrypted for `recipient`, which only it can read,
+    /// it must be added before signing, and costs an extra fee like an output memo
+    pub fn add_encrypted_memo(
+        &mut self,
+        recipient: &XfrPublicKey,
+        memo: &str,
+    ) -> Result<&mut Self> {
+        let mut prng = ChaChaRng::from_entropy();
+        let memo = EncryptedMemo::new(&mut prng, recipient, memo).c(d!())?;
+        self.txn.body.encrypted_memos.push(memo);
+        Ok(self)
+    }
+
+    /// Attach a proof that a recipient holds the credential required by an asset
+    pub fn add_credential_proof(&mut self, proof: CredentialProof) -> &mut Self {
+        self.txn.body.credentials.push(proof.into());
+        self
+    }
+
     /// Add asset creating operation to builder an return modified builder
     pub fn add_operation_create_asset(
         &mut self,
@@ -517,6 +647,32 @@ impl TransactionBuilder {
         self
     }
 
+    /// Add a operation to hand over the issuance authority of an asset,
+    /// and to add, update or remove(with a zero cap) its minters
+    pub fn add_operation_update_issuer(
+        &mut self,
+        auth_key_pair: &XfrKeyPair,
+        asset_code: AssetTypeCode,
+        new_issuer: Option<XfrPublicKey>,
+        minters: Vec<(XfrPublicKey, u64)>,
+    ) -> &mut Self {
+        let update_issuer = UpdateIssuer::new(
+            UpdateIssuerBody {
+                asset_type: asset_code,
+                new_issuer: new_issuer.map(|key| IssuerPublicKey { key }),
+                minters: minters
+                    .into_iter()
+                    .map(|(key, cap)| (IssuerPublicKey { key }, cap))
+                    .collect(),
+                no_replay_token: self.txn.body.no_replay_token,
+            },
+            auth_key_pair,
+        );
+        self.txn
+            .add_operation(Operation::UpdateIssuer(update_issuer));
+        self
+    }
+
     /// Add a operation to delegating findora account to a tendermint validator.
     /// The transfer operation to BLACK_HOLE_PUBKEY_STAKING should be sent along with.
     pub fn add_operation_delegation(
@@ -702,6 +858,27 @@ impl TransactionBuilder {
             value: amount,
             asset_type: asset.map(|a| a.val),
             lowlevel_data,
+            native: false,
+        }));
+        Ok(self)
+    }
+
+    /// Add a operation convert a custom asset to the balance kept by the xhub module.
+    pub fn add_operation_convert_account_native(
+        &mut self,
+        kp: &XfrKeyPair,
+        addr: MultiSigner,
+        amount: u64,
+        asset: AssetTypeCode,
+    ) -> Result<&mut Self> {
+        self.add_operation(Operation::ConvertAccount(ConvertAccount {
+            signer: kp.get_pk(),
+            nonce: self.txn.body.no_replay_token,
+            receiver: addr,
+            value: amount,
+            asset_type: Some(asset.val),
+            lowlevel_data: None,
+            native: true,
         }));
         Ok(self)
     }
@@ -891,6 +1068,66 @@ impl TransferOperationBuilder {
         Ok(self)
     }
 
+    /// Pick inputs out of `candidates` with `selection` to pay `amount`,
+    /// the rest of them goes back to the owner in one change output,
+    /// whose record type is `change_type` or the same as the first input.
+    ///
+    /// All candidates must be of the same asset type and owner.
+    pub fn add_inputs_by_selection(
+        &mut self,
+        candidates: Vec<(TxoRef, OpenAssetRecord)>,
+        amount: u64,
+        selection: &dyn CoinSelection,
+        change_type: Option<AssetRecordType>,
+    ) -> Result<&mut Self> {
+        if amount == 0 {
+            return Ok(self);
+        }
+        if let Some((_, first)) = candidates.first() {
+            if candidates.iter().any(|(_, oar)| {
+                oar.asset_type != first.asset_type
+                    || oar.get_pub_key() != first.get_pub_key()
+            }) {
+                return Err(eg!("Candidates of different asset types or owners"));
+            }
+        }
+
+        let amounts = candidates
+            .iter()
+            .map(|(_, oar)| oar.amount)
+            .collect::<Vec<_>>();
+        let picked = selection
+            .select(&amounts, amount)
+            .c(d!("insufficient balance"))?;
+
+        let change = picked
+            .iter()
+            .try_fold(0u64, |acc, i| acc.checked_add(amounts[*i]))
+            .and_then(|total| total.checked_sub(amount))
+            .c(d!("invalid coin selection"))?;
+        let mut candidates = candidates.into_iter().map(Some).collect::<Vec<_>>();
+        let mut change_template = None;
+        for i in picked {
+            let (txo, oar) = candidates[i].take().c(d!())?;
+            if change_template.is_none() {
+                change_template = Some(AssetRecordTemplate::with_no_asset_tracing(
+                    change,
+                    oar.asset_type,
+                    change_type.unwrap_or_else(|| oar.get_record_type()),
+                    *oar.get_pub_key(),
+                ));
+            }
+            let am = oar.amount;
+            self.add_input(txo, oar, None, None, am).c(d!())?;
+        }
+
+        if let Some(template) = change_template.filter(|_| change > 0) {
+            self.add_output(&template, None, None, None, None).c(d!())?;
+        }
+
+        Ok(self)
+    }
+
     #[allow(missing_docs)]
     pub fn add_output(
         &mut self,
@@ -1209,7 +1446,7 @@ impl TransferOperationBuilder {
 mod tests {
     use {
         super::*,
-        ledger::data_model::TxoRef,
+        ledger::data_model::{TxoRef, TxoSID},
         rand_chacha::ChaChaRng,
         rand_core::SeedableRng,
         zei::setup::PublicParams,
@@ -1406,4 +1643,184 @@ mod tests {
             .c(d!())?;
         Ok(())
     }
+
+    const HEIGHT: i64 = i64::MAX;
+
+    fn records(
+        kp: &XfrKeyPair,
+        code: AssetTypeCode,
+        record_type: AssetRecordType,
+        amounts: &[u64],
+        first_sid: u64,
+    ) -> Result<Vec<(TxoRef, OpenAssetRecord)>> {
+        let mut prng = ChaChaRng::from_entropy();
+        let params = PublicParams::default();
+        amounts
+            .iter()
+            .enumerate()
+            .map(|(i, am)| {
+                let template = AssetRecordTemplate::with_no_asset_tracing(
+                    *am,
+                    code.val,
+                    record_type,
+                    kp.get_pk(),
+                );
+                let (bar, _, memo) = build_blind_asset_record(
+                    &mut prng,
+                    &params.pc_gens,
+                    &template,
+                    vec![],
+                );
+                open_blind_asset_record(&bar, &memo, kp)
+                    .c(d!())
+                    .map(|oar| (TxoRef::Absolute(TxoSID(first_sid + i as u64)), oar))
+            })
+            .collect()
+    }
+
+    // Convert with a new builder, and open the outputs of the transfer:
+    // `(owner, amount, asset type, record type)`
+    fn convert(
+        kp: &XfrKeyPair,
+        candidates: Vec<(TxoRef, OpenAssetRecord)>,
+        code: AssetTypeCode,
+        amount: u64,
+        to_confidential: bool,
+    ) -> Result<Vec<(XfrPublicKey, u64, AssetTypeCode, AssetRecordType)>> {
+        let mut builder = TransactionBuilder::from_seq_id(1);
+        builder
+            .add_operation_convert_confidentiality(
+                kp,
+                candidates,
+                code,
+                amount,
+                to_confidential,
+                HEIGHT,
+            )
+            .c(d!())?;
+        assert!(builder.check_fee());
+
+        let xfr = match builder.transaction().body.operations.as_slice() {
+            [Operation::TransferAsset(xfr)] => xfr.clone(),
+            _ => return Err(eg!("one transfer expected")),
+        };
+        xfr.body
+            .outputs
+            .iter()
+            .zip(xfr.body.transfer.owners_memos.iter())
+            .map(|(o, memo)| {
+                open_blind_asset_record(&o.record, memo, kp)
+                    .c(d!())
+                    .map(|oar| {
+                        (
+                            o.record.public_key,
+                            oar.amount,
+                            AssetTypeCode {
+                                val: oar.asset_type,
+                            },
+                            oar.get_record_type(),
+                        )
+                    })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_convert_confidentiality_fra() {
+        pnk!(test_convert_confidentiality_fra_inner());
+    }
+
+    fn test_convert_confidentiality_fra_inner() -> Result<()> {
+        let mut prng = ChaChaRng::from_entropy();
+        let alice = XfrKeyPair::generate(&mut prng);
+        let bob = XfrKeyPair::generate(&mut prng);
+        let fra = AssetTypeCode {
+            val: ASSET_TYPE_FRA,
+        };
+        let non_conf = NonConfidentialAmount_NonConfidentialAssetType;
+        let conf = AssetRecordType::ConfidentialAmount_ConfidentialAssetType;
+        let fee = min_transfer_fee(HEIGHT, 3, 0);
+
+        // Shield 120 out of `fee + 100` and 50, the change is 30
+        let mut candidates = records(&alice, fra, non_conf, &[fee + 100, 50], 0)?;
+        candidates.append(&mut records(&bob, fra, non_conf, &[1000], 2)?);
+        candidates.append(&mut records(&alice, fra, conf, &[1000], 3)?);
+        let outputs = convert(&alice, candidates.clone(), fra, 120, true)?;
+        assert_eq!(
+            outputs,
+            vec![
+                (*BLACK_HOLE_PUBKEY, fee, fra, non_conf),
+                (alice.get_pk(), 120, fra, conf),
+                (alice.get_pk(), 30, fra, non_conf),
+            ]
+        );
+
+        // The fee is paid by the nonconfidential FRAs only
+        assert!(convert(&alice, candidates, fra, 151, true).is_err());
+
+        // Unshield 150 out of `fee + 200`, the change keeps confidential
+        let candidates = records(&alice, fra, conf, &[fee + 200], 0)?;
+        let outputs = convert(&alice, candidates, fra, 150, false)?;
+        assert_eq!(
+            outputs,
+            vec![
+                (*BLACK_HOLE_PUBKEY, fee, fra, non_conf),
+                (alice.get_pk(), 150, fra, non_conf),
+                (alice.get_pk(), 50, fra, conf),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_convert_confidentiality_custom_asset() {
+        pnk!(test_convert_confidentiality_custom_asset_inner());
+    }
+
+    fn test_convert_confidentiality_custom_asset_inner() -> Result<()> {
+        let alice = XfrKeyPair::generate(&mut ChaChaRng::from_entropy());
+        let fra = AssetTypeCode {
+            val: ASSET_TYPE_FRA,
+        };
+        let code = AssetTypeCode::gen_random();
+        let non_conf = NonConfidentialAmount_NonConfidentialAssetType;
+        let conf = AssetRecordType::ConfidentialAmount_ConfidentialAssetType;
+        let fee = min_transfer_fee(HEIGHT, 4, 0);
+
+        // Shield 100 out of 70 and 40, the fee is paid by `fee + 5` FRAs,
+        // with the changes of both of them
+        let mut candidates = records(&alice, code, non_conf, &[70, 40], 0)?;
+        candidates.append(&mut records(&alice, fra, non_conf, &[fee + 5], 2)?);
+        candidates.append(&mut records(&alice, fra, conf, &[fee], 3)?);
+        let outputs = convert(&alice, candidates, code, 100, true)?;
+        assert_eq!(
+            outputs,
+            vec![
+                (*BLACK_HOLE_PUBKEY, fee, fra, non_conf),
+                (alice.get_pk(), 100, code, conf),
+                (alice.get_pk(), 10, code, non_conf),
+                (alice.get_pk(), 5, fra, non_conf),
+            ]
+        );
+
+        // Unshield all of it, no change at all
+        let mut candidates = records(&alice, code, conf, &[100], 0)?;
+        candidates.append(&mut records(&alice, fra, non_conf, &[fee], 1)?);
+        let outputs = convert(&alice, candidates.clone(), code, 100, false)?;
+        assert_eq!(
+            outputs,
+            vec![
+                (*BLACK_HOLE_PUBKEY, fee, fra, non_conf),
+                (alice.get_pk(), 100, code, non_conf),
+            ]
+        );
+
+        // Confidential FRAs can not pay the fee
+        let mut candidates = records(&alice, code, conf, &[100], 0)?;
+        candidates.append(&mut records(&alice, fra, conf, &[fee], 1)?);
+        assert!(convert(&alice, candidates, code, 100, false).is_err());
+
+        Ok(())
+    }
 }
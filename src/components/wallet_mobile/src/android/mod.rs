@@ -401,6 +401,42 @@ pub extern "system" fn Java_com_findora_JniApi_openClientAssetRecordGetPubKey(
     Box::into_raw(Box::new(types::XfrPublicKey::from(*record.get_pub_key()))) as jlong
 }
 
+#[no_mangle]
+/// The codespace of an error code, e.g. `ledger` or `evm`.
+pub extern "system" fn Java_com_findora_JniApi_txErrorCodespace(
+    env: JNIEnv,
+    _: JClass,
+    code: jint,
+) -> jstring {
+    let output = env
+        .new_string(tx_error_codespace(code as u32))
+        .expect("Couldn't create java string!");
+    **output
+}
+
+#[no_mangle]
+/// The snake_case name of an error code, e.g. `invalid_nonce`.
+pub extern "system" fn Java_com_findora_JniApi_txErrorName(
+    env: JNIEnv,
+    _: JClass,
+    code: jint,
+) -> jstring {
+    let output = env
+        .new_string(tx_error_name(code as u32))
+        .expect("Couldn't create java string!");
+    **output
+}
+
+#[no_mangle]
+/// Whether a transaction rejected with this code may succeed if it is rebuilt or resubmitted later.
+pub extern "system" fn Java_com_findora_JniApi_txErrorIsRetryable(
+    _env: JNIEnv,
+    _: JClass,
+    code: jint,
+) -> jboolean {
+    tx_error_is_retryable(code as u32) as jboolean
+}
+
 pub(super) fn jStringToString(env: JNIEnv, s: JString) -> String {
     env.get_string(s)
         .expect("Couldn't create rust String!")
@@ -554,6 +554,24 @@ pub extern "C" fn findora_ffi_cred_issuer_public_key_get_sk(
     Box::into_raw(Box::new(types::CredUserSecretKey::from(pair.get_sk())))
 }
 
+#[no_mangle]
+/// The codespace of an error code, e.g. `ledger` or `evm`.
+pub extern "C" fn findora_ffi_tx_error_codespace(code: u32) -> *mut c_char {
+    string_to_c_char(tx_error_codespace(code))
+}
+
+#[no_mangle]
+/// The snake_case name of an error code, e.g. `invalid_nonce`.
+pub extern "C" fn findora_ffi_tx_error_name(code: u32) -> *mut c_char {
+    string_to_c_char(tx_error_name(code))
+}
+
+#[no_mangle]
+/// Whether a transaction rejected with this code may succeed if it is rebuilt or resubmitted later.
+pub extern "C" fn findora_ffi_tx_error_is_retryable(code: u32) -> bool {
+    tx_error_is_retryable(code)
+}
+
 pub(super) fn parse_u64(n: *const c_char) -> u64 {
     c_char_to_string(n).parse().expect("Invalid u64.")
 }
@@ -2,8 +2,6 @@
 use wasm_bindgen::prelude::*;
 
 use super::data_model::*;
-use aes_gcm::aead::{generic_array::GenericArray, Aead, NewAead};
-use aes_gcm::Aes256Gcm;
 use credentials::{
     credential_commit, credential_issuer_key_gen, credential_open_commitment,
     credential_reveal, credential_sign, credential_user_key_gen, credential_verify,
@@ -14,17 +12,14 @@ use cryptohash::sha256;
 use globutils::wallet;
 use ledger::{
     data_model::{
-        AssetTypeCode, ASSET_TYPE_FRA, BLACK_HOLE_PUBKEY, BLACK_HOLE_PUBKEY_STAKING,
-        TX_FEE_MIN_V1,
+        AssetTypeCode, TxErrorCode, ASSET_TYPE_FRA, BLACK_HOLE_PUBKEY,
+        BLACK_HOLE_PUBKEY_STAKING, TX_FEE_MIN_V1,
     },
     staking::{MAX_DELEGATION_AMOUNT, MIN_DELEGATION_AMOUNT},
 };
-use rand::{thread_rng, Rng};
 use rand_chacha::ChaChaRng;
 use rand_core::SeedableRng;
-use ring::pbkdf2;
 use ruc::Result;
-use std::num::NonZeroU32;
 use std::str;
 use zei::serialization::ZeiFromToBytes;
 use zei::xfr::asset_record::open_blind_asset_record as open_bar;
@@ -316,66 +311,24 @@ pub fn rs_base64_to_bech32(pk: &str) -> Result<String> {
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
 pub fn encryption_pbkdf2_aes256gcm(key_pair: String, password: String) -> Vec<u8> {
-    const CREDENTIAL_LEN: usize = 32;
-    const IV_LEN: usize = 12;
-    let n_iter = NonZeroU32::new(32).unwrap();
-    let mut rng = thread_rng();
-
-    let mut salt = [0u8; CREDENTIAL_LEN];
-    rng.fill(&mut salt);
-    let mut derived_key = [0u8; CREDENTIAL_LEN];
-    pbkdf2::derive(
-        pbkdf2::PBKDF2_HMAC_SHA512,
-        n_iter,
-        &salt,
-        password.as_bytes(),
-        &mut derived_key,
-    );
-
-    let mut iv = [0u8; IV_LEN];
-    rng.fill(&mut iv);
-
-    let cipher = Aes256Gcm::new(GenericArray::from_slice(&derived_key));
-    let ciphertext = cipher
-        .encrypt(GenericArray::from_slice(&iv), key_pair.as_ref())
-        .unwrap_or_default();
-
-    // this is a hack, wasm-bindgen not support tuple of vectors
-    let mut res: Vec<u8> = Vec::new();
-    res.append(&mut salt.to_vec());
-    res.append(&mut iv.to_vec());
-    res.append(&mut ciphertext.to_vec());
-    res
+    finutils::crypto::encryption_pbkdf2_aes256gcm(
+        key_pair.as_bytes(),
+        &password,
+        finutils::crypto::WALLET_PBKDF2_ITERATIONS,
+    )
+    .unwrap_or_default()
 }
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
 pub fn decryption_pbkdf2_aes256gcm(enc_key_pair: Vec<u8>, password: String) -> String {
-    const CREDENTIAL_LEN: usize = 32;
-    const IV_LEN: usize = 12;
-    let n_iter = NonZeroU32::new(32).unwrap();
-
-    if enc_key_pair.len() <= CREDENTIAL_LEN + IV_LEN {
-        return "".to_string();
-    }
-
-    let salt = &enc_key_pair[0..CREDENTIAL_LEN];
-    let iv = &enc_key_pair[CREDENTIAL_LEN..(CREDENTIAL_LEN + IV_LEN)];
-    let ciphertext = &enc_key_pair[(CREDENTIAL_LEN + IV_LEN)..];
-
-    let mut derived_key = [0u8; CREDENTIAL_LEN];
-    pbkdf2::derive(
-        pbkdf2::PBKDF2_HMAC_SHA512,
-        n_iter,
-        salt,
-        password.as_bytes(),
-        &mut derived_key,
-    );
-    let cipher = Aes256Gcm::new(GenericArray::from_slice(&derived_key));
-    let plaintext = cipher
-        .decrypt(GenericArray::from_slice(iv), ciphertext.as_ref())
-        .unwrap_or_default();
-
-    String::from_utf8(plaintext).unwrap_or_else(|_| "".to_string())
+    finutils::crypto::decryption_pbkdf2_aes256gcm(
+        &enc_key_pair,
+        &password,
+        finutils::crypto::WALLET_PBKDF2_ITERATIONS,
+    )
+    .ok()
+    .and_then(|plaintext| String::from_utf8(plaintext).ok())
+    .unwrap_or_default()
 }
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
@@ -506,3 +459,30 @@ pub fn get_delegation_min_amount() -> u64 {
 pub fn get_delegation_max_amount() -> u64 {
     MAX_DELEGATION_AMOUNT
 }
+
+/// The codespace of an error code, e.g. `ledger` or `evm`,
+/// an empty string is returned for unknown codes.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn tx_error_codespace(code: u32) -> String {
+    TxErrorCode::from_code(code)
+        .map(|c| c.codespace().to_owned())
+        .unwrap_or_default()
+}
+
+/// The snake_case name of an error code, e.g. `invalid_nonce`,
+/// an empty string is returned for unknown codes.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn tx_error_name(code: u32) -> String {
+    TxErrorCode::from_code(code)
+        .map(|c| c.name().to_owned())
+        .unwrap_or_default()
+}
+
+/// Whether a transaction rejected with this code may succeed
+/// if it is rebuilt or resubmitted later, e.g. with a fresh nonce or a higher fee.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn tx_error_is_retryable(code: u32) -> bool {
+    TxErrorCode::from_code(code)
+        .map(|c| c.is_retryable())
+        .unwrap_or(false)
+}
@@ -11,7 +11,7 @@ use globutils::HashOf;
 use ledger::{
     data_model::{
         gen_random_keypair, AssetTypeCode, AuthenticatedTransaction, Operation,
-        TransferType, TxOutput,
+        Transaction, TransferType, TxOutput,
     },
     staking::{td_addr_to_bytes, PartialUnDelegation, TendermintAddr},
 };
@@ -33,6 +33,12 @@ pub fn rs_verify_authenticated_txn(
     Ok(authenticated_txn.is_valid(state_commitment))
 }
 
+/// Decrypts the encrypted memos sent to `kp` by a serialized transaction.
+pub fn rs_decrypt_memos(tx: String, kp: &XfrKeyPair) -> Result<Vec<String>> {
+    let tx = serde_json::from_str::<Transaction>(&tx)?;
+    Ok(tx.decrypt_memos(kp))
+}
+
 #[derive(Clone)]
 struct FeeInput {
     // Amount
@@ -128,7 +134,9 @@ impl TransactionBuilder {
         mut self,
         kp: XfrKeyPair,
     ) -> RucResult<TransactionBuilder> {
-        self.transaction_builder.add_fee_relative_auto(&kp, None)?;
+        // wallets follow the latest fee rules of the mainnet
+        self.transaction_builder
+            .add_fee_relative_auto(&kp, None, i64::MAX)?;
         Ok(self)
     }
 
@@ -156,7 +164,9 @@ impl TransactionBuilder {
     /// As the last operation of any transaction,
     /// add a static fee to the transaction.
     pub fn add_fee(mut self, inputs: FeeInputs) -> RucResult<TransactionBuilder> {
-        self.transaction_builder.add_fee(inputs.into(), None)?;
+        // wallets follow the latest fee rules of the mainnet
+        self.transaction_builder
+            .add_fee(inputs.into(), None, i64::MAX)?;
         Ok(self)
     }
 
@@ -335,6 +345,44 @@ impl TransactionBuilder {
         Ok(self)
     }
 
+    /// Adds a transfer which shields (`to_confidential`) or unshields `amount` of
+    /// the asset `code` of `kp`, the inputs are picked out of `utxos`,
+    /// and the fee required at `height` is paid by the transfer itself.
+    pub fn add_operation_convert_confidentiality(
+        mut self,
+        kp: &XfrKeyPair,
+        utxos: FeeInputs,
+        code: String,
+        amount: u64,
+        to_confidential: bool,
+        height: i64,
+    ) -> RucResult<TransactionBuilder> {
+        let code = AssetTypeCode::new_from_base64(&code)?;
+        let candidates = PlatformFeeInputs::from(utxos).open()?;
+        self.get_builder_mut()
+            .add_operation_convert_confidentiality(
+                kp,
+                candidates,
+                code,
+                amount,
+                to_confidential,
+                height,
+            )?;
+        Ok(self)
+    }
+
+    /// Attaches a memo that only `recipient` can read, such as an invoice ID,
+    /// it must be added before signing.
+    pub fn add_encrypted_memo(
+        mut self,
+        recipient: &XfrPublicKey,
+        memo: String,
+    ) -> RucResult<TransactionBuilder> {
+        self.get_builder_mut()
+            .add_encrypted_memo(recipient, &memo)?;
+        Ok(self)
+    }
+
     pub fn sign(mut self, kp: &XfrKeyPair) -> Result<TransactionBuilder> {
         self.get_builder_mut().sign(kp);
         Ok(self)
@@ -49,8 +49,9 @@ use {
     ledger::{
         data_model::{
             gen_random_keypair, AssetTypeCode, AssetTypePrefix,
-            AuthenticatedTransaction, Operation, TransferType, TxOutput, ASSET_TYPE_FRA,
-            BLACK_HOLE_PUBKEY, BLACK_HOLE_PUBKEY_STAKING, TX_FEE_MIN_V1,
+            AuthenticatedTransaction, CredentialProof, Operation, Transaction,
+            TransferType, TxErrorCode, TxOutput, ASSET_TYPE_FRA, BLACK_HOLE_PUBKEY,
+            BLACK_HOLE_PUBKEY_STAKING, TX_FEE_MIN_V1,
         },
         staking::{
             td_addr_to_bytes, PartialUnDelegation, TendermintAddr,
@@ -260,8 +261,9 @@ impl TransactionBuilder {
         kp: XfrKeyPair,
         memo: Option<String>,
     ) -> Result<TransactionBuilder, JsValue> {
+        // wallets follow the latest fee rules of the mainnet
         self.transaction_builder
-            .add_fee_relative_auto(&kp, memo)
+            .add_fee_relative_auto(&kp, memo, i64::MAX)
             .c(d!())
             .map_err(error_to_jsvalue)?;
         Ok(self)
@@ -299,8 +301,9 @@ impl TransactionBuilder {
         inputs: FeeInputs,
         memo: Option<String>,
     ) -> Result<TransactionBuilder, JsValue> {
+        // wallets follow the latest fee rules of the mainnet
         self.transaction_builder
-            .add_fee(inputs.into(), memo)
+            .add_fee(inputs.into(), memo, i64::MAX)
             .c(d!())
             .map_err(error_to_jsvalue)?;
         Ok(self)
@@ -446,6 +449,39 @@ impl TransactionBuilder {
         Ok(self)
     }
 
+    /// Proves that the owner of `address` holds a credential, as required by the credential rule
+    /// of an asset that is transferred to `address`.
+    /// @param {CredUserSecretKey} user_sk - Secret key of credential user.
+    /// @param {Credential} credential - Credential object.
+    /// @param {XfrPublicKey} address - Ledger address that receives the asset.
+    /// @param {JsValue} reveal_fields - Array of string names representing the attributes required
+    /// by the rule (i.e. `["kyc"]`).
+    /// @see {@link module:Findora-Wasm~AssetRules#set_credential_rule|AssetRules.set_credential_rule} for more information
+    /// about credential rules.
+    pub fn add_credential_proof(
+        mut self,
+        user_sk: &CredUserSecretKey,
+        credential: &Credential,
+        address: &XfrPublicKey,
+        reveal_fields: JsValue,
+    ) -> Result<TransactionBuilder, JsValue> {
+        let reveal_fields: Vec<String> = reveal_fields
+            .into_serde()
+            .c(d!())
+            .map_err(error_to_jsvalue)?;
+        let proof = CredentialProof::new(
+            &mut ChaChaRng::from_entropy(),
+            user_sk,
+            credential.get_cred_ref(),
+            address,
+            &reveal_fields,
+        )
+        .c(d!())
+        .map_err(error_to_jsvalue)?;
+        self.get_builder_mut().add_credential_proof(proof);
+        Ok(self)
+    }
+
     #[allow(missing_docs)]
     pub fn add_operation_delegate(
         mut self,
@@ -568,6 +604,63 @@ impl TransactionBuilder {
         Ok(self)
     }
 
+    /// Adds a transfer which converts some balance of an owner between the confidential form
+    /// (confidential amount and asset type) and the nonconfidential form.
+    /// The inputs and the change are handled, and the fee is paid by the transfer itself.
+    /// @param {XfrKeyPair} kp - Owner of the balance.
+    /// @param {FeeInputs} utxos - UTXOs of the owner to pick the inputs from, their amounts are ignored.
+    /// @param {string} code - Base64 string of the asset code.
+    /// @param {BigInt} amount - Amount to convert.
+    /// @param {boolean} to_confidential - `true` to shield the amount, `false` to unshield it.
+    /// @param {BigInt} height - The next block height of the network, which decides the fee.
+    /// @throws Will throw an error if the balance is insufficient.
+    pub fn add_operation_convert_confidentiality(
+        mut self,
+        kp: &XfrKeyPair,
+        utxos: FeeInputs,
+        code: String,
+        amount: u64,
+        to_confidential: bool,
+        height: i64,
+    ) -> Result<TransactionBuilder, JsValue> {
+        let code = AssetTypeCode::new_from_base64(&code)
+            .c(d!())
+            .map_err(error_to_jsvalue)?;
+        let candidates = PlatformFeeInputs::from(utxos)
+            .open()
+            .c(d!())
+            .map_err(error_to_jsvalue)?;
+        self.get_builder_mut()
+            .add_operation_convert_confidentiality(
+                kp,
+                candidates,
+                code,
+                amount,
+                to_confidential,
+                height,
+            )
+            .c(d!())
+            .map_err(error_to_jsvalue)?;
+        Ok(self)
+    }
+
+    /// Attaches a memo that only `recipient` can read, such as an invoice ID.
+    /// It must be added before signing, and costs an extra fee like an output memo.
+    /// @param {XfrPublicKey} recipient - Who can decrypt the memo.
+    /// @param {string} memo - The memo, at most 256 bytes.
+    /// @see {@link module:Findora-Wasm~decrypt_memos|decrypt_memos} for decrypting the memos.
+    pub fn add_encrypted_memo(
+        mut self,
+        recipient: &XfrPublicKey,
+        memo: String,
+    ) -> Result<TransactionBuilder, JsValue> {
+        self.get_builder_mut()
+            .add_encrypted_memo(recipient, &memo)
+            .c(d!())
+            .map_err(error_to_jsvalue)?;
+        Ok(self)
+    }
+
     /// Do nothing, compatible with frontend
     pub fn build(mut self) -> Result<TransactionBuilder, JsValue> {
         Ok(self)
@@ -987,6 +1080,20 @@ pub fn open_client_asset_record(
     .and_then(|oa| JsValue::from_serde(&oa).c(d!()).map_err(error_to_jsvalue))
 }
 
+#[wasm_bindgen]
+/// Decrypts the encrypted memos sent to `keypair` by a transaction.
+/// @param {string} tx - JSON-serialized transaction.
+/// @param {XfrKeyPair} keypair - Keypair of the memo recipient.
+/// @returns {string[]} The decrypted memos.
+pub fn decrypt_memos(tx: String, keypair: &XfrKeyPair) -> Result<JsValue, JsValue> {
+    let tx = serde_json::from_str::<Transaction>(&tx)
+        .c(d!())
+        .map_err(error_to_jsvalue)?;
+    JsValue::from_serde(&tx.decrypt_memos(keypair))
+        .c(d!())
+        .map_err(error_to_jsvalue)
+}
+
 #[wasm_bindgen]
 /// Extracts the public key as a string from a transfer key pair.
 pub fn get_pub_key_str(key_pair: &XfrKeyPair) -> String {
@@ -1299,11 +1406,6 @@ pub fn trace_assets(
 // Author: Chao Ma, github.com/chaosma. //
 //////////////////////////////////////////
 
-use aes_gcm::aead::{generic_array::GenericArray, Aead, NewAead};
-use aes_gcm::Aes256Gcm;
-use rand::{thread_rng, Rng};
-use ring::pbkdf2;
-use std::num::NonZeroU32;
 use std::str;
 
 #[wasm_bindgen]
@@ -1337,67 +1439,25 @@ pub fn base64_to_bech32(pk: &str) -> Result<String, JsValue> {
 #[wasm_bindgen]
 #[allow(missing_docs)]
 pub fn encryption_pbkdf2_aes256gcm(key_pair: String, password: String) -> Vec<u8> {
-    const CREDENTIAL_LEN: usize = 32;
-    const IV_LEN: usize = 12;
-    let n_iter = NonZeroU32::new(32).unwrap();
-    let mut rng = thread_rng();
-
-    let mut salt = [0u8; CREDENTIAL_LEN];
-    rng.fill(&mut salt);
-    let mut derived_key = [0u8; CREDENTIAL_LEN];
-    pbkdf2::derive(
-        pbkdf2::PBKDF2_HMAC_SHA512,
-        n_iter,
-        &salt,
-        password.as_bytes(),
-        &mut derived_key,
-    );
-
-    let mut iv = [0u8; IV_LEN];
-    rng.fill(&mut iv);
-
-    let cipher = Aes256Gcm::new(GenericArray::from_slice(&derived_key));
-    let ciphertext = cipher
-        .encrypt(GenericArray::from_slice(&iv), key_pair.as_ref())
-        .unwrap_or_default();
-
-    // this is a hack, wasm-bindgen not support tuple of vectors
-    let mut res: Vec<u8> = Vec::new();
-    res.append(&mut salt.to_vec());
-    res.append(&mut iv.to_vec());
-    res.append(&mut ciphertext.to_vec());
-    res
+    finutils::crypto::encryption_pbkdf2_aes256gcm(
+        key_pair.as_bytes(),
+        &password,
+        finutils::crypto::WALLET_PBKDF2_ITERATIONS,
+    )
+    .unwrap_or_default()
 }
 
 #[wasm_bindgen]
 #[allow(missing_docs)]
 pub fn decryption_pbkdf2_aes256gcm(enc_key_pair: Vec<u8>, password: String) -> String {
-    const CREDENTIAL_LEN: usize = 32;
-    const IV_LEN: usize = 12;
-    let n_iter = NonZeroU32::new(32).unwrap();
-
-    if enc_key_pair.len() <= CREDENTIAL_LEN + IV_LEN {
-        return "".to_string();
-    }
-
-    let salt = &enc_key_pair[0..CREDENTIAL_LEN];
-    let iv = &enc_key_pair[CREDENTIAL_LEN..(CREDENTIAL_LEN + IV_LEN)];
-    let ciphertext = &enc_key_pair[(CREDENTIAL_LEN + IV_LEN)..];
-
-    let mut derived_key = [0u8; CREDENTIAL_LEN];
-    pbkdf2::derive(
-        pbkdf2::PBKDF2_HMAC_SHA512,
-        n_iter,
-        salt,
-        password.as_bytes(),
-        &mut derived_key,
-    );
-    let cipher = Aes256Gcm::new(GenericArray::from_slice(&derived_key));
-    let plaintext = cipher
-        .decrypt(GenericArray::from_slice(iv), ciphertext.as_ref())
-        .unwrap_or_default();
-
-    String::from_utf8(plaintext).unwrap_or_else(|_| "".to_string())
+    finutils::crypto::decryption_pbkdf2_aes256gcm(
+        &enc_key_pair,
+        &password,
+        finutils::crypto::WALLET_PBKDF2_ITERATIONS,
+    )
+    .ok()
+    .and_then(|plaintext| String::from_utf8(plaintext).ok())
+    .unwrap_or_default()
 }
 
 #[wasm_bindgen]
@@ -1549,6 +1609,51 @@ pub fn get_delegation_max_amount() -> u64 {
     MAX_DELEGATION_AMOUNT
 }
 
+#[wasm_bindgen]
+/// The codespace of an error code, e.g. `ledger` or `evm`,
+/// an empty string is returned for unknown codes.
+pub fn tx_error_codespace(code: u32) -> String {
+    TxErrorCode::from_code(code)
+        .map(|c| c.codespace().to_owned())
+        .unwrap_or_default()
+}
+
+#[wasm_bindgen]
+/// The snake_case name of an error code, e.g. `invalid_nonce`,
+/// an empty string is returned for unknown codes.
+pub fn tx_error_name(code: u32) -> String {
+    TxErrorCode::from_code(code)
+        .map(|c| c.name().to_owned())
+        .unwrap_or_default()
+}
+
+#[wasm_bindgen]
+/// Whether a transaction rejected with this code may succeed
+/// if it is rebuilt or resubmitted later, e.g. with a fresh nonce or a higher fee.
+pub fn tx_error_is_retryable(code: u32) -> bool {
+    TxErrorCode::from_code(code)
+        .map(|c| c.is_retryable())
+        .unwrap_or(false)
+}
+
+#[wasm_bindgen]
+/// All error codes of rejected transactions, as a JSON array of
+/// `{"code", "name", "codespace", "retryable"}` objects.
+pub fn tx_error_codes() -> String {
+    let codes = TxErrorCode::ALL
+        .iter()
+        .map(|c| {
+            serde_json::json!({
+                "code": c.code(),
+                "name": c.name(),
+                "codespace": c.codespace(),
+                "retryable": c.is_retryable(),
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::Value::Array(codes).to_string()
+}
+
 #[cfg(test)]
 #[allow(missing_docs)]
 mod test {
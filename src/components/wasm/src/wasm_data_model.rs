@@ -8,8 +8,8 @@ use {
     globutils::{wallet, HashOf},
     ledger::data_model::{
         AssetRules as PlatformAssetRules, AssetType as PlatformAssetType,
-        AuthenticatedUtxo, SignatureRules as PlatformSignatureRules, TxOutput,
-        TxoRef as PlatformTxoRef, TxoSID,
+        AuthenticatedUtxo, CredentialRule, SignatureRules as PlatformSignatureRules,
+        TxOutput, TxoRef as PlatformTxoRef, TxoSID,
     },
     rand_chacha::ChaChaRng,
     rand_core::SeedableRng,
@@ -703,6 +703,30 @@ impl AssetRules {
         self
     }
 
+    /// Credential rule. Only the holders of a credential signed by the issuer, with the given
+    /// attribute values, can receive the asset.
+    /// @param {CredIssuerPublicKey} issuer_pub_key - Public key of the credential issuer.
+    /// @param {JsValue} attributes - Array of required attribute assignments of the form
+    /// `[{name: "kyc", val: "1"}]`.
+    /// @see {@link module:Findora-Wasm~TransactionBuilder#add_credential_proof|add_credential_proof} for information about how
+    /// to prove that a recipient holds the credential.
+    pub fn set_credential_rule(
+        mut self,
+        issuer_pub_key: &CredIssuerPublicKey,
+        attributes: JsValue,
+    ) -> Result<AssetRules, JsValue> {
+        let attributes: Vec<AttributeAssignment> =
+            attributes.into_serde().c(d!()).map_err(error_to_jsvalue)?;
+        self.rules.set_credential_rule(Some(CredentialRule {
+            issuer: issuer_pub_key.clone(),
+            attributes: attributes
+                .into_iter()
+                .map(|attr| (attr.name, attr.val.into_bytes()))
+                .collect(),
+        }));
+        Ok(self)
+    }
+
     /// Set the decimal number of asset. Return error string if failed, otherwise return changed asset.
     /// #param {Number} decimals - The number of decimals used to set its user representation.
     /// Decimals should be 0 ~ 255.
@@ -41,6 +41,12 @@ fn is_empty(x: &Option<Vec<u8>>) -> bool {
         }
     }
 }
+
+#[inline(always)]
+fn is_false(x: &bool) -> bool {
+    !*x
+}
+
 /// Use this operation to transfer.
 ///
 /// This operation only support binded xfr_address is sender address.
@@ -63,6 +69,11 @@ pub struct ConvertAccount {
     /// convert asset lowlevel data.
     #[serde(skip_serializing_if = "is_empty")]
     pub lowlevel_data: Option<Vec<u8>>,
+
+    /// credit a custom asset to the balances kept by the xhub module,
+    /// instead of going through the prism bridge contract.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub native: bool,
 }
 
 #[allow(missing_docs)]
@@ -97,12 +108,13 @@ pub fn is_convert_account(tx: &Transaction) -> bool {
 pub fn check_convert_account(
     tx: &Transaction,
     height: i64,
-) -> Result<(XfrPublicKey, H160, u64, AssetType, Vec<u8>)> {
+) -> Result<(XfrPublicKey, H160, u64, AssetType, Vec<u8>, bool)> {
     let signer;
     let target;
     let expected_value;
     let expected_asset;
     let expected_lowlevel;
+    let native;
 
     if let Some(Operation::ConvertAccount(ca)) = tx.body.operations.last() {
         if ca.nonce != tx.body.no_replay_token {
@@ -133,6 +145,20 @@ pub fn check_convert_account(
         } else {
             expected_lowlevel = Vec::new();
         }
+
+        native = ca.native;
+        if native {
+            if CFG.checkpoint.native_asset_bridge_height > height {
+                return Err(eg!(
+                    "TransferUTXOsToEVM error: the native asset bridge is not enabled"
+                ));
+            }
+            if expected_asset == ASSET_TYPE_FRA || !expected_lowlevel.is_empty() {
+                return Err(eg!(
+                    "TransferUTXOsToEVM error: only custom assets without lowlevel data are bridged natively"
+                ));
+            }
+        }
     } else {
         return Err(eg!(
             "TransferUTXOsToEVM error: invalid ConvertAccount operation"
@@ -179,5 +205,6 @@ pub fn check_convert_account(
         expected_value,
         expected_asset,
         expected_lowlevel,
+        native,
     ))
 }
@@ -1,9 +1,12 @@
 use {
     crate::{
         data_model::{
+            error_code::{TxErrorExt, TxResult},
             AssetType, AssetTypeCode, DefineAsset, IssueAsset, IssuerPublicKey, Memo,
             NoReplayToken, Operation, Transaction, TransferAsset, TransferType,
-            TxOutput, TxnTempSID, TxoRef, TxoSID, UpdateMemo,
+            TxErrorCode, TxOutput, TxnCredential, TxnTempSID, TxoRef, TxoSID,
+            UpdateIssuer, UpdateIssuerBody, UpdateMemo, ASSET_TYPE_FRA,
+            BLACK_HOLE_PUBKEY, MAX_ENCRYPTED_MEMOS,
         },
         staking::{
             self,
@@ -14,18 +17,19 @@ use {
                 update_staker::UpdateStakerOps, update_validator::UpdateValidatorOps,
             },
         },
+        LEDGER_TENDERMINT_BLOCK_HEIGHT,
     },
     config::abci::global_cfg::CFG,
-    globutils::HashOf,
+    globutils::{wallet, HashOf},
     lazy_static::lazy_static,
     parking_lot::Mutex,
     rand_chacha::{ChaCha20Rng, ChaChaRng},
     rand_core::SeedableRng,
     ruc::*,
-    serde::Serialize,
+    serde::{Deserialize, Serialize},
     std::{
         collections::{HashMap, HashSet},
-        sync::Arc,
+        sync::{atomic::Ordering, Arc},
     },
     zei::{
         serialization::ZeiFromToBytes,
@@ -76,6 +80,8 @@ pub struct TxnEffect {
     pub asset_types_involved: HashSet<AssetTypeCode>,
     /// Memo updates
     pub memo_updates: Vec<(AssetTypeCode, XfrPublicKey, Memo)>,
+    /// Issuer and minter updates, along with their signers
+    pub issuer_updates: Vec<(XfrPublicKey, UpdateIssuerBody)>,
 
     /// Staking operations
     pub delegations: Vec<DelegationOps>,
@@ -118,7 +124,29 @@ impl TxnEffect {
     /// `input_txos` and that Transfer should be valid if all those TXO SIDs
     /// exist unspent in the ledger and correspond to the correct
     /// TxOutput).
-    pub fn compute_effect(txn: Transaction) -> Result<TxnEffect> {
+    pub fn compute_effect(txn: Transaction) -> TxResult<TxnEffect> {
+        if !txn.body.encrypted_memos.is_empty() {
+            if LEDGER_TENDERMINT_BLOCK_HEIGHT.load(Ordering::Relaxed)
+                < CFG.checkpoint.encrypted_memo_height
+            {
+                return Err(TxErrorCode::InvalidMemo
+                    .error("Invalid encrypted memo: not enabled"));
+            }
+            if txn.body.encrypted_memos.len() > MAX_ENCRYPTED_MEMOS {
+                return Err(TxErrorCode::InvalidMemo.error(format!(
+                    "Invalid encrypted memo: more than {MAX_ENCRYPTED_MEMOS} in a transaction"
+                )));
+            }
+            for memo in txn.body.encrypted_memos.iter() {
+                memo.check().tx_err(TxErrorCode::InvalidMemo)?;
+            }
+        }
+
+        Self::compute_operations(txn).tx_err(TxErrorCode::InvalidTx)
+    }
+
+    // Validate and record the operations one by one, see `compute_effect`.
+    fn compute_operations(txn: Transaction) -> Result<TxnEffect> {
         let mut te = TxnEffect::default();
         let mut txo_count: usize = 0;
 
@@ -186,6 +214,9 @@ impl TxnEffect {
                 Operation::UpdateMemo(update_memo) => {
                     te.add_update_memo(&txn, update_memo).c(d!())?;
                 }
+                Operation::UpdateIssuer(update_issuer) => {
+                    te.add_update_issuer(&txn, update_issuer).c(d!())?;
+                }
                 Operation::Governance(i) => {
                     check_nonce!(i);
                     te.governances.push(i.clone());
@@ -200,6 +231,16 @@ impl TxnEffect {
             }
         }
 
+        // Credential proofs are checked against the asset rules in `check_txn_effects`,
+        // the credentials of historical transactions were never checked
+        if LEDGER_TENDERMINT_BLOCK_HEIGHT.load(Ordering::Relaxed)
+            >= CFG.checkpoint.credential_rule_height
+        {
+            for proof in txn.body.credentials.iter().filter_map(TxnCredential::proof) {
+                proof.verify().c(d!())?;
+            }
+        }
+
         te.txn = txn;
         Ok(te)
     }
@@ -538,6 +579,144 @@ impl TxnEffect {
 
         Ok(())
     }
+
+    // An issuer update is valid iff:
+    // 1) The signature is valid.
+    // 2) The asset is updated at most once in this transaction.
+    // 3) The signing key is the current asset issuer key (checked later).
+    fn add_update_issuer(
+        &mut self,
+        txn: &Transaction,
+        update_issuer: &UpdateIssuer,
+    ) -> Result<()> {
+        let pk = update_issuer.pubkey;
+        if txn.body.no_replay_token != update_issuer.body.no_replay_token {
+            return Err(eg!("replay token not match"));
+        }
+        // 1)
+        update_issuer
+            .signature
+            .verify(&pk, &update_issuer.body)
+            .c(d!())?;
+        // 2)
+        let code = update_issuer.body.asset_type;
+        if self
+            .issuer_updates
+            .iter()
+            .any(|(_, body)| body.asset_type == code)
+        {
+            return Err(eg!("Multiple issuer updates of the same asset"));
+        }
+        self.asset_types_involved.insert(code);
+        self.issuer_updates.push((pk, update_issuer.body.clone()));
+
+        Ok(())
+    }
+}
+
+/// What a transaction would do to the ledger, see `LedgerState::simulate_transaction`.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TxnEffectSummary {
+    /// TXOs spent by the transaction
+    pub inputs: Vec<TxoSID>,
+    /// New UTXOs, the internally-spent ones are excluded
+    pub outputs: Vec<TxOutput>,
+    /// FRAs paid to `BLACK_HOLE_PUBKEY`
+    pub fee: u64,
+    /// Staking operations, in a human-readable form
+    pub staking_changes: Vec<String>,
+}
+
+/// Result of simulating a transaction without applying it.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum TxnSimulation {
+    /// The transaction would be accepted
+    Accepted(TxnEffectSummary),
+    /// The transaction would be rejected
+    Rejected {
+        #[allow(missing_docs)]
+        code: TxErrorCode,
+        #[allow(missing_docs)]
+        codespace: String,
+        /// The validation error
+        log: String,
+    },
+}
+
+impl TxnEffect {
+    /// Summarize the effect of this transaction.
+    pub fn summary(&self) -> TxnEffectSummary {
+        let mut inputs = self.input_txos.keys().copied().collect::<Vec<_>>();
+        inputs.sort_unstable();
+
+        let fee =
+            self.txn
+                .body
+                .operations
+                .iter()
+                .filter_map(|op| match op {
+                    Operation::TransferAsset(xfr) => Some(xfr.body.outputs.iter()),
+                    _ => None,
+                })
+                .flatten()
+                .filter(|o| *BLACK_HOLE_PUBKEY == o.record.public_key)
+                .filter_map(|o| match (o.record.asset_type, o.record.amount) {
+                    (
+                        XfrAssetType::NonConfidential(ty),
+                        XfrAmount::NonConfidential(am),
+                    ) if ty == ASSET_TYPE_FRA => Some(am),
+                    _ => None,
+                })
+                .sum();
+
+        let mut staking_changes = vec![];
+        for i in self.update_stakers.iter() {
+            staking_changes.push(format!("update staker {}", i.body.validator));
+        }
+        for i in self.replace_stakers.iter() {
+            staking_changes.push(format!(
+                "replace staker {}",
+                wallet::public_key_to_bech32(&i.get_related_pubkeys()[0])
+            ));
+        }
+        for i in self.delegations.iter() {
+            staking_changes.push(format!(
+                "delegate {} to {}",
+                i.body.amount, i.body.validator
+            ));
+        }
+        for i in self.undelegations.iter() {
+            staking_changes.push(format!(
+                "undelegate by {}",
+                wallet::public_key_to_bech32(&i.pubkey)
+            ));
+        }
+        for i in self.claims.iter() {
+            staking_changes.push(format!(
+                "claim {} rewards",
+                i.body
+                    .amount
+                    .map(|am| am.to_string())
+                    .unwrap_or_else(|| "all".to_owned())
+            ));
+        }
+        if !self.update_validators.is_empty() {
+            staking_changes.push("update validators".to_owned());
+        }
+        if !self.governances.is_empty() {
+            staking_changes.push("governance".to_owned());
+        }
+        if !self.fra_distributions.is_empty() {
+            staking_changes.push("FRA distribution".to_owned());
+        }
+
+        TxnEffectSummary {
+            inputs,
+            outputs: self.txos.iter().flatten().cloned().collect(),
+            fee,
+            staking_changes,
+        }
+    }
 }
 
 /// Check tx in the context of a block, partially.
@@ -567,6 +746,8 @@ pub struct BlockEffect {
     pub issuance_keys: HashMap<AssetTypeCode, IssuerPublicKey>,
     /// Memo updates
     pub memo_updates: HashMap<AssetTypeCode, Memo>,
+    /// Issuer and minter updates
+    pub issuer_updates: HashMap<AssetTypeCode, UpdateIssuerBody>,
     /// counter for consensus integration; will add to a running count when applied.
     pub pulse_count: u64,
     /// simulator for safety
@@ -584,8 +765,8 @@ impl BlockEffect {
     ///   if `txn` would not interfere with any transaction in the block, the
     ///       new temp SID representing the transaction.
     ///   Otherwise, Err(...)
-    pub fn add_txn_effect(&mut self, txn_effect: TxnEffect) -> Result<TxnTempSID> {
-        self.check_txn_effect(&txn_effect).c(d!())?;
+    pub fn add_txn_effect(&mut self, txn_effect: TxnEffect) -> TxResult<TxnTempSID> {
+        self.check_txn_effect(&txn_effect)?;
 
         // By construction, no_replay_tokens entries are unique
         self.no_replay_tokens
@@ -617,14 +798,24 @@ impl BlockEffect {
             self.memo_updates.insert(code, memo);
         }
 
+        for (code, key) in txn_effect.issuance_keys {
+            self.issuance_keys.insert(code, key);
+        }
+
+        for (_, body) in txn_effect.issuer_updates {
+            self.issuer_updates.insert(body.asset_type, body);
+        }
+
         Ok(temp_sid)
     }
 
-    fn check_txn_effect(&mut self, txn_effect: &TxnEffect) -> Result<()> {
+    fn check_txn_effect(&mut self, txn_effect: &TxnEffect) -> TxResult<()> {
         // Check that no inputs are consumed twice
         for (input_sid, _) in txn_effect.input_txos.iter() {
             if self.input_txos.contains_key(&input_sid) {
-                return Err(eg!());
+                return Err(
+                    TxErrorCode::InputSpent.error("Input already spent in the block")
+                );
             }
         }
 
@@ -635,7 +826,8 @@ impl BlockEffect {
                 if self.new_asset_codes.contains_key(&type_code)
                     || self.new_issuance_nums.contains_key(&type_code)
                 {
-                    return Err(eg!());
+                    return Err(TxErrorCode::BlockConflict
+                        .error("Conflicts with a transaction in the block"));
                 }
             }
 
@@ -643,19 +835,38 @@ impl BlockEffect {
                 if self.new_asset_codes.contains_key(&type_code)
                     || self.new_issuance_nums.contains_key(&type_code)
                 {
-                    return Err(eg!());
+                    return Err(TxErrorCode::BlockConflict
+                        .error("Conflicts with a transaction in the block"));
                 }
 
                 // Debug-check that issued assets are registered in `issuance_keys`
                 if !nums.is_empty() && !txn_effect.issuance_keys.contains_key(&type_code)
                 {
-                    return Err(eg!());
+                    return Err(TxErrorCode::Internal.error("Unregistered issuance key"));
                 }
             }
             // Ensure that each asset's memo can only be updated once per block
             for (type_code, _, _) in txn_effect.memo_updates.iter() {
                 if self.memo_updates.contains_key(&type_code) {
-                    return Err(eg!());
+                    return Err(TxErrorCode::BlockConflict
+                        .error("Conflicts with a transaction in the block"));
+                }
+            }
+
+            // Ensure that the issuer of an asset can only be updated once per block,
+            // and never in the same block with an issuance of the asset
+            for (_, body) in txn_effect.issuer_updates.iter() {
+                if self.issuer_updates.contains_key(&body.asset_type)
+                    || self.new_issuance_nums.contains_key(&body.asset_type)
+                {
+                    return Err(TxErrorCode::BlockConflict
+                        .error("Conflicts with a transaction in the block"));
+                }
+            }
+            for type_code in txn_effect.new_issuance_nums.keys() {
+                if self.issuer_updates.contains_key(type_code) {
+                    return Err(TxErrorCode::BlockConflict
+                        .error("Conflicts with a transaction in the block"));
                 }
             }
         }
@@ -673,13 +884,14 @@ impl BlockEffect {
         if flag {
             for txn in self.txns.iter() {
                 if txn.body.no_replay_token == txn_effect.txn.body.no_replay_token {
-                    return Err(eg!());
+                    return Err(TxErrorCode::ReplayedTx.error("Replayed in the block"));
                 }
             }
         }
 
         // NOTE: set at the last position
-        self.check_staking(&txn_effect).c(d!())?;
+        self.check_staking(&txn_effect)
+            .tx_err(TxErrorCode::StakingRejected)?;
 
         Ok(())
     }
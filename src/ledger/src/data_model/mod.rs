@@ -12,7 +12,8 @@ mod test;
 use std::sync::atomic::Ordering;
 
 use config::abci::global_cfg::CFG;
-pub use effects::{BlockEffect, TxnEffect};
+pub use effects::{BlockEffect, TxnEffect, TxnEffectSummary, TxnSimulation};
+pub use fp_types::error_code::{self, TxErrorCode};
 use noah_algebra::bls12_381::BLSScalar;
 use noah_algebra::prelude::Scalar;
 use noah_crypto::basic::anemoi_jive::{AnemoiJive, AnemoiJive381};
@@ -33,6 +34,11 @@ use {
     },
     __trash__::{Policy, PolicyGlobals, TxnPolicyData},
     bitmap::SparseMap,
+    credentials::{
+        credential_commit, credential_open_commitment, credential_verify,
+        credential_verify_commitment, CredCommitment, CredIssuerPublicKey, CredPoK,
+        CredUserSecretKey, Credential,
+    },
     cryptohash::{sha256::Digest as BitDigest, HashValue},
     fbnc::NumKey,
     globutils::wallet::public_key_to_base64,
@@ -65,6 +71,10 @@ use {
             },
         },
     },
+    zeicrypto::basic::hybrid_encryption::{
+        hybrid_decrypt_with_ed25519_secret_key, hybrid_encrypt_with_sign_key,
+        ZeiHybridCipher,
+    },
 };
 
 const RANDOM_CODE_LENGTH: usize = 16;
@@ -403,6 +413,68 @@ impl<'de> Deserialize<'de> for Code {
 #[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct Memo(pub String);
 
+/// Max length in bytes of the plaintext of an [EncryptedMemo]
+pub const ENCRYPTED_MEMO_MAX_LEN: usize = 256;
+
+/// Max number of [EncryptedMemo]s in a transaction
+pub const MAX_ENCRYPTED_MEMOS: usize = 16;
+
+/// A transaction memo that only its recipient can read, e.g. an invoice ID.
+///
+/// The memo is locked by the same hybrid encryption as the `OwnerMemo` of a record.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EncryptedMemo {
+    /// Who can decrypt the memo
+    pub recipient: XfrPublicKey,
+    /// The encrypted memo
+    pub lock: ZeiHybridCipher,
+}
+
+impl EncryptedMemo {
+    /// Encrypt `memo` for `recipient`.
+    pub fn new<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        recipient: &XfrPublicKey,
+        memo: &str,
+    ) -> Result<Self> {
+        if memo.len() > ENCRYPTED_MEMO_MAX_LEN {
+            return Err(eg!(format!(
+                "Invalid encrypted memo: longer than {ENCRYPTED_MEMO_MAX_LEN} bytes"
+            )));
+        }
+        let pk = ed25519_dalek::PublicKey::from_bytes(recipient.as_bytes())
+            .c(d!("Invalid public key"))?;
+
+        Ok(EncryptedMemo {
+            recipient: *recipient,
+            lock: hybrid_encrypt_with_sign_key(prng, &pk, memo.as_bytes()),
+        })
+    }
+
+    /// Decrypt the memo by the key of the recipient.
+    pub fn decrypt(&self, kp: &XfrKeyPair) -> Result<String> {
+        if kp.get_pk_ref() != &self.recipient {
+            return Err(eg!("Not the recipient of the memo"));
+        }
+        let sk = ed25519_dalek::SecretKey::from_bytes(&kp.get_sk_ref().zei_to_bytes())
+            .c(d!("Invalid secret key"))?;
+
+        hybrid_decrypt_with_ed25519_secret_key(&self.lock, &sk)
+            .c(d!("Failed to decrypt the memo"))
+            .and_then(|plain| String::from_utf8(plain).c(d!()))
+    }
+
+    /// Check the size limit, the content can only be checked by the recipient.
+    pub fn check(&self) -> Result<()> {
+        if self.lock.ciphertext.len() > ENCRYPTED_MEMO_MAX_LEN {
+            return Err(eg!(format!(
+                "Invalid encrypted memo: longer than {ENCRYPTED_MEMO_MAX_LEN} bytes"
+            )));
+        }
+        Ok(())
+    }
+}
+
 #[allow(missing_docs)]
 #[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct ConfidentialMemo;
@@ -558,6 +630,10 @@ pub struct AssetRules {
     pub max_units: Option<u64>,
     /// Decimals: default to FRA_DECIMALS
     pub decimals: u8,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_default")]
+    /// Credential rule: Recipients of the asset must prove that they hold a proper credential.
+    pub credential_rule: Option<CredentialRule>,
 }
 impl Default for AssetRules {
     #[inline(always)]
@@ -569,6 +645,7 @@ impl Default for AssetRules {
             max_units: None,
             transfer_multisig_rules: None,
             decimals: FRA_DECIMALS,
+            credential_rule: None,
         }
     }
 }
@@ -612,6 +689,16 @@ impl AssetRules {
         self
     }
 
+    #[inline(always)]
+    #[allow(missing_docs)]
+    pub fn set_credential_rule(
+        &mut self,
+        credential_rule: Option<CredentialRule>,
+    ) -> &mut Self {
+        self.credential_rule = credential_rule;
+        self
+    }
+
     #[inline(always)]
     #[allow(missing_docs)]
     pub fn set_decimals(&mut self, decimals: u8) -> Result<&mut Self> {
@@ -653,6 +740,10 @@ pub struct AssetType {
     pub(crate) digest: [u8; 32],
     pub(crate) units: u64,
     pub(crate) confidential_units: Commitment,
+    /// Keys that can issue this asset besides the issuer
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_default")]
+    pub minters: Vec<Minter>,
 }
 
 impl AssetType {
@@ -660,6 +751,7 @@ impl AssetType {
     #[allow(missing_docs)]
     pub fn has_issuance_restrictions(&self) -> bool {
         self.properties.asset_rules.max_units.is_some()
+            || self.properties.asset_rules.credential_rule.is_some()
     }
 
     #[inline(always)]
@@ -687,6 +779,40 @@ impl AssetType {
     pub fn get_tracing_policies_ref(&self) -> &TracingPolicies {
         &self.properties.asset_rules.tracing_policies
     }
+
+    /// Get the active minter of this asset with the key `key`, if any.
+    #[inline(always)]
+    pub fn get_minter(&self, key: &IssuerPublicKey) -> Option<&Minter> {
+        self.minters.iter().find(|m| &m.key == key && m.cap > 0)
+    }
+
+    /// Add, update or remove minters of this asset, a minter with a zero cap is removed.
+    /// The amount that has been issued by a minter is kept when its cap is updated,
+    /// so removed minters are kept with a zero cap in case they are added again.
+    pub fn update_minters(&mut self, minters: &[(IssuerPublicKey, u64)]) {
+        for (key, cap) in minters.iter() {
+            if let Some(m) = self.minters.iter_mut().find(|m| &m.key == key) {
+                m.cap = *cap;
+            } else if *cap > 0 {
+                self.minters.push(Minter {
+                    key: *key,
+                    cap: *cap,
+                    issued: 0,
+                });
+            }
+        }
+    }
+}
+
+/// A key that is allowed to issue an asset on behalf of its issuer.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Minter {
+    /// The key of the minter
+    pub key: IssuerPublicKey,
+    /// The maximum amount that can be issued by this minter
+    pub cap: u64,
+    /// The amount that has been issued by this minter
+    pub issued: u64,
 }
 
 #[allow(missing_docs)]
@@ -695,16 +821,147 @@ pub struct CustomAssetPolicy {
     policy: Vec<u8>, // serialized policy, underlying form TBD.
 }
 
+/// KYC-like restriction of an asset,
+/// only the holders of a credential signed by `issuer` can receive it.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CredentialRule {
+    /// Public key of the credential issuer
+    pub issuer: CredIssuerPublicKey,
+    /// Attributes that must be revealed, along with their required values
+    pub attributes: Vec<(String, Vec<u8>)>,
+}
+
+impl CredentialRule {
+    /// Check if a verified proof meets this rule.
+    pub fn is_satisfied_by(&self, proof: &CredentialProof) -> bool {
+        self.issuer == proof.issuer
+            && self
+                .attributes
+                .iter()
+                .all(|attr| proof.attributes.contains(attr))
+    }
+}
+
 #[allow(missing_docs)]
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct CredentialProofKey([u8; 16]);
 
+/// The credential placeholder carried by the transactions
+/// before `credential_rule_height`, it proves nothing.
 #[allow(missing_docs)]
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
-pub struct CredentialProof {
+pub struct LegacyCredentialProof {
     pub key: CredentialProofKey,
 }
 
+/// A credential attached to a transaction,
+/// the legacy shape is kept so that historical transactions can be replayed.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum TxnCredential {
+    #[allow(missing_docs)]
+    Proof(CredentialProof),
+    #[allow(missing_docs)]
+    Legacy(LegacyCredentialProof),
+}
+
+impl TxnCredential {
+    /// The proof to check against credential rules, if any.
+    #[inline(always)]
+    pub fn proof(&self) -> Option<&CredentialProof> {
+        match self {
+            TxnCredential::Proof(p) => Some(p),
+            TxnCredential::Legacy(_) => None,
+        }
+    }
+}
+
+impl From<CredentialProof> for TxnCredential {
+    fn from(p: CredentialProof) -> Self {
+        TxnCredential::Proof(p)
+    }
+}
+
+/// Proof that the owner of `address` holds a credential,
+/// with some of its attributes revealed.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CredentialProof {
+    /// The ledger address that the credential is linked to
+    pub address: XfrPublicKey,
+    /// Public key of the credential issuer
+    pub issuer: CredIssuerPublicKey,
+    /// The revealed attributes
+    pub attributes: Vec<(String, Vec<u8>)>,
+    /// Commitment to the credential
+    pub commitment: CredCommitment,
+    /// Proof that the commitment is linked to `address`
+    pub commitment_pok: CredPoK,
+    /// Proof that the commitment contains the revealed attributes
+    pub reveal_pok: CredPoK,
+}
+
+impl CredentialProof {
+    /// Commit to a credential for `address`, and reveal the attributes in `reveal_fields`.
+    pub fn new<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        user_sk: &CredUserSecretKey,
+        credential: &Credential,
+        address: &XfrPublicKey,
+        reveal_fields: &[String],
+    ) -> Result<Self> {
+        let attributes = reveal_fields
+            .iter()
+            .map(|field| {
+                credential
+                    .attributes
+                    .iter()
+                    .find(|(name, _)| name == field)
+                    .cloned()
+                    .c(d!(format!("attribute `{field}` not found")))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let (commitment, commitment_pok, key) =
+            credential_commit(prng, user_sk, credential, address.as_bytes()).c(d!())?;
+        let reveal_pok =
+            credential_open_commitment(prng, user_sk, credential, &key, reveal_fields)
+                .c(d!())?;
+
+        Ok(CredentialProof {
+            address: *address,
+            issuer: credential.issuer_pub_key.clone(),
+            attributes,
+            commitment,
+            commitment_pok,
+            reveal_pok,
+        })
+    }
+
+    /// Verify the proof cryptographically.
+    pub fn verify(&self) -> Result<()> {
+        credential_verify_commitment(
+            &self.issuer,
+            &self.commitment,
+            &self.commitment_pok,
+            self.address.as_bytes(),
+        )
+        .c(d!("invalid credential commitment"))?;
+
+        let attributes = self
+            .attributes
+            .iter()
+            .map(|(name, val)| (name.clone(), val.as_slice()))
+            .collect::<Vec<_>>();
+        credential_verify(
+            &self.issuer,
+            &attributes,
+            &self.commitment,
+            &self.reveal_pok,
+        )
+        .c(d!("invalid credential reveal proof"))
+    }
+}
+
 #[derive(
     Clone,
     Copy,
@@ -1053,6 +1310,22 @@ pub struct UpdateMemoBody {
     pub no_replay_token: NoReplayToken,
 }
 
+/// Transfer the issuance authority of an asset to a new key,
+/// and manage the minters of the asset.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct UpdateIssuerBody {
+    /// The asset to update
+    pub asset_type: AssetTypeCode,
+    /// The new issuer, the current one is kept if `None`
+    pub new_issuer: Option<IssuerPublicKey>,
+    /// Minters to add or update along with their caps,
+    /// a minter with a zero cap is removed
+    #[serde(default)]
+    pub minters: Vec<(IssuerPublicKey, u64)>,
+    #[allow(missing_docs)]
+    pub no_replay_token: NoReplayToken,
+}
+
 #[allow(missing_docs)]
 #[derive(Clone, Debug, Deserialize)]
 pub enum AssetTypePrefix {
@@ -1294,6 +1567,30 @@ impl UpdateMemo {
     }
 }
 
+/// Operation data for updating the issuer and minters of a findora custom asset
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct UpdateIssuer {
+    /// Inner data to update
+    pub body: UpdateIssuerBody,
+    /// The findora account publickey of the current issuer
+    pub pubkey: XfrPublicKey,
+    /// the signature
+    pub signature: SignatureOf<UpdateIssuerBody>,
+}
+
+impl UpdateIssuer {
+    #[inline(always)]
+    #[allow(missing_docs)]
+    pub fn new(body: UpdateIssuerBody, signing_key: &XfrKeyPair) -> UpdateIssuer {
+        let signature = SignatureOf::new(signing_key, &body);
+        UpdateIssuer {
+            body,
+            pubkey: *signing_key.get_pk_ref(),
+            signature,
+        }
+    }
+}
+
 /// Operation list supported in findora network
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum Operation {
@@ -1325,6 +1622,8 @@ pub enum Operation {
     ConvertAccount(ConvertAccount),
     ///replace staker.
     ReplaceStaker(ReplaceStakerOps),
+    /// Update the issuer or minters of a findora custom asset
+    UpdateIssuer(UpdateIssuer),
 }
 
 fn set_no_replay_token(op: &mut Operation, no_replay_token: NoReplayToken) {
@@ -1351,6 +1650,7 @@ fn set_no_replay_token(op: &mut Operation, no_replay_token: NoReplayToken) {
             i.set_nonce(no_replay_token);
         }
         Operation::UpdateMemo(i) => i.body.no_replay_token = no_replay_token,
+        Operation::UpdateIssuer(i) => i.body.no_replay_token = no_replay_token,
         Operation::ConvertAccount(i) => i.set_nonce(no_replay_token),
         _ => {}
     }
@@ -1363,13 +1663,16 @@ pub struct TransactionBody {
     pub operations: Vec<Operation>,
     #[serde(default)]
     #[serde(skip_serializing_if = "is_default")]
-    pub credentials: Vec<CredentialProof>,
+    pub credentials: Vec<TxnCredential>,
     #[serde(default)]
     #[serde(skip_serializing_if = "is_default")]
     pub policy_options: Option<TxnPolicyData>,
     #[serde(default)]
     #[serde(skip_serializing_if = "is_default")]
     pub memos: Vec<Memo>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_default")]
+    pub encrypted_memos: Vec<EncryptedMemo>,
 }
 
 impl TransactionBody {
@@ -1687,6 +1990,33 @@ pub const TX_FEE_MIN_V0: u64 = 1_0000;
 /// 10fra
 pub const TX_FEE_MIN_V1: u64 = 10_000_000;
 
+/// 2fra, for each output beyond the third one and each output memo of a transfer
+pub const TX_FEE_PER_EXTRA_OUTPUT: u64 = 2_000_000;
+
+/// The minimum fee required by [check_fee](Transaction::check_fee) at `height`
+/// of a transfer with `outputs` outputs (the fee output itself included),
+/// `memos` of which carry a memo.
+pub fn min_transfer_fee(height: i64, outputs: u64, memos: u64) -> u64 {
+    if height > CFG.checkpoint.utxo_fee_height {
+        TX_FEE_MIN_V1
+            + outputs.saturating_sub(3) * TX_FEE_PER_EXTRA_OUTPUT
+            + memos * TX_FEE_PER_EXTRA_OUTPUT
+    } else {
+        TX_FEE_MIN_V0
+    }
+}
+
+/// The extra fee required by [check_fee](Transaction::check_fee) at `height`
+/// of `memos` encrypted memos, which is charged on top of [min_transfer_fee],
+/// even if the transaction needs no fee.
+pub fn encrypted_memo_fee(height: i64, memos: u64) -> u64 {
+    if height >= CFG.checkpoint.encrypted_memo_height {
+        memos * TX_FEE_PER_EXTRA_OUTPUT
+    } else {
+        0
+    }
+}
+
 impl Transaction {
     #[inline(always)]
     #[allow(missing_docs)]
@@ -1697,10 +2027,43 @@ impl Transaction {
             .any(|o| matches!(o, Operation::MintFra(_)))
     }
 
+    /// Decrypt all the encrypted memos sent to `kp`.
+    pub fn decrypt_memos(&self, kp: &XfrKeyPair) -> Vec<String> {
+        self.body
+            .encrypted_memos
+            .iter()
+            .filter(|m| &m.recipient == kp.get_pk_ref())
+            .filter_map(|m| m.decrypt(kp).ok())
+            .collect()
+    }
+
     /// All-in-one checker
     #[inline(always)]
     pub fn valid_in_abci(&self) -> bool {
-        self.check_fee() && !self.is_coinbase_tx()
+        self.check_in_abci().is_ok()
+    }
+
+    /// Reject the transactions that should not appear in ABCI,
+    /// coinbase ones are generated by the node itself,
+    /// the others pay the fee through a transfer unless they are exempt.
+    pub fn check_in_abci(&self) -> error_code::TxResult<()> {
+        if self.is_coinbase_tx() {
+            return Err(TxErrorCode::InvalidInAbci.error("Should not appear in ABCI"));
+        }
+        if !self.check_fee() {
+            let code = if self
+                .body
+                .operations
+                .iter()
+                .any(|op| matches!(op, Operation::TransferAsset(_)))
+            {
+                TxErrorCode::FeeTooLow
+            } else {
+                TxErrorCode::UnexpectedTxKind
+            };
+            return Err(code.error("Should not appear in ABCI"));
+        }
+        Ok(())
     }
 
     /// A simple fee checker
@@ -1711,63 +2074,62 @@ impl Transaction {
     /// - Fee destination == BLACK_HOLE_PUBKEY
     /// - A transaction with an `Operation` of defining/issuing FRA need NOT fee
     /// - A transaction with all addresses of inputs equal to BLACK_HOLE_PUBKEY need NOT fee
+    /// - Extra outputs and memos cost more, SEE [min_transfer_fee]
+    /// - Encrypted memos always cost more, SEE [encrypted_memo_fee]
     pub fn check_fee(&self) -> bool {
         // This method can not completely solve the DOS risk,
         // we should further limit the number of txo[s] in every operation.
         //
         // But it seems enough when we combine it with limiting
         // the payload size of submission-server's http-requests.
-        self.is_coinbase_tx()
-            || self.body.operations.iter().any(|ops| {
-                if let Operation::TransferAsset(ref x) = ops {
-                    let fee = if LEDGER_TENDERMINT_BLOCK_HEIGHT.load(Ordering::Relaxed)
-                        > CFG.checkpoint.utxo_fee_height
+        let height = LEDGER_TENDERMINT_BLOCK_HEIGHT.load(Ordering::Relaxed);
+        let memo_fee =
+            encrypted_memo_fee(height, self.body.encrypted_memos.len() as u64);
+
+        let pays = |x: &TransferAsset, fee: u64| {
+            x.body.outputs.iter().any(|o| {
+                if let XfrAssetType::NonConfidential(ty) = o.record.asset_type {
+                    if ty == ASSET_TYPE_FRA && *BLACK_HOLE_PUBKEY == o.record.public_key
                     {
-                        let mut fee = TX_FEE_MIN_V1;
-
-                        let len = x.body.outputs.len() as u64;
-                        if len > 3 {
-                            fee += (len - 3) * 2_000_000;
-                        }
-                        let mut memo_num = 0;
-                        for it in x.body.outputs.iter() {
-                            if it.memo.is_some() {
-                                memo_num += 1;
+                        if let XfrAmount::NonConfidential(am) = o.record.amount {
+                            if am >= fee {
+                                return true;
                             }
                         }
-                        fee += memo_num * 2_000_000;
-                        fee
-                    } else {
-                        TX_FEE_MIN_V0
-                    };
-
-                    return x.body.outputs.iter().any(|o| {
-                        if let XfrAssetType::NonConfidential(ty) = o.record.asset_type {
-                            if ty == ASSET_TYPE_FRA
-                                && *BLACK_HOLE_PUBKEY == o.record.public_key
-                            {
-                                if let XfrAmount::NonConfidential(am) = o.record.amount {
-                                    if am >= fee {
-                                        return true;
-                                    }
-                                }
-                            }
-                        }
-                        false
-                    });
-                } else if let Operation::DefineAsset(ref x) = ops {
-                    if x.body.asset.code.val == ASSET_TYPE_FRA {
-                        return true;
-                    }
-                } else if let Operation::IssueAsset(ref x) = ops {
-                    if x.body.code.val == ASSET_TYPE_FRA {
-                        return true;
                     }
-                } else if matches!(ops, Operation::UpdateValidator(_)) {
-                    return true;
                 }
                 false
             })
+        };
+
+        let fee_exempt = self.is_coinbase_tx()
+            || self.body.operations.iter().any(|ops| match ops {
+                Operation::DefineAsset(x) => x.body.asset.code.val == ASSET_TYPE_FRA,
+                Operation::IssueAsset(x) => x.body.code.val == ASSET_TYPE_FRA,
+                Operation::UpdateValidator(_) => true,
+                _ => false,
+            });
+
+        if fee_exempt && memo_fee == 0 {
+            return true;
+        }
+
+        self.body.operations.iter().any(|ops| {
+            if let Operation::TransferAsset(ref x) = ops {
+                let fee = alt!(
+                    fee_exempt,
+                    0,
+                    min_transfer_fee(
+                        height,
+                        x.body.outputs.len() as u64,
+                        x.body.outputs.iter().filter(|o| o.memo.is_some()).count()
+                            as u64,
+                    )
+                );
+                return pays(x, fee + memo_fee);
+            }
+            false
+        })
     }
 
     /// findora hash
@@ -1963,6 +2325,9 @@ impl Transaction {
                 Operation::UpdateMemo(o) => {
                     select_check(self, &o.pubkey).c(d!())?;
                 }
+                Operation::UpdateIssuer(o) => {
+                    select_check(self, &o.pubkey).c(d!())?;
+                }
                 Operation::UpdateStaker(o) => {
                     select_check(self, &o.pubkey).c(d!())?;
                 }
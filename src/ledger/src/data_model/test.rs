@@ -378,3 +378,209 @@ fn test_check_fee() {
     tx.add_operation(invalid_destination_not_black_hole);
     assert!(tx.check_fee());
 }
+
+#[test]
+fn test_check_in_abci() {
+    // the transfer pays no fee
+    let mut tx = gen_sample_tx();
+    assert_eq!(tx.check_in_abci().unwrap_err().code, TxErrorCode::FeeTooLow);
+    assert!(!tx.valid_in_abci());
+
+    // nothing can pay the fee
+    tx.body
+        .operations
+        .retain(|op| !matches!(op, Operation::TransferAsset(_)));
+    assert_eq!(
+        tx.check_in_abci().unwrap_err().code,
+        TxErrorCode::UnexpectedTxKind
+    );
+}
+
+#[test]
+fn test_tx_error_code() {
+    let mut codes = TxErrorCode::ALL
+        .iter()
+        .map(|c| c.code())
+        .collect::<Vec<_>>();
+    codes.sort_unstable();
+    codes.dedup();
+    assert_eq!(codes.len(), TxErrorCode::ALL.len());
+    for c in TxErrorCode::ALL.iter() {
+        assert_eq!(TxErrorCode::from_code(c.code()), Some(*c));
+    }
+    assert_eq!(TxErrorCode::from_code(0), None);
+
+    assert_eq!(
+        TxErrorCode::StakingRejected.codespace(),
+        error_code::CODESPACE_STAKING
+    );
+    assert_eq!(
+        TxErrorCode::InvalidNonce.codespace(),
+        error_code::CODESPACE_EVM
+    );
+    assert!(TxErrorCode::InvalidNonce.is_retryable());
+    assert_eq!(TxErrorCode::from_action_result(3), TxErrorCode::EvmReverted);
+}
+
+#[test]
+fn test_tx_error_of_rejected_tx() {
+    let mut ledger = crate::store::LedgerState::tmp_ledger();
+    let tx = Transaction::from_seq_id(0);
+
+    let mut block = ledger.start_block().unwrap();
+    let effect = TxnEffect::compute_effect(tx.clone()).unwrap();
+    ledger.apply_transaction(&mut block, effect).unwrap();
+    let effect = TxnEffect::compute_effect(tx).unwrap();
+    let e = ledger.apply_transaction(&mut block, effect).unwrap_err();
+    assert_eq!(e.code, TxErrorCode::ReplayedTx);
+}
+
+#[test]
+fn test_credential_proof() {
+    use credentials::{
+        credential_issuer_key_gen, credential_sign, credential_user_key_gen,
+    };
+
+    let mut prng = rand_chacha::ChaChaRng::from_entropy();
+    let (issuer_pk, issuer_sk) = credential_issuer_key_gen(
+        &mut prng,
+        &[("kyc".to_owned(), 1), ("age".to_owned(), 2)],
+    );
+    let (user_pk, user_sk) = credential_user_key_gen(&mut prng, &issuer_pk);
+    let attributes = vec![
+        ("kyc".to_owned(), b"1".to_vec()),
+        ("age".to_owned(), b"42".to_vec()),
+    ];
+    let signature = credential_sign(
+        &mut prng,
+        &issuer_sk,
+        &user_pk,
+        &attributes
+            .iter()
+            .map(|(n, v)| (n.clone(), v.as_slice()))
+            .collect::<Vec<_>>(),
+    )
+    .unwrap();
+    let credential = Credential {
+        attributes,
+        issuer_pub_key: issuer_pk.clone(),
+        signature,
+    };
+    let address = XfrKeyPair::generate(&mut prng).get_pk();
+
+    let proof = pnk!(CredentialProof::new(
+        &mut prng,
+        &user_sk,
+        &credential,
+        &address,
+        &["kyc".to_owned()],
+    ));
+    pnk!(proof.verify());
+
+    let mut rule = CredentialRule {
+        issuer: issuer_pk,
+        attributes: vec![("kyc".to_owned(), b"1".to_vec())],
+    };
+    assert!(rule.is_satisfied_by(&proof));
+    rule.attributes.push(("age".to_owned(), b"42".to_vec()));
+    assert!(!rule.is_satisfied_by(&proof));
+
+    let credential = TxnCredential::from(proof.clone());
+    let json = pnk!(serde_json::to_string(&credential));
+    assert_eq!(
+        pnk!(serde_json::from_str::<TxnCredential>(&json)),
+        credential
+    );
+
+    // The placeholders of historical transactions are still accepted
+    let legacy = r#"{"key":[0,1,2,3,4,5,6,7,8,9,10,11,12,13,14,15]}"#;
+    let credential = pnk!(serde_json::from_str::<TxnCredential>(legacy));
+    assert!(credential.proof().is_none());
+    assert_eq!(pnk!(serde_json::to_string(&credential)), legacy);
+
+    // A proof can not be moved to another address
+    let mut moved = proof;
+    moved.address = XfrKeyPair::generate(&mut prng).get_pk();
+    assert!(moved.verify().is_err());
+
+    assert!(CredentialProof::new(
+        &mut prng,
+        &user_sk,
+        &credential,
+        &address,
+        &["name".to_owned()],
+    )
+    .is_err());
+}
+
+#[test]
+fn test_encrypted_memo() {
+    let mut prng = rand_chacha::ChaChaRng::from_entropy();
+    let alice = XfrKeyPair::generate(&mut prng);
+    let bob = XfrKeyPair::generate(&mut prng);
+
+    let memo = pnk!(EncryptedMemo::new(
+        &mut prng,
+        alice.get_pk_ref(),
+        "invoice#42"
+    ));
+    pnk!(memo.check());
+    assert_eq!(pnk!(memo.decrypt(&alice)), "invoice#42");
+    assert!(memo.decrypt(&bob).is_err());
+
+    let mut forged = memo.clone();
+    forged.recipient = bob.get_pk();
+    assert_ne!(forged.decrypt(&bob).ok().as_deref(), Some("invoice#42"));
+
+    let long = "x".repeat(ENCRYPTED_MEMO_MAX_LEN + 1);
+    assert!(EncryptedMemo::new(&mut prng, alice.get_pk_ref(), &long).is_err());
+
+    let mut tx = Transaction::from_seq_id(0);
+    tx.body.encrypted_memos.push(memo);
+    assert_eq!(tx.decrypt_memos(&alice), vec!["invoice#42".to_owned()]);
+    assert!(tx.decrypt_memos(&bob).is_empty());
+}
+
+#[test]
+fn test_encrypted_memo_fee() {
+    let mut prng = rand_chacha::ChaChaRng::from_entropy();
+    let alice = XfrKeyPair::generate(&mut prng);
+    let height = LEDGER_TENDERMINT_BLOCK_HEIGHT.load(Ordering::Relaxed);
+    let fra = Some(ZeiAssetType([0; ASSET_TYPE_LENGTH]));
+
+    let mut tx = gen_sample_tx();
+    tx.add_operation(gen_fee_operation(
+        Some(min_transfer_fee(height, 1, 0)),
+        fra,
+        *BLACK_HOLE_PUBKEY,
+    ));
+    assert!(tx.check_fee());
+
+    tx.body.encrypted_memos.push(pnk!(EncryptedMemo::new(
+        &mut prng,
+        alice.get_pk_ref(),
+        "invoice#42"
+    )));
+    let memo_fee = encrypted_memo_fee(height, 1);
+    if height < CFG.checkpoint.encrypted_memo_height {
+        assert_eq!(memo_fee, 0);
+        assert!(tx.check_fee());
+        return;
+    }
+    assert!(!tx.check_fee());
+
+    tx.add_operation(gen_fee_operation(
+        Some(min_transfer_fee(height, 1, 0) + memo_fee),
+        fra,
+        *BLACK_HOLE_PUBKEY,
+    ));
+    assert!(tx.check_fee());
+
+    // a transaction needing no fee still pays for its encrypted memos
+    let mut coinbase = gen_sample_tx();
+    coinbase.add_operation(Operation::MintFra(MintFraOps::new(0, vec![])));
+    coinbase.body.encrypted_memos = tx.body.encrypted_memos.clone();
+    assert!(!coinbase.check_fee());
+    coinbase.add_operation(gen_fee_operation(Some(memo_fee), fra, *BLACK_HOLE_PUBKEY));
+    assert!(coinbase.check_fee());
+}
@@ -1,9 +1,10 @@
 //! For interact with BaseApp (EVM)
 
-use super::{Delegation, Validator};
+use super::{Amount, BlockHeight, Delegation, Staking, Validator};
 use once_cell::sync::{Lazy, OnceCell};
 use parking_lot::{Mutex, RwLock};
 use ruc::Result;
+use serde::{Deserialize, Serialize};
 use std::{collections::BTreeMap, sync::Arc};
 use zei::xfr::sig::XfrPublicKey;
 
@@ -14,6 +15,155 @@ pub static EVM_STAKING: OnceCell<Arc<RwLock<dyn EVMStaking>>> = OnceCell::new();
 pub static EVM_STAKING_MINTS: Lazy<Mutex<Vec<(XfrPublicKey, u64)>>> =
     Lazy::new(|| Mutex::new(Vec::with_capacity(64)));
 
+/// A read-only view of `Staking`, all amounts are in FRA units.
+///
+/// It is kept in the EVM state for the staking precompile,
+/// each block only writes the entries changed since the last one.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StakingSnapshot {
+    #[allow(missing_docs)]
+    pub global: GlobalSnapshot,
+    /// tendermint address => current validator
+    pub validators: BTreeMap<Vec<u8>, ValidatorSnapshot>,
+    /// delegator pubkey => delegation
+    pub delegations: BTreeMap<XfrPublicKey, DelegationSnapshot>,
+}
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GlobalSnapshot {
+    /// the height the snapshot was taken at
+    pub height: BlockHeight,
+    /// the sum of all delegations
+    pub global_delegation: Amount,
+    /// the circulating supply of FRA
+    pub circulating_supply: Amount,
+    /// the block rewards rate
+    pub return_rate: [u128; 2],
+}
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorSnapshot {
+    pub power: Amount,
+    pub commission_rate: [u64; 2],
+    pub delegator_cnt: u64,
+    pub signed_last_block: bool,
+}
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DelegationSnapshot {
+    /// (tendermint address, principal), of the current validators only
+    pub principals: Vec<(Vec<u8>, Amount)>,
+    pub total_principal: Amount,
+    pub rewards: Amount,
+}
+
+/// The changes of a `StakingSnapshot` in a block, `None` for a removed entry.
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StakingDiff {
+    pub global: GlobalSnapshot,
+    pub validators: Vec<(Vec<u8>, Option<ValidatorSnapshot>)>,
+    pub delegations: Vec<(XfrPublicKey, Option<DelegationSnapshot>)>,
+}
+
+impl StakingSnapshot {
+    /// Bring the snapshot up to date with `staking`, returns the changed entries.
+    ///
+    /// Unchanged delegations are compared in place and not copied.
+    ///
+    /// **NOTE:** this walks all the current validators and all the delegations
+    /// on every block, the cost is linear in the number of delegators,
+    /// only the writes to the EVM state are bounded by the changes of the block.
+    pub fn update(
+        &mut self,
+        staking: &Staking,
+        circulating_supply: Amount,
+        return_rate: [u128; 2],
+    ) -> StakingDiff {
+        let mut diff = StakingDiff::default();
+
+        let body = staking
+            .validator_get_current()
+            .map(|vd| &vd.body)
+            .into_iter()
+            .flatten();
+
+        let mut td_addrs = BTreeMap::new();
+        let mut validators = BTreeMap::new();
+        for (id, v) in body {
+            td_addrs.insert(id, v.td_addr.clone());
+            validators.insert(
+                v.td_addr.clone(),
+                ValidatorSnapshot {
+                    power: v.td_power,
+                    commission_rate: v.commission_rate,
+                    delegator_cnt: v.delegators.len() as u64,
+                    signed_last_block: v.signed_last_block,
+                },
+            );
+        }
+
+        for (addr, v) in validators.iter() {
+            if self.validators.get(addr) != Some(v) {
+                diff.validators.push((addr.clone(), Some(v.clone())));
+            }
+        }
+        for addr in self.validators.keys() {
+            if !validators.contains_key(addr) {
+                diff.validators.push((addr.clone(), None));
+            }
+        }
+        self.validators = validators;
+
+        let records = staking.get_global_delegation_records();
+        for (pk, d) in records.iter() {
+            let principals = d
+                .delegations
+                .iter()
+                .filter_map(|(v, am)| td_addrs.get(v).map(|a| (a, *am)));
+            let unchanged = self.delegations.get(pk).map_or(false, |ds| {
+                ds.total_principal == d.amount()
+                    && ds.rewards == d.rwd_amount
+                    && principals
+                        .clone()
+                        .eq(ds.principals.iter().map(|(a, am)| (a, *am)))
+            });
+            if !unchanged {
+                let ds = DelegationSnapshot {
+                    principals: principals.map(|(a, am)| (a.clone(), am)).collect(),
+                    total_principal: d.amount(),
+                    rewards: d.rwd_amount,
+                };
+                self.delegations.insert(*pk, ds.clone());
+                diff.delegations.push((*pk, Some(ds)));
+            }
+        }
+        let removed = self
+            .delegations
+            .keys()
+            .filter(|pk| !records.contains_key(pk))
+            .copied()
+            .collect::<Vec<_>>();
+        for pk in removed {
+            self.delegations.remove(&pk);
+            diff.delegations.push((pk, None));
+        }
+
+        self.global = GlobalSnapshot {
+            height: staking.cur_height(),
+            global_delegation: staking.get_global_delegation_amount(),
+            circulating_supply,
+            return_rate,
+        };
+        diff.global = self.global.clone();
+
+        diff
+    }
+}
+
 /// For account base app
 pub trait EVMStaking: Sync + Send + 'static {
     /// import_validators call
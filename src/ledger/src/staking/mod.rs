@@ -2515,6 +2515,54 @@ mod test {
         });
     }
 
+    #[test]
+    fn staking_snapshot_update() {
+        let mut staking = Staking::new();
+        let pk = XfrKeyPair::generate(&mut staking.cr).get_pk();
+        let delegation = Delegation {
+            delegations: BTreeMap::new(),
+            id: pk,
+            receiver_pk: None,
+            tmp_delegators: BTreeMap::new(),
+            start_height: 0,
+            end_height: BLOCK_HEIGHT_MAX,
+            state: DelegationState::Bond,
+            rwd_amount: 0,
+            proposer_rwd_cnt: 0,
+            delegation_rwd_cnt: 0,
+        };
+        staking
+            .delegation_info
+            .global_delegation_records_map
+            .insert(pk, delegation);
+
+        let mut snapshot = evm::StakingSnapshot::default();
+        let diff = snapshot.update(&staking, 100, [1, 100]);
+        assert_eq!(diff.delegations.len(), 1);
+        assert_eq!(diff.global.circulating_supply, 100);
+
+        // unchanged entries are not written again
+        let diff = snapshot.update(&staking, 100, [1, 100]);
+        assert!(diff.validators.is_empty() && diff.delegations.is_empty());
+
+        pnk!(staking.delegation_get_mut(&pk)).rwd_amount = 7;
+        let ds = evm::DelegationSnapshot {
+            principals: vec![],
+            total_principal: 0,
+            rewards: 7,
+        };
+        let diff = snapshot.update(&staking, 100, [1, 100]);
+        assert_eq!(diff.delegations, vec![(pk, Some(ds))]);
+
+        staking
+            .delegation_info
+            .global_delegation_records_map
+            .remove(&pk);
+        let diff = snapshot.update(&staking, 100, [1, 100]);
+        assert_eq!(diff.delegations, vec![(pk, None)]);
+        assert!(snapshot.delegations.is_empty());
+    }
+
     fn gen_round_vote_percent(lower_bound: u64, upper_bound: u64) -> [u64; 2] {
         let itv = upper_bound - lower_bound;
         let lb = if 0 == itv {
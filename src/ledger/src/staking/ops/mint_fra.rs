@@ -106,4 +106,6 @@ pub enum MintKind {
     Claim,
     UnStake,
     Other,
+    /// Custom assets withdrawn from EVM accounts.
+    FromAccount,
 }
@@ -5,12 +5,13 @@ use {
     crate::{
         data_model::{
             AssetTypeCode, AssetTypePrefix, DefineAsset, IssueAsset, IssuerPublicKey,
-            Operation, Transaction, TxOutput, TxnIDHash, TxnSID, TxoSID, XfrAddress,
-            ASSET_TYPE_FRA,
+            Operation, Transaction, TxOutput, TxnIDHash, TxnSID, TxoSID, UpdateIssuer,
+            XfrAddress, ASSET_TYPE_FRA,
         },
         staking::{
-            ops::mint_fra::MintEntry, Amount, BlockHeight, DelegationRwdDetail,
-            CHAN_D_AMOUNT_HIST, CHAN_GLOB_RATE_HIST, CHAN_V_SELF_D_HIST, KEEP_HIST,
+            ops::mint_fra::{MintEntry, MintKind},
+            Amount, BlockHeight, DelegationRwdDetail, CHAN_D_AMOUNT_HIST,
+            CHAN_GLOB_RATE_HIST, CHAN_V_SELF_D_HIST, KEEP_HIST,
         },
         store::LedgerState,
     },
@@ -19,7 +20,10 @@ use {
     globutils::wallet,
     ruc::*,
     serde::{Deserialize, Serialize},
-    std::collections::HashSet,
+    std::{
+        collections::{HashMap, HashSet},
+        ops::DerefMut,
+    },
     zei::xfr::{sig::XfrPublicKey, structs::OwnerMemo},
 };
 
@@ -67,6 +71,25 @@ pub struct ApiCache {
         Mapx<XfrPublicKey, Mapxnk<BlockHeight, DelegationRwdDetail>>,
     /// there are no transactions lost before last_sid
     pub last_sid: Mapx<String, u64>,
+    /// custom assets moved between UTXOs and EVM accounts by the native bridge
+    pub native_bridge_supply: Mapx<AssetTypeCode, NativeBridgeSupply>,
+}
+
+/// Amounts of a custom asset moved through the native bridge
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct NativeBridgeSupply {
+    /// converted from UTXOs to EVM accounts
+    pub to_account: u128,
+    /// withdrawn from EVM accounts to UTXOs
+    pub to_utxo: u128,
+}
+
+impl NativeBridgeSupply {
+    /// The amount expected to be held by EVM accounts
+    #[inline(always)]
+    pub fn in_account(&self) -> u128 {
+        self.to_account.saturating_sub(self.to_utxo)
+    }
 }
 
 impl ApiCache {
@@ -108,6 +131,9 @@ impl ApiCache {
                 "api_cache/{prefix}staking_delegation_rwd_hist",
             )),
             last_sid: new_mapx!(format!("api_cache/{prefix}last_sid",)),
+            native_bridge_supply: new_mapx!(format!(
+                "api_cache/{prefix}native_bridge_supply",
+            )),
         }
     }
 
@@ -141,6 +167,39 @@ impl ApiCache {
             .insert(code, tmp);
     }
 
+    /// Move a created asset to its new issuer
+    pub fn rotate_issuer(&mut self, update: &UpdateIssuer) {
+        let new_issuer = if let Some(k) = update.body.new_issuer {
+            k
+        } else {
+            return;
+        };
+        let code = update.body.asset_type;
+        let old_issuer = IssuerPublicKey { key: update.pubkey };
+        let mut created = if let Some(mut v) = self.created_assets.get_mut(&old_issuer) {
+            if let Some(c) = v.deref_mut().remove(&code) {
+                c
+            } else {
+                return;
+            }
+        } else {
+            return;
+        };
+        created.body.asset.issuer = new_issuer;
+
+        let prefix = self.prefix.clone();
+        self.created_assets
+            .entry(new_issuer)
+            .or_insert_with(|| {
+                new_mapxnk!(format!(
+                    "api_cache/{}created_assets/{}",
+                    prefix,
+                    new_issuer.to_base64()
+                ))
+            })
+            .insert(code, created);
+    }
+
     /// Cache issuance records
     pub fn cache_issuance(&mut self, issuance: &IssueAsset) {
         let new_records = issuance.body.records.to_vec();
@@ -162,6 +221,58 @@ impl ApiCache {
         save_issuance!(token_issuances, token_code);
     }
 
+    /// Account both directions of the native bridge in a transaction,
+    /// each transaction is counted once even if its block is cached again
+    pub fn cache_native_bridge(
+        &mut self,
+        txn_sid: TxnSID,
+        txn: &Transaction,
+        cur_height: u64,
+    ) {
+        let key = "native_bridge_txn_sid".to_owned();
+        if matches!(self.last_sid.get(&key), Some(sid) if sid >= txn_sid.0 as u64) {
+            return;
+        }
+        self.last_sid.insert(key, txn_sid.0 as u64);
+
+        let mut moved: HashMap<AssetTypeCode, NativeBridgeSupply> = HashMap::new();
+        for op in &txn.body.operations {
+            match op {
+                Operation::ConvertAccount(i) => {
+                    if let Some(at) = i.asset_type.filter(|_| i.native) {
+                        if CFG.checkpoint.native_asset_bridge_height > cur_height as i64
+                            || at == ASSET_TYPE_FRA
+                        {
+                            continue;
+                        }
+                        moved
+                            .entry(AssetTypeCode { val: at })
+                            .or_default()
+                            .to_account += i.value as u128;
+                    }
+                }
+                Operation::MintFra(i) => {
+                    for me in i.entries.iter() {
+                        if MintKind::FromAccount == me.kind {
+                            moved
+                                .entry(AssetTypeCode { val: me.asset_type })
+                                .or_default()
+                                .to_utxo += me.amount as u128;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for (code, m) in moved {
+            let mut supply = self.native_bridge_supply.get(&code).unwrap_or_default();
+            supply.to_account += m.to_account;
+            supply.to_utxo += m.to_utxo;
+            self.native_bridge_supply.insert(code, supply);
+        }
+    }
+
     /// Cache history style data
     ///
     /// Note: This function's data will migrate to findora scanner.
@@ -233,6 +344,7 @@ impl ApiCache {
 /// 3. Signer of a an issuance txn
 /// 4. Signer of a kv_update txn
 /// 5. Signer of a memo_update txn
+/// 6. Signer, new issuer and minters of an issuer update txn
 pub fn get_related_addresses<F>(
     txn: &Transaction,
     mut classify: F,
@@ -296,6 +408,19 @@ where
                     key: update_memo.pubkey,
                 });
             }
+            Operation::UpdateIssuer(update_issuer) => {
+                related_addresses.insert(XfrAddress {
+                    key: update_issuer.pubkey,
+                });
+                for key in update_issuer
+                    .body
+                    .new_issuer
+                    .iter()
+                    .chain(update_issuer.body.minters.iter().map(|(k, _)| k))
+                {
+                    related_addresses.insert(XfrAddress { key: key.key });
+                }
+            }
         }
     }
     related_addresses
@@ -340,6 +465,8 @@ pub fn check_lost_data(ledger: &mut LedgerState) -> Result<()> {
                 .unwrap()
                 .txn_sid_to_hash
                 .contains_key(&TxnSID(index))
+                // pruned transactions can not be recovered
+                && !ledger.is_pruned(TxnSID(index))
             {
                 let ftx = ledger.get_transaction_light(TxnSID(index)).c(d!())?;
                 let hash = ftx.txn.hash_tm().hex().to_uppercase();
@@ -398,7 +525,7 @@ pub fn check_lost_data(ledger: &mut LedgerState) -> Result<()> {
                         .get_transaction_light(
                             utxo.authenticated_txn.finalized_txn.tx_id,
                         )
-                        .unwrap();
+                        .c(d!())?;
                     let tx_hash = ftx.txn.hash_tm().hex().to_uppercase();
                     let owner_memos = ftx.txn.get_owner_memos_ref();
                     let addresses: Vec<XfrAddress> = ftx
@@ -590,6 +717,13 @@ pub fn update_api_cache(ledger: &mut LedgerState) -> Result<()> {
                 .insert(txn_sid, Default::default());
         }
 
+        let height = ledger.status.td_commit_height;
+        ledger
+            .api_cache
+            .as_mut()
+            .unwrap()
+            .cache_native_bridge(txn_sid, &curr_txn, height);
+
         // Add created asset
         for op in &curr_txn.body.operations {
             match op {
@@ -606,6 +740,13 @@ pub fn update_api_cache(ledger: &mut LedgerState) -> Result<()> {
                         .unwrap()
                         .cache_issuance(&issue_asset);
                 }
+                Operation::UpdateIssuer(update_issuer) => {
+                    ledger
+                        .api_cache
+                        .as_mut()
+                        .unwrap()
+                        .rotate_issuer(&update_issuer);
+                }
                 _ => {}
             };
         }
@@ -12,12 +12,14 @@ pub use fbnc;
 use {
     crate::{
         data_model::{
+            error_code::{TxErrorExt, TxResult},
             AssetType, AssetTypeCode, AssetTypePrefix, AuthenticatedBlock,
             AuthenticatedTransaction, AuthenticatedUtxo, AuthenticatedUtxoStatus,
             BlockEffect, BlockSID, FinalizedBlock, FinalizedTransaction, IssuerKeyPair,
             IssuerPublicKey, OutputPosition, StateCommitmentData, Transaction,
-            TransferType, TxnEffect, TxnSID, TxnTempSID, TxoSID, UnAuthenticatedUtxo,
-            Utxo, UtxoStatus, ASSET_TYPE_FRA, BLACK_HOLE_PUBKEY,
+            TransferType, TxErrorCode, TxnCredential, TxnEffect, TxnEffectSummary,
+            TxnSID, TxnTempSID, TxoSID, UnAuthenticatedUtxo, Utxo, UtxoStatus,
+            ASSET_TYPE_FRA, BLACK_HOLE_PUBKEY, BLACK_HOLE_PUBKEY_STAKING,
         },
         staking::{
             Amount, Power, Staking, TendermintAddrRef, FF_PK_EXTRA_120_0000, FF_PK_LIST,
@@ -56,6 +58,14 @@ use {
 
 const TRANSACTION_WINDOW_WIDTH: u64 = 128;
 
+// key of `LedgerState::prune_info`,
+// the index of the next block to be visited by `LedgerState::prune`
+const PRUNE_CURSOR_KEY: &str = "prune_cursor";
+
+// key of `LedgerState::prune_info`,
+// every transaction below this TxnSID missing from `tx_to_block_location` has been pruned
+const PRUNED_TXN_SID_KEY: &str = "pruned_txn_sid";
+
 type TmpSidMap = HashMap<TxnTempSID, (TxnSID, Vec<TxoSID>)>;
 
 /// findora ledger
@@ -71,6 +81,8 @@ pub struct LedgerState {
     pub tx_to_block_location: Mapxnk<TxnSID, [usize; 2]>,
     /// cache used in APIs
     pub api_cache: Option<ApiCache>,
+    /// progress of storage pruning, see `prune`
+    prune_info: Mapx<String, u64>,
 
     // current block effect (middle cache)
     block_ctx: Option<BlockEffect>,
@@ -114,17 +126,36 @@ impl LedgerState {
         &self,
         block: &mut BlockEffect,
         txe: TxnEffect,
-    ) -> Result<TxnTempSID> {
+    ) -> TxResult<TxnTempSID> {
         let tx = txe.txn.clone();
-        self.status
-            .check_txn_effects(&txe)
-            .c(d!())
-            .and_then(|_| block.add_txn_effect(txe).c(d!()))
-            .map(|tmpid| {
-                // NOTE: set at the last position
-                block.staking_simulator.coinbase_check_and_pay(&tx);
-                tmpid
-            })
+        self.status.check_txn_effects(&txe)?;
+        block.add_txn_effect(txe).map(|tmpid| {
+            // NOTE: set at the last position
+            block.staking_simulator.coinbase_check_and_pay(&tx);
+            tmpid
+        })
+    }
+
+    /// Check a tx against the committed state, the same as `apply_transaction`,
+    /// but in a throwaway block, so nothing will be applied
+    pub fn simulate_transaction(&self, txn: Transaction) -> TxResult<TxnEffectSummary> {
+        if txn.is_coinbase_tx() {
+            return Err(TxErrorCode::InvalidInAbci.error("Should not appear in ABCI"));
+        }
+        if !txn.check_fee() {
+            return Err(
+                TxErrorCode::FeeTooLow.error("Fee of the transaction is too low")
+            );
+        }
+
+        let txe = TxnEffect::compute_effect(txn)?;
+        let summary = txe.summary();
+
+        let mut block = BlockEffect {
+            staking_simulator: self.get_staking().clone(),
+            ..Default::default()
+        };
+        self.apply_transaction(&mut block, txe).map(|_| summary)
     }
 
     // Update the UTXO bitmap
@@ -299,6 +330,12 @@ impl LedgerState {
         self.status.next_txo
     }
 
+    /// Returns the number of currently-unspent TXOs
+    #[inline(always)]
+    pub fn get_utxo_count(&self) -> usize {
+        self.status.utxos.len()
+    }
+
     #[inline(always)]
     #[allow(missing_docs)]
     pub fn get_status(&self) -> &LedgerStatus {
@@ -408,6 +445,7 @@ impl LedgerState {
 
         let blocks_path = prefix.clone() + "blocks";
         let tx_to_block_location_path = prefix.clone() + "tx_to_block_location";
+        let prune_info_path = prefix.clone() + "prune_info";
 
         let mut ledger = LedgerState {
             status: LedgerStatus::new(&basedir, &snapshot_file).c(d!())?,
@@ -419,6 +457,7 @@ impl LedgerState {
             )),
             blocks: new_vecx!(&blocks_path),
             tx_to_block_location: new_mapxnk!(&tx_to_block_location_path),
+            prune_info: new_mapx!(&prune_info_path),
             utxo_map: Arc::new(RwLock::new(
                 LedgerState::init_utxo_map(&utxo_map_path).c(d!())?,
             )),
@@ -441,6 +480,11 @@ impl LedgerState {
         omit!(ledger.utxo_map.write().compute_checksum());
         ledger.fast_invariant_check().c(d!())?;
 
+        // the api cache needs the full history
+        if ledger.api_cache.is_some() && ledger.has_pruned() {
+            return Err(eg!("the query service can not run on a pruned ledger"));
+        }
+
         flush_data();
 
         // api_cache::check_lost_data(&mut ledger);
@@ -653,8 +697,8 @@ impl LedgerState {
     /// Get a utxo along with the transaction, spent status and commitment data which it belongs
     pub fn get_utxo(&self, id: TxoSID) -> Option<AuthenticatedUtxo> {
         if let Some(utxo) = self.status.get_utxo(id) {
-            let txn_location = self.status.txo_to_txn_location.get(&id).unwrap();
-            let authenticated_txn = self.get_transaction(txn_location.0).unwrap();
+            let txn_location = self.status.txo_to_txn_location.get(&id)?;
+            let authenticated_txn = self.get_transaction(txn_location.0).ok()?;
             let authenticated_spent_status = self.get_utxo_status(id);
             let state_commitment_data =
                 self.status.state_commitment_data.as_ref().unwrap().clone();
@@ -676,8 +720,8 @@ impl LedgerState {
     pub fn get_utxo_light(&self, id: TxoSID) -> Option<UnAuthenticatedUtxo> {
         let utxo = self.status.get_utxo(id);
         if let Some(utxo) = utxo {
-            let txn_location = self.status.txo_to_txn_location.get(&id).unwrap();
-            let txn = self.get_transaction_light(txn_location.0).unwrap();
+            let txn_location = self.status.txo_to_txn_location.get(&id)?;
+            let txn = self.get_transaction_light(txn_location.0).ok()?;
             let utxo_location = txn_location.1;
             Some(UnAuthenticatedUtxo {
                 utxo,
@@ -693,8 +737,8 @@ impl LedgerState {
     pub fn get_spent_utxo(&self, addr: TxoSID) -> Option<AuthenticatedUtxo> {
         let utxo = self.status.get_spent_utxo(addr);
         if let Some(utxo) = utxo {
-            let txn_location = self.status.txo_to_txn_location.get(&addr).unwrap();
-            let authenticated_txn = self.get_transaction(txn_location.0).unwrap();
+            let txn_location = self.status.txo_to_txn_location.get(&addr)?;
+            let authenticated_txn = self.get_transaction(txn_location.0).ok()?;
             let authenticated_spent_status = self.get_utxo_status(addr);
             let state_commitment_data =
                 self.status.state_commitment_data.as_ref().unwrap().clone();
@@ -716,8 +760,8 @@ impl LedgerState {
     pub fn get_spent_utxo_light(&self, addr: TxoSID) -> Option<UnAuthenticatedUtxo> {
         let utxo = self.status.get_spent_utxo(addr);
         if let Some(utxo) = utxo {
-            let txn_location = self.status.txo_to_txn_location.get(&addr).unwrap();
-            let txn = self.get_transaction_light(txn_location.0).unwrap();
+            let txn_location = self.status.txo_to_txn_location.get(&addr)?;
+            let txn = self.get_transaction_light(txn_location.0).ok()?;
             let utxo_location = txn_location.1;
             Some(UnAuthenticatedUtxo {
                 utxo,
@@ -910,15 +954,143 @@ impl LedgerState {
 
     /// Query the transaction by a TxnSID without its proof data to reduce latency
     pub fn get_transaction_light(&self, id: TxnSID) -> Result<FinalizedTransaction> {
-        self.tx_to_block_location
-            .get(&id)
+        let [block_idx, tx_idx] = match self.tx_to_block_location.get(&id) {
+            Some(location) => location,
+            None if self.is_pruned(id) => {
+                return Err(eg!(format!("Transaction {} has been pruned", id.0)));
+            }
+            None => return Err(eg!()),
+        };
+
+        self.blocks
+            .get(block_idx)
             .c(d!())
-            .and_then(|[block_idx, tx_idx]| {
-                self.blocks
-                    .get(block_idx)
-                    .c(d!())
-                    .and_then(|b| b.txns.get(tx_idx).cloned().c(d!()))
-            })
+            .and_then(|b| b.txns.get(tx_idx).cloned().c(d!()))
+    }
+
+    /// Whether the transaction has been dropped by `prune`
+    #[inline(always)]
+    pub fn is_pruned(&self, id: TxnSID) -> bool {
+        (id.0 as u64) < self.pruned_txn_sid()
+            && !self.tx_to_block_location.contains_key(&id)
+    }
+
+    /// Whether any transaction has been dropped by `prune`
+    #[inline(always)]
+    pub fn has_pruned(&self) -> bool {
+        0 < self.pruned_txn_sid()
+    }
+
+    #[inline(always)]
+    fn pruned_txn_sid(&self) -> u64 {
+        self.prune_info
+            .get(&PRUNED_TXN_SID_KEY.to_owned())
+            .unwrap_or(0)
+    }
+
+    /// Drop old transactions whose outputs have all been spent,
+    /// along with their spent utxos, only the latest `keep_blocks` blocks are left intact.
+    ///
+    /// The merkle trees, block states and state commitments are never touched,
+    /// so the app hash stays the same as an archive node.
+    /// Transactions that still own a live utxo are kept in their blocks,
+    /// since the queries of a utxo return its transaction.
+    /// Transactions without outputs, or whose outputs were all burned, are only history,
+    /// they are dropped with the spent ones once they leave the kept blocks.
+    /// Only the highest pruned TxnSID is recorded, transactions below it
+    /// that are no longer located in a block are the pruned ones.
+    ///
+    /// At most `max_blocks` blocks are visited per call, starting from where
+    /// the previous call stopped, so it is cheap enough to run on every commit.
+    /// The cursor goes back to the first block after reaching the prunable range,
+    /// outputs spent after a visit will be caught in the next round.
+    /// If `max_blocks` is `None`, all the prunable blocks are visited once.
+    ///
+    /// Return the number of pruned transactions.
+    pub fn prune(&mut self, keep_blocks: u64, max_blocks: Option<usize>) -> Result<u64> {
+        let end = self.blocks.len().saturating_sub(keep_blocks as usize);
+        if 0 == end {
+            return Ok(0);
+        }
+
+        let (start, n) = match max_blocks {
+            Some(n) => {
+                let cursor = self
+                    .prune_info
+                    .get(&PRUNE_CURSOR_KEY.to_owned())
+                    .map(|c| c as usize)
+                    .unwrap_or(0);
+                (alt!(cursor < end, cursor, 0), n)
+            }
+            None => (0, end),
+        };
+        let stop = end.min(start.saturating_add(n));
+
+        let mut cnt = 0;
+        for block_idx in start..stop {
+            cnt += self.prune_block(block_idx).c(d!())?;
+        }
+
+        if max_blocks.is_some() {
+            self.prune_info
+                .insert(PRUNE_CURSOR_KEY.to_owned(), stop as u64);
+        }
+
+        Ok(cnt)
+    }
+
+    fn prune_block(&mut self, block_idx: usize) -> Result<u64> {
+        let mut block = self.blocks.get(block_idx).c(d!())?;
+        let (kept, pruned): (Vec<_>, Vec<_>) = block
+            .txns
+            .into_iter()
+            .partition(|tx| self.has_live_utxo(tx));
+
+        if pruned.is_empty() {
+            return Ok(0);
+        }
+
+        block.txns = kept;
+        self.blocks.set_value(block_idx, block.clone());
+
+        for (tx_idx, tx) in block.txns.iter().enumerate() {
+            self.tx_to_block_location
+                .insert(tx.tx_id, [block_idx, tx_idx]);
+        }
+        let pruned_txn_sid = pruned
+            .iter()
+            .map(|tx| tx.tx_id.0 as u64 + 1)
+            .max()
+            .unwrap_or(0)
+            .max(self.pruned_txn_sid());
+        self.prune_info
+            .insert(PRUNED_TXN_SID_KEY.to_owned(), pruned_txn_sid);
+
+        for tx in pruned.iter() {
+            self.tx_to_block_location.remove(&tx.tx_id);
+            for sid in tx.txo_ids.iter() {
+                if !self.status.is_unspent_txo(*sid) {
+                    self.status.spent_utxos.remove(sid);
+                    self.status.txo_to_txn_location.remove(sid);
+                }
+            }
+        }
+
+        Ok(pruned.len() as u64)
+    }
+
+    // Outputs sent to the black holes can never be spent,
+    // so they should not prevent a transaction from being pruned.
+    fn has_live_utxo(&self, tx: &FinalizedTransaction) -> bool {
+        tx.txo_ids.iter().any(|sid| {
+            self.status
+                .get_utxo(*sid)
+                .map(|utxo| {
+                    let pk = &utxo.0.record.public_key;
+                    pk != &*BLACK_HOLE_PUBKEY && pk != &*BLACK_HOLE_PUBKEY_STAKING
+                })
+                .unwrap_or(false)
+        })
     }
 
     /// Query the Block by a BlockSID along with its proof data
@@ -1132,20 +1304,23 @@ impl LedgerStatus {
     //
     //  ledger.check_txn_effects(txn_effect);
     //  block.add_txn_effect(txn_effect);
-    fn check_txn_effects(&self, txn_effect: &TxnEffect) -> Result<()> {
+    fn check_txn_effects(&self, txn_effect: &TxnEffect) -> TxResult<()> {
         // The current transactions seq_id must be within the sliding window over seq_ids
         let (rand, seq_id) = (
             txn_effect.txn.body.no_replay_token.get_rand(),
             txn_effect.txn.body.no_replay_token.get_seq_id(),
         );
         if seq_id > self.block_commit_count {
-            return Err(eg!(("Transaction seq_id ahead of block_count")));
+            return Err(
+                TxErrorCode::SeqIdAhead.error("Transaction seq_id ahead of block_count")
+            );
         } else if seq_id + TRANSACTION_WINDOW_WIDTH < self.block_commit_count {
-            return Err(eg!(("Transaction seq_id too far behind block_count")));
+            return Err(TxErrorCode::SeqIdExpired
+                .error("Transaction seq_id too far behind block_count"));
         } else {
             // Check to see that this nrpt has not been seen before
             if self.sliding_set.has_key_at(seq_id as usize, rand) {
-                return Err(eg!(format!(
+                return Err(TxErrorCode::ReplayedTx.error(format!(
                     "No replay token ({rand:?}, {seq_id})seen before at  possible replay",
                 )));
             }
@@ -1155,14 +1330,17 @@ impl LedgerStatus {
         // 2. Inputs with transfer restrictions can only be owned by the asset issuer
         for (inp_sid, inp_record) in txn_effect.input_txos.iter() {
             // (1)
-            let inp_utxo = self.utxos.get(inp_sid).c(d!("Input must be unspent"))?;
+            let inp_utxo = self
+                .utxos
+                .get(inp_sid)
+                .ok_or_else(|| TxErrorCode::InputSpent.error("Input must be unspent"))?;
             let record = &(inp_utxo.0);
             if record != inp_record {
-                return Err(eg!((format!(
+                return Err(TxErrorCode::InputMismatch.error(format!(
                     "Input must correspond to claimed record: {} != {}",
-                    serde_json::to_string(&record).c(d!())?,
-                    serde_json::to_string(inp_record).unwrap()
-                ))));
+                    serde_json::to_string(&record).tx_err(TxErrorCode::Internal)?,
+                    serde_json::to_string(inp_record).tx_err(TxErrorCode::Internal)?
+                )));
             }
             // (2)
             if let Some(code) = record
@@ -1175,12 +1353,12 @@ impl LedgerStatus {
                     .asset_types
                     .get(&code)
                     .or_else(|| txn_effect.new_asset_codes.get(&code).cloned())
-                    .c(d!())?;
+                    .tx_err(TxErrorCode::LedgerRejected)?;
                 if !asset_type.properties.asset_rules.transferable
                     && asset_type.properties.issuer.deref() != &record.record.public_key
                 {
-                    return Err(eg!(
-                        ("Non-transferable asset type must be owned by asset issuer")
+                    return Err(TxErrorCode::NonTransferableAsset.error(
+                        "Non-transferable asset type must be owned by asset issuer",
                     ));
                 }
             }
@@ -1198,24 +1376,87 @@ impl LedgerStatus {
                     .asset_types
                     .get(&code)
                     .or_else(|| txn_effect.new_asset_codes.get(&code).cloned())
-                    .c(d!())?;
+                    .tx_err(TxErrorCode::LedgerRejected)?;
                 if !asset_type.properties.asset_rules.transferable
                     && asset_type.properties.issuer.deref() != &record.record.public_key
                 {
-                    return Err(eg!(
-                        ("Non-transferable asset type must be owned by asset issuer")
+                    return Err(TxErrorCode::NonTransferableAsset.error(
+                        "Non-transferable asset type must be owned by asset issuer",
                     ));
                 }
             }
         }
 
+        // Credential rules are not known before the checkpoint
+        if (self.td_commit_height as i64) < CFG.checkpoint.credential_rule_height {
+            if txn_effect
+                .txn
+                .body
+                .credentials
+                .iter()
+                .any(|c| c.proof().is_some())
+                || txn_effect
+                    .new_asset_codes
+                    .values()
+                    .any(|a| a.properties.asset_rules.credential_rule.is_some())
+            {
+                return Err(TxErrorCode::MissingCredential
+                    .error("Credential rules are not enabled"));
+            }
+        }
+
+        // Recipients of assets with a credential rule must hold a proper credential,
+        // except the asset issuer and the senders of the same asset in this transaction.
+        // Such assets can never be confidential, see `AssetType::has_issuance_restrictions`
+        // and `confidential_transfer_inputs` below, so records of confidential types are skipped.
+        for record in txn_effect
+            .txos
+            .iter()
+            .flatten()
+            .chain(txn_effect.internally_spent_txos.iter())
+        {
+            if let Some(code) = record
+                .record
+                .asset_type
+                .get_asset_type()
+                .map(|v| AssetTypeCode { val: v })
+            {
+                let asset_type = self
+                    .asset_types
+                    .get(&code)
+                    .or_else(|| txn_effect.new_asset_codes.get(&code).cloned())
+                    .tx_err(TxErrorCode::LedgerRejected)?;
+                if let Some(rule) = asset_type.properties.asset_rules.credential_rule {
+                    let owner = &record.record.public_key;
+                    let is_sender = txn_effect.input_txos.values().any(|i| {
+                        &i.record.public_key == owner
+                            && i.record.asset_type == record.record.asset_type
+                    });
+                    if asset_type.properties.issuer.deref() != owner
+                        && !is_sender
+                        && !txn_effect
+                            .txn
+                            .body
+                            .credentials
+                            .iter()
+                            .filter_map(TxnCredential::proof)
+                            .any(|p| &p.address == owner && rule.is_satisfied_by(p))
+                    {
+                        return Err(TxErrorCode::MissingCredential
+                            .error("Recipient does not hold the required credential"));
+                    }
+                }
+            }
+        }
+
         // New asset types must not already exist
         for (code, _asset_type) in txn_effect.new_asset_codes.iter() {
             if self.asset_types.contains_key(&code) {
-                return Err(eg!(format!("Asset type {:?} already defined", &code)));
+                return Err(TxErrorCode::AssetAlreadyDefined
+                    .error(format!("Asset type {:?} already defined", &code)));
             }
             if self.issuance_num.contains_key(&code) {
-                return Err(eg!(format!(
+                return Err(TxErrorCode::AssetAlreadyDefined.error(format!(
                     "Asset type {:?} is being defined after issue",
                     &code
                 )));
@@ -1233,73 +1474,134 @@ impl LedgerStatus {
         //  - NOTE: this relies on the sequence numbers appearing in sorted
         //    order
         for (code, seq_nums) in txn_effect.new_issuance_nums.iter() {
-            let iss_key = txn_effect.issuance_keys.get(&code).c(d!())?;
+            let iss_key = txn_effect
+                .issuance_keys
+                .get(&code)
+                .tx_err(TxErrorCode::LedgerRejected)?;
             let asset_type = self
                 .asset_types
                 .get(&code)
                 .or_else(|| txn_effect.new_asset_codes.get(&code).cloned())
-                .c(d!())?;
+                .tx_err(TxErrorCode::LedgerRejected)?;
             let proper_key = asset_type.properties.issuer;
-            if *iss_key != proper_key {
-                return Err(eg!(
-                    ("Issuance key is not the same as key of properties issuer")
-                ));
+            if *iss_key != proper_key && asset_type.get_minter(iss_key).is_none() {
+                return Err(TxErrorCode::IssuanceKeyMismatch
+                    .error("Issuance key is not the same as key of properties issuer"));
             }
 
             if seq_nums.is_empty() {
                 if !txn_effect.new_asset_codes.contains_key(&code) {
-                    return Err(eg!(("Code is not contained in new asset codes")));
+                    return Err(TxErrorCode::InvalidIssuanceSeqNum
+                        .error("Code is not contained in new asset codes"));
                 }
             // We could re-check that self.issuance_num doesn't contain `code`,
             // but currently it's redundant with the new-asset-type checks
             } else {
                 let curr_seq_num_limit = self.issuance_num.get(&code).unwrap_or(0);
-                let min_seq_num = seq_nums.first().c(d!())?;
+                let min_seq_num =
+                    seq_nums.first().tx_err(TxErrorCode::LedgerRejected)?;
                 if *min_seq_num < curr_seq_num_limit {
-                    return Err(eg!(("Minimum seq num is less than limit")));
+                    return Err(TxErrorCode::InvalidIssuanceSeqNum
+                        .error("Minimum seq num is less than limit"));
                 }
             }
         }
 
         // Asset Caps
         // (1) New issuance amounts cannot exceed asset cap
-        // (2) No confidential issuances allowed for assets with issuance restrictions
+        // (2) New issuance amounts of a minter cannot exceed its cap
+        // (3) No confidential issuances allowed for assets with issuance restrictions,
+        //     or from minters
         for (code, amount) in txn_effect.issuance_amounts.iter() {
             let asset_type = self
                 .asset_types
                 .get(&code)
                 .or_else(|| txn_effect.new_asset_codes.get(&code).cloned())
-                .c(d!())?;
+                .tx_err(TxErrorCode::LedgerRejected)?;
             // (1)
             if let Some(cap) = asset_type.properties.asset_rules.max_units {
                 let current_amount = self.issuance_amounts.get(code).unwrap_or(0);
-                if current_amount.checked_add(*amount).c(d!())? > cap {
-                    return Err(eg!(("Amount exceeds asset cap")));
+                if current_amount
+                    .checked_add(*amount)
+                    .tx_err(TxErrorCode::AssetCapExceeded)?
+                    > cap
+                {
+                    return Err(
+                        TxErrorCode::AssetCapExceeded.error("Amount exceeds asset cap")
+                    );
+                }
+            }
+            // (2)
+            if let Some(minter) = txn_effect
+                .issuance_keys
+                .get(code)
+                .and_then(|k| asset_type.get_minter(k))
+            {
+                if minter
+                    .issued
+                    .checked_add(*amount)
+                    .tx_err(TxErrorCode::AssetCapExceeded)?
+                    > minter.cap
+                {
+                    return Err(
+                        TxErrorCode::AssetCapExceeded.error("Amount exceeds minter cap")
+                    );
                 }
             }
         }
 
-        // (2)
+        // (3)
         for code in txn_effect.confidential_issuance_types.iter() {
             let asset_type = self
                 .asset_types
                 .get(&code)
                 .or_else(|| txn_effect.new_asset_codes.get(&code).cloned())
-                .c(d!())?;
+                .tx_err(TxErrorCode::LedgerRejected)?;
             if asset_type.has_issuance_restrictions() {
-                return Err(eg!(("This asset type has issuance restrictions")));
+                return Err(TxErrorCode::LedgerRejected
+                    .error("This asset type has issuance restrictions"));
+            }
+            if let Some(k) = txn_effect.issuance_keys.get(code) {
+                if asset_type.get_minter(k).is_some() {
+                    return Err(TxErrorCode::LedgerRejected
+                        .error("Minters can not issue confidential amounts"));
+                }
             }
         }
 
         // Memo updates
         // Multiple memo updates for the same asset are allowed, but only the last one will be applied.
         for memo_update in txn_effect.memo_updates.iter() {
-            let asset = self.asset_types.get(&memo_update.0).c(d!())?;
+            let asset = self
+                .asset_types
+                .get(&memo_update.0)
+                .tx_err(TxErrorCode::LedgerRejected)?;
             // Asset must be updatable and key must be correct
             if !asset.properties.asset_rules.updatable
                 || asset.properties.issuer != (IssuerPublicKey { key: memo_update.1 })
             {
-                return Err(eg!(("Non updatable asset or issuer mismatch")));
+                return Err(TxErrorCode::IssuanceKeyMismatch
+                    .error("Non updatable asset or issuer mismatch"));
+            }
+        }
+
+        // Issuer updates
+        // Only the current issuer can hand over the issuance authority or manage the minters
+        if !txn_effect.issuer_updates.is_empty()
+            && (self.td_commit_height as i64) < CFG.checkpoint.issuer_update_height
+        {
+            return Err(
+                TxErrorCode::LedgerRejected.error("Issuer updates are not enabled")
+            );
+        }
+        for (signer, body) in txn_effect.issuer_updates.iter() {
+            let asset = self
+                .asset_types
+                .get(&body.asset_type)
+                .tx_err(TxErrorCode::LedgerRejected)?;
+            if asset.properties.issuer != (IssuerPublicKey { key: *signer }) {
+                return Err(TxErrorCode::IssuanceKeyMismatch
+                    .error("Issuer update is not signed by the asset issuer"));
             }
         }
 
@@ -1311,10 +1613,10 @@ impl LedgerStatus {
                 .asset_types
                 .get(&code)
                 .or_else(|| txn_effect.new_asset_codes.get(&code).cloned())
-                .c(d!())?;
+                .tx_err(TxErrorCode::LedgerRejected)?;
             if asset_type.has_transfer_restrictions() {
-                return Err(eg!(
-                    ("non-confidential assets with transfer restrictions can't become confidential")
+                return Err(TxErrorCode::NonTransferableAsset.error(
+                    "non-confidential assets with transfer restrictions can't become confidential",
                 ));
             }
         }
@@ -1377,6 +1679,27 @@ impl LedgerStatus {
             }
         }
 
+        // Record the amounts issued by minters
+        for (code, amount) in block.issuance_amounts.iter() {
+            if let Some(key) = block.issuance_keys.get(code) {
+                if let Some(ref mut asset) = self.asset_types.get_mut(code) {
+                    if let Some(m) = asset.minters.iter_mut().find(|m| &m.key == key) {
+                        m.issued += amount;
+                    }
+                }
+            }
+        }
+
+        // Apply issuer updates
+        for (code, body) in block.issuer_updates.drain() {
+            if let Some(ref mut asset) = self.asset_types.get_mut(&code) {
+                if let Some(new_issuer) = body.new_issuer {
+                    asset.properties.issuer = new_issuer;
+                }
+                asset.update_minters(&body.minters);
+            }
+        }
+
         for (code, amount) in block.issuance_amounts.drain() {
             let code = handle_asset_type_code(code);
             let mut amt = self.issuance_amounts.entry(code).or_insert(0);
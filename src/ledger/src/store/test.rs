@@ -4,9 +4,10 @@
 use {
     super::{helpers::*, *},
     crate::data_model::{
-        AssetRules, AssetTypeCode, IssueAsset, IssueAssetBody, Memo, Operation,
-        Transaction, TransferAsset, TransferAssetBody, TxOutput, TxnEffect, TxoRef,
-        TxoSID, ASSET_TYPE_FRA, BLACK_HOLE_PUBKEY, TX_FEE_MIN_V0,
+        AssetRules, AssetTypeCode, CredentialRule, IssueAsset, IssueAssetBody,
+        IssuerPublicKey, Memo, Operation, Transaction, TransferAsset, TransferAssetBody,
+        TxOutput, TxnEffect, TxoRef, TxoSID, UpdateIssuer, UpdateIssuerBody,
+        ASSET_TYPE_FRA, BLACK_HOLE_PUBKEY, TX_FEE_MIN_V0,
     },
     rand_core::SeedableRng,
     zei::{
@@ -681,7 +682,7 @@ pub fn test_max_units() {
 
         let mut block = ledger.start_block().unwrap();
         let res = ledger.apply_transaction(&mut block, effect);
-        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().code, TxErrorCode::AssetCapExceeded);
 
         // Ensure that cap can be reached
         let tx = create_issuance_txn(
@@ -714,6 +715,230 @@ pub fn test_max_units() {
     }
 }
 
+#[test]
+fn test_credential_rule() {
+    let mut ledger = LedgerState::tmp_ledger();
+    let params = PublicParams::default();
+
+    let issuer = XfrKeyPair::generate(&mut ledger.get_prng());
+    let (cred_issuer, _) = credentials::credential_issuer_key_gen(
+        &mut ledger.get_prng(),
+        &[("kyc".to_owned(), 1)],
+    );
+    let rule = CredentialRule {
+        issuer: cred_issuer,
+        attributes: vec![("kyc".to_owned(), b"1".to_vec())],
+    };
+
+    let code = AssetTypeCode::gen_random();
+    let seq_id = ledger.get_block_commit_count();
+    let (tx, mut new_code) = create_definition_transaction(
+        &code,
+        &issuer,
+        AssetRules::default()
+            .set_credential_rule(Some(rule))
+            .clone(),
+        Some(Memo("test".to_string())),
+        seq_id,
+    )
+    .unwrap();
+    if CFG.checkpoint.utxo_asset_prefix_height > ledger.get_tendermint_height() {
+        new_code = code;
+    }
+
+    let effect = TxnEffect::compute_effect(tx.clone()).unwrap();
+    let mut block = ledger.start_block().unwrap();
+    let res = ledger.apply_transaction(&mut block, effect);
+    if (ledger.get_tendermint_height() as i64) < CFG.checkpoint.credential_rule_height {
+        assert_eq!(res.unwrap_err().code, TxErrorCode::MissingCredential);
+        return;
+    }
+    res.unwrap();
+    ledger.finish_block(block).unwrap();
+
+    // Confidential records would escape the rule
+    let tx = create_issuance_txn(
+        &mut ledger,
+        &params,
+        &new_code,
+        10,
+        0,
+        AssetRecordType::ConfidentialAmount_NonConfidentialAssetType,
+        &issuer,
+    );
+    let effect = TxnEffect::compute_effect(tx).unwrap();
+    let mut block = ledger.start_block().unwrap();
+    let res = ledger.apply_transaction(&mut block, effect);
+    assert_eq!(res.unwrap_err().code, TxErrorCode::LedgerRejected);
+}
+
+#[test]
+fn test_update_issuer() {
+    let mut ledger = LedgerState::tmp_ledger();
+    let params = PublicParams::default();
+
+    let issuer = XfrKeyPair::generate(&mut ledger.get_prng());
+    let new_issuer = XfrKeyPair::generate(&mut ledger.get_prng());
+    let minter = XfrKeyPair::generate(&mut ledger.get_prng());
+    let minter_key = IssuerPublicKey {
+        key: minter.get_pk(),
+    };
+
+    let code = AssetTypeCode::gen_random();
+    let seq_id = ledger.get_block_commit_count();
+    let (tx, mut new_code) = create_definition_transaction(
+        &code,
+        &issuer,
+        AssetRules::default(),
+        Some(Memo("test".to_string())),
+        seq_id,
+    )
+    .unwrap();
+    if CFG.checkpoint.utxo_asset_prefix_height > ledger.get_tendermint_height() {
+        new_code = code;
+    }
+    apply_transaction(&mut ledger, tx);
+
+    let update_issuer_txn =
+        |ledger: &LedgerState, signer: &XfrKeyPair, body: UpdateIssuerBody| {
+            let mut body = body;
+            let seq_id = ledger.get_block_commit_count();
+            let mut tx = Transaction::from_seq_id(seq_id);
+            body.no_replay_token = tx.body.no_replay_token;
+            tx.add_operation(Operation::UpdateIssuer(UpdateIssuer::new(body, signer)));
+            tx
+        };
+    let body = UpdateIssuerBody {
+        asset_type: new_code,
+        new_issuer: Some(IssuerPublicKey {
+            key: new_issuer.get_pk(),
+        }),
+        minters: vec![(minter_key, 100)],
+        no_replay_token: Default::default(),
+    };
+
+    // Only the current issuer can update the issuer
+    let tx = update_issuer_txn(&ledger, &minter, body.clone());
+    let effect = TxnEffect::compute_effect(tx).unwrap();
+    let mut block = ledger.start_block().unwrap();
+    assert!(ledger.apply_transaction(&mut block, effect).is_err());
+    ledger.finish_block(block).unwrap();
+
+    let tx = update_issuer_txn(&ledger, &issuer, body);
+    if (ledger.get_tendermint_height() as i64) < CFG.checkpoint.issuer_update_height {
+        let effect = TxnEffect::compute_effect(tx).unwrap();
+        let mut block = ledger.start_block().unwrap();
+        assert!(ledger.apply_transaction(&mut block, effect).is_err());
+        return;
+    }
+    apply_transaction(&mut ledger, tx);
+    let asset = ledger.get_asset_type(&new_code).unwrap();
+    assert_eq!(asset.properties.issuer.key, new_issuer.get_pk());
+    assert_eq!(asset.minters.len(), 1);
+
+    // The old issuer can not issue any more
+    let tx = create_issuance_txn(
+        &mut ledger,
+        &params,
+        &new_code,
+        10,
+        0,
+        AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType,
+        &issuer,
+    );
+    let effect = TxnEffect::compute_effect(tx).unwrap();
+    let mut block = ledger.start_block().unwrap();
+    assert!(ledger.apply_transaction(&mut block, effect).is_err());
+    ledger.finish_block(block).unwrap();
+
+    // The new issuer and the minter can
+    let tx = create_issuance_txn(
+        &mut ledger,
+        &params,
+        &new_code,
+        1000,
+        0,
+        AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType,
+        &new_issuer,
+    );
+    apply_transaction(&mut ledger, tx);
+    let tx = create_issuance_txn(
+        &mut ledger,
+        &params,
+        &new_code,
+        60,
+        1,
+        AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType,
+        &minter,
+    );
+    apply_transaction(&mut ledger, tx);
+    let asset = ledger.get_asset_type(&new_code).unwrap();
+    assert_eq!(asset.minters[0].issued, 60);
+
+    // But the minter can not exceed its cap
+    let tx = create_issuance_txn(
+        &mut ledger,
+        &params,
+        &new_code,
+        41,
+        2,
+        AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType,
+        &minter,
+    );
+    let effect = TxnEffect::compute_effect(tx).unwrap();
+    let mut block = ledger.start_block().unwrap();
+    assert!(ledger.apply_transaction(&mut block, effect).is_err());
+    ledger.finish_block(block).unwrap();
+
+    // Removed minters can not issue any more
+    let mut body = UpdateIssuerBody {
+        asset_type: new_code,
+        new_issuer: None,
+        minters: vec![(minter_key, 0)],
+        no_replay_token: Default::default(),
+    };
+    let tx = update_issuer_txn(&ledger, &new_issuer, body.clone());
+    apply_transaction(&mut ledger, tx);
+    let asset = ledger.get_asset_type(&new_code).unwrap();
+    assert!(asset.get_minter(&minter_key).is_none());
+    assert_eq!(asset.properties.issuer.key, new_issuer.get_pk());
+
+    let tx = create_issuance_txn(
+        &mut ledger,
+        &params,
+        &new_code,
+        1,
+        2,
+        AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType,
+        &minter,
+    );
+    let effect = TxnEffect::compute_effect(tx).unwrap();
+    let mut block = ledger.start_block().unwrap();
+    assert!(ledger.apply_transaction(&mut block, effect).is_err());
+    ledger.finish_block(block).unwrap();
+
+    // A re-added minter keeps what it has issued
+    body.minters = vec![(minter_key, 100)];
+    let tx = update_issuer_txn(&ledger, &new_issuer, body);
+    apply_transaction(&mut ledger, tx);
+    let asset = ledger.get_asset_type(&new_code).unwrap();
+    assert_eq!(asset.get_minter(&minter_key).unwrap().issued, 60);
+
+    let tx = create_issuance_txn(
+        &mut ledger,
+        &params,
+        &new_code,
+        41,
+        2,
+        AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType,
+        &minter,
+    );
+    let effect = TxnEffect::compute_effect(tx).unwrap();
+    let mut block = ledger.start_block().unwrap();
+    assert!(ledger.apply_transaction(&mut block, effect).is_err());
+    ledger.finish_block(block).unwrap();
+}
+
 fn gen_fee_operation(
     l: &mut LedgerState,
     txo_sid: TxoSID,
@@ -807,3 +1032,269 @@ fn test_check_fee_with_ledger() {
     let mut block = ledger.start_block().unwrap();
     assert!(ledger.apply_transaction(&mut block, effect).is_err());
 }
+
+#[test]
+fn test_simulate_transaction() {
+    let mut ledger = LedgerState::tmp_ledger();
+    let fra_owner_kp = XfrKeyPair::generate(&mut ChaChaRng::from_entropy());
+
+    let tx = utils::fra_gen_initial_tx(&fra_owner_kp);
+    let effect = TxnEffect::compute_effect(tx).unwrap();
+    let mut block = ledger.start_block().unwrap();
+    let tmp_sid = ledger.apply_transaction(&mut block, effect).unwrap();
+    let txo_sid = ledger
+        .finish_block(block)
+        .unwrap()
+        .remove(&tmp_sid)
+        .unwrap()
+        .1[0];
+
+    let tx2 = Transaction::from_operation(
+        gen_fee_operation(&mut ledger, txo_sid, &fra_owner_kp),
+        1,
+    );
+
+    // Nothing is applied, so the same tx can be simulated again.
+    for _ in 0..2 {
+        let summary = ledger.simulate_transaction(tx2.clone()).unwrap();
+        assert_eq!(summary.inputs, vec![txo_sid]);
+        assert_eq!(summary.outputs.len(), 2);
+        assert_eq!(summary.fee, TX_FEE_MIN_V0);
+        assert!(summary.staking_changes.is_empty());
+    }
+    assert!(ledger.get_utxo_light(txo_sid).is_some());
+
+    let effect = TxnEffect::compute_effect(tx2.clone()).unwrap();
+    let mut block = ledger.start_block().unwrap();
+    ledger.apply_transaction(&mut block, effect).unwrap();
+    ledger.finish_block(block).unwrap();
+
+    assert!(ledger.simulate_transaction(tx2).is_err());
+}
+
+#[test]
+fn test_prune() {
+    let mut ledger = LedgerState::tmp_ledger();
+    let fra_owner_kp = XfrKeyPair::generate(&mut ChaChaRng::from_entropy());
+
+    let tx = utils::fra_gen_initial_tx(&fra_owner_kp);
+    let effect = TxnEffect::compute_effect(tx).unwrap();
+    let mut block = ledger.start_block().unwrap();
+    let tmp_sid = ledger.apply_transaction(&mut block, effect).unwrap();
+    let (txn_sid, txos) = ledger
+        .finish_block(block)
+        .unwrap()
+        .remove(&tmp_sid)
+        .unwrap();
+
+    // a transaction without outputs
+    let (tx, _) = create_definition_transaction(
+        &AssetTypeCode::gen_random(),
+        &fra_owner_kp,
+        AssetRules::default(),
+        None,
+        ledger.get_block_commit_count(),
+    )
+    .unwrap();
+    let effect = TxnEffect::compute_effect(tx).unwrap();
+    let mut block = ledger.start_block().unwrap();
+    let tmp_sid = ledger.apply_transaction(&mut block, effect).unwrap();
+    let (def_txn_sid, _) = ledger
+        .finish_block(block)
+        .unwrap()
+        .remove(&tmp_sid)
+        .unwrap();
+
+    for (i, txo_sid) in txos.iter().enumerate() {
+        let tx = Transaction::from_operation(
+            gen_fee_operation(&mut ledger, *txo_sid, &fra_owner_kp),
+            ledger.get_block_commit_count(),
+        );
+        let effect = TxnEffect::compute_effect(tx).unwrap();
+        let mut block = ledger.start_block().unwrap();
+        ledger.apply_transaction(&mut block, effect).unwrap();
+        ledger.finish_block(block).unwrap();
+
+        // the issuance still owns a live utxo
+        if 0 == i {
+            assert_eq!(0, ledger.prune(1, Some(1)).unwrap());
+            assert!(ledger.get_transaction_light(txn_sid).is_ok());
+        }
+    }
+
+    assert!(!ledger.has_pruned());
+
+    // the definition owns no utxo, the first transfer still owns its change,
+    // then the cursor goes back to the first block, which is visited again
+    let state_commitment = ledger.get_state_commitment();
+    assert_eq!(1, ledger.prune(1, Some(1)).unwrap());
+    assert_eq!(0, ledger.prune(1, Some(1)).unwrap());
+    assert_eq!(1, ledger.prune(1, Some(1)).unwrap());
+    assert_eq!(state_commitment, ledger.get_state_commitment());
+    assert_eq!(4, ledger.get_block_count());
+    assert_eq!(0, ledger.prune(1, None).unwrap());
+
+    assert!(ledger.has_pruned());
+    assert!(ledger.is_pruned(txn_sid));
+    assert!(ledger.is_pruned(def_txn_sid));
+    let e = ledger.get_transaction_light(txn_sid).unwrap_err();
+    assert!(e.to_string().contains("has been pruned"));
+    let e = ledger.get_transaction_light(def_txn_sid).unwrap_err();
+    assert!(e.to_string().contains("has been pruned"));
+    assert!(ledger.get_spent_utxo_light(txos[0]).is_none());
+
+    // transactions kept below the pruned ones and unknown ones are not reported as pruned
+    let kept = TxnSID(def_txn_sid.0 + 1);
+    assert!(!ledger.is_pruned(kept));
+    assert!(ledger.get_transaction_light(kept).is_ok());
+    assert!(!ledger.is_pruned(TxnSID(100)));
+}
+
+#[test]
+fn test_cache_native_bridge() {
+    use {
+        crate::{
+            converter::ConvertAccount,
+            data_model::NoReplayToken,
+            staking::ops::mint_fra::{MintEntry, MintFraOps, MintKind},
+        },
+        fp_types::crypto::MultiSigner,
+        zei::xfr::structs::AssetType,
+    };
+
+    let mut prng = rand_chacha::ChaChaRng::from_entropy();
+    let owner = XfrKeyPair::generate(&mut prng).get_pk();
+    let asset = AssetType([9; 32]);
+    let code = AssetTypeCode { val: asset };
+    let height = CFG.checkpoint.native_asset_bridge_height.max(0) as u64;
+    let mut api_cache = api_cache::ApiCache::new("test_native_bridge/");
+
+    let convert = |value, asset_type, native| {
+        Transaction::from_operation(
+            Operation::ConvertAccount(ConvertAccount {
+                signer: owner,
+                nonce: NoReplayToken::default(),
+                receiver: MultiSigner::Xfr(owner),
+                value,
+                asset_type: Some(asset_type),
+                lowlevel_data: None,
+                native,
+            }),
+            0,
+        )
+    };
+    let withdraw = |amount| {
+        Transaction::from_operation(
+            Operation::MintFra(MintFraOps::new(
+                height,
+                vec![MintEntry::new(
+                    MintKind::FromAccount,
+                    owner,
+                    None,
+                    amount,
+                    asset,
+                )],
+            )),
+            0,
+        )
+    };
+
+    // FRA is kept by the account module, not by the bridge
+    api_cache.cache_native_bridge(TxnSID(0), &convert(7, ASSET_TYPE_FRA, true), height);
+    assert!(api_cache.native_bridge_supply.get(&code).is_none());
+
+    // conversions through the prism bridge are not accounted
+    api_cache.cache_native_bridge(TxnSID(1), &convert(5, asset, false), height);
+    assert!(api_cache.native_bridge_supply.get(&code).is_none());
+
+    api_cache.cache_native_bridge(TxnSID(2), &convert(10, asset, true), height);
+    api_cache.cache_native_bridge(TxnSID(3), &withdraw(4), height);
+    let supply = api_cache.native_bridge_supply.get(&code).unwrap();
+    assert_eq!((supply.to_account, supply.to_utxo), (10, 4));
+    assert_eq!(supply.in_account(), 6);
+
+    // a block cached again is not counted twice
+    api_cache.cache_native_bridge(TxnSID(2), &convert(10, asset, true), height);
+    api_cache.cache_native_bridge(TxnSID(3), &withdraw(4), height);
+    assert_eq!(api_cache.native_bridge_supply.get(&code), Some(supply));
+}
+
+#[test]
+fn test_check_convert_account() {
+    use {
+        crate::{
+            converter::{check_convert_account, ConvertAccount},
+            data_model::{TransferType, BLACK_HOLE_PUBKEY_STAKING},
+        },
+        fp_types::{crypto::MultiSigner, H160},
+        zei::xfr::structs::AssetType,
+    };
+
+    let mut prng = rand_chacha::ChaChaRng::from_entropy();
+    let kp = XfrKeyPair::generate(&mut prng);
+    let receiver = H160::from_low_u64_be(1);
+    let asset = AssetType([9; 32]);
+    let height = CFG.checkpoint.native_asset_bridge_height.max(0);
+
+    let mut convert = |lowlevel_data: Option<Vec<u8>>, native| {
+        let record = |public_key| {
+            let template = AssetRecordTemplate::with_no_asset_tracing(
+                10,
+                asset,
+                AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType,
+                public_key,
+            );
+            AssetRecord::from_template_no_identity_tracing(&mut prng, &template).unwrap()
+        };
+        let input = record(kp.get_pk());
+        let output = record(*BLACK_HOLE_PUBKEY_STAKING);
+        let mut transfer = TransferAsset::new(
+            TransferAssetBody::new(
+                &mut prng,
+                vec![TxoRef::Absolute(TxoSID(0))],
+                &[input],
+                &[output],
+                &[None],
+                None,
+                vec![],
+                TransferType::Standard,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        transfer.sign(&kp);
+
+        let mut tx = Transaction::from_operation(Operation::TransferAsset(transfer), 0);
+        tx.add_operation(Operation::ConvertAccount(ConvertAccount {
+            signer: kp.get_pk(),
+            nonce: tx.body.no_replay_token,
+            receiver: MultiSigner::Ethereum(receiver),
+            value: 10,
+            asset_type: Some(asset),
+            lowlevel_data,
+            native,
+        }));
+        tx.sign(&kp);
+        tx.sign_to_map(&kp);
+        tx
+    };
+
+    // custom assets go through the prism bridge with their lowlevel data by default
+    let (_, target, value, asset_type, lowlevel, native) =
+        check_convert_account(&convert(Some(vec![1, 2]), false), height).unwrap();
+    assert_eq!((target, value, asset_type), (receiver, 10, asset));
+    assert_eq!(lowlevel, vec![1, 2]);
+    assert!(!native);
+
+    // the native bridge is opted in by the operation
+    let native_tx = convert(None, true);
+    let (.., native) = check_convert_account(&native_tx, height).unwrap();
+    assert!(native);
+
+    // lowlevel data is not dropped silently
+    assert!(check_convert_account(&convert(Some(vec![1, 2]), true), height).is_err());
+
+    if height - 1 < CFG.checkpoint.native_asset_bridge_height {
+        assert!(check_convert_account(&native_tx, height - 1).is_err());
+    }
+}